@@ -1,7 +1,6 @@
 #![no_std]
 #![cfg_attr(not(test), no_main)]
 
-#[cfg(test)]
 extern crate alloc;
 
 #[cfg(not(test))]
@@ -15,6 +14,7 @@ pub use ckb_gen_types::packed as blockchain;
 #[allow(dead_code)]
 mod schema;
 
+use alloc::vec::Vec;
 use ckb_gen_types::prelude::*;
 use ckb_gen_types_cobuild::prelude::Unpack as CobuildUnpack;
 use ckb_std::{ckb_constants::Source, error::SysError, high_level};
@@ -22,6 +22,16 @@ use ckb_transaction_cobuild::{fetch_message, parse_otx_structure, Error as Cobui
 use ethnum::U256;
 use molecule::prelude::Entity;
 
+// Native CKB has no type script, orders use this sentinel token hash for it.
+// CKB amounts are kept in cell capacity instead of cell data.
+const CKB_TOKEN: [u8; 32] = [0u8; 32];
+// Freestanding cells of orders without a deadline expire this many blocks
+// after they are created, roughly 30 days.
+const MAX_ORDER_AGE: u64 = 300_000;
+// Flags of a relative since value in block numbers, and the mask of the value
+const RELATIVE_BLOCK_NUMBER_SINCE: u64 = 0x80;
+const SINCE_VALUE_MASK: u64 = 0x00ff_ffff_ffff_ffff;
+
 pub fn program_entry() -> i8 {
     let current_script = high_level::load_script().expect("loading script");
     if current_script.args().len() == 96 {
@@ -31,7 +41,7 @@ pub fn program_entry() -> i8 {
         if let Ok(Some(t)) = high_level::load_cell_type(0, Source::GroupOutput) {
             assert_ne!(current_script, t);
         }
-        // Current script is used as the lock script of an order cell, there are 2 ways
+        // Current script is used as the lock script of an order cell, there are 3 ways
         // to unlock it:
         // 1. When the order cell is processed by the dex as a normal order;
         {
@@ -55,7 +65,9 @@ pub fn program_entry() -> i8 {
                 i += 1;
             }
         }
-        // 2. By owner locks, in this case the original user cancels the order
+        // 2. By owner locks, in this case the original user cancels the order.
+        // The owner is the order recipient, unless the order specifies a
+        // separate owner.
         {
             let mut i = 0;
             loop {
@@ -73,6 +85,59 @@ pub fn program_entry() -> i8 {
                 i += 1;
             }
         }
+        // 3. By anyone after the order expires, in which case the cell is returned
+        // as a whole to the order owner. The order is provided in the lock field
+        // of the witness, while the input since enforces expiry: the deadline of
+        // orders with one, or MAX_ORDER_AGE blocks after the freestanding cell is
+        // created for other orders.
+        {
+            assert!(
+                high_level::load_cell_capacity(1, Source::GroupInput)
+                    == Err(SysError::IndexOutOfBound),
+                "Expired freestanding cells must be returned one at a time!"
+            );
+            let witness_args =
+                high_level::load_witness_args(0, Source::GroupInput).expect("loading witness args");
+            let order_data = witness_args
+                .lock()
+                .to_opt()
+                .expect("order is missing from witness!")
+                .raw_data();
+            let order = schema::Order::from_slice(&order_data).expect("parsing order");
+            assert_eq!(
+                hash_order(&order),
+                *current_script.args().raw_data().slice(64..96),
+                "Order does not match freestanding cell!"
+            );
+            let since = high_level::load_input_since(0, Source::GroupInput).expect("load since");
+            assert!(order_expired(&order, since), "Order has not expired!");
+
+            let current_script_hash = high_level::load_script_hash().expect("load script hash");
+            let mut index = 0;
+            while high_level::load_cell_lock_hash(index, Source::Input).expect("load lock hash")
+                != current_script_hash
+            {
+                index += 1;
+            }
+            assert_eq!(
+                high_level::load_cell_lock_hash(index, Source::Output).expect("load lock hash"),
+                *order_owner(&order).raw_data(),
+                "Expired cell is not returned to owner!"
+            );
+            assert_eq!(
+                high_level::load_cell_type_hash(index, Source::Output).expect("load type hash"),
+                high_level::load_cell_type_hash(index, Source::Input).expect("load type hash"),
+            );
+            assert_eq!(
+                high_level::load_cell_data(index, Source::Output).expect("load cell data"),
+                high_level::load_cell_data(index, Source::Input).expect("load cell data"),
+            );
+            assert!(
+                high_level::load_cell_capacity(index, Source::Output).expect("load capacity")
+                    >= high_level::load_cell_capacity(index, Source::Input).expect("load capacity")
+            );
+            return 0;
+        }
     }
     assert_eq!(
         current_script.args().len(),
@@ -145,13 +210,15 @@ pub fn program_entry() -> i8 {
         .map(|(i, _)| i)
         .next()
         .unwrap();
-    // Entity cell has no data
-    assert!(tx
+    // Entity cell data optionally keeps trading rules of pairs, which are
+    // fixed once the entity cell is created.
+    let entity_data = tx
         .raw()
         .outputs_data()
         .get(output_entity_index)
         .unwrap()
-        .is_empty());
+        .raw_data();
+    let trading_rules = parse_trading_rules(&entity_data);
     if input_entity_index.is_none() {
         // Initial creation mode
         assert!(otx_iter.is_none());
@@ -173,6 +240,10 @@ pub fn program_entry() -> i8 {
         high_level::load_cell_lock(input_entity_index, Source::Input).expect("load entity lock"),
         tx.raw().outputs().get(output_entity_index).unwrap().lock(),
     );
+    assert_eq!(
+        high_level::load_cell_data(input_entity_index, Source::Input).expect("load entity data"),
+        *entity_data
+    );
     let input_entity_header = high_level::load_header(input_entity_index, Source::Input)
         .expect("load input entity header");
 
@@ -185,6 +256,10 @@ pub fn program_entry() -> i8 {
         otx_input_end: 0,
         otx_output_start: usize::max_value(),
         otx_output_end: 0,
+        tokens: Vec::new(),
+        trading_rules,
+        nft_sells: Vec::new(),
+        nft_buys: Vec::new(),
     };
     let current_script_hash = high_level::load_script_hash().expect("load script hash");
     if let Some(otxs) = otx_iter {
@@ -220,6 +295,7 @@ pub fn program_entry() -> i8 {
             };
 
             for order in action.orders() {
+                context.check_trading_rules(&order);
                 context.process(order);
             }
         }
@@ -272,6 +348,11 @@ pub fn program_entry() -> i8 {
         }
     }
 
+    // Each NFT sold must land at the lock of a buyer
+    context.verify_nft_trades();
+    // Surplus of crossed orders must be paid to someone
+    context.verify_tokens_conserved();
+
     // Validate that dex1 related cells do not belong to part of OTXs
     if context.otx_input_start < context.otx_input_end {
         assert!(
@@ -299,9 +380,64 @@ struct Context {
     otx_input_end: usize,
     otx_output_start: usize,
     otx_output_end: usize,
+    // Type hashes of all tokens traded in current tx
+    tokens: Vec<[u8; 32]>,
+    trading_rules: Option<schema::TradingRules>,
+    // NFT orders of the tx, which must be settled in pairs
+    nft_sells: Vec<schema::NftSellOrder>,
+    nft_buys: Vec<(schema::NftBuyOrder, u128)>,
 }
 
 impl Context {
+    // Limit orders from OTXs must follow trading rules of their pairs.
+    // Freestanding cells keep remaining orders of partial fills, which are
+    // exempted.
+    fn check_trading_rules(&self, order: &schema::Order) {
+        let Some(trading_rules) = &self.trading_rules else {
+            return;
+        };
+        let Some(order) = limit_order(order) else {
+            return;
+        };
+        let bid_amount: u128 = order.bid_amount().unpack();
+        let ask_amount: u128 = order.ask_amount().unpack();
+        for rule in trading_rules.clone() {
+            let (size, notional) = if rule.first_token().as_slice() == order.bid_token().as_slice()
+                && rule.second_token().as_slice() == order.ask_token().as_slice()
+            {
+                (bid_amount, ask_amount)
+            } else if rule.first_token().as_slice() == order.ask_token().as_slice()
+                && rule.second_token().as_slice() == order.bid_token().as_slice()
+            {
+                (ask_amount, bid_amount)
+            } else {
+                continue;
+            };
+            let min_size: u128 = rule.min_size().unpack();
+            let min_notional: u128 = rule.min_notional().unpack();
+            let price_tick: u128 = rule.price_tick().unpack();
+            let lot_size: u128 = rule.lot_size().unpack();
+            assert!(size >= min_size, "Order size is below minimum size!");
+            assert!(
+                notional >= min_notional,
+                "Order notional is below minimum notional!"
+            );
+            let lot_size = if lot_size == 0 { 1 } else { lot_size };
+            assert!(
+                size % lot_size == 0,
+                "Order size is not a multiple of lot size!"
+            );
+            if price_tick > 0 {
+                let lot_notional = U256::from(notional) * U256::from(lot_size);
+                assert!(
+                    lot_notional % U256::from(size) == 0
+                        && (lot_notional / U256::from(size)) % U256::from(price_tick) == 0,
+                    "Order price is not a multiple of price tick!"
+                );
+            }
+        }
+    }
+
     fn process(&mut self, order: schema::Order) {
         match order.to_enum() {
             schema::OrderUnion::LimitOrder(_) => self.validate_limit_order(order),
@@ -314,6 +450,24 @@ impl Context {
                 assert!(input_block < deadline_block);
                 self.validate_limit_order(order);
             }
+            schema::OrderUnion::FillOrKillOrder(_)
+            | schema::OrderUnion::ImmediateOrCancelOrder(_)
+            | schema::OrderUnion::MinFillLimitOrder(_)
+            | schema::OrderUnion::IcebergOrder(_)
+            | schema::OrderUnion::BrokeredLimitOrder(_)
+            | schema::OrderUnion::OwnedLimitOrder(_) => self.validate_limit_order(order),
+            schema::OrderUnion::StopLimitOrder(o) => {
+                assert!(stop_triggered(&o), "Stop-limit order is not yet triggered!");
+                self.validate_limit_order(order);
+            }
+            schema::OrderUnion::SlicedOrder(o) => {
+                // Like deadline, the next slice is scheduled against the block
+                // number of dex1 entity cell.
+                let not_before: u64 = o.not_before().unpack();
+                let input_block: u64 = self.input_entity_header.raw().number().unpack();
+                assert!(input_block >= not_before, "Next slice is not yet due!");
+                self.validate_limit_order(order);
+            }
             schema::OrderUnion::MarketOrder(o) => {
                 self.validate_market_order(o);
             }
@@ -322,15 +476,16 @@ impl Context {
                 let minimum_ask: u128 = o.minimum_ask().unpack();
                 assert!(ask_amount >= minimum_ask);
             }
+            schema::OrderUnion::NftSellOrder(o) => self.validate_nft_sell_order(o),
+            schema::OrderUnion::NftBuyOrder(o) => self.validate_nft_buy_order(o),
+            schema::OrderUnion::RouteOrder(o) => self.validate_route_order(o),
         }
     }
 
     fn validate_limit_order(&mut self, full_order: schema::Order) {
-        let order = match full_order.to_enum() {
-            schema::OrderUnion::LimitOrder(o) => o,
-            schema::OrderUnion::LimitOrderWithDeadline(o) => o.order(),
-            _ => unreachable!(),
-        };
+        let order = limit_order(&full_order).unwrap();
+        self.record_token(&order.bid_token());
+        self.record_token(&order.ask_token());
 
         let bid_amount: u128 = order.bid_amount().unpack();
         let ask_amount: u128 = order.ask_amount().unpack();
@@ -346,19 +501,33 @@ impl Context {
         // can be processed later.
         // * The order is not filled at all, only one freestanding cell will be created
         // here.
+        // When CKB is traded, cells returned to recipient have no type script,
+        // their capacity first covers claimed CKBytes not kept elsewhere, the
+        // rest is traded CKB. A freestanding cell of CKB keeps remaining CKB in
+        // cell data as well, on top of its CKBytes.
+        // Fill-or-kill orders can only be fully filled. Immediate-or-cancel orders
+        // never create freestanding cells, when partially filled, the first cell
+        // returns remaining bid tokens to recipient instead. Iceberg and sliced
+        // orders fill at most the displayed or sliced bid tokens in one tx.
+        // Brokered orders have a cell paying the broker fee right after the
+        // cell containing filled tokens, unless the fee rounds down to 0. The
+        // broker cell is funded by claimed CKBytes as well.
+        let fill_mode = full_order.to_enum();
         let next_lock = high_level::load_cell_lock(self.output_entity_end, Source::Output)
             .expect("load pay cell lock");
         if next_lock.code_hash() == self.current_script.code_hash()
             && next_lock.hash_type() == self.current_script.hash_type()
         {
-            assert_eq!(
-                high_level::load_cell_type_hash(self.output_entity_end, Source::Output)
-                    .expect("load pay cell type hash")
-                    .unwrap(),
-                *order.bid_token().raw_data()
-            );
+            assert!(!matches!(
+                fill_mode,
+                schema::OrderUnion::FillOrKillOrder(_)
+                    | schema::OrderUnion::ImmediateOrCancelOrder(_)
+            ));
+            assert!(self.output_cell_is_token(self.output_entity_end, &order.bid_token()));
             // Freestanding cell available
             let freestanding_amount = self.output_cell_udt_amount(self.output_entity_end);
+            let freestanding_ckbytes =
+                self.freestanding_cell_ckbytes(self.output_entity_end, &order.bid_token());
             if freestanding_amount < bid_amount {
                 // Partial filled, there must be an additional cell containing filled tokens
                 assert_eq!(
@@ -366,73 +535,195 @@ impl Context {
                         .expect("load pay cell lock hash"),
                     *order.recipient().raw_data()
                 );
-                assert_eq!(
-                    high_level::load_cell_type_hash(self.output_entity_end + 1, Source::Output)
-                        .expect("load pay cell type hash")
-                        .unwrap(),
-                    *order.ask_token().raw_data()
-                );
+                assert!(self.output_cell_is_token(self.output_entity_end + 1, &order.ask_token()));
                 // Validate price first
                 let actual_bid_amount = bid_amount - freestanding_amount;
-                let actual_paid_amount = self.output_cell_udt_amount(self.output_entity_end + 1);
+                let claimed_ckbytes: u64 = order.claimed_ckbytes().unpack();
+                let broker_fee =
+                    self.broker_fee(&fill_mode, self.output_entity_end + 2, &order.ask_token());
+                let (actual_paid_amount, payback_ckbytes) = self.output_cell_amounts(
+                    self.output_entity_end + 1,
+                    &order.ask_token(),
+                    claimed_ckbytes
+                        .saturating_sub(freestanding_ckbytes)
+                        .saturating_sub(broker_fee.ckbytes),
+                );
+                let actual_paid_amount = broker_fee.total(&fill_mode, actual_paid_amount);
                 // For simplicity I picked this formula, but you might want to tweak it.
                 assert!(
                     U256::from(actual_paid_amount) * U256::from(bid_amount)
                         >= U256::from(ask_amount) * U256::from(actual_bid_amount),
                 );
+                match &fill_mode {
+                    schema::OrderUnion::MinFillLimitOrder(o) => {
+                        let min_fill: u128 = o.min_fill().unpack();
+                        assert!(actual_bid_amount >= min_fill);
+                    }
+                    schema::OrderUnion::IcebergOrder(o) => {
+                        let display_amount: u128 = o.display_amount().unpack();
+                        assert!(actual_bid_amount <= display_amount);
+                    }
+                    schema::OrderUnion::SlicedOrder(o) => {
+                        let slice_amount: u128 = o.slice_amount().unpack();
+                        assert!(actual_bid_amount <= slice_amount);
+                    }
+                    _ => (),
+                }
                 // Now that the price is legit, we will validate claimed CKBytes
-                let freestanding_ckbytes = self.output_cell_ckbytes(self.output_entity_end);
-                let payback_ckbytes = self.output_cell_ckbytes(self.output_entity_end + 1);
                 assert!(
                     freestanding_ckbytes
                         .checked_add(payback_ckbytes)
+                        .and_then(|ckbytes| ckbytes.checked_add(broker_fee.ckbytes))
                         .expect("overflow")
-                        >= order.claimed_ckbytes().unpack()
+                        >= claimed_ckbytes
                 );
                 // Partial filled freestanding cells have a new order
-                let new_order = carve_limit_order(
-                    &full_order,
-                    freestanding_amount,
-                    ask_amount - actual_paid_amount,
-                    freestanding_ckbytes,
+                let new_order = next_slice(
+                    carve_limit_order(
+                        &full_order,
+                        freestanding_amount,
+                        ask_amount - actual_paid_amount,
+                        freestanding_ckbytes,
+                    ),
+                    self.input_entity_header.raw().number().unpack(),
                 );
-                let freestanding_args =
-                    self.freestanding_script_args(&hash_order(&new_order), &order.recipient());
+                let freestanding_args = self
+                    .freestanding_script_args(&hash_order(&new_order), &order_owner(&full_order));
                 assert_eq!(*next_lock.args().raw_data(), freestanding_args);
-                self.output_entity_end += 2;
+                self.output_entity_end += 2 + broker_fee.cells;
             } else {
                 // Fully filled freestanding cell
                 // UDT amount kept in the freestanding cell has been asserted above.
                 // All we need to do here is CKBytes comparison
-                assert!(
-                    self.output_cell_ckbytes(self.output_entity_end)
-                        >= order.claimed_ckbytes().unpack()
-                );
-                let freestanding_args =
-                    self.freestanding_script_args(&hash_order(&full_order), &order.recipient());
+                assert!(freestanding_ckbytes >= order.claimed_ckbytes().unpack());
+                let freestanding_args = self
+                    .freestanding_script_args(&hash_order(&full_order), &order_owner(&full_order));
                 assert_eq!(*next_lock.args().raw_data(), freestanding_args);
                 self.output_entity_end += 1;
             }
-        } else {
-            // Properly filled cell
+        } else if matches!(fill_mode, schema::OrderUnion::ImmediateOrCancelOrder(_))
+            && self.output_cell_is_token(self.output_entity_end, &order.bid_token())
+        {
+            // Partially filled immediate-or-cancel order, remaining bid tokens are
+            // returned to recipient, followed by the cell containing filled tokens.
             assert_eq!(
-                high_level::load_cell_type_hash(self.output_entity_end, Source::Output)
-                    .expect("load pay cell type hash")
-                    .unwrap(),
-                *order.ask_token().raw_data()
+                high_level::load_cell_lock_hash(self.output_entity_end, Source::Output)
+                    .expect("load refund cell lock hash"),
+                *order.recipient().raw_data()
             );
             assert_eq!(
-                high_level::load_cell_lock_hash(self.output_entity_end, Source::Output)
+                high_level::load_cell_lock_hash(self.output_entity_end + 1, Source::Output)
                     .expect("load pay cell lock hash"),
                 *order.recipient().raw_data()
             );
-            let actual_amount = self.output_cell_udt_amount(self.output_entity_end);
-            assert!(actual_amount >= ask_amount);
+            assert!(self.output_cell_is_token(self.output_entity_end + 1, &order.ask_token()));
+            // At most one of the 2 cells holds CKB, claimed CKBytes are first
+            // taken from the other one.
+            let claimed_ckbytes: u64 = order.claimed_ckbytes().unpack();
+            let ((refund_amount, refund_ckbytes), (actual_paid_amount, payback_ckbytes)) =
+                if order.bid_token().as_slice() == CKB_TOKEN {
+                    let paid = self.output_cell_amounts(
+                        self.output_entity_end + 1,
+                        &order.ask_token(),
+                        claimed_ckbytes,
+                    );
+                    let refund = self.output_cell_amounts(
+                        self.output_entity_end,
+                        &order.bid_token(),
+                        claimed_ckbytes.saturating_sub(paid.1),
+                    );
+                    (refund, paid)
+                } else {
+                    let refund = self.output_cell_amounts(
+                        self.output_entity_end,
+                        &order.bid_token(),
+                        claimed_ckbytes,
+                    );
+                    let paid = self.output_cell_amounts(
+                        self.output_entity_end + 1,
+                        &order.ask_token(),
+                        claimed_ckbytes.saturating_sub(refund.1),
+                    );
+                    (refund, paid)
+                };
+            assert!(refund_amount < bid_amount);
+            let actual_bid_amount = bid_amount - refund_amount;
             assert!(
-                self.output_cell_ckbytes(self.output_entity_end)
-                    >= order.claimed_ckbytes().unpack()
+                U256::from(actual_paid_amount) * U256::from(bid_amount)
+                    >= U256::from(ask_amount) * U256::from(actual_bid_amount),
+            );
+            assert!(
+                refund_ckbytes
+                    .checked_add(payback_ckbytes)
+                    .expect("overflow")
+                    >= claimed_ckbytes
+            );
+            self.output_entity_end += 2;
+        } else {
+            // Properly filled cell
+            match &fill_mode {
+                schema::OrderUnion::IcebergOrder(o) => {
+                    let display_amount: u128 = o.display_amount().unpack();
+                    assert!(bid_amount <= display_amount);
+                }
+                schema::OrderUnion::SlicedOrder(o) => {
+                    let slice_amount: u128 = o.slice_amount().unpack();
+                    assert!(bid_amount <= slice_amount);
+                }
+                _ => (),
+            }
+            assert!(self.output_cell_is_token(self.output_entity_end, &order.ask_token()));
+            assert_eq!(
+                high_level::load_cell_lock_hash(self.output_entity_end, Source::Output)
+                    .expect("load pay cell lock hash"),
+                *order.recipient().raw_data()
             );
-            self.output_entity_end += 1;
+            let claimed_ckbytes: u64 = order.claimed_ckbytes().unpack();
+            let broker_fee =
+                self.broker_fee(&fill_mode, self.output_entity_end + 1, &order.ask_token());
+            let (actual_amount, ckbytes) = self.output_cell_amounts(
+                self.output_entity_end,
+                &order.ask_token(),
+                claimed_ckbytes.saturating_sub(broker_fee.ckbytes),
+            );
+            let actual_amount = broker_fee.total(&fill_mode, actual_amount);
+            assert!(actual_amount >= ask_amount);
+            assert!(ckbytes.checked_add(broker_fee.ckbytes).expect("overflow") >= claimed_ckbytes);
+            self.output_entity_end += 1 + broker_fee.cells;
+        }
+    }
+
+    // Broker cell at +index+ for brokered orders, a cell of +token+ at broker
+    // lock. It is missing when the fee rounds down to 0, which is then checked
+    // by BrokerFee::total.
+    fn broker_fee(
+        &self,
+        fill_mode: &schema::OrderUnion,
+        index: usize,
+        token: &blockchain::Byte32,
+    ) -> BrokerFee {
+        let schema::OrderUnion::BrokeredLimitOrder(o) = fill_mode else {
+            return BrokerFee::default();
+        };
+        match high_level::load_cell_lock_hash(index, Source::Output) {
+            Ok(lock_hash)
+                if lock_hash == *o.broker().raw_data()
+                    && self.output_cell_is_token(index, token) =>
+            {
+                let ckbytes = if token.as_slice() == CKB_TOKEN {
+                    high_level::load_cell_occupied_capacity(index, Source::Output)
+                        .expect("load occupied capacity")
+                } else {
+                    self.output_cell_ckbytes(index)
+                };
+                BrokerFee {
+                    fee: self.output_cell_surplus_amount(index, token),
+                    ckbytes,
+                    cells: 1,
+                }
+            }
+            Ok(_) | Err(SysError::IndexOutOfBound) => BrokerFee::default(),
+            Err(e) => panic!("Error loading broker cell lock hash: {:?}", e),
         }
     }
 
@@ -440,23 +731,154 @@ impl Context {
         // A market order must be fully fulfilled when included on chain, there is no
         // partial filling of market order.
         // TODO: anything we can do to mitigate market order censorship?
+        self.record_token(&order.bid_token());
+        self.record_token(&order.ask_token());
+        assert!(self.output_cell_is_token(self.output_entity_end, &order.ask_token()));
         assert_eq!(
-            high_level::load_cell_type_hash(self.output_entity_end, Source::Output)
-                .expect("load pay cell type hash")
-                .unwrap(),
-            *order.ask_token().raw_data()
+            high_level::load_cell_lock_hash(self.output_entity_end, Source::Output)
+                .expect("load pay cell lock hash"),
+            *order.recipient().raw_data()
         );
+        let claimed_ckbytes: u64 = order.claimed_ckbytes().unpack();
+        let (actual_amount, ckbytes) =
+            self.output_cell_amounts(self.output_entity_end, &order.ask_token(), claimed_ckbytes);
+        assert!(ckbytes >= claimed_ckbytes);
+        self.output_entity_end += 1;
+        actual_amount
+    }
+
+    // NFT orders are always fully filled. The seller gets a payment cell much
+    // like a market order, the NFT cell is checked by the paired buy order.
+    fn validate_nft_sell_order(&mut self, order: schema::NftSellOrder) {
+        self.record_token(&order.ask_token());
+        assert!(self.output_cell_is_token(self.output_entity_end, &order.ask_token()));
         assert_eq!(
             high_level::load_cell_lock_hash(self.output_entity_end, Source::Output)
                 .expect("load pay cell lock hash"),
             *order.recipient().raw_data()
         );
-        assert!(
-            self.output_cell_ckbytes(self.output_entity_end) >= order.claimed_ckbytes().unpack()
+        let claimed_ckbytes: u64 = order.claimed_ckbytes().unpack();
+        let (actual_amount, ckbytes) =
+            self.output_cell_amounts(self.output_entity_end, &order.ask_token(), claimed_ckbytes);
+        let ask_amount: u128 = order.ask_amount().unpack();
+        assert!(actual_amount >= ask_amount);
+        assert!(ckbytes >= claimed_ckbytes);
+        self.output_entity_end += 1;
+        self.nft_sells.push(order);
+    }
+
+    // The buyer gets the unique cell at recipient lock, followed by a cell
+    // refunding bid tokens not paid to the seller, also at recipient lock.
+    // The refund cell is left out when nothing is refunded. Claimed CKBytes
+    // are shared by both cells, the refund cell first takes what the NFT cell
+    // does not keep.
+    fn validate_nft_buy_order(&mut self, order: schema::NftBuyOrder) {
+        self.record_token(&order.bid_token());
+        assert_eq!(
+            high_level::load_cell_type_hash(self.output_entity_end, Source::Output)
+                .expect("load nft cell type hash")
+                .expect("nft cell has no type script"),
+            *order.nft().raw_data()
+        );
+        assert_eq!(
+            high_level::load_cell_lock_hash(self.output_entity_end, Source::Output)
+                .expect("load nft cell lock hash"),
+            *order.recipient().raw_data()
         );
-        let actual_amount = self.output_cell_udt_amount(self.output_entity_end);
+        let claimed_ckbytes: u64 = order.claimed_ckbytes().unpack();
+        let nft_ckbytes = self.output_cell_ckbytes(self.output_entity_end);
         self.output_entity_end += 1;
-        actual_amount
+        let (refund, refund_ckbytes) =
+            match high_level::load_cell_lock_hash(self.output_entity_end, Source::Output) {
+                Ok(lock_hash)
+                    if lock_hash == *order.recipient().raw_data()
+                        && self
+                            .output_cell_is_token(self.output_entity_end, &order.bid_token()) =>
+                {
+                    let amounts = self.output_cell_amounts(
+                        self.output_entity_end,
+                        &order.bid_token(),
+                        claimed_ckbytes.saturating_sub(nft_ckbytes),
+                    );
+                    self.output_entity_end += 1;
+                    amounts
+                }
+                Ok(_) | Err(SysError::IndexOutOfBound) => (0, 0),
+                Err(e) => panic!("Error loading refund cell lock hash: {:?}", e),
+            };
+        assert!(nft_ckbytes.checked_add(refund_ckbytes).expect("overflow") >= claimed_ckbytes);
+        self.nft_buys.push((order, refund));
+    }
+
+    // Each NFT sell order is paired with a buy order of the same NFT, whose
+    // validation has put the NFT cell at the buyer's lock. The buyer pays no
+    // more than the ask amount of the seller, the rest of the bid is refunded.
+    fn verify_nft_trades(&self) {
+        assert_eq!(self.nft_sells.len(), self.nft_buys.len());
+        let mut buys: Vec<_> = self.nft_buys.iter().collect();
+        for sell in &self.nft_sells {
+            let position = buys
+                .iter()
+                .position(|(buy, _)| buy.nft().as_slice() == sell.nft().as_slice())
+                .expect("NFT is not bought by anyone!");
+            let (buy, refund) = buys.swap_remove(position);
+            assert_eq!(buy.bid_token().as_slice(), sell.ask_token().as_slice());
+            let bid_amount: u128 = buy.bid_amount().unpack();
+            let ask_amount: u128 = sell.ask_amount().unpack();
+            assert!(
+                refund.checked_add(ask_amount).expect("overflow") >= bid_amount,
+                "NFT buyer is not refunded!"
+            );
+        }
+    }
+
+    // Like a market order, a route order is fully filled. Intermediate tokens
+    // are recorded by the orders filled along the path, so only the final
+    // output needs to be checked here.
+    fn validate_route_order(&mut self, order: schema::RouteOrder) {
+        let path = order.path();
+        assert!(path.len() >= 2);
+        let bid_token = path.get(0).unwrap();
+        let ask_token = path.get(path.len() - 1).unwrap();
+        assert_ne!(bid_token.as_slice(), ask_token.as_slice());
+        self.record_token(&bid_token);
+        self.record_token(&ask_token);
+        assert!(self.output_cell_is_token(self.output_entity_end, &ask_token));
+        assert_eq!(
+            high_level::load_cell_lock_hash(self.output_entity_end, Source::Output)
+                .expect("load pay cell lock hash"),
+            *order.recipient().raw_data()
+        );
+        let claimed_ckbytes: u64 = order.claimed_ckbytes().unpack();
+        let (actual_amount, ckbytes) =
+            self.output_cell_amounts(self.output_entity_end, &ask_token, claimed_ckbytes);
+        let minimum_ask: u128 = order.minimum_ask().unpack();
+        assert!(actual_amount >= minimum_ask);
+        assert!(ckbytes >= claimed_ckbytes);
+        self.output_entity_end += 1;
+    }
+
+    // CKB is left out, its total capacity is guarded by CKB itself, though
+    // CKB surplus of crossed orders might end up as tx fee.
+    fn record_token(&mut self, token: &blockchain::Byte32) {
+        let mut hash = [0u8; 32];
+        hash.copy_from_slice(&token.raw_data());
+        if hash != CKB_TOKEN && !self.tokens.contains(&hash) {
+            self.tokens.push(hash);
+        }
+    }
+
+    // Orders are only validated to be paid at least their limit prices, the
+    // total amount of each traded token must also stay the same in inputs
+    // and outputs, so surplus of crossed orders never silently disappears.
+    fn verify_tokens_conserved(&self) {
+        for token in &self.tokens {
+            assert_eq!(
+                udt_amount(token, Source::Input),
+                udt_amount(token, Source::Output),
+                "Traded token amounts are not conserved!"
+            );
+        }
     }
 
     fn output_cell_ckbytes(&self, index: usize) -> u64 {
@@ -469,26 +891,209 @@ impl Context {
             .unpack()
     }
 
+    // Whether an output cell holds +token+, CKB cells have no type script.
+    fn output_cell_is_token(&self, index: usize, token: &blockchain::Byte32) -> bool {
+        let type_hash = high_level::load_cell_type_hash(index, Source::Output)
+            .expect("load output cell type hash");
+        if token.as_slice() == CKB_TOKEN {
+            type_hash.is_none()
+        } else {
+            type_hash
+                .map(|hash| hash == *token.raw_data())
+                .unwrap_or(false)
+        }
+    }
+
+    // Traded amount of +token+ and CKBytes kept by an output cell returned to
+    // recipient. Capacity of a CKB cell first covers +ckbytes_due+, the rest
+    // is traded CKB.
+    fn output_cell_amounts(
+        &self,
+        index: usize,
+        token: &blockchain::Byte32,
+        ckbytes_due: u64,
+    ) -> (u128, u64) {
+        let capacity = self.output_cell_ckbytes(index);
+        if token.as_slice() == CKB_TOKEN {
+            let ckbytes = core::cmp::min(capacity, ckbytes_due);
+            ((capacity - ckbytes) as u128, ckbytes)
+        } else {
+            (self.output_cell_udt_amount(index), capacity)
+        }
+    }
+
+    // CKBytes kept by a freestanding cell, excluding remaining CKB of the order
+    fn freestanding_cell_ckbytes(&self, index: usize, token: &blockchain::Byte32) -> u64 {
+        let capacity = self.output_cell_ckbytes(index);
+        if token.as_slice() == CKB_TOKEN {
+            let amount: u64 = self
+                .output_cell_udt_amount(index)
+                .try_into()
+                .expect("overflow");
+            capacity.checked_sub(amount).expect("overflow")
+        } else {
+            capacity
+        }
+    }
+
+    // Token amount of a cell only keeping occupied CKBytes, such as a broker
+    // cell, where CKB amount is the capacity beyond occupied one.
+    fn output_cell_surplus_amount(&self, index: usize, token: &blockchain::Byte32) -> u128 {
+        if token.as_slice() == CKB_TOKEN {
+            let occupied = high_level::load_cell_occupied_capacity(index, Source::Output)
+                .expect("load occupied capacity");
+            self.output_cell_ckbytes(index)
+                .checked_sub(occupied)
+                .expect("overflow") as u128
+        } else {
+            self.output_cell_udt_amount(index)
+        }
+    }
+
     fn output_cell_udt_amount(&self, index: usize) -> u128 {
-        let mut data = [0u8; 16];
-        let full_data = self.tx.raw().outputs_data().get(index).unwrap().raw_data();
-        data.copy_from_slice(&full_data[0..16]);
-        u128::from_le_bytes(data)
+        udt_data_amount(&self.tx.raw().outputs_data().get(index).unwrap().raw_data())
     }
 
     fn freestanding_script_args(
         &self,
         order_hash: &[u8; 32],
-        recipient: &blockchain::Byte32,
+        owner: &blockchain::Byte32,
     ) -> [u8; 96] {
         let mut r = [0u8; 96];
         r[0..32].copy_from_slice(&self.current_script.args().raw_data().slice(0..32));
-        r[32..64].copy_from_slice(&recipient.raw_data());
+        r[32..64].copy_from_slice(&owner.raw_data());
         r[64..96].copy_from_slice(order_hash);
         r
     }
 }
 
+// Total UDT amount of cells using the token as type script
+fn udt_amount(token: &[u8; 32], source: Source) -> u128 {
+    let mut total: u128 = 0;
+    let mut i = 0;
+    loop {
+        match high_level::load_cell_type_hash(i, source) {
+            Ok(Some(type_hash)) => {
+                if type_hash == *token {
+                    let data = high_level::load_cell_data(i, source).expect("load cell data");
+                    total = total.checked_add(udt_data_amount(&data)).expect("overflow");
+                }
+            }
+            Ok(None) => (),
+            Err(SysError::IndexOutOfBound) => break,
+            Err(e) => panic!("Error loading cell type hash: {:?}", e),
+        }
+        i += 1;
+    }
+    total
+}
+
+// Both sUDT and xUDT keep the amount in the first 16 bytes of cell data, xUDT
+// might have extension data following it.
+fn udt_data_amount(data: &[u8]) -> u128 {
+    assert!(data.len() >= 16, "Invalid UDT data!");
+    let mut amount = [0u8; 16];
+    amount.copy_from_slice(&data[0..16]);
+    u128::from_le_bytes(amount)
+}
+
+// The remaining order of a sliced order has its next slice scheduled interval
+// blocks after the current slice is filled at +block_number+, so a slice
+// filled late does not leave a remainder that is already due. Other orders
+// are kept as is.
+fn next_slice(order: schema::Order, block_number: u64) -> schema::Order {
+    let schema::OrderUnion::SlicedOrder(o) = order.to_enum() else {
+        return order;
+    };
+    let interval: u64 = o.interval().unpack();
+    if interval == 0 {
+        return order;
+    }
+    let not_before: u64 = o.not_before().unpack();
+    let next = core::cmp::max(not_before, block_number).saturating_add(interval);
+    schema::Order::new_builder()
+        .set(o.as_builder().not_before(next.pack()).build())
+        .build()
+}
+
+// The oracle cell is located in cell deps by its type script hash, a stop-limit
+// order is triggered when the oracle values its bid token at or below the
+// trigger price in its ask token.
+fn stop_triggered(order: &schema::StopLimitOrder) -> bool {
+    let mut i = 0;
+    let data = loop {
+        match high_level::load_cell_type_hash(i, Source::CellDep) {
+            Ok(Some(type_hash)) if type_hash == *order.oracle().raw_data() => {
+                break high_level::load_cell_data(i, Source::CellDep).expect("load oracle data");
+            }
+            Ok(_) => (),
+            Err(SysError::IndexOutOfBound) => panic!("Oracle cell is missing!"),
+            Err(e) => panic!("Error loading cell dep type hash: {:?}", e),
+        }
+        i += 1;
+    };
+    let price = schema::OraclePrice::from_slice(&data).expect("parsing oracle data");
+    let limit_order = order.order();
+    let base_amount: u128 = price.base_amount().unpack();
+    let quote_amount: u128 = price.quote_amount().unpack();
+    let (bid_value, ask_value) = if price.base_token().as_slice()
+        == limit_order.bid_token().as_slice()
+        && price.quote_token().as_slice() == limit_order.ask_token().as_slice()
+    {
+        (base_amount, quote_amount)
+    } else if price.base_token().as_slice() == limit_order.ask_token().as_slice()
+        && price.quote_token().as_slice() == limit_order.bid_token().as_slice()
+    {
+        (quote_amount, base_amount)
+    } else {
+        panic!("Oracle does not price the traded tokens!");
+    };
+    assert!(bid_value > 0);
+    let trigger_bid_amount: u128 = order.trigger_bid_amount().unpack();
+    let trigger_ask_amount: u128 = order.trigger_ask_amount().unpack();
+    U256::from(ask_value) * U256::from(trigger_bid_amount)
+        <= U256::from(trigger_ask_amount) * U256::from(bid_value)
+}
+
+// Entity cells created before trading rules were introduced might keep
+// arbitrary data, which is fixed for the life of the entity cell. Data that
+// does not parse as trading rules, including empty data, enforces no rules
+// instead of locking such entity cells forever.
+fn parse_trading_rules(entity_data: &[u8]) -> Option<schema::TradingRules> {
+    schema::TradingRules::from_slice(entity_data).ok()
+}
+
+// Fee paid by the broker cell of an order, along with CKBytes it takes from
+// claimed CKBytes and the number of broker cells(0 or 1).
+#[derive(Default)]
+struct BrokerFee {
+    fee: u128,
+    ckbytes: u64,
+    cells: usize,
+}
+
+impl BrokerFee {
+    // Ask tokens received by an order, including the broker fee. The fee must
+    // be exactly fee_bps of the total.
+    fn total(&self, fill_mode: &schema::OrderUnion, paid_amount: u128) -> u128 {
+        let schema::OrderUnion::BrokeredLimitOrder(o) = fill_mode else {
+            return paid_amount;
+        };
+        brokered_total(paid_amount, self.fee, o.fee_bps().unpack())
+    }
+}
+
+// Total amount filled for a brokered order. The broker fee must be exactly the
+// +fee_bps+ share of the total, otherwise the fill could be shifted from the
+// recipient to the broker without breaking the order price.
+fn brokered_total(paid_amount: u128, fee: u128, fee_bps: u32) -> u128 {
+    assert!(fee_bps <= 10000);
+    let total = paid_amount.checked_add(fee).expect("overflow");
+    let expected_fee = U256::from(total) * U256::from(fee_bps) / U256::from(10000u32);
+    assert!(U256::from(fee) == expected_fee, "Invalid broker fee!");
+    total
+}
+
 fn hash_order(order: &schema::Order) -> [u8; 32] {
     let mut blake = blake2b_ref::Blake2bBuilder::new(32)
         .personal(b"ckb-default-hash")
@@ -499,6 +1104,47 @@ fn hash_order(order: &schema::Order) -> [u8; 32] {
     hash
 }
 
+// Limit order wrapped in any of the limit order variants
+fn limit_order(order: &schema::Order) -> Option<schema::LimitOrder> {
+    match order.to_enum() {
+        schema::OrderUnion::LimitOrder(o) => Some(o),
+        schema::OrderUnion::LimitOrderWithDeadline(o) => Some(o.order()),
+        schema::OrderUnion::FillOrKillOrder(o) => Some(o.order()),
+        schema::OrderUnion::ImmediateOrCancelOrder(o) => Some(o.order()),
+        schema::OrderUnion::MinFillLimitOrder(o) => Some(o.order()),
+        schema::OrderUnion::StopLimitOrder(o) => Some(o.order()),
+        schema::OrderUnion::IcebergOrder(o) => Some(o.order()),
+        schema::OrderUnion::SlicedOrder(o) => Some(o.order()),
+        schema::OrderUnion::BrokeredLimitOrder(o) => Some(o.order()),
+        schema::OrderUnion::OwnedLimitOrder(o) => Some(o.order()),
+        _ => None,
+    }
+}
+
+// Lock hash able to cancel an order kept in a freestanding cell, which is the
+// recipient unless a separate owner is specified.
+// Checks the input since of an expired freestanding cell. Only absolute block
+// number since values are accepted for orders with a deadline, and only
+// relative block number ones for other orders.
+fn order_expired(order: &schema::Order, since: u64) -> bool {
+    match order.to_enum() {
+        schema::OrderUnion::LimitOrderWithDeadline(o) => {
+            let deadline_block: u64 = o.deadline().unpack();
+            since >> 56 == 0 && since >= deadline_block
+        }
+        _ => {
+            since >> 56 == RELATIVE_BLOCK_NUMBER_SINCE && since & SINCE_VALUE_MASK >= MAX_ORDER_AGE
+        }
+    }
+}
+
+fn order_owner(order: &schema::Order) -> blockchain::Byte32 {
+    match order.to_enum() {
+        schema::OrderUnion::OwnedLimitOrder(o) => o.owner(),
+        _ => limit_order(order).expect("limit order").recipient(),
+    }
+}
+
 // Carve an existing limit order to update bid & ask amounts.
 // This provides a minimal solution without introduing the whole builder
 // implementation.
@@ -520,6 +1166,36 @@ fn carve_limit_order(
                     o.order().as_slice().as_ptr() as usize - reader.as_slice().as_ptr() as usize;
                 (o.order(), offset)
             }
+            schema::OrderUnionReader::MinFillLimitOrder(o) => {
+                let offset =
+                    o.order().as_slice().as_ptr() as usize - reader.as_slice().as_ptr() as usize;
+                (o.order(), offset)
+            }
+            schema::OrderUnionReader::StopLimitOrder(o) => {
+                let offset =
+                    o.order().as_slice().as_ptr() as usize - reader.as_slice().as_ptr() as usize;
+                (o.order(), offset)
+            }
+            schema::OrderUnionReader::IcebergOrder(o) => {
+                let offset =
+                    o.order().as_slice().as_ptr() as usize - reader.as_slice().as_ptr() as usize;
+                (o.order(), offset)
+            }
+            schema::OrderUnionReader::SlicedOrder(o) => {
+                let offset =
+                    o.order().as_slice().as_ptr() as usize - reader.as_slice().as_ptr() as usize;
+                (o.order(), offset)
+            }
+            schema::OrderUnionReader::BrokeredLimitOrder(o) => {
+                let offset =
+                    o.order().as_slice().as_ptr() as usize - reader.as_slice().as_ptr() as usize;
+                (o.order(), offset)
+            }
+            schema::OrderUnionReader::OwnedLimitOrder(o) => {
+                let offset =
+                    o.order().as_slice().as_ptr() as usize - reader.as_slice().as_ptr() as usize;
+                (o.order(), offset)
+            }
             _ => unreachable!(),
         };
         (
@@ -540,3 +1216,73 @@ fn carve_limit_order(
     data[ckb_offset..ckb_offset + 8].copy_from_slice(&new_claimed_ckbytes.to_le_bytes());
     schema::Order::from_slice(&data).expect("creating new order")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_legacy_entity_data_enforces_no_trading_rules() {
+        assert!(parse_trading_rules(&[]).is_none());
+        assert!(parse_trading_rules(&[0xab; 32]).is_none());
+
+        let trading_rules = schema::TradingRules::new_builder()
+            .push(schema::TradingRule::new_builder().build())
+            .build();
+        assert_eq!(
+            parse_trading_rules(trading_rules.as_slice()).map(|rules| rules.len()),
+            Some(1)
+        );
+    }
+
+    #[test]
+    fn test_exact_broker_fee_is_accepted() {
+        assert_eq!(brokered_total(9900, 100, 100), 10000);
+        assert_eq!(brokered_total(1000, 0, 0), 1000);
+        // Missing broker cell when the fee rounds down to 0
+        assert_eq!(brokered_total(10, 0, 250), 10);
+        // Fee is rounded down
+        assert_eq!(brokered_total(999, 10, 100), 1009);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid broker fee!")]
+    fn test_overpaid_broker_fee_is_rejected() {
+        brokered_total(100, 9900, 100);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid broker fee!")]
+    fn test_underpaid_broker_fee_is_rejected() {
+        brokered_total(9950, 50, 100);
+    }
+
+    #[test]
+    fn test_orders_expire_by_deadline_or_age() {
+        let limit_order = schema::LimitOrder::new_builder().build();
+        let deadline = schema::Order::new_builder()
+            .set(
+                schema::LimitOrderWithDeadline::new_builder()
+                    .order(limit_order.clone())
+                    .deadline(100u64.pack())
+                    .build(),
+            )
+            .build();
+        assert!(order_expired(&deadline, 100));
+        assert!(!order_expired(&deadline, 99));
+        assert!(!order_expired(&deadline, (1 << 63) | 100));
+
+        let relative = |age: u64| (RELATIVE_BLOCK_NUMBER_SINCE << 56) | age;
+        let iceberg = schema::Order::new_builder()
+            .set(
+                schema::IcebergOrder::new_builder()
+                    .order(limit_order)
+                    .build(),
+            )
+            .build();
+        assert!(order_expired(&iceberg, relative(MAX_ORDER_AGE)));
+        assert!(!order_expired(&iceberg, relative(MAX_ORDER_AGE - 1)));
+        // Absolute since values say nothing about the age of the cell
+        assert!(!order_expired(&iceberg, MAX_ORDER_AGE));
+    }
+}
@@ -1247,8 +1247,8 @@ impl molecule::prelude::Builder for MarketOrderWithMinimumAskBuilder {
     }
 }
 #[derive(Clone)]
-pub struct Order(molecule::bytes::Bytes);
-impl ::core::fmt::LowerHex for Order {
+pub struct FillOrKillOrder(molecule::bytes::Bytes);
+impl ::core::fmt::LowerHex for FillOrKillOrder {
     fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
         use molecule::hex_string;
         if f.alternate() {
@@ -1257,56 +1257,73 @@ impl ::core::fmt::LowerHex for Order {
         write!(f, "{}", hex_string(self.as_slice()))
     }
 }
-impl ::core::fmt::Debug for Order {
+impl ::core::fmt::Debug for FillOrKillOrder {
     fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
         write!(f, "{}({:#x})", Self::NAME, self)
     }
 }
-impl ::core::fmt::Display for Order {
+impl ::core::fmt::Display for FillOrKillOrder {
     fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
-        write!(f, "{}(", Self::NAME)?;
-        self.to_enum().display_inner(f)?;
-        write!(f, ")")
+        write!(f, "{} {{ ", Self::NAME)?;
+        write!(f, "{}: {}", "order", self.order())?;
+        let extra_count = self.count_extra_fields();
+        if extra_count != 0 {
+            write!(f, ", .. ({} fields)", extra_count)?;
+        }
+        write!(f, " }}")
     }
 }
-impl ::core::default::Default for Order {
+impl ::core::default::Default for FillOrKillOrder {
     fn default() -> Self {
         let v = molecule::bytes::Bytes::from_static(&Self::DEFAULT_VALUE);
-        Order::new_unchecked(v)
+        FillOrKillOrder::new_unchecked(v)
     }
 }
-impl Order {
-    const DEFAULT_VALUE: [u8; 168] = [
-        0, 0, 0, 0, 164, 0, 0, 0, 28, 0, 0, 0, 60, 0, 0, 0, 76, 0, 0, 0, 108, 0, 0, 0, 124, 0, 0,
-        0, 156, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+impl FillOrKillOrder {
+    const DEFAULT_VALUE: [u8; 172] = [
+        172, 0, 0, 0, 8, 0, 0, 0, 164, 0, 0, 0, 28, 0, 0, 0, 60, 0, 0, 0, 76, 0, 0, 0, 108, 0, 0,
+        0, 124, 0, 0, 0, 156, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
         0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
         0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
         0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
-        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
     ];
-    pub const ITEMS_COUNT: usize = 4;
-    pub fn item_id(&self) -> molecule::Number {
-        molecule::unpack_number(self.as_slice())
+    pub const FIELD_COUNT: usize = 1;
+    pub fn total_size(&self) -> usize {
+        molecule::unpack_number(self.as_slice()) as usize
     }
-    pub fn to_enum(&self) -> OrderUnion {
-        let inner = self.0.slice(molecule::NUMBER_SIZE..);
-        match self.item_id() {
-            0 => LimitOrder::new_unchecked(inner).into(),
-            1 => LimitOrderWithDeadline::new_unchecked(inner).into(),
-            2 => MarketOrder::new_unchecked(inner).into(),
-            3 => MarketOrderWithMinimumAsk::new_unchecked(inner).into(),
-            _ => panic!("{}: invalid data", Self::NAME),
+    pub fn field_count(&self) -> usize {
+        if self.total_size() == molecule::NUMBER_SIZE {
+            0
+        } else {
+            (molecule::unpack_number(&self.as_slice()[molecule::NUMBER_SIZE..]) as usize / 4) - 1
         }
     }
-    pub fn as_reader<'r>(&'r self) -> OrderReader<'r> {
-        OrderReader::new_unchecked(self.as_slice())
+    pub fn count_extra_fields(&self) -> usize {
+        self.field_count() - Self::FIELD_COUNT
+    }
+    pub fn has_extra_fields(&self) -> bool {
+        Self::FIELD_COUNT != self.field_count()
+    }
+    pub fn order(&self) -> LimitOrder {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[4..]) as usize;
+        if self.has_extra_fields() {
+            let end = molecule::unpack_number(&slice[8..]) as usize;
+            LimitOrder::new_unchecked(self.0.slice(start..end))
+        } else {
+            LimitOrder::new_unchecked(self.0.slice(start..))
+        }
+    }
+    pub fn as_reader<'r>(&'r self) -> FillOrKillOrderReader<'r> {
+        FillOrKillOrderReader::new_unchecked(self.as_slice())
     }
 }
-impl molecule::prelude::Entity for Order {
-    type Builder = OrderBuilder;
-    const NAME: &'static str = "Order";
+impl molecule::prelude::Entity for FillOrKillOrder {
+    type Builder = FillOrKillOrderBuilder;
+    const NAME: &'static str = "FillOrKillOrder";
     fn new_unchecked(data: molecule::bytes::Bytes) -> Self {
-        Order(data)
+        FillOrKillOrder(data)
     }
     fn as_bytes(&self) -> molecule::bytes::Bytes {
         self.0.clone()
@@ -1315,21 +1332,21 @@ impl molecule::prelude::Entity for Order {
         &self.0[..]
     }
     fn from_slice(slice: &[u8]) -> molecule::error::VerificationResult<Self> {
-        OrderReader::from_slice(slice).map(|reader| reader.to_entity())
+        FillOrKillOrderReader::from_slice(slice).map(|reader| reader.to_entity())
     }
     fn from_compatible_slice(slice: &[u8]) -> molecule::error::VerificationResult<Self> {
-        OrderReader::from_compatible_slice(slice).map(|reader| reader.to_entity())
+        FillOrKillOrderReader::from_compatible_slice(slice).map(|reader| reader.to_entity())
     }
     fn new_builder() -> Self::Builder {
         ::core::default::Default::default()
     }
     fn as_builder(self) -> Self::Builder {
-        Self::new_builder().set(self.to_enum())
+        Self::new_builder().order(self.order())
     }
 }
 #[derive(Clone, Copy)]
-pub struct OrderReader<'r>(&'r [u8]);
-impl<'r> ::core::fmt::LowerHex for OrderReader<'r> {
+pub struct FillOrKillOrderReader<'r>(&'r [u8]);
+impl<'r> ::core::fmt::LowerHex for FillOrKillOrderReader<'r> {
     fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
         use molecule::hex_string;
         if f.alternate() {
@@ -1338,42 +1355,59 @@ impl<'r> ::core::fmt::LowerHex for OrderReader<'r> {
         write!(f, "{}", hex_string(self.as_slice()))
     }
 }
-impl<'r> ::core::fmt::Debug for OrderReader<'r> {
+impl<'r> ::core::fmt::Debug for FillOrKillOrderReader<'r> {
     fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
         write!(f, "{}({:#x})", Self::NAME, self)
     }
 }
-impl<'r> ::core::fmt::Display for OrderReader<'r> {
+impl<'r> ::core::fmt::Display for FillOrKillOrderReader<'r> {
     fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
-        write!(f, "{}(", Self::NAME)?;
-        self.to_enum().display_inner(f)?;
-        write!(f, ")")
+        write!(f, "{} {{ ", Self::NAME)?;
+        write!(f, "{}: {}", "order", self.order())?;
+        let extra_count = self.count_extra_fields();
+        if extra_count != 0 {
+            write!(f, ", .. ({} fields)", extra_count)?;
+        }
+        write!(f, " }}")
     }
 }
-impl<'r> OrderReader<'r> {
-    pub const ITEMS_COUNT: usize = 4;
-    pub fn item_id(&self) -> molecule::Number {
-        molecule::unpack_number(self.as_slice())
+impl<'r> FillOrKillOrderReader<'r> {
+    pub const FIELD_COUNT: usize = 1;
+    pub fn total_size(&self) -> usize {
+        molecule::unpack_number(self.as_slice()) as usize
     }
-    pub fn to_enum(&self) -> OrderUnionReader<'r> {
-        let inner = &self.as_slice()[molecule::NUMBER_SIZE..];
-        match self.item_id() {
-            0 => LimitOrderReader::new_unchecked(inner).into(),
-            1 => LimitOrderWithDeadlineReader::new_unchecked(inner).into(),
-            2 => MarketOrderReader::new_unchecked(inner).into(),
-            3 => MarketOrderWithMinimumAskReader::new_unchecked(inner).into(),
-            _ => panic!("{}: invalid data", Self::NAME),
+    pub fn field_count(&self) -> usize {
+        if self.total_size() == molecule::NUMBER_SIZE {
+            0
+        } else {
+            (molecule::unpack_number(&self.as_slice()[molecule::NUMBER_SIZE..]) as usize / 4) - 1
+        }
+    }
+    pub fn count_extra_fields(&self) -> usize {
+        self.field_count() - Self::FIELD_COUNT
+    }
+    pub fn has_extra_fields(&self) -> bool {
+        Self::FIELD_COUNT != self.field_count()
+    }
+    pub fn order(&self) -> LimitOrderReader<'r> {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[4..]) as usize;
+        if self.has_extra_fields() {
+            let end = molecule::unpack_number(&slice[8..]) as usize;
+            LimitOrderReader::new_unchecked(&self.as_slice()[start..end])
+        } else {
+            LimitOrderReader::new_unchecked(&self.as_slice()[start..])
         }
     }
 }
-impl<'r> molecule::prelude::Reader<'r> for OrderReader<'r> {
-    type Entity = Order;
-    const NAME: &'static str = "OrderReader";
+impl<'r> molecule::prelude::Reader<'r> for FillOrKillOrderReader<'r> {
+    type Entity = FillOrKillOrder;
+    const NAME: &'static str = "FillOrKillOrderReader";
     fn to_entity(&self) -> Self::Entity {
         Self::Entity::new_unchecked(self.as_slice().to_owned().into())
     }
     fn new_unchecked(slice: &'r [u8]) -> Self {
-        OrderReader(slice)
+        FillOrKillOrderReader(slice)
     }
     fn as_slice(&self) -> &'r [u8] {
         self.0
@@ -1384,259 +1418,4776 @@ impl<'r> molecule::prelude::Reader<'r> for OrderReader<'r> {
         if slice_len < molecule::NUMBER_SIZE {
             return ve!(Self, HeaderIsBroken, molecule::NUMBER_SIZE, slice_len);
         }
-        let item_id = molecule::unpack_number(slice);
-        let inner_slice = &slice[molecule::NUMBER_SIZE..];
-        match item_id {
-            0 => LimitOrderReader::verify(inner_slice, compatible),
-            1 => LimitOrderWithDeadlineReader::verify(inner_slice, compatible),
-            2 => MarketOrderReader::verify(inner_slice, compatible),
-            3 => MarketOrderWithMinimumAskReader::verify(inner_slice, compatible),
-            _ => ve!(Self, UnknownItem, Self::ITEMS_COUNT, item_id),
-        }?;
+        let total_size = molecule::unpack_number(slice) as usize;
+        if slice_len != total_size {
+            return ve!(Self, TotalSizeNotMatch, total_size, slice_len);
+        }
+        if slice_len < molecule::NUMBER_SIZE * 2 {
+            return ve!(Self, HeaderIsBroken, molecule::NUMBER_SIZE * 2, slice_len);
+        }
+        let offset_first = molecule::unpack_number(&slice[molecule::NUMBER_SIZE..]) as usize;
+        if offset_first % molecule::NUMBER_SIZE != 0 || offset_first < molecule::NUMBER_SIZE * 2 {
+            return ve!(Self, OffsetsNotMatch);
+        }
+        if slice_len < offset_first {
+            return ve!(Self, HeaderIsBroken, offset_first, slice_len);
+        }
+        let field_count = offset_first / molecule::NUMBER_SIZE - 1;
+        if field_count < Self::FIELD_COUNT {
+            return ve!(Self, FieldCountNotMatch, Self::FIELD_COUNT, field_count);
+        } else if !compatible && field_count > Self::FIELD_COUNT {
+            return ve!(Self, FieldCountNotMatch, Self::FIELD_COUNT, field_count);
+        };
+        let mut offsets: Vec<usize> = slice[molecule::NUMBER_SIZE..offset_first]
+            .chunks_exact(molecule::NUMBER_SIZE)
+            .map(|x| molecule::unpack_number(x) as usize)
+            .collect();
+        offsets.push(total_size);
+        if offsets.windows(2).any(|i| i[0] > i[1]) {
+            return ve!(Self, OffsetsNotMatch);
+        }
+        LimitOrderReader::verify(&slice[offsets[0]..offsets[1]], compatible)?;
         Ok(())
     }
 }
 #[derive(Debug, Default)]
-pub struct OrderBuilder(pub(crate) OrderUnion);
-impl OrderBuilder {
-    pub const ITEMS_COUNT: usize = 4;
-    pub fn set<I>(mut self, v: I) -> Self
-    where
-        I: ::core::convert::Into<OrderUnion>,
-    {
-        self.0 = v.into();
+pub struct FillOrKillOrderBuilder {
+    pub(crate) order: LimitOrder,
+}
+impl FillOrKillOrderBuilder {
+    pub const FIELD_COUNT: usize = 1;
+    pub fn order(mut self, v: LimitOrder) -> Self {
+        self.order = v;
         self
     }
 }
-impl molecule::prelude::Builder for OrderBuilder {
-    type Entity = Order;
-    const NAME: &'static str = "OrderBuilder";
+impl molecule::prelude::Builder for FillOrKillOrderBuilder {
+    type Entity = FillOrKillOrder;
+    const NAME: &'static str = "FillOrKillOrderBuilder";
     fn expected_length(&self) -> usize {
-        molecule::NUMBER_SIZE + self.0.as_slice().len()
+        molecule::NUMBER_SIZE * (Self::FIELD_COUNT + 1) + self.order.as_slice().len()
     }
     fn write<W: molecule::io::Write>(&self, writer: &mut W) -> molecule::io::Result<()> {
-        writer.write_all(&molecule::pack_number(self.0.item_id()))?;
-        writer.write_all(self.0.as_slice())
+        let mut total_size = molecule::NUMBER_SIZE * (Self::FIELD_COUNT + 1);
+        let mut offsets = Vec::with_capacity(Self::FIELD_COUNT);
+        offsets.push(total_size);
+        total_size += self.order.as_slice().len();
+        writer.write_all(&molecule::pack_number(total_size as molecule::Number))?;
+        for offset in offsets.into_iter() {
+            writer.write_all(&molecule::pack_number(offset as molecule::Number))?;
+        }
+        writer.write_all(self.order.as_slice())?;
+        Ok(())
     }
     fn build(&self) -> Self::Entity {
         let mut inner = Vec::with_capacity(self.expected_length());
         self.write(&mut inner)
             .unwrap_or_else(|_| panic!("{} build should be ok", Self::NAME));
-        Order::new_unchecked(inner.into())
+        FillOrKillOrder::new_unchecked(inner.into())
     }
 }
-#[derive(Debug, Clone)]
-pub enum OrderUnion {
-    LimitOrder(LimitOrder),
-    LimitOrderWithDeadline(LimitOrderWithDeadline),
-    MarketOrder(MarketOrder),
-    MarketOrderWithMinimumAsk(MarketOrderWithMinimumAsk),
-}
-#[derive(Debug, Clone, Copy)]
-pub enum OrderUnionReader<'r> {
-    LimitOrder(LimitOrderReader<'r>),
-    LimitOrderWithDeadline(LimitOrderWithDeadlineReader<'r>),
-    MarketOrder(MarketOrderReader<'r>),
-    MarketOrderWithMinimumAsk(MarketOrderWithMinimumAskReader<'r>),
+#[derive(Clone)]
+pub struct ImmediateOrCancelOrder(molecule::bytes::Bytes);
+impl ::core::fmt::LowerHex for ImmediateOrCancelOrder {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        use molecule::hex_string;
+        if f.alternate() {
+            write!(f, "0x")?;
+        }
+        write!(f, "{}", hex_string(self.as_slice()))
+    }
 }
-impl ::core::default::Default for OrderUnion {
-    fn default() -> Self {
-        OrderUnion::LimitOrder(::core::default::Default::default())
+impl ::core::fmt::Debug for ImmediateOrCancelOrder {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{}({:#x})", Self::NAME, self)
     }
 }
-impl ::core::fmt::Display for OrderUnion {
+impl ::core::fmt::Display for ImmediateOrCancelOrder {
     fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
-        match self {
-            OrderUnion::LimitOrder(ref item) => {
-                write!(f, "{}::{}({})", Self::NAME, LimitOrder::NAME, item)
-            }
-            OrderUnion::LimitOrderWithDeadline(ref item) => {
-                write!(
-                    f,
-                    "{}::{}({})",
-                    Self::NAME,
-                    LimitOrderWithDeadline::NAME,
-                    item
-                )
-            }
-            OrderUnion::MarketOrder(ref item) => {
-                write!(f, "{}::{}({})", Self::NAME, MarketOrder::NAME, item)
-            }
-            OrderUnion::MarketOrderWithMinimumAsk(ref item) => {
-                write!(
-                    f,
-                    "{}::{}({})",
-                    Self::NAME,
-                    MarketOrderWithMinimumAsk::NAME,
-                    item
-                )
-            }
+        write!(f, "{} {{ ", Self::NAME)?;
+        write!(f, "{}: {}", "order", self.order())?;
+        let extra_count = self.count_extra_fields();
+        if extra_count != 0 {
+            write!(f, ", .. ({} fields)", extra_count)?;
         }
+        write!(f, " }}")
     }
 }
-impl<'r> ::core::fmt::Display for OrderUnionReader<'r> {
-    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
-        match self {
-            OrderUnionReader::LimitOrder(ref item) => {
-                write!(f, "{}::{}({})", Self::NAME, LimitOrder::NAME, item)
-            }
-            OrderUnionReader::LimitOrderWithDeadline(ref item) => {
-                write!(
-                    f,
-                    "{}::{}({})",
-                    Self::NAME,
-                    LimitOrderWithDeadline::NAME,
-                    item
-                )
-            }
-            OrderUnionReader::MarketOrder(ref item) => {
+impl ::core::default::Default for ImmediateOrCancelOrder {
+    fn default() -> Self {
+        let v = molecule::bytes::Bytes::from_static(&Self::DEFAULT_VALUE);
+        ImmediateOrCancelOrder::new_unchecked(v)
+    }
+}
+impl ImmediateOrCancelOrder {
+    const DEFAULT_VALUE: [u8; 172] = [
+        172, 0, 0, 0, 8, 0, 0, 0, 164, 0, 0, 0, 28, 0, 0, 0, 60, 0, 0, 0, 76, 0, 0, 0, 108, 0, 0,
+        0, 124, 0, 0, 0, 156, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    ];
+    pub const FIELD_COUNT: usize = 1;
+    pub fn total_size(&self) -> usize {
+        molecule::unpack_number(self.as_slice()) as usize
+    }
+    pub fn field_count(&self) -> usize {
+        if self.total_size() == molecule::NUMBER_SIZE {
+            0
+        } else {
+            (molecule::unpack_number(&self.as_slice()[molecule::NUMBER_SIZE..]) as usize / 4) - 1
+        }
+    }
+    pub fn count_extra_fields(&self) -> usize {
+        self.field_count() - Self::FIELD_COUNT
+    }
+    pub fn has_extra_fields(&self) -> bool {
+        Self::FIELD_COUNT != self.field_count()
+    }
+    pub fn order(&self) -> LimitOrder {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[4..]) as usize;
+        if self.has_extra_fields() {
+            let end = molecule::unpack_number(&slice[8..]) as usize;
+            LimitOrder::new_unchecked(self.0.slice(start..end))
+        } else {
+            LimitOrder::new_unchecked(self.0.slice(start..))
+        }
+    }
+    pub fn as_reader<'r>(&'r self) -> ImmediateOrCancelOrderReader<'r> {
+        ImmediateOrCancelOrderReader::new_unchecked(self.as_slice())
+    }
+}
+impl molecule::prelude::Entity for ImmediateOrCancelOrder {
+    type Builder = ImmediateOrCancelOrderBuilder;
+    const NAME: &'static str = "ImmediateOrCancelOrder";
+    fn new_unchecked(data: molecule::bytes::Bytes) -> Self {
+        ImmediateOrCancelOrder(data)
+    }
+    fn as_bytes(&self) -> molecule::bytes::Bytes {
+        self.0.clone()
+    }
+    fn as_slice(&self) -> &[u8] {
+        &self.0[..]
+    }
+    fn from_slice(slice: &[u8]) -> molecule::error::VerificationResult<Self> {
+        ImmediateOrCancelOrderReader::from_slice(slice).map(|reader| reader.to_entity())
+    }
+    fn from_compatible_slice(slice: &[u8]) -> molecule::error::VerificationResult<Self> {
+        ImmediateOrCancelOrderReader::from_compatible_slice(slice).map(|reader| reader.to_entity())
+    }
+    fn new_builder() -> Self::Builder {
+        ::core::default::Default::default()
+    }
+    fn as_builder(self) -> Self::Builder {
+        Self::new_builder().order(self.order())
+    }
+}
+#[derive(Clone, Copy)]
+pub struct ImmediateOrCancelOrderReader<'r>(&'r [u8]);
+impl<'r> ::core::fmt::LowerHex for ImmediateOrCancelOrderReader<'r> {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        use molecule::hex_string;
+        if f.alternate() {
+            write!(f, "0x")?;
+        }
+        write!(f, "{}", hex_string(self.as_slice()))
+    }
+}
+impl<'r> ::core::fmt::Debug for ImmediateOrCancelOrderReader<'r> {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{}({:#x})", Self::NAME, self)
+    }
+}
+impl<'r> ::core::fmt::Display for ImmediateOrCancelOrderReader<'r> {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{} {{ ", Self::NAME)?;
+        write!(f, "{}: {}", "order", self.order())?;
+        let extra_count = self.count_extra_fields();
+        if extra_count != 0 {
+            write!(f, ", .. ({} fields)", extra_count)?;
+        }
+        write!(f, " }}")
+    }
+}
+impl<'r> ImmediateOrCancelOrderReader<'r> {
+    pub const FIELD_COUNT: usize = 1;
+    pub fn total_size(&self) -> usize {
+        molecule::unpack_number(self.as_slice()) as usize
+    }
+    pub fn field_count(&self) -> usize {
+        if self.total_size() == molecule::NUMBER_SIZE {
+            0
+        } else {
+            (molecule::unpack_number(&self.as_slice()[molecule::NUMBER_SIZE..]) as usize / 4) - 1
+        }
+    }
+    pub fn count_extra_fields(&self) -> usize {
+        self.field_count() - Self::FIELD_COUNT
+    }
+    pub fn has_extra_fields(&self) -> bool {
+        Self::FIELD_COUNT != self.field_count()
+    }
+    pub fn order(&self) -> LimitOrderReader<'r> {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[4..]) as usize;
+        if self.has_extra_fields() {
+            let end = molecule::unpack_number(&slice[8..]) as usize;
+            LimitOrderReader::new_unchecked(&self.as_slice()[start..end])
+        } else {
+            LimitOrderReader::new_unchecked(&self.as_slice()[start..])
+        }
+    }
+}
+impl<'r> molecule::prelude::Reader<'r> for ImmediateOrCancelOrderReader<'r> {
+    type Entity = ImmediateOrCancelOrder;
+    const NAME: &'static str = "ImmediateOrCancelOrderReader";
+    fn to_entity(&self) -> Self::Entity {
+        Self::Entity::new_unchecked(self.as_slice().to_owned().into())
+    }
+    fn new_unchecked(slice: &'r [u8]) -> Self {
+        ImmediateOrCancelOrderReader(slice)
+    }
+    fn as_slice(&self) -> &'r [u8] {
+        self.0
+    }
+    fn verify(slice: &[u8], compatible: bool) -> molecule::error::VerificationResult<()> {
+        use molecule::verification_error as ve;
+        let slice_len = slice.len();
+        if slice_len < molecule::NUMBER_SIZE {
+            return ve!(Self, HeaderIsBroken, molecule::NUMBER_SIZE, slice_len);
+        }
+        let total_size = molecule::unpack_number(slice) as usize;
+        if slice_len != total_size {
+            return ve!(Self, TotalSizeNotMatch, total_size, slice_len);
+        }
+        if slice_len < molecule::NUMBER_SIZE * 2 {
+            return ve!(Self, HeaderIsBroken, molecule::NUMBER_SIZE * 2, slice_len);
+        }
+        let offset_first = molecule::unpack_number(&slice[molecule::NUMBER_SIZE..]) as usize;
+        if offset_first % molecule::NUMBER_SIZE != 0 || offset_first < molecule::NUMBER_SIZE * 2 {
+            return ve!(Self, OffsetsNotMatch);
+        }
+        if slice_len < offset_first {
+            return ve!(Self, HeaderIsBroken, offset_first, slice_len);
+        }
+        let field_count = offset_first / molecule::NUMBER_SIZE - 1;
+        if field_count < Self::FIELD_COUNT {
+            return ve!(Self, FieldCountNotMatch, Self::FIELD_COUNT, field_count);
+        } else if !compatible && field_count > Self::FIELD_COUNT {
+            return ve!(Self, FieldCountNotMatch, Self::FIELD_COUNT, field_count);
+        };
+        let mut offsets: Vec<usize> = slice[molecule::NUMBER_SIZE..offset_first]
+            .chunks_exact(molecule::NUMBER_SIZE)
+            .map(|x| molecule::unpack_number(x) as usize)
+            .collect();
+        offsets.push(total_size);
+        if offsets.windows(2).any(|i| i[0] > i[1]) {
+            return ve!(Self, OffsetsNotMatch);
+        }
+        LimitOrderReader::verify(&slice[offsets[0]..offsets[1]], compatible)?;
+        Ok(())
+    }
+}
+#[derive(Debug, Default)]
+pub struct ImmediateOrCancelOrderBuilder {
+    pub(crate) order: LimitOrder,
+}
+impl ImmediateOrCancelOrderBuilder {
+    pub const FIELD_COUNT: usize = 1;
+    pub fn order(mut self, v: LimitOrder) -> Self {
+        self.order = v;
+        self
+    }
+}
+impl molecule::prelude::Builder for ImmediateOrCancelOrderBuilder {
+    type Entity = ImmediateOrCancelOrder;
+    const NAME: &'static str = "ImmediateOrCancelOrderBuilder";
+    fn expected_length(&self) -> usize {
+        molecule::NUMBER_SIZE * (Self::FIELD_COUNT + 1) + self.order.as_slice().len()
+    }
+    fn write<W: molecule::io::Write>(&self, writer: &mut W) -> molecule::io::Result<()> {
+        let mut total_size = molecule::NUMBER_SIZE * (Self::FIELD_COUNT + 1);
+        let mut offsets = Vec::with_capacity(Self::FIELD_COUNT);
+        offsets.push(total_size);
+        total_size += self.order.as_slice().len();
+        writer.write_all(&molecule::pack_number(total_size as molecule::Number))?;
+        for offset in offsets.into_iter() {
+            writer.write_all(&molecule::pack_number(offset as molecule::Number))?;
+        }
+        writer.write_all(self.order.as_slice())?;
+        Ok(())
+    }
+    fn build(&self) -> Self::Entity {
+        let mut inner = Vec::with_capacity(self.expected_length());
+        self.write(&mut inner)
+            .unwrap_or_else(|_| panic!("{} build should be ok", Self::NAME));
+        ImmediateOrCancelOrder::new_unchecked(inner.into())
+    }
+}
+#[derive(Clone)]
+pub struct MinFillLimitOrder(molecule::bytes::Bytes);
+impl ::core::fmt::LowerHex for MinFillLimitOrder {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        use molecule::hex_string;
+        if f.alternate() {
+            write!(f, "0x")?;
+        }
+        write!(f, "{}", hex_string(self.as_slice()))
+    }
+}
+impl ::core::fmt::Debug for MinFillLimitOrder {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{}({:#x})", Self::NAME, self)
+    }
+}
+impl ::core::fmt::Display for MinFillLimitOrder {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{} {{ ", Self::NAME)?;
+        write!(f, "{}: {}", "order", self.order())?;
+        write!(f, ", {}: {}", "min_fill", self.min_fill())?;
+        let extra_count = self.count_extra_fields();
+        if extra_count != 0 {
+            write!(f, ", .. ({} fields)", extra_count)?;
+        }
+        write!(f, " }}")
+    }
+}
+impl ::core::default::Default for MinFillLimitOrder {
+    fn default() -> Self {
+        let v = molecule::bytes::Bytes::from_static(&Self::DEFAULT_VALUE);
+        MinFillLimitOrder::new_unchecked(v)
+    }
+}
+impl MinFillLimitOrder {
+    const DEFAULT_VALUE: [u8; 192] = [
+        192, 0, 0, 0, 12, 0, 0, 0, 176, 0, 0, 0, 164, 0, 0, 0, 28, 0, 0, 0, 60, 0, 0, 0, 76, 0, 0,
+        0, 108, 0, 0, 0, 124, 0, 0, 0, 156, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    ];
+    pub const FIELD_COUNT: usize = 2;
+    pub fn total_size(&self) -> usize {
+        molecule::unpack_number(self.as_slice()) as usize
+    }
+    pub fn field_count(&self) -> usize {
+        if self.total_size() == molecule::NUMBER_SIZE {
+            0
+        } else {
+            (molecule::unpack_number(&self.as_slice()[molecule::NUMBER_SIZE..]) as usize / 4) - 1
+        }
+    }
+    pub fn count_extra_fields(&self) -> usize {
+        self.field_count() - Self::FIELD_COUNT
+    }
+    pub fn has_extra_fields(&self) -> bool {
+        Self::FIELD_COUNT != self.field_count()
+    }
+    pub fn order(&self) -> LimitOrder {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[4..]) as usize;
+        let end = molecule::unpack_number(&slice[8..]) as usize;
+        LimitOrder::new_unchecked(self.0.slice(start..end))
+    }
+    pub fn min_fill(&self) -> Uint128 {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[8..]) as usize;
+        if self.has_extra_fields() {
+            let end = molecule::unpack_number(&slice[12..]) as usize;
+            Uint128::new_unchecked(self.0.slice(start..end))
+        } else {
+            Uint128::new_unchecked(self.0.slice(start..))
+        }
+    }
+    pub fn as_reader<'r>(&'r self) -> MinFillLimitOrderReader<'r> {
+        MinFillLimitOrderReader::new_unchecked(self.as_slice())
+    }
+}
+impl molecule::prelude::Entity for MinFillLimitOrder {
+    type Builder = MinFillLimitOrderBuilder;
+    const NAME: &'static str = "MinFillLimitOrder";
+    fn new_unchecked(data: molecule::bytes::Bytes) -> Self {
+        MinFillLimitOrder(data)
+    }
+    fn as_bytes(&self) -> molecule::bytes::Bytes {
+        self.0.clone()
+    }
+    fn as_slice(&self) -> &[u8] {
+        &self.0[..]
+    }
+    fn from_slice(slice: &[u8]) -> molecule::error::VerificationResult<Self> {
+        MinFillLimitOrderReader::from_slice(slice).map(|reader| reader.to_entity())
+    }
+    fn from_compatible_slice(slice: &[u8]) -> molecule::error::VerificationResult<Self> {
+        MinFillLimitOrderReader::from_compatible_slice(slice).map(|reader| reader.to_entity())
+    }
+    fn new_builder() -> Self::Builder {
+        ::core::default::Default::default()
+    }
+    fn as_builder(self) -> Self::Builder {
+        Self::new_builder()
+            .order(self.order())
+            .min_fill(self.min_fill())
+    }
+}
+#[derive(Clone, Copy)]
+pub struct MinFillLimitOrderReader<'r>(&'r [u8]);
+impl<'r> ::core::fmt::LowerHex for MinFillLimitOrderReader<'r> {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        use molecule::hex_string;
+        if f.alternate() {
+            write!(f, "0x")?;
+        }
+        write!(f, "{}", hex_string(self.as_slice()))
+    }
+}
+impl<'r> ::core::fmt::Debug for MinFillLimitOrderReader<'r> {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{}({:#x})", Self::NAME, self)
+    }
+}
+impl<'r> ::core::fmt::Display for MinFillLimitOrderReader<'r> {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{} {{ ", Self::NAME)?;
+        write!(f, "{}: {}", "order", self.order())?;
+        write!(f, ", {}: {}", "min_fill", self.min_fill())?;
+        let extra_count = self.count_extra_fields();
+        if extra_count != 0 {
+            write!(f, ", .. ({} fields)", extra_count)?;
+        }
+        write!(f, " }}")
+    }
+}
+impl<'r> MinFillLimitOrderReader<'r> {
+    pub const FIELD_COUNT: usize = 2;
+    pub fn total_size(&self) -> usize {
+        molecule::unpack_number(self.as_slice()) as usize
+    }
+    pub fn field_count(&self) -> usize {
+        if self.total_size() == molecule::NUMBER_SIZE {
+            0
+        } else {
+            (molecule::unpack_number(&self.as_slice()[molecule::NUMBER_SIZE..]) as usize / 4) - 1
+        }
+    }
+    pub fn count_extra_fields(&self) -> usize {
+        self.field_count() - Self::FIELD_COUNT
+    }
+    pub fn has_extra_fields(&self) -> bool {
+        Self::FIELD_COUNT != self.field_count()
+    }
+    pub fn order(&self) -> LimitOrderReader<'r> {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[4..]) as usize;
+        let end = molecule::unpack_number(&slice[8..]) as usize;
+        LimitOrderReader::new_unchecked(&self.as_slice()[start..end])
+    }
+    pub fn min_fill(&self) -> Uint128Reader<'r> {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[8..]) as usize;
+        if self.has_extra_fields() {
+            let end = molecule::unpack_number(&slice[12..]) as usize;
+            Uint128Reader::new_unchecked(&self.as_slice()[start..end])
+        } else {
+            Uint128Reader::new_unchecked(&self.as_slice()[start..])
+        }
+    }
+}
+impl<'r> molecule::prelude::Reader<'r> for MinFillLimitOrderReader<'r> {
+    type Entity = MinFillLimitOrder;
+    const NAME: &'static str = "MinFillLimitOrderReader";
+    fn to_entity(&self) -> Self::Entity {
+        Self::Entity::new_unchecked(self.as_slice().to_owned().into())
+    }
+    fn new_unchecked(slice: &'r [u8]) -> Self {
+        MinFillLimitOrderReader(slice)
+    }
+    fn as_slice(&self) -> &'r [u8] {
+        self.0
+    }
+    fn verify(slice: &[u8], compatible: bool) -> molecule::error::VerificationResult<()> {
+        use molecule::verification_error as ve;
+        let slice_len = slice.len();
+        if slice_len < molecule::NUMBER_SIZE {
+            return ve!(Self, HeaderIsBroken, molecule::NUMBER_SIZE, slice_len);
+        }
+        let total_size = molecule::unpack_number(slice) as usize;
+        if slice_len != total_size {
+            return ve!(Self, TotalSizeNotMatch, total_size, slice_len);
+        }
+        if slice_len < molecule::NUMBER_SIZE * 2 {
+            return ve!(Self, HeaderIsBroken, molecule::NUMBER_SIZE * 2, slice_len);
+        }
+        let offset_first = molecule::unpack_number(&slice[molecule::NUMBER_SIZE..]) as usize;
+        if offset_first % molecule::NUMBER_SIZE != 0 || offset_first < molecule::NUMBER_SIZE * 2 {
+            return ve!(Self, OffsetsNotMatch);
+        }
+        if slice_len < offset_first {
+            return ve!(Self, HeaderIsBroken, offset_first, slice_len);
+        }
+        let field_count = offset_first / molecule::NUMBER_SIZE - 1;
+        if field_count < Self::FIELD_COUNT {
+            return ve!(Self, FieldCountNotMatch, Self::FIELD_COUNT, field_count);
+        } else if !compatible && field_count > Self::FIELD_COUNT {
+            return ve!(Self, FieldCountNotMatch, Self::FIELD_COUNT, field_count);
+        };
+        let mut offsets: Vec<usize> = slice[molecule::NUMBER_SIZE..offset_first]
+            .chunks_exact(molecule::NUMBER_SIZE)
+            .map(|x| molecule::unpack_number(x) as usize)
+            .collect();
+        offsets.push(total_size);
+        if offsets.windows(2).any(|i| i[0] > i[1]) {
+            return ve!(Self, OffsetsNotMatch);
+        }
+        LimitOrderReader::verify(&slice[offsets[0]..offsets[1]], compatible)?;
+        Uint128Reader::verify(&slice[offsets[1]..offsets[2]], compatible)?;
+        Ok(())
+    }
+}
+#[derive(Debug, Default)]
+pub struct MinFillLimitOrderBuilder {
+    pub(crate) order: LimitOrder,
+    pub(crate) min_fill: Uint128,
+}
+impl MinFillLimitOrderBuilder {
+    pub const FIELD_COUNT: usize = 2;
+    pub fn order(mut self, v: LimitOrder) -> Self {
+        self.order = v;
+        self
+    }
+    pub fn min_fill(mut self, v: Uint128) -> Self {
+        self.min_fill = v;
+        self
+    }
+}
+impl molecule::prelude::Builder for MinFillLimitOrderBuilder {
+    type Entity = MinFillLimitOrder;
+    const NAME: &'static str = "MinFillLimitOrderBuilder";
+    fn expected_length(&self) -> usize {
+        molecule::NUMBER_SIZE * (Self::FIELD_COUNT + 1)
+            + self.order.as_slice().len()
+            + self.min_fill.as_slice().len()
+    }
+    fn write<W: molecule::io::Write>(&self, writer: &mut W) -> molecule::io::Result<()> {
+        let mut total_size = molecule::NUMBER_SIZE * (Self::FIELD_COUNT + 1);
+        let mut offsets = Vec::with_capacity(Self::FIELD_COUNT);
+        offsets.push(total_size);
+        total_size += self.order.as_slice().len();
+        offsets.push(total_size);
+        total_size += self.min_fill.as_slice().len();
+        writer.write_all(&molecule::pack_number(total_size as molecule::Number))?;
+        for offset in offsets.into_iter() {
+            writer.write_all(&molecule::pack_number(offset as molecule::Number))?;
+        }
+        writer.write_all(self.order.as_slice())?;
+        writer.write_all(self.min_fill.as_slice())?;
+        Ok(())
+    }
+    fn build(&self) -> Self::Entity {
+        let mut inner = Vec::with_capacity(self.expected_length());
+        self.write(&mut inner)
+            .unwrap_or_else(|_| panic!("{} build should be ok", Self::NAME));
+        MinFillLimitOrder::new_unchecked(inner.into())
+    }
+}
+#[derive(Clone)]
+pub struct StopLimitOrder(molecule::bytes::Bytes);
+impl ::core::fmt::LowerHex for StopLimitOrder {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        use molecule::hex_string;
+        if f.alternate() {
+            write!(f, "0x")?;
+        }
+        write!(f, "{}", hex_string(self.as_slice()))
+    }
+}
+impl ::core::fmt::Debug for StopLimitOrder {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{}({:#x})", Self::NAME, self)
+    }
+}
+impl ::core::fmt::Display for StopLimitOrder {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{} {{ ", Self::NAME)?;
+        write!(f, "{}: {}", "order", self.order())?;
+        write!(f, ", {}: {}", "oracle", self.oracle())?;
+        write!(
+            f,
+            ", {}: {}",
+            "trigger_bid_amount",
+            self.trigger_bid_amount()
+        )?;
+        write!(
+            f,
+            ", {}: {}",
+            "trigger_ask_amount",
+            self.trigger_ask_amount()
+        )?;
+        let extra_count = self.count_extra_fields();
+        if extra_count != 0 {
+            write!(f, ", .. ({} fields)", extra_count)?;
+        }
+        write!(f, " }}")
+    }
+}
+impl ::core::default::Default for StopLimitOrder {
+    fn default() -> Self {
+        let v = molecule::bytes::Bytes::from_static(&Self::DEFAULT_VALUE);
+        StopLimitOrder::new_unchecked(v)
+    }
+}
+impl StopLimitOrder {
+    const DEFAULT_VALUE: [u8; 248] = [
+        248, 0, 0, 0, 20, 0, 0, 0, 184, 0, 0, 0, 216, 0, 0, 0, 232, 0, 0, 0, 164, 0, 0, 0, 28, 0,
+        0, 0, 60, 0, 0, 0, 76, 0, 0, 0, 108, 0, 0, 0, 124, 0, 0, 0, 156, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    ];
+    pub const FIELD_COUNT: usize = 4;
+    pub fn total_size(&self) -> usize {
+        molecule::unpack_number(self.as_slice()) as usize
+    }
+    pub fn field_count(&self) -> usize {
+        if self.total_size() == molecule::NUMBER_SIZE {
+            0
+        } else {
+            (molecule::unpack_number(&self.as_slice()[molecule::NUMBER_SIZE..]) as usize / 4) - 1
+        }
+    }
+    pub fn count_extra_fields(&self) -> usize {
+        self.field_count() - Self::FIELD_COUNT
+    }
+    pub fn has_extra_fields(&self) -> bool {
+        Self::FIELD_COUNT != self.field_count()
+    }
+    pub fn order(&self) -> LimitOrder {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[4..]) as usize;
+        let end = molecule::unpack_number(&slice[8..]) as usize;
+        LimitOrder::new_unchecked(self.0.slice(start..end))
+    }
+    pub fn oracle(&self) -> Byte32 {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[8..]) as usize;
+        let end = molecule::unpack_number(&slice[12..]) as usize;
+        Byte32::new_unchecked(self.0.slice(start..end))
+    }
+    pub fn trigger_bid_amount(&self) -> Uint128 {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[12..]) as usize;
+        let end = molecule::unpack_number(&slice[16..]) as usize;
+        Uint128::new_unchecked(self.0.slice(start..end))
+    }
+    pub fn trigger_ask_amount(&self) -> Uint128 {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[16..]) as usize;
+        if self.has_extra_fields() {
+            let end = molecule::unpack_number(&slice[20..]) as usize;
+            Uint128::new_unchecked(self.0.slice(start..end))
+        } else {
+            Uint128::new_unchecked(self.0.slice(start..))
+        }
+    }
+    pub fn as_reader<'r>(&'r self) -> StopLimitOrderReader<'r> {
+        StopLimitOrderReader::new_unchecked(self.as_slice())
+    }
+}
+impl molecule::prelude::Entity for StopLimitOrder {
+    type Builder = StopLimitOrderBuilder;
+    const NAME: &'static str = "StopLimitOrder";
+    fn new_unchecked(data: molecule::bytes::Bytes) -> Self {
+        StopLimitOrder(data)
+    }
+    fn as_bytes(&self) -> molecule::bytes::Bytes {
+        self.0.clone()
+    }
+    fn as_slice(&self) -> &[u8] {
+        &self.0[..]
+    }
+    fn from_slice(slice: &[u8]) -> molecule::error::VerificationResult<Self> {
+        StopLimitOrderReader::from_slice(slice).map(|reader| reader.to_entity())
+    }
+    fn from_compatible_slice(slice: &[u8]) -> molecule::error::VerificationResult<Self> {
+        StopLimitOrderReader::from_compatible_slice(slice).map(|reader| reader.to_entity())
+    }
+    fn new_builder() -> Self::Builder {
+        ::core::default::Default::default()
+    }
+    fn as_builder(self) -> Self::Builder {
+        Self::new_builder()
+            .order(self.order())
+            .oracle(self.oracle())
+            .trigger_bid_amount(self.trigger_bid_amount())
+            .trigger_ask_amount(self.trigger_ask_amount())
+    }
+}
+#[derive(Clone, Copy)]
+pub struct StopLimitOrderReader<'r>(&'r [u8]);
+impl<'r> ::core::fmt::LowerHex for StopLimitOrderReader<'r> {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        use molecule::hex_string;
+        if f.alternate() {
+            write!(f, "0x")?;
+        }
+        write!(f, "{}", hex_string(self.as_slice()))
+    }
+}
+impl<'r> ::core::fmt::Debug for StopLimitOrderReader<'r> {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{}({:#x})", Self::NAME, self)
+    }
+}
+impl<'r> ::core::fmt::Display for StopLimitOrderReader<'r> {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{} {{ ", Self::NAME)?;
+        write!(f, "{}: {}", "order", self.order())?;
+        write!(f, ", {}: {}", "oracle", self.oracle())?;
+        write!(
+            f,
+            ", {}: {}",
+            "trigger_bid_amount",
+            self.trigger_bid_amount()
+        )?;
+        write!(
+            f,
+            ", {}: {}",
+            "trigger_ask_amount",
+            self.trigger_ask_amount()
+        )?;
+        let extra_count = self.count_extra_fields();
+        if extra_count != 0 {
+            write!(f, ", .. ({} fields)", extra_count)?;
+        }
+        write!(f, " }}")
+    }
+}
+impl<'r> StopLimitOrderReader<'r> {
+    pub const FIELD_COUNT: usize = 4;
+    pub fn total_size(&self) -> usize {
+        molecule::unpack_number(self.as_slice()) as usize
+    }
+    pub fn field_count(&self) -> usize {
+        if self.total_size() == molecule::NUMBER_SIZE {
+            0
+        } else {
+            (molecule::unpack_number(&self.as_slice()[molecule::NUMBER_SIZE..]) as usize / 4) - 1
+        }
+    }
+    pub fn count_extra_fields(&self) -> usize {
+        self.field_count() - Self::FIELD_COUNT
+    }
+    pub fn has_extra_fields(&self) -> bool {
+        Self::FIELD_COUNT != self.field_count()
+    }
+    pub fn order(&self) -> LimitOrderReader<'r> {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[4..]) as usize;
+        let end = molecule::unpack_number(&slice[8..]) as usize;
+        LimitOrderReader::new_unchecked(&self.as_slice()[start..end])
+    }
+    pub fn oracle(&self) -> Byte32Reader<'r> {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[8..]) as usize;
+        let end = molecule::unpack_number(&slice[12..]) as usize;
+        Byte32Reader::new_unchecked(&self.as_slice()[start..end])
+    }
+    pub fn trigger_bid_amount(&self) -> Uint128Reader<'r> {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[12..]) as usize;
+        let end = molecule::unpack_number(&slice[16..]) as usize;
+        Uint128Reader::new_unchecked(&self.as_slice()[start..end])
+    }
+    pub fn trigger_ask_amount(&self) -> Uint128Reader<'r> {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[16..]) as usize;
+        if self.has_extra_fields() {
+            let end = molecule::unpack_number(&slice[20..]) as usize;
+            Uint128Reader::new_unchecked(&self.as_slice()[start..end])
+        } else {
+            Uint128Reader::new_unchecked(&self.as_slice()[start..])
+        }
+    }
+}
+impl<'r> molecule::prelude::Reader<'r> for StopLimitOrderReader<'r> {
+    type Entity = StopLimitOrder;
+    const NAME: &'static str = "StopLimitOrderReader";
+    fn to_entity(&self) -> Self::Entity {
+        Self::Entity::new_unchecked(self.as_slice().to_owned().into())
+    }
+    fn new_unchecked(slice: &'r [u8]) -> Self {
+        StopLimitOrderReader(slice)
+    }
+    fn as_slice(&self) -> &'r [u8] {
+        self.0
+    }
+    fn verify(slice: &[u8], compatible: bool) -> molecule::error::VerificationResult<()> {
+        use molecule::verification_error as ve;
+        let slice_len = slice.len();
+        if slice_len < molecule::NUMBER_SIZE {
+            return ve!(Self, HeaderIsBroken, molecule::NUMBER_SIZE, slice_len);
+        }
+        let total_size = molecule::unpack_number(slice) as usize;
+        if slice_len != total_size {
+            return ve!(Self, TotalSizeNotMatch, total_size, slice_len);
+        }
+        if slice_len < molecule::NUMBER_SIZE * 2 {
+            return ve!(Self, HeaderIsBroken, molecule::NUMBER_SIZE * 2, slice_len);
+        }
+        let offset_first = molecule::unpack_number(&slice[molecule::NUMBER_SIZE..]) as usize;
+        if offset_first % molecule::NUMBER_SIZE != 0 || offset_first < molecule::NUMBER_SIZE * 2 {
+            return ve!(Self, OffsetsNotMatch);
+        }
+        if slice_len < offset_first {
+            return ve!(Self, HeaderIsBroken, offset_first, slice_len);
+        }
+        let field_count = offset_first / molecule::NUMBER_SIZE - 1;
+        if field_count < Self::FIELD_COUNT {
+            return ve!(Self, FieldCountNotMatch, Self::FIELD_COUNT, field_count);
+        } else if !compatible && field_count > Self::FIELD_COUNT {
+            return ve!(Self, FieldCountNotMatch, Self::FIELD_COUNT, field_count);
+        };
+        let mut offsets: Vec<usize> = slice[molecule::NUMBER_SIZE..offset_first]
+            .chunks_exact(molecule::NUMBER_SIZE)
+            .map(|x| molecule::unpack_number(x) as usize)
+            .collect();
+        offsets.push(total_size);
+        if offsets.windows(2).any(|i| i[0] > i[1]) {
+            return ve!(Self, OffsetsNotMatch);
+        }
+        LimitOrderReader::verify(&slice[offsets[0]..offsets[1]], compatible)?;
+        Byte32Reader::verify(&slice[offsets[1]..offsets[2]], compatible)?;
+        Uint128Reader::verify(&slice[offsets[2]..offsets[3]], compatible)?;
+        Uint128Reader::verify(&slice[offsets[3]..offsets[4]], compatible)?;
+        Ok(())
+    }
+}
+#[derive(Debug, Default)]
+pub struct StopLimitOrderBuilder {
+    pub(crate) order: LimitOrder,
+    pub(crate) oracle: Byte32,
+    pub(crate) trigger_bid_amount: Uint128,
+    pub(crate) trigger_ask_amount: Uint128,
+}
+impl StopLimitOrderBuilder {
+    pub const FIELD_COUNT: usize = 4;
+    pub fn order(mut self, v: LimitOrder) -> Self {
+        self.order = v;
+        self
+    }
+    pub fn oracle(mut self, v: Byte32) -> Self {
+        self.oracle = v;
+        self
+    }
+    pub fn trigger_bid_amount(mut self, v: Uint128) -> Self {
+        self.trigger_bid_amount = v;
+        self
+    }
+    pub fn trigger_ask_amount(mut self, v: Uint128) -> Self {
+        self.trigger_ask_amount = v;
+        self
+    }
+}
+impl molecule::prelude::Builder for StopLimitOrderBuilder {
+    type Entity = StopLimitOrder;
+    const NAME: &'static str = "StopLimitOrderBuilder";
+    fn expected_length(&self) -> usize {
+        molecule::NUMBER_SIZE * (Self::FIELD_COUNT + 1)
+            + self.order.as_slice().len()
+            + self.oracle.as_slice().len()
+            + self.trigger_bid_amount.as_slice().len()
+            + self.trigger_ask_amount.as_slice().len()
+    }
+    fn write<W: molecule::io::Write>(&self, writer: &mut W) -> molecule::io::Result<()> {
+        let mut total_size = molecule::NUMBER_SIZE * (Self::FIELD_COUNT + 1);
+        let mut offsets = Vec::with_capacity(Self::FIELD_COUNT);
+        offsets.push(total_size);
+        total_size += self.order.as_slice().len();
+        offsets.push(total_size);
+        total_size += self.oracle.as_slice().len();
+        offsets.push(total_size);
+        total_size += self.trigger_bid_amount.as_slice().len();
+        offsets.push(total_size);
+        total_size += self.trigger_ask_amount.as_slice().len();
+        writer.write_all(&molecule::pack_number(total_size as molecule::Number))?;
+        for offset in offsets.into_iter() {
+            writer.write_all(&molecule::pack_number(offset as molecule::Number))?;
+        }
+        writer.write_all(self.order.as_slice())?;
+        writer.write_all(self.oracle.as_slice())?;
+        writer.write_all(self.trigger_bid_amount.as_slice())?;
+        writer.write_all(self.trigger_ask_amount.as_slice())?;
+        Ok(())
+    }
+    fn build(&self) -> Self::Entity {
+        let mut inner = Vec::with_capacity(self.expected_length());
+        self.write(&mut inner)
+            .unwrap_or_else(|_| panic!("{} build should be ok", Self::NAME));
+        StopLimitOrder::new_unchecked(inner.into())
+    }
+}
+#[derive(Clone)]
+pub struct IcebergOrder(molecule::bytes::Bytes);
+impl ::core::fmt::LowerHex for IcebergOrder {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        use molecule::hex_string;
+        if f.alternate() {
+            write!(f, "0x")?;
+        }
+        write!(f, "{}", hex_string(self.as_slice()))
+    }
+}
+impl ::core::fmt::Debug for IcebergOrder {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{}({:#x})", Self::NAME, self)
+    }
+}
+impl ::core::fmt::Display for IcebergOrder {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{} {{ ", Self::NAME)?;
+        write!(f, "{}: {}", "order", self.order())?;
+        write!(f, ", {}: {}", "display_amount", self.display_amount())?;
+        let extra_count = self.count_extra_fields();
+        if extra_count != 0 {
+            write!(f, ", .. ({} fields)", extra_count)?;
+        }
+        write!(f, " }}")
+    }
+}
+impl ::core::default::Default for IcebergOrder {
+    fn default() -> Self {
+        let v = molecule::bytes::Bytes::from_static(&Self::DEFAULT_VALUE);
+        IcebergOrder::new_unchecked(v)
+    }
+}
+impl IcebergOrder {
+    const DEFAULT_VALUE: [u8; 192] = [
+        192, 0, 0, 0, 12, 0, 0, 0, 176, 0, 0, 0, 164, 0, 0, 0, 28, 0, 0, 0, 60, 0, 0, 0, 76, 0, 0,
+        0, 108, 0, 0, 0, 124, 0, 0, 0, 156, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    ];
+    pub const FIELD_COUNT: usize = 2;
+    pub fn total_size(&self) -> usize {
+        molecule::unpack_number(self.as_slice()) as usize
+    }
+    pub fn field_count(&self) -> usize {
+        if self.total_size() == molecule::NUMBER_SIZE {
+            0
+        } else {
+            (molecule::unpack_number(&self.as_slice()[molecule::NUMBER_SIZE..]) as usize / 4) - 1
+        }
+    }
+    pub fn count_extra_fields(&self) -> usize {
+        self.field_count() - Self::FIELD_COUNT
+    }
+    pub fn has_extra_fields(&self) -> bool {
+        Self::FIELD_COUNT != self.field_count()
+    }
+    pub fn order(&self) -> LimitOrder {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[4..]) as usize;
+        let end = molecule::unpack_number(&slice[8..]) as usize;
+        LimitOrder::new_unchecked(self.0.slice(start..end))
+    }
+    pub fn display_amount(&self) -> Uint128 {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[8..]) as usize;
+        if self.has_extra_fields() {
+            let end = molecule::unpack_number(&slice[12..]) as usize;
+            Uint128::new_unchecked(self.0.slice(start..end))
+        } else {
+            Uint128::new_unchecked(self.0.slice(start..))
+        }
+    }
+    pub fn as_reader<'r>(&'r self) -> IcebergOrderReader<'r> {
+        IcebergOrderReader::new_unchecked(self.as_slice())
+    }
+}
+impl molecule::prelude::Entity for IcebergOrder {
+    type Builder = IcebergOrderBuilder;
+    const NAME: &'static str = "IcebergOrder";
+    fn new_unchecked(data: molecule::bytes::Bytes) -> Self {
+        IcebergOrder(data)
+    }
+    fn as_bytes(&self) -> molecule::bytes::Bytes {
+        self.0.clone()
+    }
+    fn as_slice(&self) -> &[u8] {
+        &self.0[..]
+    }
+    fn from_slice(slice: &[u8]) -> molecule::error::VerificationResult<Self> {
+        IcebergOrderReader::from_slice(slice).map(|reader| reader.to_entity())
+    }
+    fn from_compatible_slice(slice: &[u8]) -> molecule::error::VerificationResult<Self> {
+        IcebergOrderReader::from_compatible_slice(slice).map(|reader| reader.to_entity())
+    }
+    fn new_builder() -> Self::Builder {
+        ::core::default::Default::default()
+    }
+    fn as_builder(self) -> Self::Builder {
+        Self::new_builder()
+            .order(self.order())
+            .display_amount(self.display_amount())
+    }
+}
+#[derive(Clone, Copy)]
+pub struct IcebergOrderReader<'r>(&'r [u8]);
+impl<'r> ::core::fmt::LowerHex for IcebergOrderReader<'r> {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        use molecule::hex_string;
+        if f.alternate() {
+            write!(f, "0x")?;
+        }
+        write!(f, "{}", hex_string(self.as_slice()))
+    }
+}
+impl<'r> ::core::fmt::Debug for IcebergOrderReader<'r> {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{}({:#x})", Self::NAME, self)
+    }
+}
+impl<'r> ::core::fmt::Display for IcebergOrderReader<'r> {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{} {{ ", Self::NAME)?;
+        write!(f, "{}: {}", "order", self.order())?;
+        write!(f, ", {}: {}", "display_amount", self.display_amount())?;
+        let extra_count = self.count_extra_fields();
+        if extra_count != 0 {
+            write!(f, ", .. ({} fields)", extra_count)?;
+        }
+        write!(f, " }}")
+    }
+}
+impl<'r> IcebergOrderReader<'r> {
+    pub const FIELD_COUNT: usize = 2;
+    pub fn total_size(&self) -> usize {
+        molecule::unpack_number(self.as_slice()) as usize
+    }
+    pub fn field_count(&self) -> usize {
+        if self.total_size() == molecule::NUMBER_SIZE {
+            0
+        } else {
+            (molecule::unpack_number(&self.as_slice()[molecule::NUMBER_SIZE..]) as usize / 4) - 1
+        }
+    }
+    pub fn count_extra_fields(&self) -> usize {
+        self.field_count() - Self::FIELD_COUNT
+    }
+    pub fn has_extra_fields(&self) -> bool {
+        Self::FIELD_COUNT != self.field_count()
+    }
+    pub fn order(&self) -> LimitOrderReader<'r> {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[4..]) as usize;
+        let end = molecule::unpack_number(&slice[8..]) as usize;
+        LimitOrderReader::new_unchecked(&self.as_slice()[start..end])
+    }
+    pub fn display_amount(&self) -> Uint128Reader<'r> {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[8..]) as usize;
+        if self.has_extra_fields() {
+            let end = molecule::unpack_number(&slice[12..]) as usize;
+            Uint128Reader::new_unchecked(&self.as_slice()[start..end])
+        } else {
+            Uint128Reader::new_unchecked(&self.as_slice()[start..])
+        }
+    }
+}
+impl<'r> molecule::prelude::Reader<'r> for IcebergOrderReader<'r> {
+    type Entity = IcebergOrder;
+    const NAME: &'static str = "IcebergOrderReader";
+    fn to_entity(&self) -> Self::Entity {
+        Self::Entity::new_unchecked(self.as_slice().to_owned().into())
+    }
+    fn new_unchecked(slice: &'r [u8]) -> Self {
+        IcebergOrderReader(slice)
+    }
+    fn as_slice(&self) -> &'r [u8] {
+        self.0
+    }
+    fn verify(slice: &[u8], compatible: bool) -> molecule::error::VerificationResult<()> {
+        use molecule::verification_error as ve;
+        let slice_len = slice.len();
+        if slice_len < molecule::NUMBER_SIZE {
+            return ve!(Self, HeaderIsBroken, molecule::NUMBER_SIZE, slice_len);
+        }
+        let total_size = molecule::unpack_number(slice) as usize;
+        if slice_len != total_size {
+            return ve!(Self, TotalSizeNotMatch, total_size, slice_len);
+        }
+        if slice_len < molecule::NUMBER_SIZE * 2 {
+            return ve!(Self, HeaderIsBroken, molecule::NUMBER_SIZE * 2, slice_len);
+        }
+        let offset_first = molecule::unpack_number(&slice[molecule::NUMBER_SIZE..]) as usize;
+        if offset_first % molecule::NUMBER_SIZE != 0 || offset_first < molecule::NUMBER_SIZE * 2 {
+            return ve!(Self, OffsetsNotMatch);
+        }
+        if slice_len < offset_first {
+            return ve!(Self, HeaderIsBroken, offset_first, slice_len);
+        }
+        let field_count = offset_first / molecule::NUMBER_SIZE - 1;
+        if field_count < Self::FIELD_COUNT {
+            return ve!(Self, FieldCountNotMatch, Self::FIELD_COUNT, field_count);
+        } else if !compatible && field_count > Self::FIELD_COUNT {
+            return ve!(Self, FieldCountNotMatch, Self::FIELD_COUNT, field_count);
+        };
+        let mut offsets: Vec<usize> = slice[molecule::NUMBER_SIZE..offset_first]
+            .chunks_exact(molecule::NUMBER_SIZE)
+            .map(|x| molecule::unpack_number(x) as usize)
+            .collect();
+        offsets.push(total_size);
+        if offsets.windows(2).any(|i| i[0] > i[1]) {
+            return ve!(Self, OffsetsNotMatch);
+        }
+        LimitOrderReader::verify(&slice[offsets[0]..offsets[1]], compatible)?;
+        Uint128Reader::verify(&slice[offsets[1]..offsets[2]], compatible)?;
+        Ok(())
+    }
+}
+#[derive(Debug, Default)]
+pub struct IcebergOrderBuilder {
+    pub(crate) order: LimitOrder,
+    pub(crate) display_amount: Uint128,
+}
+impl IcebergOrderBuilder {
+    pub const FIELD_COUNT: usize = 2;
+    pub fn order(mut self, v: LimitOrder) -> Self {
+        self.order = v;
+        self
+    }
+    pub fn display_amount(mut self, v: Uint128) -> Self {
+        self.display_amount = v;
+        self
+    }
+}
+impl molecule::prelude::Builder for IcebergOrderBuilder {
+    type Entity = IcebergOrder;
+    const NAME: &'static str = "IcebergOrderBuilder";
+    fn expected_length(&self) -> usize {
+        molecule::NUMBER_SIZE * (Self::FIELD_COUNT + 1)
+            + self.order.as_slice().len()
+            + self.display_amount.as_slice().len()
+    }
+    fn write<W: molecule::io::Write>(&self, writer: &mut W) -> molecule::io::Result<()> {
+        let mut total_size = molecule::NUMBER_SIZE * (Self::FIELD_COUNT + 1);
+        let mut offsets = Vec::with_capacity(Self::FIELD_COUNT);
+        offsets.push(total_size);
+        total_size += self.order.as_slice().len();
+        offsets.push(total_size);
+        total_size += self.display_amount.as_slice().len();
+        writer.write_all(&molecule::pack_number(total_size as molecule::Number))?;
+        for offset in offsets.into_iter() {
+            writer.write_all(&molecule::pack_number(offset as molecule::Number))?;
+        }
+        writer.write_all(self.order.as_slice())?;
+        writer.write_all(self.display_amount.as_slice())?;
+        Ok(())
+    }
+    fn build(&self) -> Self::Entity {
+        let mut inner = Vec::with_capacity(self.expected_length());
+        self.write(&mut inner)
+            .unwrap_or_else(|_| panic!("{} build should be ok", Self::NAME));
+        IcebergOrder::new_unchecked(inner.into())
+    }
+}
+#[derive(Clone)]
+pub struct SlicedOrder(molecule::bytes::Bytes);
+impl ::core::fmt::LowerHex for SlicedOrder {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        use molecule::hex_string;
+        if f.alternate() {
+            write!(f, "0x")?;
+        }
+        write!(f, "{}", hex_string(self.as_slice()))
+    }
+}
+impl ::core::fmt::Debug for SlicedOrder {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{}({:#x})", Self::NAME, self)
+    }
+}
+impl ::core::fmt::Display for SlicedOrder {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{} {{ ", Self::NAME)?;
+        write!(f, "{}: {}", "order", self.order())?;
+        write!(f, ", {}: {}", "slice_amount", self.slice_amount())?;
+        write!(f, ", {}: {}", "interval", self.interval())?;
+        write!(f, ", {}: {}", "not_before", self.not_before())?;
+        let extra_count = self.count_extra_fields();
+        if extra_count != 0 {
+            write!(f, ", .. ({} fields)", extra_count)?;
+        }
+        write!(f, " }}")
+    }
+}
+impl ::core::default::Default for SlicedOrder {
+    fn default() -> Self {
+        let v = molecule::bytes::Bytes::from_static(&Self::DEFAULT_VALUE);
+        SlicedOrder::new_unchecked(v)
+    }
+}
+impl SlicedOrder {
+    const DEFAULT_VALUE: [u8; 216] = [
+        216, 0, 0, 0, 20, 0, 0, 0, 184, 0, 0, 0, 200, 0, 0, 0, 208, 0, 0, 0, 164, 0, 0, 0, 28, 0,
+        0, 0, 60, 0, 0, 0, 76, 0, 0, 0, 108, 0, 0, 0, 124, 0, 0, 0, 156, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    ];
+    pub const FIELD_COUNT: usize = 4;
+    pub fn total_size(&self) -> usize {
+        molecule::unpack_number(self.as_slice()) as usize
+    }
+    pub fn field_count(&self) -> usize {
+        if self.total_size() == molecule::NUMBER_SIZE {
+            0
+        } else {
+            (molecule::unpack_number(&self.as_slice()[molecule::NUMBER_SIZE..]) as usize / 4) - 1
+        }
+    }
+    pub fn count_extra_fields(&self) -> usize {
+        self.field_count() - Self::FIELD_COUNT
+    }
+    pub fn has_extra_fields(&self) -> bool {
+        Self::FIELD_COUNT != self.field_count()
+    }
+    pub fn order(&self) -> LimitOrder {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[4..]) as usize;
+        let end = molecule::unpack_number(&slice[8..]) as usize;
+        LimitOrder::new_unchecked(self.0.slice(start..end))
+    }
+    pub fn slice_amount(&self) -> Uint128 {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[8..]) as usize;
+        let end = molecule::unpack_number(&slice[12..]) as usize;
+        Uint128::new_unchecked(self.0.slice(start..end))
+    }
+    pub fn interval(&self) -> Uint64 {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[12..]) as usize;
+        let end = molecule::unpack_number(&slice[16..]) as usize;
+        Uint64::new_unchecked(self.0.slice(start..end))
+    }
+    pub fn not_before(&self) -> Uint64 {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[16..]) as usize;
+        if self.has_extra_fields() {
+            let end = molecule::unpack_number(&slice[20..]) as usize;
+            Uint64::new_unchecked(self.0.slice(start..end))
+        } else {
+            Uint64::new_unchecked(self.0.slice(start..))
+        }
+    }
+    pub fn as_reader<'r>(&'r self) -> SlicedOrderReader<'r> {
+        SlicedOrderReader::new_unchecked(self.as_slice())
+    }
+}
+impl molecule::prelude::Entity for SlicedOrder {
+    type Builder = SlicedOrderBuilder;
+    const NAME: &'static str = "SlicedOrder";
+    fn new_unchecked(data: molecule::bytes::Bytes) -> Self {
+        SlicedOrder(data)
+    }
+    fn as_bytes(&self) -> molecule::bytes::Bytes {
+        self.0.clone()
+    }
+    fn as_slice(&self) -> &[u8] {
+        &self.0[..]
+    }
+    fn from_slice(slice: &[u8]) -> molecule::error::VerificationResult<Self> {
+        SlicedOrderReader::from_slice(slice).map(|reader| reader.to_entity())
+    }
+    fn from_compatible_slice(slice: &[u8]) -> molecule::error::VerificationResult<Self> {
+        SlicedOrderReader::from_compatible_slice(slice).map(|reader| reader.to_entity())
+    }
+    fn new_builder() -> Self::Builder {
+        ::core::default::Default::default()
+    }
+    fn as_builder(self) -> Self::Builder {
+        Self::new_builder()
+            .order(self.order())
+            .slice_amount(self.slice_amount())
+            .interval(self.interval())
+            .not_before(self.not_before())
+    }
+}
+#[derive(Clone, Copy)]
+pub struct SlicedOrderReader<'r>(&'r [u8]);
+impl<'r> ::core::fmt::LowerHex for SlicedOrderReader<'r> {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        use molecule::hex_string;
+        if f.alternate() {
+            write!(f, "0x")?;
+        }
+        write!(f, "{}", hex_string(self.as_slice()))
+    }
+}
+impl<'r> ::core::fmt::Debug for SlicedOrderReader<'r> {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{}({:#x})", Self::NAME, self)
+    }
+}
+impl<'r> ::core::fmt::Display for SlicedOrderReader<'r> {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{} {{ ", Self::NAME)?;
+        write!(f, "{}: {}", "order", self.order())?;
+        write!(f, ", {}: {}", "slice_amount", self.slice_amount())?;
+        write!(f, ", {}: {}", "interval", self.interval())?;
+        write!(f, ", {}: {}", "not_before", self.not_before())?;
+        let extra_count = self.count_extra_fields();
+        if extra_count != 0 {
+            write!(f, ", .. ({} fields)", extra_count)?;
+        }
+        write!(f, " }}")
+    }
+}
+impl<'r> SlicedOrderReader<'r> {
+    pub const FIELD_COUNT: usize = 4;
+    pub fn total_size(&self) -> usize {
+        molecule::unpack_number(self.as_slice()) as usize
+    }
+    pub fn field_count(&self) -> usize {
+        if self.total_size() == molecule::NUMBER_SIZE {
+            0
+        } else {
+            (molecule::unpack_number(&self.as_slice()[molecule::NUMBER_SIZE..]) as usize / 4) - 1
+        }
+    }
+    pub fn count_extra_fields(&self) -> usize {
+        self.field_count() - Self::FIELD_COUNT
+    }
+    pub fn has_extra_fields(&self) -> bool {
+        Self::FIELD_COUNT != self.field_count()
+    }
+    pub fn order(&self) -> LimitOrderReader<'r> {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[4..]) as usize;
+        let end = molecule::unpack_number(&slice[8..]) as usize;
+        LimitOrderReader::new_unchecked(&self.as_slice()[start..end])
+    }
+    pub fn slice_amount(&self) -> Uint128Reader<'r> {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[8..]) as usize;
+        let end = molecule::unpack_number(&slice[12..]) as usize;
+        Uint128Reader::new_unchecked(&self.as_slice()[start..end])
+    }
+    pub fn interval(&self) -> Uint64Reader<'r> {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[12..]) as usize;
+        let end = molecule::unpack_number(&slice[16..]) as usize;
+        Uint64Reader::new_unchecked(&self.as_slice()[start..end])
+    }
+    pub fn not_before(&self) -> Uint64Reader<'r> {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[16..]) as usize;
+        if self.has_extra_fields() {
+            let end = molecule::unpack_number(&slice[20..]) as usize;
+            Uint64Reader::new_unchecked(&self.as_slice()[start..end])
+        } else {
+            Uint64Reader::new_unchecked(&self.as_slice()[start..])
+        }
+    }
+}
+impl<'r> molecule::prelude::Reader<'r> for SlicedOrderReader<'r> {
+    type Entity = SlicedOrder;
+    const NAME: &'static str = "SlicedOrderReader";
+    fn to_entity(&self) -> Self::Entity {
+        Self::Entity::new_unchecked(self.as_slice().to_owned().into())
+    }
+    fn new_unchecked(slice: &'r [u8]) -> Self {
+        SlicedOrderReader(slice)
+    }
+    fn as_slice(&self) -> &'r [u8] {
+        self.0
+    }
+    fn verify(slice: &[u8], compatible: bool) -> molecule::error::VerificationResult<()> {
+        use molecule::verification_error as ve;
+        let slice_len = slice.len();
+        if slice_len < molecule::NUMBER_SIZE {
+            return ve!(Self, HeaderIsBroken, molecule::NUMBER_SIZE, slice_len);
+        }
+        let total_size = molecule::unpack_number(slice) as usize;
+        if slice_len != total_size {
+            return ve!(Self, TotalSizeNotMatch, total_size, slice_len);
+        }
+        if slice_len < molecule::NUMBER_SIZE * 2 {
+            return ve!(Self, HeaderIsBroken, molecule::NUMBER_SIZE * 2, slice_len);
+        }
+        let offset_first = molecule::unpack_number(&slice[molecule::NUMBER_SIZE..]) as usize;
+        if offset_first % molecule::NUMBER_SIZE != 0 || offset_first < molecule::NUMBER_SIZE * 2 {
+            return ve!(Self, OffsetsNotMatch);
+        }
+        if slice_len < offset_first {
+            return ve!(Self, HeaderIsBroken, offset_first, slice_len);
+        }
+        let field_count = offset_first / molecule::NUMBER_SIZE - 1;
+        if field_count < Self::FIELD_COUNT {
+            return ve!(Self, FieldCountNotMatch, Self::FIELD_COUNT, field_count);
+        } else if !compatible && field_count > Self::FIELD_COUNT {
+            return ve!(Self, FieldCountNotMatch, Self::FIELD_COUNT, field_count);
+        };
+        let mut offsets: Vec<usize> = slice[molecule::NUMBER_SIZE..offset_first]
+            .chunks_exact(molecule::NUMBER_SIZE)
+            .map(|x| molecule::unpack_number(x) as usize)
+            .collect();
+        offsets.push(total_size);
+        if offsets.windows(2).any(|i| i[0] > i[1]) {
+            return ve!(Self, OffsetsNotMatch);
+        }
+        LimitOrderReader::verify(&slice[offsets[0]..offsets[1]], compatible)?;
+        Uint128Reader::verify(&slice[offsets[1]..offsets[2]], compatible)?;
+        Uint64Reader::verify(&slice[offsets[2]..offsets[3]], compatible)?;
+        Uint64Reader::verify(&slice[offsets[3]..offsets[4]], compatible)?;
+        Ok(())
+    }
+}
+#[derive(Debug, Default)]
+pub struct SlicedOrderBuilder {
+    pub(crate) order: LimitOrder,
+    pub(crate) slice_amount: Uint128,
+    pub(crate) interval: Uint64,
+    pub(crate) not_before: Uint64,
+}
+impl SlicedOrderBuilder {
+    pub const FIELD_COUNT: usize = 4;
+    pub fn order(mut self, v: LimitOrder) -> Self {
+        self.order = v;
+        self
+    }
+    pub fn slice_amount(mut self, v: Uint128) -> Self {
+        self.slice_amount = v;
+        self
+    }
+    pub fn interval(mut self, v: Uint64) -> Self {
+        self.interval = v;
+        self
+    }
+    pub fn not_before(mut self, v: Uint64) -> Self {
+        self.not_before = v;
+        self
+    }
+}
+impl molecule::prelude::Builder for SlicedOrderBuilder {
+    type Entity = SlicedOrder;
+    const NAME: &'static str = "SlicedOrderBuilder";
+    fn expected_length(&self) -> usize {
+        molecule::NUMBER_SIZE * (Self::FIELD_COUNT + 1)
+            + self.order.as_slice().len()
+            + self.slice_amount.as_slice().len()
+            + self.interval.as_slice().len()
+            + self.not_before.as_slice().len()
+    }
+    fn write<W: molecule::io::Write>(&self, writer: &mut W) -> molecule::io::Result<()> {
+        let mut total_size = molecule::NUMBER_SIZE * (Self::FIELD_COUNT + 1);
+        let mut offsets = Vec::with_capacity(Self::FIELD_COUNT);
+        offsets.push(total_size);
+        total_size += self.order.as_slice().len();
+        offsets.push(total_size);
+        total_size += self.slice_amount.as_slice().len();
+        offsets.push(total_size);
+        total_size += self.interval.as_slice().len();
+        offsets.push(total_size);
+        total_size += self.not_before.as_slice().len();
+        writer.write_all(&molecule::pack_number(total_size as molecule::Number))?;
+        for offset in offsets.into_iter() {
+            writer.write_all(&molecule::pack_number(offset as molecule::Number))?;
+        }
+        writer.write_all(self.order.as_slice())?;
+        writer.write_all(self.slice_amount.as_slice())?;
+        writer.write_all(self.interval.as_slice())?;
+        writer.write_all(self.not_before.as_slice())?;
+        Ok(())
+    }
+    fn build(&self) -> Self::Entity {
+        let mut inner = Vec::with_capacity(self.expected_length());
+        self.write(&mut inner)
+            .unwrap_or_else(|_| panic!("{} build should be ok", Self::NAME));
+        SlicedOrder::new_unchecked(inner.into())
+    }
+}
+#[derive(Clone)]
+pub struct NftSellOrder(molecule::bytes::Bytes);
+impl ::core::fmt::LowerHex for NftSellOrder {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        use molecule::hex_string;
+        if f.alternate() {
+            write!(f, "0x")?;
+        }
+        write!(f, "{}", hex_string(self.as_slice()))
+    }
+}
+impl ::core::fmt::Debug for NftSellOrder {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{}({:#x})", Self::NAME, self)
+    }
+}
+impl ::core::fmt::Display for NftSellOrder {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{} {{ ", Self::NAME)?;
+        write!(f, "{}: {}", "nft", self.nft())?;
+        write!(f, ", {}: {}", "ask_token", self.ask_token())?;
+        write!(f, ", {}: {}", "ask_amount", self.ask_amount())?;
+        write!(f, ", {}: {}", "recipient", self.recipient())?;
+        write!(f, ", {}: {}", "claimed_ckbytes", self.claimed_ckbytes())?;
+        let extra_count = self.count_extra_fields();
+        if extra_count != 0 {
+            write!(f, ", .. ({} fields)", extra_count)?;
+        }
+        write!(f, " }}")
+    }
+}
+impl ::core::default::Default for NftSellOrder {
+    fn default() -> Self {
+        let v = molecule::bytes::Bytes::from_static(&Self::DEFAULT_VALUE);
+        NftSellOrder::new_unchecked(v)
+    }
+}
+impl NftSellOrder {
+    const DEFAULT_VALUE: [u8; 144] = [
+        144, 0, 0, 0, 24, 0, 0, 0, 56, 0, 0, 0, 88, 0, 0, 0, 104, 0, 0, 0, 136, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    ];
+    pub const FIELD_COUNT: usize = 5;
+    pub fn total_size(&self) -> usize {
+        molecule::unpack_number(self.as_slice()) as usize
+    }
+    pub fn field_count(&self) -> usize {
+        if self.total_size() == molecule::NUMBER_SIZE {
+            0
+        } else {
+            (molecule::unpack_number(&self.as_slice()[molecule::NUMBER_SIZE..]) as usize / 4) - 1
+        }
+    }
+    pub fn count_extra_fields(&self) -> usize {
+        self.field_count() - Self::FIELD_COUNT
+    }
+    pub fn has_extra_fields(&self) -> bool {
+        Self::FIELD_COUNT != self.field_count()
+    }
+    pub fn nft(&self) -> Byte32 {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[4..]) as usize;
+        let end = molecule::unpack_number(&slice[8..]) as usize;
+        Byte32::new_unchecked(self.0.slice(start..end))
+    }
+    pub fn ask_token(&self) -> Byte32 {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[8..]) as usize;
+        let end = molecule::unpack_number(&slice[12..]) as usize;
+        Byte32::new_unchecked(self.0.slice(start..end))
+    }
+    pub fn ask_amount(&self) -> Uint128 {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[12..]) as usize;
+        let end = molecule::unpack_number(&slice[16..]) as usize;
+        Uint128::new_unchecked(self.0.slice(start..end))
+    }
+    pub fn recipient(&self) -> Byte32 {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[16..]) as usize;
+        let end = molecule::unpack_number(&slice[20..]) as usize;
+        Byte32::new_unchecked(self.0.slice(start..end))
+    }
+    pub fn claimed_ckbytes(&self) -> Uint64 {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[20..]) as usize;
+        if self.has_extra_fields() {
+            let end = molecule::unpack_number(&slice[24..]) as usize;
+            Uint64::new_unchecked(self.0.slice(start..end))
+        } else {
+            Uint64::new_unchecked(self.0.slice(start..))
+        }
+    }
+    pub fn as_reader<'r>(&'r self) -> NftSellOrderReader<'r> {
+        NftSellOrderReader::new_unchecked(self.as_slice())
+    }
+}
+impl molecule::prelude::Entity for NftSellOrder {
+    type Builder = NftSellOrderBuilder;
+    const NAME: &'static str = "NftSellOrder";
+    fn new_unchecked(data: molecule::bytes::Bytes) -> Self {
+        NftSellOrder(data)
+    }
+    fn as_bytes(&self) -> molecule::bytes::Bytes {
+        self.0.clone()
+    }
+    fn as_slice(&self) -> &[u8] {
+        &self.0[..]
+    }
+    fn from_slice(slice: &[u8]) -> molecule::error::VerificationResult<Self> {
+        NftSellOrderReader::from_slice(slice).map(|reader| reader.to_entity())
+    }
+    fn from_compatible_slice(slice: &[u8]) -> molecule::error::VerificationResult<Self> {
+        NftSellOrderReader::from_compatible_slice(slice).map(|reader| reader.to_entity())
+    }
+    fn new_builder() -> Self::Builder {
+        ::core::default::Default::default()
+    }
+    fn as_builder(self) -> Self::Builder {
+        Self::new_builder()
+            .nft(self.nft())
+            .ask_token(self.ask_token())
+            .ask_amount(self.ask_amount())
+            .recipient(self.recipient())
+            .claimed_ckbytes(self.claimed_ckbytes())
+    }
+}
+#[derive(Clone, Copy)]
+pub struct NftSellOrderReader<'r>(&'r [u8]);
+impl<'r> ::core::fmt::LowerHex for NftSellOrderReader<'r> {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        use molecule::hex_string;
+        if f.alternate() {
+            write!(f, "0x")?;
+        }
+        write!(f, "{}", hex_string(self.as_slice()))
+    }
+}
+impl<'r> ::core::fmt::Debug for NftSellOrderReader<'r> {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{}({:#x})", Self::NAME, self)
+    }
+}
+impl<'r> ::core::fmt::Display for NftSellOrderReader<'r> {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{} {{ ", Self::NAME)?;
+        write!(f, "{}: {}", "nft", self.nft())?;
+        write!(f, ", {}: {}", "ask_token", self.ask_token())?;
+        write!(f, ", {}: {}", "ask_amount", self.ask_amount())?;
+        write!(f, ", {}: {}", "recipient", self.recipient())?;
+        write!(f, ", {}: {}", "claimed_ckbytes", self.claimed_ckbytes())?;
+        let extra_count = self.count_extra_fields();
+        if extra_count != 0 {
+            write!(f, ", .. ({} fields)", extra_count)?;
+        }
+        write!(f, " }}")
+    }
+}
+impl<'r> NftSellOrderReader<'r> {
+    pub const FIELD_COUNT: usize = 5;
+    pub fn total_size(&self) -> usize {
+        molecule::unpack_number(self.as_slice()) as usize
+    }
+    pub fn field_count(&self) -> usize {
+        if self.total_size() == molecule::NUMBER_SIZE {
+            0
+        } else {
+            (molecule::unpack_number(&self.as_slice()[molecule::NUMBER_SIZE..]) as usize / 4) - 1
+        }
+    }
+    pub fn count_extra_fields(&self) -> usize {
+        self.field_count() - Self::FIELD_COUNT
+    }
+    pub fn has_extra_fields(&self) -> bool {
+        Self::FIELD_COUNT != self.field_count()
+    }
+    pub fn nft(&self) -> Byte32Reader<'r> {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[4..]) as usize;
+        let end = molecule::unpack_number(&slice[8..]) as usize;
+        Byte32Reader::new_unchecked(&self.as_slice()[start..end])
+    }
+    pub fn ask_token(&self) -> Byte32Reader<'r> {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[8..]) as usize;
+        let end = molecule::unpack_number(&slice[12..]) as usize;
+        Byte32Reader::new_unchecked(&self.as_slice()[start..end])
+    }
+    pub fn ask_amount(&self) -> Uint128Reader<'r> {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[12..]) as usize;
+        let end = molecule::unpack_number(&slice[16..]) as usize;
+        Uint128Reader::new_unchecked(&self.as_slice()[start..end])
+    }
+    pub fn recipient(&self) -> Byte32Reader<'r> {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[16..]) as usize;
+        let end = molecule::unpack_number(&slice[20..]) as usize;
+        Byte32Reader::new_unchecked(&self.as_slice()[start..end])
+    }
+    pub fn claimed_ckbytes(&self) -> Uint64Reader<'r> {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[20..]) as usize;
+        if self.has_extra_fields() {
+            let end = molecule::unpack_number(&slice[24..]) as usize;
+            Uint64Reader::new_unchecked(&self.as_slice()[start..end])
+        } else {
+            Uint64Reader::new_unchecked(&self.as_slice()[start..])
+        }
+    }
+}
+impl<'r> molecule::prelude::Reader<'r> for NftSellOrderReader<'r> {
+    type Entity = NftSellOrder;
+    const NAME: &'static str = "NftSellOrderReader";
+    fn to_entity(&self) -> Self::Entity {
+        Self::Entity::new_unchecked(self.as_slice().to_owned().into())
+    }
+    fn new_unchecked(slice: &'r [u8]) -> Self {
+        NftSellOrderReader(slice)
+    }
+    fn as_slice(&self) -> &'r [u8] {
+        self.0
+    }
+    fn verify(slice: &[u8], compatible: bool) -> molecule::error::VerificationResult<()> {
+        use molecule::verification_error as ve;
+        let slice_len = slice.len();
+        if slice_len < molecule::NUMBER_SIZE {
+            return ve!(Self, HeaderIsBroken, molecule::NUMBER_SIZE, slice_len);
+        }
+        let total_size = molecule::unpack_number(slice) as usize;
+        if slice_len != total_size {
+            return ve!(Self, TotalSizeNotMatch, total_size, slice_len);
+        }
+        if slice_len < molecule::NUMBER_SIZE * 2 {
+            return ve!(Self, HeaderIsBroken, molecule::NUMBER_SIZE * 2, slice_len);
+        }
+        let offset_first = molecule::unpack_number(&slice[molecule::NUMBER_SIZE..]) as usize;
+        if offset_first % molecule::NUMBER_SIZE != 0 || offset_first < molecule::NUMBER_SIZE * 2 {
+            return ve!(Self, OffsetsNotMatch);
+        }
+        if slice_len < offset_first {
+            return ve!(Self, HeaderIsBroken, offset_first, slice_len);
+        }
+        let field_count = offset_first / molecule::NUMBER_SIZE - 1;
+        if field_count < Self::FIELD_COUNT {
+            return ve!(Self, FieldCountNotMatch, Self::FIELD_COUNT, field_count);
+        } else if !compatible && field_count > Self::FIELD_COUNT {
+            return ve!(Self, FieldCountNotMatch, Self::FIELD_COUNT, field_count);
+        };
+        let mut offsets: Vec<usize> = slice[molecule::NUMBER_SIZE..offset_first]
+            .chunks_exact(molecule::NUMBER_SIZE)
+            .map(|x| molecule::unpack_number(x) as usize)
+            .collect();
+        offsets.push(total_size);
+        if offsets.windows(2).any(|i| i[0] > i[1]) {
+            return ve!(Self, OffsetsNotMatch);
+        }
+        Byte32Reader::verify(&slice[offsets[0]..offsets[1]], compatible)?;
+        Byte32Reader::verify(&slice[offsets[1]..offsets[2]], compatible)?;
+        Uint128Reader::verify(&slice[offsets[2]..offsets[3]], compatible)?;
+        Byte32Reader::verify(&slice[offsets[3]..offsets[4]], compatible)?;
+        Uint64Reader::verify(&slice[offsets[4]..offsets[5]], compatible)?;
+        Ok(())
+    }
+}
+#[derive(Debug, Default)]
+pub struct NftSellOrderBuilder {
+    pub(crate) nft: Byte32,
+    pub(crate) ask_token: Byte32,
+    pub(crate) ask_amount: Uint128,
+    pub(crate) recipient: Byte32,
+    pub(crate) claimed_ckbytes: Uint64,
+}
+impl NftSellOrderBuilder {
+    pub const FIELD_COUNT: usize = 5;
+    pub fn nft(mut self, v: Byte32) -> Self {
+        self.nft = v;
+        self
+    }
+    pub fn ask_token(mut self, v: Byte32) -> Self {
+        self.ask_token = v;
+        self
+    }
+    pub fn ask_amount(mut self, v: Uint128) -> Self {
+        self.ask_amount = v;
+        self
+    }
+    pub fn recipient(mut self, v: Byte32) -> Self {
+        self.recipient = v;
+        self
+    }
+    pub fn claimed_ckbytes(mut self, v: Uint64) -> Self {
+        self.claimed_ckbytes = v;
+        self
+    }
+}
+impl molecule::prelude::Builder for NftSellOrderBuilder {
+    type Entity = NftSellOrder;
+    const NAME: &'static str = "NftSellOrderBuilder";
+    fn expected_length(&self) -> usize {
+        molecule::NUMBER_SIZE * (Self::FIELD_COUNT + 1)
+            + self.nft.as_slice().len()
+            + self.ask_token.as_slice().len()
+            + self.ask_amount.as_slice().len()
+            + self.recipient.as_slice().len()
+            + self.claimed_ckbytes.as_slice().len()
+    }
+    fn write<W: molecule::io::Write>(&self, writer: &mut W) -> molecule::io::Result<()> {
+        let mut total_size = molecule::NUMBER_SIZE * (Self::FIELD_COUNT + 1);
+        let mut offsets = Vec::with_capacity(Self::FIELD_COUNT);
+        offsets.push(total_size);
+        total_size += self.nft.as_slice().len();
+        offsets.push(total_size);
+        total_size += self.ask_token.as_slice().len();
+        offsets.push(total_size);
+        total_size += self.ask_amount.as_slice().len();
+        offsets.push(total_size);
+        total_size += self.recipient.as_slice().len();
+        offsets.push(total_size);
+        total_size += self.claimed_ckbytes.as_slice().len();
+        writer.write_all(&molecule::pack_number(total_size as molecule::Number))?;
+        for offset in offsets.into_iter() {
+            writer.write_all(&molecule::pack_number(offset as molecule::Number))?;
+        }
+        writer.write_all(self.nft.as_slice())?;
+        writer.write_all(self.ask_token.as_slice())?;
+        writer.write_all(self.ask_amount.as_slice())?;
+        writer.write_all(self.recipient.as_slice())?;
+        writer.write_all(self.claimed_ckbytes.as_slice())?;
+        Ok(())
+    }
+    fn build(&self) -> Self::Entity {
+        let mut inner = Vec::with_capacity(self.expected_length());
+        self.write(&mut inner)
+            .unwrap_or_else(|_| panic!("{} build should be ok", Self::NAME));
+        NftSellOrder::new_unchecked(inner.into())
+    }
+}
+#[derive(Clone)]
+pub struct NftBuyOrder(molecule::bytes::Bytes);
+impl ::core::fmt::LowerHex for NftBuyOrder {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        use molecule::hex_string;
+        if f.alternate() {
+            write!(f, "0x")?;
+        }
+        write!(f, "{}", hex_string(self.as_slice()))
+    }
+}
+impl ::core::fmt::Debug for NftBuyOrder {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{}({:#x})", Self::NAME, self)
+    }
+}
+impl ::core::fmt::Display for NftBuyOrder {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{} {{ ", Self::NAME)?;
+        write!(f, "{}: {}", "nft", self.nft())?;
+        write!(f, ", {}: {}", "bid_token", self.bid_token())?;
+        write!(f, ", {}: {}", "bid_amount", self.bid_amount())?;
+        write!(f, ", {}: {}", "recipient", self.recipient())?;
+        write!(f, ", {}: {}", "claimed_ckbytes", self.claimed_ckbytes())?;
+        let extra_count = self.count_extra_fields();
+        if extra_count != 0 {
+            write!(f, ", .. ({} fields)", extra_count)?;
+        }
+        write!(f, " }}")
+    }
+}
+impl ::core::default::Default for NftBuyOrder {
+    fn default() -> Self {
+        let v = molecule::bytes::Bytes::from_static(&Self::DEFAULT_VALUE);
+        NftBuyOrder::new_unchecked(v)
+    }
+}
+impl NftBuyOrder {
+    const DEFAULT_VALUE: [u8; 144] = [
+        144, 0, 0, 0, 24, 0, 0, 0, 56, 0, 0, 0, 88, 0, 0, 0, 104, 0, 0, 0, 136, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    ];
+    pub const FIELD_COUNT: usize = 5;
+    pub fn total_size(&self) -> usize {
+        molecule::unpack_number(self.as_slice()) as usize
+    }
+    pub fn field_count(&self) -> usize {
+        if self.total_size() == molecule::NUMBER_SIZE {
+            0
+        } else {
+            (molecule::unpack_number(&self.as_slice()[molecule::NUMBER_SIZE..]) as usize / 4) - 1
+        }
+    }
+    pub fn count_extra_fields(&self) -> usize {
+        self.field_count() - Self::FIELD_COUNT
+    }
+    pub fn has_extra_fields(&self) -> bool {
+        Self::FIELD_COUNT != self.field_count()
+    }
+    pub fn nft(&self) -> Byte32 {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[4..]) as usize;
+        let end = molecule::unpack_number(&slice[8..]) as usize;
+        Byte32::new_unchecked(self.0.slice(start..end))
+    }
+    pub fn bid_token(&self) -> Byte32 {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[8..]) as usize;
+        let end = molecule::unpack_number(&slice[12..]) as usize;
+        Byte32::new_unchecked(self.0.slice(start..end))
+    }
+    pub fn bid_amount(&self) -> Uint128 {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[12..]) as usize;
+        let end = molecule::unpack_number(&slice[16..]) as usize;
+        Uint128::new_unchecked(self.0.slice(start..end))
+    }
+    pub fn recipient(&self) -> Byte32 {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[16..]) as usize;
+        let end = molecule::unpack_number(&slice[20..]) as usize;
+        Byte32::new_unchecked(self.0.slice(start..end))
+    }
+    pub fn claimed_ckbytes(&self) -> Uint64 {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[20..]) as usize;
+        if self.has_extra_fields() {
+            let end = molecule::unpack_number(&slice[24..]) as usize;
+            Uint64::new_unchecked(self.0.slice(start..end))
+        } else {
+            Uint64::new_unchecked(self.0.slice(start..))
+        }
+    }
+    pub fn as_reader<'r>(&'r self) -> NftBuyOrderReader<'r> {
+        NftBuyOrderReader::new_unchecked(self.as_slice())
+    }
+}
+impl molecule::prelude::Entity for NftBuyOrder {
+    type Builder = NftBuyOrderBuilder;
+    const NAME: &'static str = "NftBuyOrder";
+    fn new_unchecked(data: molecule::bytes::Bytes) -> Self {
+        NftBuyOrder(data)
+    }
+    fn as_bytes(&self) -> molecule::bytes::Bytes {
+        self.0.clone()
+    }
+    fn as_slice(&self) -> &[u8] {
+        &self.0[..]
+    }
+    fn from_slice(slice: &[u8]) -> molecule::error::VerificationResult<Self> {
+        NftBuyOrderReader::from_slice(slice).map(|reader| reader.to_entity())
+    }
+    fn from_compatible_slice(slice: &[u8]) -> molecule::error::VerificationResult<Self> {
+        NftBuyOrderReader::from_compatible_slice(slice).map(|reader| reader.to_entity())
+    }
+    fn new_builder() -> Self::Builder {
+        ::core::default::Default::default()
+    }
+    fn as_builder(self) -> Self::Builder {
+        Self::new_builder()
+            .nft(self.nft())
+            .bid_token(self.bid_token())
+            .bid_amount(self.bid_amount())
+            .recipient(self.recipient())
+            .claimed_ckbytes(self.claimed_ckbytes())
+    }
+}
+#[derive(Clone, Copy)]
+pub struct NftBuyOrderReader<'r>(&'r [u8]);
+impl<'r> ::core::fmt::LowerHex for NftBuyOrderReader<'r> {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        use molecule::hex_string;
+        if f.alternate() {
+            write!(f, "0x")?;
+        }
+        write!(f, "{}", hex_string(self.as_slice()))
+    }
+}
+impl<'r> ::core::fmt::Debug for NftBuyOrderReader<'r> {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{}({:#x})", Self::NAME, self)
+    }
+}
+impl<'r> ::core::fmt::Display for NftBuyOrderReader<'r> {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{} {{ ", Self::NAME)?;
+        write!(f, "{}: {}", "nft", self.nft())?;
+        write!(f, ", {}: {}", "bid_token", self.bid_token())?;
+        write!(f, ", {}: {}", "bid_amount", self.bid_amount())?;
+        write!(f, ", {}: {}", "recipient", self.recipient())?;
+        write!(f, ", {}: {}", "claimed_ckbytes", self.claimed_ckbytes())?;
+        let extra_count = self.count_extra_fields();
+        if extra_count != 0 {
+            write!(f, ", .. ({} fields)", extra_count)?;
+        }
+        write!(f, " }}")
+    }
+}
+impl<'r> NftBuyOrderReader<'r> {
+    pub const FIELD_COUNT: usize = 5;
+    pub fn total_size(&self) -> usize {
+        molecule::unpack_number(self.as_slice()) as usize
+    }
+    pub fn field_count(&self) -> usize {
+        if self.total_size() == molecule::NUMBER_SIZE {
+            0
+        } else {
+            (molecule::unpack_number(&self.as_slice()[molecule::NUMBER_SIZE..]) as usize / 4) - 1
+        }
+    }
+    pub fn count_extra_fields(&self) -> usize {
+        self.field_count() - Self::FIELD_COUNT
+    }
+    pub fn has_extra_fields(&self) -> bool {
+        Self::FIELD_COUNT != self.field_count()
+    }
+    pub fn nft(&self) -> Byte32Reader<'r> {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[4..]) as usize;
+        let end = molecule::unpack_number(&slice[8..]) as usize;
+        Byte32Reader::new_unchecked(&self.as_slice()[start..end])
+    }
+    pub fn bid_token(&self) -> Byte32Reader<'r> {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[8..]) as usize;
+        let end = molecule::unpack_number(&slice[12..]) as usize;
+        Byte32Reader::new_unchecked(&self.as_slice()[start..end])
+    }
+    pub fn bid_amount(&self) -> Uint128Reader<'r> {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[12..]) as usize;
+        let end = molecule::unpack_number(&slice[16..]) as usize;
+        Uint128Reader::new_unchecked(&self.as_slice()[start..end])
+    }
+    pub fn recipient(&self) -> Byte32Reader<'r> {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[16..]) as usize;
+        let end = molecule::unpack_number(&slice[20..]) as usize;
+        Byte32Reader::new_unchecked(&self.as_slice()[start..end])
+    }
+    pub fn claimed_ckbytes(&self) -> Uint64Reader<'r> {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[20..]) as usize;
+        if self.has_extra_fields() {
+            let end = molecule::unpack_number(&slice[24..]) as usize;
+            Uint64Reader::new_unchecked(&self.as_slice()[start..end])
+        } else {
+            Uint64Reader::new_unchecked(&self.as_slice()[start..])
+        }
+    }
+}
+impl<'r> molecule::prelude::Reader<'r> for NftBuyOrderReader<'r> {
+    type Entity = NftBuyOrder;
+    const NAME: &'static str = "NftBuyOrderReader";
+    fn to_entity(&self) -> Self::Entity {
+        Self::Entity::new_unchecked(self.as_slice().to_owned().into())
+    }
+    fn new_unchecked(slice: &'r [u8]) -> Self {
+        NftBuyOrderReader(slice)
+    }
+    fn as_slice(&self) -> &'r [u8] {
+        self.0
+    }
+    fn verify(slice: &[u8], compatible: bool) -> molecule::error::VerificationResult<()> {
+        use molecule::verification_error as ve;
+        let slice_len = slice.len();
+        if slice_len < molecule::NUMBER_SIZE {
+            return ve!(Self, HeaderIsBroken, molecule::NUMBER_SIZE, slice_len);
+        }
+        let total_size = molecule::unpack_number(slice) as usize;
+        if slice_len != total_size {
+            return ve!(Self, TotalSizeNotMatch, total_size, slice_len);
+        }
+        if slice_len < molecule::NUMBER_SIZE * 2 {
+            return ve!(Self, HeaderIsBroken, molecule::NUMBER_SIZE * 2, slice_len);
+        }
+        let offset_first = molecule::unpack_number(&slice[molecule::NUMBER_SIZE..]) as usize;
+        if offset_first % molecule::NUMBER_SIZE != 0 || offset_first < molecule::NUMBER_SIZE * 2 {
+            return ve!(Self, OffsetsNotMatch);
+        }
+        if slice_len < offset_first {
+            return ve!(Self, HeaderIsBroken, offset_first, slice_len);
+        }
+        let field_count = offset_first / molecule::NUMBER_SIZE - 1;
+        if field_count < Self::FIELD_COUNT {
+            return ve!(Self, FieldCountNotMatch, Self::FIELD_COUNT, field_count);
+        } else if !compatible && field_count > Self::FIELD_COUNT {
+            return ve!(Self, FieldCountNotMatch, Self::FIELD_COUNT, field_count);
+        };
+        let mut offsets: Vec<usize> = slice[molecule::NUMBER_SIZE..offset_first]
+            .chunks_exact(molecule::NUMBER_SIZE)
+            .map(|x| molecule::unpack_number(x) as usize)
+            .collect();
+        offsets.push(total_size);
+        if offsets.windows(2).any(|i| i[0] > i[1]) {
+            return ve!(Self, OffsetsNotMatch);
+        }
+        Byte32Reader::verify(&slice[offsets[0]..offsets[1]], compatible)?;
+        Byte32Reader::verify(&slice[offsets[1]..offsets[2]], compatible)?;
+        Uint128Reader::verify(&slice[offsets[2]..offsets[3]], compatible)?;
+        Byte32Reader::verify(&slice[offsets[3]..offsets[4]], compatible)?;
+        Uint64Reader::verify(&slice[offsets[4]..offsets[5]], compatible)?;
+        Ok(())
+    }
+}
+#[derive(Debug, Default)]
+pub struct NftBuyOrderBuilder {
+    pub(crate) nft: Byte32,
+    pub(crate) bid_token: Byte32,
+    pub(crate) bid_amount: Uint128,
+    pub(crate) recipient: Byte32,
+    pub(crate) claimed_ckbytes: Uint64,
+}
+impl NftBuyOrderBuilder {
+    pub const FIELD_COUNT: usize = 5;
+    pub fn nft(mut self, v: Byte32) -> Self {
+        self.nft = v;
+        self
+    }
+    pub fn bid_token(mut self, v: Byte32) -> Self {
+        self.bid_token = v;
+        self
+    }
+    pub fn bid_amount(mut self, v: Uint128) -> Self {
+        self.bid_amount = v;
+        self
+    }
+    pub fn recipient(mut self, v: Byte32) -> Self {
+        self.recipient = v;
+        self
+    }
+    pub fn claimed_ckbytes(mut self, v: Uint64) -> Self {
+        self.claimed_ckbytes = v;
+        self
+    }
+}
+impl molecule::prelude::Builder for NftBuyOrderBuilder {
+    type Entity = NftBuyOrder;
+    const NAME: &'static str = "NftBuyOrderBuilder";
+    fn expected_length(&self) -> usize {
+        molecule::NUMBER_SIZE * (Self::FIELD_COUNT + 1)
+            + self.nft.as_slice().len()
+            + self.bid_token.as_slice().len()
+            + self.bid_amount.as_slice().len()
+            + self.recipient.as_slice().len()
+            + self.claimed_ckbytes.as_slice().len()
+    }
+    fn write<W: molecule::io::Write>(&self, writer: &mut W) -> molecule::io::Result<()> {
+        let mut total_size = molecule::NUMBER_SIZE * (Self::FIELD_COUNT + 1);
+        let mut offsets = Vec::with_capacity(Self::FIELD_COUNT);
+        offsets.push(total_size);
+        total_size += self.nft.as_slice().len();
+        offsets.push(total_size);
+        total_size += self.bid_token.as_slice().len();
+        offsets.push(total_size);
+        total_size += self.bid_amount.as_slice().len();
+        offsets.push(total_size);
+        total_size += self.recipient.as_slice().len();
+        offsets.push(total_size);
+        total_size += self.claimed_ckbytes.as_slice().len();
+        writer.write_all(&molecule::pack_number(total_size as molecule::Number))?;
+        for offset in offsets.into_iter() {
+            writer.write_all(&molecule::pack_number(offset as molecule::Number))?;
+        }
+        writer.write_all(self.nft.as_slice())?;
+        writer.write_all(self.bid_token.as_slice())?;
+        writer.write_all(self.bid_amount.as_slice())?;
+        writer.write_all(self.recipient.as_slice())?;
+        writer.write_all(self.claimed_ckbytes.as_slice())?;
+        Ok(())
+    }
+    fn build(&self) -> Self::Entity {
+        let mut inner = Vec::with_capacity(self.expected_length());
+        self.write(&mut inner)
+            .unwrap_or_else(|_| panic!("{} build should be ok", Self::NAME));
+        NftBuyOrder::new_unchecked(inner.into())
+    }
+}
+#[derive(Clone)]
+pub struct RouteOrder(molecule::bytes::Bytes);
+impl ::core::fmt::LowerHex for RouteOrder {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        use molecule::hex_string;
+        if f.alternate() {
+            write!(f, "0x")?;
+        }
+        write!(f, "{}", hex_string(self.as_slice()))
+    }
+}
+impl ::core::fmt::Debug for RouteOrder {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{}({:#x})", Self::NAME, self)
+    }
+}
+impl ::core::fmt::Display for RouteOrder {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{} {{ ", Self::NAME)?;
+        write!(f, "{}: {}", "path", self.path())?;
+        write!(f, ", {}: {}", "bid_amount", self.bid_amount())?;
+        write!(f, ", {}: {}", "minimum_ask", self.minimum_ask())?;
+        write!(f, ", {}: {}", "recipient", self.recipient())?;
+        write!(f, ", {}: {}", "claimed_ckbytes", self.claimed_ckbytes())?;
+        let extra_count = self.count_extra_fields();
+        if extra_count != 0 {
+            write!(f, ", .. ({} fields)", extra_count)?;
+        }
+        write!(f, " }}")
+    }
+}
+impl ::core::default::Default for RouteOrder {
+    fn default() -> Self {
+        let v = molecule::bytes::Bytes::from_static(&Self::DEFAULT_VALUE);
+        RouteOrder::new_unchecked(v)
+    }
+}
+impl RouteOrder {
+    const DEFAULT_VALUE: [u8; 100] = [
+        100, 0, 0, 0, 24, 0, 0, 0, 28, 0, 0, 0, 44, 0, 0, 0, 60, 0, 0, 0, 92, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    ];
+    pub const FIELD_COUNT: usize = 5;
+    pub fn total_size(&self) -> usize {
+        molecule::unpack_number(self.as_slice()) as usize
+    }
+    pub fn field_count(&self) -> usize {
+        if self.total_size() == molecule::NUMBER_SIZE {
+            0
+        } else {
+            (molecule::unpack_number(&self.as_slice()[molecule::NUMBER_SIZE..]) as usize / 4) - 1
+        }
+    }
+    pub fn count_extra_fields(&self) -> usize {
+        self.field_count() - Self::FIELD_COUNT
+    }
+    pub fn has_extra_fields(&self) -> bool {
+        Self::FIELD_COUNT != self.field_count()
+    }
+    pub fn path(&self) -> Byte32Vec {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[4..]) as usize;
+        let end = molecule::unpack_number(&slice[8..]) as usize;
+        Byte32Vec::new_unchecked(self.0.slice(start..end))
+    }
+    pub fn bid_amount(&self) -> Uint128 {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[8..]) as usize;
+        let end = molecule::unpack_number(&slice[12..]) as usize;
+        Uint128::new_unchecked(self.0.slice(start..end))
+    }
+    pub fn minimum_ask(&self) -> Uint128 {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[12..]) as usize;
+        let end = molecule::unpack_number(&slice[16..]) as usize;
+        Uint128::new_unchecked(self.0.slice(start..end))
+    }
+    pub fn recipient(&self) -> Byte32 {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[16..]) as usize;
+        let end = molecule::unpack_number(&slice[20..]) as usize;
+        Byte32::new_unchecked(self.0.slice(start..end))
+    }
+    pub fn claimed_ckbytes(&self) -> Uint64 {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[20..]) as usize;
+        if self.has_extra_fields() {
+            let end = molecule::unpack_number(&slice[24..]) as usize;
+            Uint64::new_unchecked(self.0.slice(start..end))
+        } else {
+            Uint64::new_unchecked(self.0.slice(start..))
+        }
+    }
+    pub fn as_reader<'r>(&'r self) -> RouteOrderReader<'r> {
+        RouteOrderReader::new_unchecked(self.as_slice())
+    }
+}
+impl molecule::prelude::Entity for RouteOrder {
+    type Builder = RouteOrderBuilder;
+    const NAME: &'static str = "RouteOrder";
+    fn new_unchecked(data: molecule::bytes::Bytes) -> Self {
+        RouteOrder(data)
+    }
+    fn as_bytes(&self) -> molecule::bytes::Bytes {
+        self.0.clone()
+    }
+    fn as_slice(&self) -> &[u8] {
+        &self.0[..]
+    }
+    fn from_slice(slice: &[u8]) -> molecule::error::VerificationResult<Self> {
+        RouteOrderReader::from_slice(slice).map(|reader| reader.to_entity())
+    }
+    fn from_compatible_slice(slice: &[u8]) -> molecule::error::VerificationResult<Self> {
+        RouteOrderReader::from_compatible_slice(slice).map(|reader| reader.to_entity())
+    }
+    fn new_builder() -> Self::Builder {
+        ::core::default::Default::default()
+    }
+    fn as_builder(self) -> Self::Builder {
+        Self::new_builder()
+            .path(self.path())
+            .bid_amount(self.bid_amount())
+            .minimum_ask(self.minimum_ask())
+            .recipient(self.recipient())
+            .claimed_ckbytes(self.claimed_ckbytes())
+    }
+}
+#[derive(Clone, Copy)]
+pub struct RouteOrderReader<'r>(&'r [u8]);
+impl<'r> ::core::fmt::LowerHex for RouteOrderReader<'r> {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        use molecule::hex_string;
+        if f.alternate() {
+            write!(f, "0x")?;
+        }
+        write!(f, "{}", hex_string(self.as_slice()))
+    }
+}
+impl<'r> ::core::fmt::Debug for RouteOrderReader<'r> {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{}({:#x})", Self::NAME, self)
+    }
+}
+impl<'r> ::core::fmt::Display for RouteOrderReader<'r> {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{} {{ ", Self::NAME)?;
+        write!(f, "{}: {}", "path", self.path())?;
+        write!(f, ", {}: {}", "bid_amount", self.bid_amount())?;
+        write!(f, ", {}: {}", "minimum_ask", self.minimum_ask())?;
+        write!(f, ", {}: {}", "recipient", self.recipient())?;
+        write!(f, ", {}: {}", "claimed_ckbytes", self.claimed_ckbytes())?;
+        let extra_count = self.count_extra_fields();
+        if extra_count != 0 {
+            write!(f, ", .. ({} fields)", extra_count)?;
+        }
+        write!(f, " }}")
+    }
+}
+impl<'r> RouteOrderReader<'r> {
+    pub const FIELD_COUNT: usize = 5;
+    pub fn total_size(&self) -> usize {
+        molecule::unpack_number(self.as_slice()) as usize
+    }
+    pub fn field_count(&self) -> usize {
+        if self.total_size() == molecule::NUMBER_SIZE {
+            0
+        } else {
+            (molecule::unpack_number(&self.as_slice()[molecule::NUMBER_SIZE..]) as usize / 4) - 1
+        }
+    }
+    pub fn count_extra_fields(&self) -> usize {
+        self.field_count() - Self::FIELD_COUNT
+    }
+    pub fn has_extra_fields(&self) -> bool {
+        Self::FIELD_COUNT != self.field_count()
+    }
+    pub fn path(&self) -> Byte32VecReader<'r> {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[4..]) as usize;
+        let end = molecule::unpack_number(&slice[8..]) as usize;
+        Byte32VecReader::new_unchecked(&self.as_slice()[start..end])
+    }
+    pub fn bid_amount(&self) -> Uint128Reader<'r> {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[8..]) as usize;
+        let end = molecule::unpack_number(&slice[12..]) as usize;
+        Uint128Reader::new_unchecked(&self.as_slice()[start..end])
+    }
+    pub fn minimum_ask(&self) -> Uint128Reader<'r> {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[12..]) as usize;
+        let end = molecule::unpack_number(&slice[16..]) as usize;
+        Uint128Reader::new_unchecked(&self.as_slice()[start..end])
+    }
+    pub fn recipient(&self) -> Byte32Reader<'r> {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[16..]) as usize;
+        let end = molecule::unpack_number(&slice[20..]) as usize;
+        Byte32Reader::new_unchecked(&self.as_slice()[start..end])
+    }
+    pub fn claimed_ckbytes(&self) -> Uint64Reader<'r> {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[20..]) as usize;
+        if self.has_extra_fields() {
+            let end = molecule::unpack_number(&slice[24..]) as usize;
+            Uint64Reader::new_unchecked(&self.as_slice()[start..end])
+        } else {
+            Uint64Reader::new_unchecked(&self.as_slice()[start..])
+        }
+    }
+}
+impl<'r> molecule::prelude::Reader<'r> for RouteOrderReader<'r> {
+    type Entity = RouteOrder;
+    const NAME: &'static str = "RouteOrderReader";
+    fn to_entity(&self) -> Self::Entity {
+        Self::Entity::new_unchecked(self.as_slice().to_owned().into())
+    }
+    fn new_unchecked(slice: &'r [u8]) -> Self {
+        RouteOrderReader(slice)
+    }
+    fn as_slice(&self) -> &'r [u8] {
+        self.0
+    }
+    fn verify(slice: &[u8], compatible: bool) -> molecule::error::VerificationResult<()> {
+        use molecule::verification_error as ve;
+        let slice_len = slice.len();
+        if slice_len < molecule::NUMBER_SIZE {
+            return ve!(Self, HeaderIsBroken, molecule::NUMBER_SIZE, slice_len);
+        }
+        let total_size = molecule::unpack_number(slice) as usize;
+        if slice_len != total_size {
+            return ve!(Self, TotalSizeNotMatch, total_size, slice_len);
+        }
+        if slice_len < molecule::NUMBER_SIZE * 2 {
+            return ve!(Self, HeaderIsBroken, molecule::NUMBER_SIZE * 2, slice_len);
+        }
+        let offset_first = molecule::unpack_number(&slice[molecule::NUMBER_SIZE..]) as usize;
+        if offset_first % molecule::NUMBER_SIZE != 0 || offset_first < molecule::NUMBER_SIZE * 2 {
+            return ve!(Self, OffsetsNotMatch);
+        }
+        if slice_len < offset_first {
+            return ve!(Self, HeaderIsBroken, offset_first, slice_len);
+        }
+        let field_count = offset_first / molecule::NUMBER_SIZE - 1;
+        if field_count < Self::FIELD_COUNT {
+            return ve!(Self, FieldCountNotMatch, Self::FIELD_COUNT, field_count);
+        } else if !compatible && field_count > Self::FIELD_COUNT {
+            return ve!(Self, FieldCountNotMatch, Self::FIELD_COUNT, field_count);
+        };
+        let mut offsets: Vec<usize> = slice[molecule::NUMBER_SIZE..offset_first]
+            .chunks_exact(molecule::NUMBER_SIZE)
+            .map(|x| molecule::unpack_number(x) as usize)
+            .collect();
+        offsets.push(total_size);
+        if offsets.windows(2).any(|i| i[0] > i[1]) {
+            return ve!(Self, OffsetsNotMatch);
+        }
+        Byte32VecReader::verify(&slice[offsets[0]..offsets[1]], compatible)?;
+        Uint128Reader::verify(&slice[offsets[1]..offsets[2]], compatible)?;
+        Uint128Reader::verify(&slice[offsets[2]..offsets[3]], compatible)?;
+        Byte32Reader::verify(&slice[offsets[3]..offsets[4]], compatible)?;
+        Uint64Reader::verify(&slice[offsets[4]..offsets[5]], compatible)?;
+        Ok(())
+    }
+}
+#[derive(Debug, Default)]
+pub struct RouteOrderBuilder {
+    pub(crate) path: Byte32Vec,
+    pub(crate) bid_amount: Uint128,
+    pub(crate) minimum_ask: Uint128,
+    pub(crate) recipient: Byte32,
+    pub(crate) claimed_ckbytes: Uint64,
+}
+impl RouteOrderBuilder {
+    pub const FIELD_COUNT: usize = 5;
+    pub fn path(mut self, v: Byte32Vec) -> Self {
+        self.path = v;
+        self
+    }
+    pub fn bid_amount(mut self, v: Uint128) -> Self {
+        self.bid_amount = v;
+        self
+    }
+    pub fn minimum_ask(mut self, v: Uint128) -> Self {
+        self.minimum_ask = v;
+        self
+    }
+    pub fn recipient(mut self, v: Byte32) -> Self {
+        self.recipient = v;
+        self
+    }
+    pub fn claimed_ckbytes(mut self, v: Uint64) -> Self {
+        self.claimed_ckbytes = v;
+        self
+    }
+}
+impl molecule::prelude::Builder for RouteOrderBuilder {
+    type Entity = RouteOrder;
+    const NAME: &'static str = "RouteOrderBuilder";
+    fn expected_length(&self) -> usize {
+        molecule::NUMBER_SIZE * (Self::FIELD_COUNT + 1)
+            + self.path.as_slice().len()
+            + self.bid_amount.as_slice().len()
+            + self.minimum_ask.as_slice().len()
+            + self.recipient.as_slice().len()
+            + self.claimed_ckbytes.as_slice().len()
+    }
+    fn write<W: molecule::io::Write>(&self, writer: &mut W) -> molecule::io::Result<()> {
+        let mut total_size = molecule::NUMBER_SIZE * (Self::FIELD_COUNT + 1);
+        let mut offsets = Vec::with_capacity(Self::FIELD_COUNT);
+        offsets.push(total_size);
+        total_size += self.path.as_slice().len();
+        offsets.push(total_size);
+        total_size += self.bid_amount.as_slice().len();
+        offsets.push(total_size);
+        total_size += self.minimum_ask.as_slice().len();
+        offsets.push(total_size);
+        total_size += self.recipient.as_slice().len();
+        offsets.push(total_size);
+        total_size += self.claimed_ckbytes.as_slice().len();
+        writer.write_all(&molecule::pack_number(total_size as molecule::Number))?;
+        for offset in offsets.into_iter() {
+            writer.write_all(&molecule::pack_number(offset as molecule::Number))?;
+        }
+        writer.write_all(self.path.as_slice())?;
+        writer.write_all(self.bid_amount.as_slice())?;
+        writer.write_all(self.minimum_ask.as_slice())?;
+        writer.write_all(self.recipient.as_slice())?;
+        writer.write_all(self.claimed_ckbytes.as_slice())?;
+        Ok(())
+    }
+    fn build(&self) -> Self::Entity {
+        let mut inner = Vec::with_capacity(self.expected_length());
+        self.write(&mut inner)
+            .unwrap_or_else(|_| panic!("{} build should be ok", Self::NAME));
+        RouteOrder::new_unchecked(inner.into())
+    }
+}
+#[derive(Clone)]
+pub struct BrokeredLimitOrder(molecule::bytes::Bytes);
+impl ::core::fmt::LowerHex for BrokeredLimitOrder {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        use molecule::hex_string;
+        if f.alternate() {
+            write!(f, "0x")?;
+        }
+        write!(f, "{}", hex_string(self.as_slice()))
+    }
+}
+impl ::core::fmt::Debug for BrokeredLimitOrder {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{}({:#x})", Self::NAME, self)
+    }
+}
+impl ::core::fmt::Display for BrokeredLimitOrder {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{} {{ ", Self::NAME)?;
+        write!(f, "{}: {}", "order", self.order())?;
+        write!(f, ", {}: {}", "broker", self.broker())?;
+        write!(f, ", {}: {}", "fee_bps", self.fee_bps())?;
+        let extra_count = self.count_extra_fields();
+        if extra_count != 0 {
+            write!(f, ", .. ({} fields)", extra_count)?;
+        }
+        write!(f, " }}")
+    }
+}
+impl ::core::default::Default for BrokeredLimitOrder {
+    fn default() -> Self {
+        let v = molecule::bytes::Bytes::from_static(&Self::DEFAULT_VALUE);
+        BrokeredLimitOrder::new_unchecked(v)
+    }
+}
+impl BrokeredLimitOrder {
+    const DEFAULT_VALUE: [u8; 216] = [
+        216, 0, 0, 0, 16, 0, 0, 0, 180, 0, 0, 0, 212, 0, 0, 0, 164, 0, 0, 0, 28, 0, 0, 0, 60, 0, 0,
+        0, 76, 0, 0, 0, 108, 0, 0, 0, 124, 0, 0, 0, 156, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    ];
+    pub const FIELD_COUNT: usize = 3;
+    pub fn total_size(&self) -> usize {
+        molecule::unpack_number(self.as_slice()) as usize
+    }
+    pub fn field_count(&self) -> usize {
+        if self.total_size() == molecule::NUMBER_SIZE {
+            0
+        } else {
+            (molecule::unpack_number(&self.as_slice()[molecule::NUMBER_SIZE..]) as usize / 4) - 1
+        }
+    }
+    pub fn count_extra_fields(&self) -> usize {
+        self.field_count() - Self::FIELD_COUNT
+    }
+    pub fn has_extra_fields(&self) -> bool {
+        Self::FIELD_COUNT != self.field_count()
+    }
+    pub fn order(&self) -> LimitOrder {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[4..]) as usize;
+        let end = molecule::unpack_number(&slice[8..]) as usize;
+        LimitOrder::new_unchecked(self.0.slice(start..end))
+    }
+    pub fn broker(&self) -> Byte32 {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[8..]) as usize;
+        let end = molecule::unpack_number(&slice[12..]) as usize;
+        Byte32::new_unchecked(self.0.slice(start..end))
+    }
+    pub fn fee_bps(&self) -> Uint32 {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[12..]) as usize;
+        if self.has_extra_fields() {
+            let end = molecule::unpack_number(&slice[16..]) as usize;
+            Uint32::new_unchecked(self.0.slice(start..end))
+        } else {
+            Uint32::new_unchecked(self.0.slice(start..))
+        }
+    }
+    pub fn as_reader<'r>(&'r self) -> BrokeredLimitOrderReader<'r> {
+        BrokeredLimitOrderReader::new_unchecked(self.as_slice())
+    }
+}
+impl molecule::prelude::Entity for BrokeredLimitOrder {
+    type Builder = BrokeredLimitOrderBuilder;
+    const NAME: &'static str = "BrokeredLimitOrder";
+    fn new_unchecked(data: molecule::bytes::Bytes) -> Self {
+        BrokeredLimitOrder(data)
+    }
+    fn as_bytes(&self) -> molecule::bytes::Bytes {
+        self.0.clone()
+    }
+    fn as_slice(&self) -> &[u8] {
+        &self.0[..]
+    }
+    fn from_slice(slice: &[u8]) -> molecule::error::VerificationResult<Self> {
+        BrokeredLimitOrderReader::from_slice(slice).map(|reader| reader.to_entity())
+    }
+    fn from_compatible_slice(slice: &[u8]) -> molecule::error::VerificationResult<Self> {
+        BrokeredLimitOrderReader::from_compatible_slice(slice).map(|reader| reader.to_entity())
+    }
+    fn new_builder() -> Self::Builder {
+        ::core::default::Default::default()
+    }
+    fn as_builder(self) -> Self::Builder {
+        Self::new_builder()
+            .order(self.order())
+            .broker(self.broker())
+            .fee_bps(self.fee_bps())
+    }
+}
+#[derive(Clone, Copy)]
+pub struct BrokeredLimitOrderReader<'r>(&'r [u8]);
+impl<'r> ::core::fmt::LowerHex for BrokeredLimitOrderReader<'r> {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        use molecule::hex_string;
+        if f.alternate() {
+            write!(f, "0x")?;
+        }
+        write!(f, "{}", hex_string(self.as_slice()))
+    }
+}
+impl<'r> ::core::fmt::Debug for BrokeredLimitOrderReader<'r> {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{}({:#x})", Self::NAME, self)
+    }
+}
+impl<'r> ::core::fmt::Display for BrokeredLimitOrderReader<'r> {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{} {{ ", Self::NAME)?;
+        write!(f, "{}: {}", "order", self.order())?;
+        write!(f, ", {}: {}", "broker", self.broker())?;
+        write!(f, ", {}: {}", "fee_bps", self.fee_bps())?;
+        let extra_count = self.count_extra_fields();
+        if extra_count != 0 {
+            write!(f, ", .. ({} fields)", extra_count)?;
+        }
+        write!(f, " }}")
+    }
+}
+impl<'r> BrokeredLimitOrderReader<'r> {
+    pub const FIELD_COUNT: usize = 3;
+    pub fn total_size(&self) -> usize {
+        molecule::unpack_number(self.as_slice()) as usize
+    }
+    pub fn field_count(&self) -> usize {
+        if self.total_size() == molecule::NUMBER_SIZE {
+            0
+        } else {
+            (molecule::unpack_number(&self.as_slice()[molecule::NUMBER_SIZE..]) as usize / 4) - 1
+        }
+    }
+    pub fn count_extra_fields(&self) -> usize {
+        self.field_count() - Self::FIELD_COUNT
+    }
+    pub fn has_extra_fields(&self) -> bool {
+        Self::FIELD_COUNT != self.field_count()
+    }
+    pub fn order(&self) -> LimitOrderReader<'r> {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[4..]) as usize;
+        let end = molecule::unpack_number(&slice[8..]) as usize;
+        LimitOrderReader::new_unchecked(&self.as_slice()[start..end])
+    }
+    pub fn broker(&self) -> Byte32Reader<'r> {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[8..]) as usize;
+        let end = molecule::unpack_number(&slice[12..]) as usize;
+        Byte32Reader::new_unchecked(&self.as_slice()[start..end])
+    }
+    pub fn fee_bps(&self) -> Uint32Reader<'r> {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[12..]) as usize;
+        if self.has_extra_fields() {
+            let end = molecule::unpack_number(&slice[16..]) as usize;
+            Uint32Reader::new_unchecked(&self.as_slice()[start..end])
+        } else {
+            Uint32Reader::new_unchecked(&self.as_slice()[start..])
+        }
+    }
+}
+impl<'r> molecule::prelude::Reader<'r> for BrokeredLimitOrderReader<'r> {
+    type Entity = BrokeredLimitOrder;
+    const NAME: &'static str = "BrokeredLimitOrderReader";
+    fn to_entity(&self) -> Self::Entity {
+        Self::Entity::new_unchecked(self.as_slice().to_owned().into())
+    }
+    fn new_unchecked(slice: &'r [u8]) -> Self {
+        BrokeredLimitOrderReader(slice)
+    }
+    fn as_slice(&self) -> &'r [u8] {
+        self.0
+    }
+    fn verify(slice: &[u8], compatible: bool) -> molecule::error::VerificationResult<()> {
+        use molecule::verification_error as ve;
+        let slice_len = slice.len();
+        if slice_len < molecule::NUMBER_SIZE {
+            return ve!(Self, HeaderIsBroken, molecule::NUMBER_SIZE, slice_len);
+        }
+        let total_size = molecule::unpack_number(slice) as usize;
+        if slice_len != total_size {
+            return ve!(Self, TotalSizeNotMatch, total_size, slice_len);
+        }
+        if slice_len < molecule::NUMBER_SIZE * 2 {
+            return ve!(Self, HeaderIsBroken, molecule::NUMBER_SIZE * 2, slice_len);
+        }
+        let offset_first = molecule::unpack_number(&slice[molecule::NUMBER_SIZE..]) as usize;
+        if offset_first % molecule::NUMBER_SIZE != 0 || offset_first < molecule::NUMBER_SIZE * 2 {
+            return ve!(Self, OffsetsNotMatch);
+        }
+        if slice_len < offset_first {
+            return ve!(Self, HeaderIsBroken, offset_first, slice_len);
+        }
+        let field_count = offset_first / molecule::NUMBER_SIZE - 1;
+        if field_count < Self::FIELD_COUNT {
+            return ve!(Self, FieldCountNotMatch, Self::FIELD_COUNT, field_count);
+        } else if !compatible && field_count > Self::FIELD_COUNT {
+            return ve!(Self, FieldCountNotMatch, Self::FIELD_COUNT, field_count);
+        };
+        let mut offsets: Vec<usize> = slice[molecule::NUMBER_SIZE..offset_first]
+            .chunks_exact(molecule::NUMBER_SIZE)
+            .map(|x| molecule::unpack_number(x) as usize)
+            .collect();
+        offsets.push(total_size);
+        if offsets.windows(2).any(|i| i[0] > i[1]) {
+            return ve!(Self, OffsetsNotMatch);
+        }
+        LimitOrderReader::verify(&slice[offsets[0]..offsets[1]], compatible)?;
+        Byte32Reader::verify(&slice[offsets[1]..offsets[2]], compatible)?;
+        Uint32Reader::verify(&slice[offsets[2]..offsets[3]], compatible)?;
+        Ok(())
+    }
+}
+#[derive(Debug, Default)]
+pub struct BrokeredLimitOrderBuilder {
+    pub(crate) order: LimitOrder,
+    pub(crate) broker: Byte32,
+    pub(crate) fee_bps: Uint32,
+}
+impl BrokeredLimitOrderBuilder {
+    pub const FIELD_COUNT: usize = 3;
+    pub fn order(mut self, v: LimitOrder) -> Self {
+        self.order = v;
+        self
+    }
+    pub fn broker(mut self, v: Byte32) -> Self {
+        self.broker = v;
+        self
+    }
+    pub fn fee_bps(mut self, v: Uint32) -> Self {
+        self.fee_bps = v;
+        self
+    }
+}
+impl molecule::prelude::Builder for BrokeredLimitOrderBuilder {
+    type Entity = BrokeredLimitOrder;
+    const NAME: &'static str = "BrokeredLimitOrderBuilder";
+    fn expected_length(&self) -> usize {
+        molecule::NUMBER_SIZE * (Self::FIELD_COUNT + 1)
+            + self.order.as_slice().len()
+            + self.broker.as_slice().len()
+            + self.fee_bps.as_slice().len()
+    }
+    fn write<W: molecule::io::Write>(&self, writer: &mut W) -> molecule::io::Result<()> {
+        let mut total_size = molecule::NUMBER_SIZE * (Self::FIELD_COUNT + 1);
+        let mut offsets = Vec::with_capacity(Self::FIELD_COUNT);
+        offsets.push(total_size);
+        total_size += self.order.as_slice().len();
+        offsets.push(total_size);
+        total_size += self.broker.as_slice().len();
+        offsets.push(total_size);
+        total_size += self.fee_bps.as_slice().len();
+        writer.write_all(&molecule::pack_number(total_size as molecule::Number))?;
+        for offset in offsets.into_iter() {
+            writer.write_all(&molecule::pack_number(offset as molecule::Number))?;
+        }
+        writer.write_all(self.order.as_slice())?;
+        writer.write_all(self.broker.as_slice())?;
+        writer.write_all(self.fee_bps.as_slice())?;
+        Ok(())
+    }
+    fn build(&self) -> Self::Entity {
+        let mut inner = Vec::with_capacity(self.expected_length());
+        self.write(&mut inner)
+            .unwrap_or_else(|_| panic!("{} build should be ok", Self::NAME));
+        BrokeredLimitOrder::new_unchecked(inner.into())
+    }
+}
+#[derive(Clone)]
+pub struct OwnedLimitOrder(molecule::bytes::Bytes);
+impl ::core::fmt::LowerHex for OwnedLimitOrder {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        use molecule::hex_string;
+        if f.alternate() {
+            write!(f, "0x")?;
+        }
+        write!(f, "{}", hex_string(self.as_slice()))
+    }
+}
+impl ::core::fmt::Debug for OwnedLimitOrder {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{}({:#x})", Self::NAME, self)
+    }
+}
+impl ::core::fmt::Display for OwnedLimitOrder {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{} {{ ", Self::NAME)?;
+        write!(f, "{}: {}", "order", self.order())?;
+        write!(f, ", {}: {}", "owner", self.owner())?;
+        let extra_count = self.count_extra_fields();
+        if extra_count != 0 {
+            write!(f, ", .. ({} fields)", extra_count)?;
+        }
+        write!(f, " }}")
+    }
+}
+impl ::core::default::Default for OwnedLimitOrder {
+    fn default() -> Self {
+        let v = molecule::bytes::Bytes::from_static(&Self::DEFAULT_VALUE);
+        OwnedLimitOrder::new_unchecked(v)
+    }
+}
+impl OwnedLimitOrder {
+    const DEFAULT_VALUE: [u8; 208] = [
+        208, 0, 0, 0, 12, 0, 0, 0, 176, 0, 0, 0, 164, 0, 0, 0, 28, 0, 0, 0, 60, 0, 0, 0, 76, 0, 0,
+        0, 108, 0, 0, 0, 124, 0, 0, 0, 156, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0,
+    ];
+    pub const FIELD_COUNT: usize = 2;
+    pub fn total_size(&self) -> usize {
+        molecule::unpack_number(self.as_slice()) as usize
+    }
+    pub fn field_count(&self) -> usize {
+        if self.total_size() == molecule::NUMBER_SIZE {
+            0
+        } else {
+            (molecule::unpack_number(&self.as_slice()[molecule::NUMBER_SIZE..]) as usize / 4) - 1
+        }
+    }
+    pub fn count_extra_fields(&self) -> usize {
+        self.field_count() - Self::FIELD_COUNT
+    }
+    pub fn has_extra_fields(&self) -> bool {
+        Self::FIELD_COUNT != self.field_count()
+    }
+    pub fn order(&self) -> LimitOrder {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[4..]) as usize;
+        let end = molecule::unpack_number(&slice[8..]) as usize;
+        LimitOrder::new_unchecked(self.0.slice(start..end))
+    }
+    pub fn owner(&self) -> Byte32 {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[8..]) as usize;
+        if self.has_extra_fields() {
+            let end = molecule::unpack_number(&slice[12..]) as usize;
+            Byte32::new_unchecked(self.0.slice(start..end))
+        } else {
+            Byte32::new_unchecked(self.0.slice(start..))
+        }
+    }
+    pub fn as_reader<'r>(&'r self) -> OwnedLimitOrderReader<'r> {
+        OwnedLimitOrderReader::new_unchecked(self.as_slice())
+    }
+}
+impl molecule::prelude::Entity for OwnedLimitOrder {
+    type Builder = OwnedLimitOrderBuilder;
+    const NAME: &'static str = "OwnedLimitOrder";
+    fn new_unchecked(data: molecule::bytes::Bytes) -> Self {
+        OwnedLimitOrder(data)
+    }
+    fn as_bytes(&self) -> molecule::bytes::Bytes {
+        self.0.clone()
+    }
+    fn as_slice(&self) -> &[u8] {
+        &self.0[..]
+    }
+    fn from_slice(slice: &[u8]) -> molecule::error::VerificationResult<Self> {
+        OwnedLimitOrderReader::from_slice(slice).map(|reader| reader.to_entity())
+    }
+    fn from_compatible_slice(slice: &[u8]) -> molecule::error::VerificationResult<Self> {
+        OwnedLimitOrderReader::from_compatible_slice(slice).map(|reader| reader.to_entity())
+    }
+    fn new_builder() -> Self::Builder {
+        ::core::default::Default::default()
+    }
+    fn as_builder(self) -> Self::Builder {
+        Self::new_builder().order(self.order()).owner(self.owner())
+    }
+}
+#[derive(Clone, Copy)]
+pub struct OwnedLimitOrderReader<'r>(&'r [u8]);
+impl<'r> ::core::fmt::LowerHex for OwnedLimitOrderReader<'r> {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        use molecule::hex_string;
+        if f.alternate() {
+            write!(f, "0x")?;
+        }
+        write!(f, "{}", hex_string(self.as_slice()))
+    }
+}
+impl<'r> ::core::fmt::Debug for OwnedLimitOrderReader<'r> {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{}({:#x})", Self::NAME, self)
+    }
+}
+impl<'r> ::core::fmt::Display for OwnedLimitOrderReader<'r> {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{} {{ ", Self::NAME)?;
+        write!(f, "{}: {}", "order", self.order())?;
+        write!(f, ", {}: {}", "owner", self.owner())?;
+        let extra_count = self.count_extra_fields();
+        if extra_count != 0 {
+            write!(f, ", .. ({} fields)", extra_count)?;
+        }
+        write!(f, " }}")
+    }
+}
+impl<'r> OwnedLimitOrderReader<'r> {
+    pub const FIELD_COUNT: usize = 2;
+    pub fn total_size(&self) -> usize {
+        molecule::unpack_number(self.as_slice()) as usize
+    }
+    pub fn field_count(&self) -> usize {
+        if self.total_size() == molecule::NUMBER_SIZE {
+            0
+        } else {
+            (molecule::unpack_number(&self.as_slice()[molecule::NUMBER_SIZE..]) as usize / 4) - 1
+        }
+    }
+    pub fn count_extra_fields(&self) -> usize {
+        self.field_count() - Self::FIELD_COUNT
+    }
+    pub fn has_extra_fields(&self) -> bool {
+        Self::FIELD_COUNT != self.field_count()
+    }
+    pub fn order(&self) -> LimitOrderReader<'r> {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[4..]) as usize;
+        let end = molecule::unpack_number(&slice[8..]) as usize;
+        LimitOrderReader::new_unchecked(&self.as_slice()[start..end])
+    }
+    pub fn owner(&self) -> Byte32Reader<'r> {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[8..]) as usize;
+        if self.has_extra_fields() {
+            let end = molecule::unpack_number(&slice[12..]) as usize;
+            Byte32Reader::new_unchecked(&self.as_slice()[start..end])
+        } else {
+            Byte32Reader::new_unchecked(&self.as_slice()[start..])
+        }
+    }
+}
+impl<'r> molecule::prelude::Reader<'r> for OwnedLimitOrderReader<'r> {
+    type Entity = OwnedLimitOrder;
+    const NAME: &'static str = "OwnedLimitOrderReader";
+    fn to_entity(&self) -> Self::Entity {
+        Self::Entity::new_unchecked(self.as_slice().to_owned().into())
+    }
+    fn new_unchecked(slice: &'r [u8]) -> Self {
+        OwnedLimitOrderReader(slice)
+    }
+    fn as_slice(&self) -> &'r [u8] {
+        self.0
+    }
+    fn verify(slice: &[u8], compatible: bool) -> molecule::error::VerificationResult<()> {
+        use molecule::verification_error as ve;
+        let slice_len = slice.len();
+        if slice_len < molecule::NUMBER_SIZE {
+            return ve!(Self, HeaderIsBroken, molecule::NUMBER_SIZE, slice_len);
+        }
+        let total_size = molecule::unpack_number(slice) as usize;
+        if slice_len != total_size {
+            return ve!(Self, TotalSizeNotMatch, total_size, slice_len);
+        }
+        if slice_len < molecule::NUMBER_SIZE * 2 {
+            return ve!(Self, HeaderIsBroken, molecule::NUMBER_SIZE * 2, slice_len);
+        }
+        let offset_first = molecule::unpack_number(&slice[molecule::NUMBER_SIZE..]) as usize;
+        if offset_first % molecule::NUMBER_SIZE != 0 || offset_first < molecule::NUMBER_SIZE * 2 {
+            return ve!(Self, OffsetsNotMatch);
+        }
+        if slice_len < offset_first {
+            return ve!(Self, HeaderIsBroken, offset_first, slice_len);
+        }
+        let field_count = offset_first / molecule::NUMBER_SIZE - 1;
+        if field_count < Self::FIELD_COUNT {
+            return ve!(Self, FieldCountNotMatch, Self::FIELD_COUNT, field_count);
+        } else if !compatible && field_count > Self::FIELD_COUNT {
+            return ve!(Self, FieldCountNotMatch, Self::FIELD_COUNT, field_count);
+        };
+        let mut offsets: Vec<usize> = slice[molecule::NUMBER_SIZE..offset_first]
+            .chunks_exact(molecule::NUMBER_SIZE)
+            .map(|x| molecule::unpack_number(x) as usize)
+            .collect();
+        offsets.push(total_size);
+        if offsets.windows(2).any(|i| i[0] > i[1]) {
+            return ve!(Self, OffsetsNotMatch);
+        }
+        LimitOrderReader::verify(&slice[offsets[0]..offsets[1]], compatible)?;
+        Byte32Reader::verify(&slice[offsets[1]..offsets[2]], compatible)?;
+        Ok(())
+    }
+}
+#[derive(Debug, Default)]
+pub struct OwnedLimitOrderBuilder {
+    pub(crate) order: LimitOrder,
+    pub(crate) owner: Byte32,
+}
+impl OwnedLimitOrderBuilder {
+    pub const FIELD_COUNT: usize = 2;
+    pub fn order(mut self, v: LimitOrder) -> Self {
+        self.order = v;
+        self
+    }
+    pub fn owner(mut self, v: Byte32) -> Self {
+        self.owner = v;
+        self
+    }
+}
+impl molecule::prelude::Builder for OwnedLimitOrderBuilder {
+    type Entity = OwnedLimitOrder;
+    const NAME: &'static str = "OwnedLimitOrderBuilder";
+    fn expected_length(&self) -> usize {
+        molecule::NUMBER_SIZE * (Self::FIELD_COUNT + 1)
+            + self.order.as_slice().len()
+            + self.owner.as_slice().len()
+    }
+    fn write<W: molecule::io::Write>(&self, writer: &mut W) -> molecule::io::Result<()> {
+        let mut total_size = molecule::NUMBER_SIZE * (Self::FIELD_COUNT + 1);
+        let mut offsets = Vec::with_capacity(Self::FIELD_COUNT);
+        offsets.push(total_size);
+        total_size += self.order.as_slice().len();
+        offsets.push(total_size);
+        total_size += self.owner.as_slice().len();
+        writer.write_all(&molecule::pack_number(total_size as molecule::Number))?;
+        for offset in offsets.into_iter() {
+            writer.write_all(&molecule::pack_number(offset as molecule::Number))?;
+        }
+        writer.write_all(self.order.as_slice())?;
+        writer.write_all(self.owner.as_slice())?;
+        Ok(())
+    }
+    fn build(&self) -> Self::Entity {
+        let mut inner = Vec::with_capacity(self.expected_length());
+        self.write(&mut inner)
+            .unwrap_or_else(|_| panic!("{} build should be ok", Self::NAME));
+        OwnedLimitOrder::new_unchecked(inner.into())
+    }
+}
+#[derive(Clone)]
+pub struct Order(molecule::bytes::Bytes);
+impl ::core::fmt::LowerHex for Order {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        use molecule::hex_string;
+        if f.alternate() {
+            write!(f, "0x")?;
+        }
+        write!(f, "{}", hex_string(self.as_slice()))
+    }
+}
+impl ::core::fmt::Debug for Order {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{}({:#x})", Self::NAME, self)
+    }
+}
+impl ::core::fmt::Display for Order {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{}(", Self::NAME)?;
+        self.to_enum().display_inner(f)?;
+        write!(f, ")")
+    }
+}
+impl ::core::default::Default for Order {
+    fn default() -> Self {
+        let v = molecule::bytes::Bytes::from_static(&Self::DEFAULT_VALUE);
+        Order::new_unchecked(v)
+    }
+}
+impl Order {
+    const DEFAULT_VALUE: [u8; 168] = [
+        0, 0, 0, 0, 164, 0, 0, 0, 28, 0, 0, 0, 60, 0, 0, 0, 76, 0, 0, 0, 108, 0, 0, 0, 124, 0, 0,
+        0, 156, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    ];
+    pub const ITEMS_COUNT: usize = 15;
+    pub fn item_id(&self) -> molecule::Number {
+        molecule::unpack_number(self.as_slice())
+    }
+    pub fn to_enum(&self) -> OrderUnion {
+        let inner = self.0.slice(molecule::NUMBER_SIZE..);
+        match self.item_id() {
+            0 => LimitOrder::new_unchecked(inner).into(),
+            1 => LimitOrderWithDeadline::new_unchecked(inner).into(),
+            2 => MarketOrder::new_unchecked(inner).into(),
+            3 => MarketOrderWithMinimumAsk::new_unchecked(inner).into(),
+            4 => FillOrKillOrder::new_unchecked(inner).into(),
+            5 => ImmediateOrCancelOrder::new_unchecked(inner).into(),
+            6 => MinFillLimitOrder::new_unchecked(inner).into(),
+            7 => StopLimitOrder::new_unchecked(inner).into(),
+            8 => IcebergOrder::new_unchecked(inner).into(),
+            9 => SlicedOrder::new_unchecked(inner).into(),
+            10 => NftSellOrder::new_unchecked(inner).into(),
+            11 => NftBuyOrder::new_unchecked(inner).into(),
+            12 => RouteOrder::new_unchecked(inner).into(),
+            13 => BrokeredLimitOrder::new_unchecked(inner).into(),
+            14 => OwnedLimitOrder::new_unchecked(inner).into(),
+            _ => panic!("{}: invalid data", Self::NAME),
+        }
+    }
+    pub fn as_reader<'r>(&'r self) -> OrderReader<'r> {
+        OrderReader::new_unchecked(self.as_slice())
+    }
+}
+impl molecule::prelude::Entity for Order {
+    type Builder = OrderBuilder;
+    const NAME: &'static str = "Order";
+    fn new_unchecked(data: molecule::bytes::Bytes) -> Self {
+        Order(data)
+    }
+    fn as_bytes(&self) -> molecule::bytes::Bytes {
+        self.0.clone()
+    }
+    fn as_slice(&self) -> &[u8] {
+        &self.0[..]
+    }
+    fn from_slice(slice: &[u8]) -> molecule::error::VerificationResult<Self> {
+        OrderReader::from_slice(slice).map(|reader| reader.to_entity())
+    }
+    fn from_compatible_slice(slice: &[u8]) -> molecule::error::VerificationResult<Self> {
+        OrderReader::from_compatible_slice(slice).map(|reader| reader.to_entity())
+    }
+    fn new_builder() -> Self::Builder {
+        ::core::default::Default::default()
+    }
+    fn as_builder(self) -> Self::Builder {
+        Self::new_builder().set(self.to_enum())
+    }
+}
+#[derive(Clone, Copy)]
+pub struct OrderReader<'r>(&'r [u8]);
+impl<'r> ::core::fmt::LowerHex for OrderReader<'r> {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        use molecule::hex_string;
+        if f.alternate() {
+            write!(f, "0x")?;
+        }
+        write!(f, "{}", hex_string(self.as_slice()))
+    }
+}
+impl<'r> ::core::fmt::Debug for OrderReader<'r> {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{}({:#x})", Self::NAME, self)
+    }
+}
+impl<'r> ::core::fmt::Display for OrderReader<'r> {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{}(", Self::NAME)?;
+        self.to_enum().display_inner(f)?;
+        write!(f, ")")
+    }
+}
+impl<'r> OrderReader<'r> {
+    pub const ITEMS_COUNT: usize = 15;
+    pub fn item_id(&self) -> molecule::Number {
+        molecule::unpack_number(self.as_slice())
+    }
+    pub fn to_enum(&self) -> OrderUnionReader<'r> {
+        let inner = &self.as_slice()[molecule::NUMBER_SIZE..];
+        match self.item_id() {
+            0 => LimitOrderReader::new_unchecked(inner).into(),
+            1 => LimitOrderWithDeadlineReader::new_unchecked(inner).into(),
+            2 => MarketOrderReader::new_unchecked(inner).into(),
+            3 => MarketOrderWithMinimumAskReader::new_unchecked(inner).into(),
+            4 => FillOrKillOrderReader::new_unchecked(inner).into(),
+            5 => ImmediateOrCancelOrderReader::new_unchecked(inner).into(),
+            6 => MinFillLimitOrderReader::new_unchecked(inner).into(),
+            7 => StopLimitOrderReader::new_unchecked(inner).into(),
+            8 => IcebergOrderReader::new_unchecked(inner).into(),
+            9 => SlicedOrderReader::new_unchecked(inner).into(),
+            10 => NftSellOrderReader::new_unchecked(inner).into(),
+            11 => NftBuyOrderReader::new_unchecked(inner).into(),
+            12 => RouteOrderReader::new_unchecked(inner).into(),
+            13 => BrokeredLimitOrderReader::new_unchecked(inner).into(),
+            14 => OwnedLimitOrderReader::new_unchecked(inner).into(),
+            _ => panic!("{}: invalid data", Self::NAME),
+        }
+    }
+}
+impl<'r> molecule::prelude::Reader<'r> for OrderReader<'r> {
+    type Entity = Order;
+    const NAME: &'static str = "OrderReader";
+    fn to_entity(&self) -> Self::Entity {
+        Self::Entity::new_unchecked(self.as_slice().to_owned().into())
+    }
+    fn new_unchecked(slice: &'r [u8]) -> Self {
+        OrderReader(slice)
+    }
+    fn as_slice(&self) -> &'r [u8] {
+        self.0
+    }
+    fn verify(slice: &[u8], compatible: bool) -> molecule::error::VerificationResult<()> {
+        use molecule::verification_error as ve;
+        let slice_len = slice.len();
+        if slice_len < molecule::NUMBER_SIZE {
+            return ve!(Self, HeaderIsBroken, molecule::NUMBER_SIZE, slice_len);
+        }
+        let item_id = molecule::unpack_number(slice);
+        let inner_slice = &slice[molecule::NUMBER_SIZE..];
+        match item_id {
+            0 => LimitOrderReader::verify(inner_slice, compatible),
+            1 => LimitOrderWithDeadlineReader::verify(inner_slice, compatible),
+            2 => MarketOrderReader::verify(inner_slice, compatible),
+            3 => MarketOrderWithMinimumAskReader::verify(inner_slice, compatible),
+            4 => FillOrKillOrderReader::verify(inner_slice, compatible),
+            5 => ImmediateOrCancelOrderReader::verify(inner_slice, compatible),
+            6 => MinFillLimitOrderReader::verify(inner_slice, compatible),
+            7 => StopLimitOrderReader::verify(inner_slice, compatible),
+            8 => IcebergOrderReader::verify(inner_slice, compatible),
+            9 => SlicedOrderReader::verify(inner_slice, compatible),
+            10 => NftSellOrderReader::verify(inner_slice, compatible),
+            11 => NftBuyOrderReader::verify(inner_slice, compatible),
+            12 => RouteOrderReader::verify(inner_slice, compatible),
+            13 => BrokeredLimitOrderReader::verify(inner_slice, compatible),
+            14 => OwnedLimitOrderReader::verify(inner_slice, compatible),
+            _ => ve!(Self, UnknownItem, Self::ITEMS_COUNT, item_id),
+        }?;
+        Ok(())
+    }
+}
+#[derive(Debug, Default)]
+pub struct OrderBuilder(pub(crate) OrderUnion);
+impl OrderBuilder {
+    pub const ITEMS_COUNT: usize = 15;
+    pub fn set<I>(mut self, v: I) -> Self
+    where
+        I: ::core::convert::Into<OrderUnion>,
+    {
+        self.0 = v.into();
+        self
+    }
+}
+impl molecule::prelude::Builder for OrderBuilder {
+    type Entity = Order;
+    const NAME: &'static str = "OrderBuilder";
+    fn expected_length(&self) -> usize {
+        molecule::NUMBER_SIZE + self.0.as_slice().len()
+    }
+    fn write<W: molecule::io::Write>(&self, writer: &mut W) -> molecule::io::Result<()> {
+        writer.write_all(&molecule::pack_number(self.0.item_id()))?;
+        writer.write_all(self.0.as_slice())
+    }
+    fn build(&self) -> Self::Entity {
+        let mut inner = Vec::with_capacity(self.expected_length());
+        self.write(&mut inner)
+            .unwrap_or_else(|_| panic!("{} build should be ok", Self::NAME));
+        Order::new_unchecked(inner.into())
+    }
+}
+#[derive(Debug, Clone)]
+pub enum OrderUnion {
+    LimitOrder(LimitOrder),
+    LimitOrderWithDeadline(LimitOrderWithDeadline),
+    MarketOrder(MarketOrder),
+    MarketOrderWithMinimumAsk(MarketOrderWithMinimumAsk),
+    FillOrKillOrder(FillOrKillOrder),
+    ImmediateOrCancelOrder(ImmediateOrCancelOrder),
+    MinFillLimitOrder(MinFillLimitOrder),
+    StopLimitOrder(StopLimitOrder),
+    IcebergOrder(IcebergOrder),
+    SlicedOrder(SlicedOrder),
+    NftSellOrder(NftSellOrder),
+    NftBuyOrder(NftBuyOrder),
+    RouteOrder(RouteOrder),
+    BrokeredLimitOrder(BrokeredLimitOrder),
+    OwnedLimitOrder(OwnedLimitOrder),
+}
+#[derive(Debug, Clone, Copy)]
+pub enum OrderUnionReader<'r> {
+    LimitOrder(LimitOrderReader<'r>),
+    LimitOrderWithDeadline(LimitOrderWithDeadlineReader<'r>),
+    MarketOrder(MarketOrderReader<'r>),
+    MarketOrderWithMinimumAsk(MarketOrderWithMinimumAskReader<'r>),
+    FillOrKillOrder(FillOrKillOrderReader<'r>),
+    ImmediateOrCancelOrder(ImmediateOrCancelOrderReader<'r>),
+    MinFillLimitOrder(MinFillLimitOrderReader<'r>),
+    StopLimitOrder(StopLimitOrderReader<'r>),
+    IcebergOrder(IcebergOrderReader<'r>),
+    SlicedOrder(SlicedOrderReader<'r>),
+    NftSellOrder(NftSellOrderReader<'r>),
+    NftBuyOrder(NftBuyOrderReader<'r>),
+    RouteOrder(RouteOrderReader<'r>),
+    BrokeredLimitOrder(BrokeredLimitOrderReader<'r>),
+    OwnedLimitOrder(OwnedLimitOrderReader<'r>),
+}
+impl ::core::default::Default for OrderUnion {
+    fn default() -> Self {
+        OrderUnion::LimitOrder(::core::default::Default::default())
+    }
+}
+impl ::core::fmt::Display for OrderUnion {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        match self {
+            OrderUnion::LimitOrder(ref item) => {
+                write!(f, "{}::{}({})", Self::NAME, LimitOrder::NAME, item)
+            }
+            OrderUnion::LimitOrderWithDeadline(ref item) => {
+                write!(
+                    f,
+                    "{}::{}({})",
+                    Self::NAME,
+                    LimitOrderWithDeadline::NAME,
+                    item
+                )
+            }
+            OrderUnion::MarketOrder(ref item) => {
+                write!(f, "{}::{}({})", Self::NAME, MarketOrder::NAME, item)
+            }
+            OrderUnion::MarketOrderWithMinimumAsk(ref item) => {
+                write!(
+                    f,
+                    "{}::{}({})",
+                    Self::NAME,
+                    MarketOrderWithMinimumAsk::NAME,
+                    item
+                )
+            }
+            OrderUnion::FillOrKillOrder(ref item) => {
+                write!(f, "{}::{}({})", Self::NAME, FillOrKillOrder::NAME, item)
+            }
+            OrderUnion::ImmediateOrCancelOrder(ref item) => {
+                write!(
+                    f,
+                    "{}::{}({})",
+                    Self::NAME,
+                    ImmediateOrCancelOrder::NAME,
+                    item
+                )
+            }
+            OrderUnion::MinFillLimitOrder(ref item) => {
+                write!(f, "{}::{}({})", Self::NAME, MinFillLimitOrder::NAME, item)
+            }
+            OrderUnion::StopLimitOrder(ref item) => {
+                write!(f, "{}::{}({})", Self::NAME, StopLimitOrder::NAME, item)
+            }
+            OrderUnion::IcebergOrder(ref item) => {
+                write!(f, "{}::{}({})", Self::NAME, IcebergOrder::NAME, item)
+            }
+            OrderUnion::SlicedOrder(ref item) => {
+                write!(f, "{}::{}({})", Self::NAME, SlicedOrder::NAME, item)
+            }
+            OrderUnion::NftSellOrder(ref item) => {
+                write!(f, "{}::{}({})", Self::NAME, NftSellOrder::NAME, item)
+            }
+            OrderUnion::NftBuyOrder(ref item) => {
+                write!(f, "{}::{}({})", Self::NAME, NftBuyOrder::NAME, item)
+            }
+            OrderUnion::RouteOrder(ref item) => {
+                write!(f, "{}::{}({})", Self::NAME, RouteOrder::NAME, item)
+            }
+            OrderUnion::BrokeredLimitOrder(ref item) => {
+                write!(f, "{}::{}({})", Self::NAME, BrokeredLimitOrder::NAME, item)
+            }
+            OrderUnion::OwnedLimitOrder(ref item) => {
+                write!(f, "{}::{}({})", Self::NAME, OwnedLimitOrder::NAME, item)
+            }
+        }
+    }
+}
+impl<'r> ::core::fmt::Display for OrderUnionReader<'r> {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        match self {
+            OrderUnionReader::LimitOrder(ref item) => {
+                write!(f, "{}::{}({})", Self::NAME, LimitOrder::NAME, item)
+            }
+            OrderUnionReader::LimitOrderWithDeadline(ref item) => {
+                write!(
+                    f,
+                    "{}::{}({})",
+                    Self::NAME,
+                    LimitOrderWithDeadline::NAME,
+                    item
+                )
+            }
+            OrderUnionReader::MarketOrder(ref item) => {
                 write!(f, "{}::{}({})", Self::NAME, MarketOrder::NAME, item)
             }
-            OrderUnionReader::MarketOrderWithMinimumAsk(ref item) => {
-                write!(
-                    f,
-                    "{}::{}({})",
-                    Self::NAME,
-                    MarketOrderWithMinimumAsk::NAME,
-                    item
-                )
+            OrderUnionReader::MarketOrderWithMinimumAsk(ref item) => {
+                write!(
+                    f,
+                    "{}::{}({})",
+                    Self::NAME,
+                    MarketOrderWithMinimumAsk::NAME,
+                    item
+                )
+            }
+            OrderUnionReader::FillOrKillOrder(ref item) => {
+                write!(f, "{}::{}({})", Self::NAME, FillOrKillOrder::NAME, item)
+            }
+            OrderUnionReader::ImmediateOrCancelOrder(ref item) => {
+                write!(
+                    f,
+                    "{}::{}({})",
+                    Self::NAME,
+                    ImmediateOrCancelOrder::NAME,
+                    item
+                )
+            }
+            OrderUnionReader::MinFillLimitOrder(ref item) => {
+                write!(f, "{}::{}({})", Self::NAME, MinFillLimitOrder::NAME, item)
+            }
+            OrderUnionReader::StopLimitOrder(ref item) => {
+                write!(f, "{}::{}({})", Self::NAME, StopLimitOrder::NAME, item)
+            }
+            OrderUnionReader::IcebergOrder(ref item) => {
+                write!(f, "{}::{}({})", Self::NAME, IcebergOrder::NAME, item)
+            }
+            OrderUnionReader::SlicedOrder(ref item) => {
+                write!(f, "{}::{}({})", Self::NAME, SlicedOrder::NAME, item)
+            }
+            OrderUnionReader::NftSellOrder(ref item) => {
+                write!(f, "{}::{}({})", Self::NAME, NftSellOrder::NAME, item)
+            }
+            OrderUnionReader::NftBuyOrder(ref item) => {
+                write!(f, "{}::{}({})", Self::NAME, NftBuyOrder::NAME, item)
+            }
+            OrderUnionReader::RouteOrder(ref item) => {
+                write!(f, "{}::{}({})", Self::NAME, RouteOrder::NAME, item)
+            }
+            OrderUnionReader::BrokeredLimitOrder(ref item) => {
+                write!(f, "{}::{}({})", Self::NAME, BrokeredLimitOrder::NAME, item)
+            }
+            OrderUnionReader::OwnedLimitOrder(ref item) => {
+                write!(f, "{}::{}({})", Self::NAME, OwnedLimitOrder::NAME, item)
+            }
+        }
+    }
+}
+impl OrderUnion {
+    pub(crate) fn display_inner(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        match self {
+            OrderUnion::LimitOrder(ref item) => write!(f, "{}", item),
+            OrderUnion::LimitOrderWithDeadline(ref item) => write!(f, "{}", item),
+            OrderUnion::MarketOrder(ref item) => write!(f, "{}", item),
+            OrderUnion::MarketOrderWithMinimumAsk(ref item) => write!(f, "{}", item),
+            OrderUnion::FillOrKillOrder(ref item) => write!(f, "{}", item),
+            OrderUnion::ImmediateOrCancelOrder(ref item) => write!(f, "{}", item),
+            OrderUnion::MinFillLimitOrder(ref item) => write!(f, "{}", item),
+            OrderUnion::StopLimitOrder(ref item) => write!(f, "{}", item),
+            OrderUnion::IcebergOrder(ref item) => write!(f, "{}", item),
+            OrderUnion::SlicedOrder(ref item) => write!(f, "{}", item),
+            OrderUnion::NftSellOrder(ref item) => write!(f, "{}", item),
+            OrderUnion::NftBuyOrder(ref item) => write!(f, "{}", item),
+            OrderUnion::RouteOrder(ref item) => write!(f, "{}", item),
+            OrderUnion::BrokeredLimitOrder(ref item) => write!(f, "{}", item),
+            OrderUnion::OwnedLimitOrder(ref item) => write!(f, "{}", item),
+        }
+    }
+}
+impl<'r> OrderUnionReader<'r> {
+    pub(crate) fn display_inner(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        match self {
+            OrderUnionReader::LimitOrder(ref item) => write!(f, "{}", item),
+            OrderUnionReader::LimitOrderWithDeadline(ref item) => write!(f, "{}", item),
+            OrderUnionReader::MarketOrder(ref item) => write!(f, "{}", item),
+            OrderUnionReader::MarketOrderWithMinimumAsk(ref item) => write!(f, "{}", item),
+            OrderUnionReader::FillOrKillOrder(ref item) => write!(f, "{}", item),
+            OrderUnionReader::ImmediateOrCancelOrder(ref item) => write!(f, "{}", item),
+            OrderUnionReader::MinFillLimitOrder(ref item) => write!(f, "{}", item),
+            OrderUnionReader::StopLimitOrder(ref item) => write!(f, "{}", item),
+            OrderUnionReader::IcebergOrder(ref item) => write!(f, "{}", item),
+            OrderUnionReader::SlicedOrder(ref item) => write!(f, "{}", item),
+            OrderUnionReader::NftSellOrder(ref item) => write!(f, "{}", item),
+            OrderUnionReader::NftBuyOrder(ref item) => write!(f, "{}", item),
+            OrderUnionReader::RouteOrder(ref item) => write!(f, "{}", item),
+            OrderUnionReader::BrokeredLimitOrder(ref item) => write!(f, "{}", item),
+            OrderUnionReader::OwnedLimitOrder(ref item) => write!(f, "{}", item),
+        }
+    }
+}
+impl ::core::convert::From<LimitOrder> for OrderUnion {
+    fn from(item: LimitOrder) -> Self {
+        OrderUnion::LimitOrder(item)
+    }
+}
+impl ::core::convert::From<LimitOrderWithDeadline> for OrderUnion {
+    fn from(item: LimitOrderWithDeadline) -> Self {
+        OrderUnion::LimitOrderWithDeadline(item)
+    }
+}
+impl ::core::convert::From<MarketOrder> for OrderUnion {
+    fn from(item: MarketOrder) -> Self {
+        OrderUnion::MarketOrder(item)
+    }
+}
+impl ::core::convert::From<MarketOrderWithMinimumAsk> for OrderUnion {
+    fn from(item: MarketOrderWithMinimumAsk) -> Self {
+        OrderUnion::MarketOrderWithMinimumAsk(item)
+    }
+}
+impl ::core::convert::From<FillOrKillOrder> for OrderUnion {
+    fn from(item: FillOrKillOrder) -> Self {
+        OrderUnion::FillOrKillOrder(item)
+    }
+}
+impl ::core::convert::From<ImmediateOrCancelOrder> for OrderUnion {
+    fn from(item: ImmediateOrCancelOrder) -> Self {
+        OrderUnion::ImmediateOrCancelOrder(item)
+    }
+}
+impl ::core::convert::From<MinFillLimitOrder> for OrderUnion {
+    fn from(item: MinFillLimitOrder) -> Self {
+        OrderUnion::MinFillLimitOrder(item)
+    }
+}
+impl ::core::convert::From<StopLimitOrder> for OrderUnion {
+    fn from(item: StopLimitOrder) -> Self {
+        OrderUnion::StopLimitOrder(item)
+    }
+}
+impl ::core::convert::From<IcebergOrder> for OrderUnion {
+    fn from(item: IcebergOrder) -> Self {
+        OrderUnion::IcebergOrder(item)
+    }
+}
+impl ::core::convert::From<SlicedOrder> for OrderUnion {
+    fn from(item: SlicedOrder) -> Self {
+        OrderUnion::SlicedOrder(item)
+    }
+}
+impl ::core::convert::From<NftSellOrder> for OrderUnion {
+    fn from(item: NftSellOrder) -> Self {
+        OrderUnion::NftSellOrder(item)
+    }
+}
+impl ::core::convert::From<NftBuyOrder> for OrderUnion {
+    fn from(item: NftBuyOrder) -> Self {
+        OrderUnion::NftBuyOrder(item)
+    }
+}
+impl ::core::convert::From<RouteOrder> for OrderUnion {
+    fn from(item: RouteOrder) -> Self {
+        OrderUnion::RouteOrder(item)
+    }
+}
+impl ::core::convert::From<BrokeredLimitOrder> for OrderUnion {
+    fn from(item: BrokeredLimitOrder) -> Self {
+        OrderUnion::BrokeredLimitOrder(item)
+    }
+}
+impl ::core::convert::From<OwnedLimitOrder> for OrderUnion {
+    fn from(item: OwnedLimitOrder) -> Self {
+        OrderUnion::OwnedLimitOrder(item)
+    }
+}
+impl<'r> ::core::convert::From<LimitOrderReader<'r>> for OrderUnionReader<'r> {
+    fn from(item: LimitOrderReader<'r>) -> Self {
+        OrderUnionReader::LimitOrder(item)
+    }
+}
+impl<'r> ::core::convert::From<LimitOrderWithDeadlineReader<'r>> for OrderUnionReader<'r> {
+    fn from(item: LimitOrderWithDeadlineReader<'r>) -> Self {
+        OrderUnionReader::LimitOrderWithDeadline(item)
+    }
+}
+impl<'r> ::core::convert::From<MarketOrderReader<'r>> for OrderUnionReader<'r> {
+    fn from(item: MarketOrderReader<'r>) -> Self {
+        OrderUnionReader::MarketOrder(item)
+    }
+}
+impl<'r> ::core::convert::From<MarketOrderWithMinimumAskReader<'r>> for OrderUnionReader<'r> {
+    fn from(item: MarketOrderWithMinimumAskReader<'r>) -> Self {
+        OrderUnionReader::MarketOrderWithMinimumAsk(item)
+    }
+}
+impl<'r> ::core::convert::From<FillOrKillOrderReader<'r>> for OrderUnionReader<'r> {
+    fn from(item: FillOrKillOrderReader<'r>) -> Self {
+        OrderUnionReader::FillOrKillOrder(item)
+    }
+}
+impl<'r> ::core::convert::From<ImmediateOrCancelOrderReader<'r>> for OrderUnionReader<'r> {
+    fn from(item: ImmediateOrCancelOrderReader<'r>) -> Self {
+        OrderUnionReader::ImmediateOrCancelOrder(item)
+    }
+}
+impl<'r> ::core::convert::From<MinFillLimitOrderReader<'r>> for OrderUnionReader<'r> {
+    fn from(item: MinFillLimitOrderReader<'r>) -> Self {
+        OrderUnionReader::MinFillLimitOrder(item)
+    }
+}
+impl<'r> ::core::convert::From<StopLimitOrderReader<'r>> for OrderUnionReader<'r> {
+    fn from(item: StopLimitOrderReader<'r>) -> Self {
+        OrderUnionReader::StopLimitOrder(item)
+    }
+}
+impl<'r> ::core::convert::From<IcebergOrderReader<'r>> for OrderUnionReader<'r> {
+    fn from(item: IcebergOrderReader<'r>) -> Self {
+        OrderUnionReader::IcebergOrder(item)
+    }
+}
+impl<'r> ::core::convert::From<SlicedOrderReader<'r>> for OrderUnionReader<'r> {
+    fn from(item: SlicedOrderReader<'r>) -> Self {
+        OrderUnionReader::SlicedOrder(item)
+    }
+}
+impl<'r> ::core::convert::From<NftSellOrderReader<'r>> for OrderUnionReader<'r> {
+    fn from(item: NftSellOrderReader<'r>) -> Self {
+        OrderUnionReader::NftSellOrder(item)
+    }
+}
+impl<'r> ::core::convert::From<NftBuyOrderReader<'r>> for OrderUnionReader<'r> {
+    fn from(item: NftBuyOrderReader<'r>) -> Self {
+        OrderUnionReader::NftBuyOrder(item)
+    }
+}
+impl<'r> ::core::convert::From<RouteOrderReader<'r>> for OrderUnionReader<'r> {
+    fn from(item: RouteOrderReader<'r>) -> Self {
+        OrderUnionReader::RouteOrder(item)
+    }
+}
+impl<'r> ::core::convert::From<BrokeredLimitOrderReader<'r>> for OrderUnionReader<'r> {
+    fn from(item: BrokeredLimitOrderReader<'r>) -> Self {
+        OrderUnionReader::BrokeredLimitOrder(item)
+    }
+}
+impl<'r> ::core::convert::From<OwnedLimitOrderReader<'r>> for OrderUnionReader<'r> {
+    fn from(item: OwnedLimitOrderReader<'r>) -> Self {
+        OrderUnionReader::OwnedLimitOrder(item)
+    }
+}
+impl OrderUnion {
+    pub const NAME: &'static str = "OrderUnion";
+    pub fn as_bytes(&self) -> molecule::bytes::Bytes {
+        match self {
+            OrderUnion::LimitOrder(item) => item.as_bytes(),
+            OrderUnion::LimitOrderWithDeadline(item) => item.as_bytes(),
+            OrderUnion::MarketOrder(item) => item.as_bytes(),
+            OrderUnion::MarketOrderWithMinimumAsk(item) => item.as_bytes(),
+            OrderUnion::FillOrKillOrder(item) => item.as_bytes(),
+            OrderUnion::ImmediateOrCancelOrder(item) => item.as_bytes(),
+            OrderUnion::MinFillLimitOrder(item) => item.as_bytes(),
+            OrderUnion::StopLimitOrder(item) => item.as_bytes(),
+            OrderUnion::IcebergOrder(item) => item.as_bytes(),
+            OrderUnion::SlicedOrder(item) => item.as_bytes(),
+            OrderUnion::NftSellOrder(item) => item.as_bytes(),
+            OrderUnion::NftBuyOrder(item) => item.as_bytes(),
+            OrderUnion::RouteOrder(item) => item.as_bytes(),
+            OrderUnion::BrokeredLimitOrder(item) => item.as_bytes(),
+            OrderUnion::OwnedLimitOrder(item) => item.as_bytes(),
+        }
+    }
+    pub fn as_slice(&self) -> &[u8] {
+        match self {
+            OrderUnion::LimitOrder(item) => item.as_slice(),
+            OrderUnion::LimitOrderWithDeadline(item) => item.as_slice(),
+            OrderUnion::MarketOrder(item) => item.as_slice(),
+            OrderUnion::MarketOrderWithMinimumAsk(item) => item.as_slice(),
+            OrderUnion::FillOrKillOrder(item) => item.as_slice(),
+            OrderUnion::ImmediateOrCancelOrder(item) => item.as_slice(),
+            OrderUnion::MinFillLimitOrder(item) => item.as_slice(),
+            OrderUnion::StopLimitOrder(item) => item.as_slice(),
+            OrderUnion::IcebergOrder(item) => item.as_slice(),
+            OrderUnion::SlicedOrder(item) => item.as_slice(),
+            OrderUnion::NftSellOrder(item) => item.as_slice(),
+            OrderUnion::NftBuyOrder(item) => item.as_slice(),
+            OrderUnion::RouteOrder(item) => item.as_slice(),
+            OrderUnion::BrokeredLimitOrder(item) => item.as_slice(),
+            OrderUnion::OwnedLimitOrder(item) => item.as_slice(),
+        }
+    }
+    pub fn item_id(&self) -> molecule::Number {
+        match self {
+            OrderUnion::LimitOrder(_) => 0,
+            OrderUnion::LimitOrderWithDeadline(_) => 1,
+            OrderUnion::MarketOrder(_) => 2,
+            OrderUnion::MarketOrderWithMinimumAsk(_) => 3,
+            OrderUnion::FillOrKillOrder(_) => 4,
+            OrderUnion::ImmediateOrCancelOrder(_) => 5,
+            OrderUnion::MinFillLimitOrder(_) => 6,
+            OrderUnion::StopLimitOrder(_) => 7,
+            OrderUnion::IcebergOrder(_) => 8,
+            OrderUnion::SlicedOrder(_) => 9,
+            OrderUnion::NftSellOrder(_) => 10,
+            OrderUnion::NftBuyOrder(_) => 11,
+            OrderUnion::RouteOrder(_) => 12,
+            OrderUnion::BrokeredLimitOrder(_) => 13,
+            OrderUnion::OwnedLimitOrder(_) => 14,
+        }
+    }
+    pub fn item_name(&self) -> &str {
+        match self {
+            OrderUnion::LimitOrder(_) => "LimitOrder",
+            OrderUnion::LimitOrderWithDeadline(_) => "LimitOrderWithDeadline",
+            OrderUnion::MarketOrder(_) => "MarketOrder",
+            OrderUnion::MarketOrderWithMinimumAsk(_) => "MarketOrderWithMinimumAsk",
+            OrderUnion::FillOrKillOrder(_) => "FillOrKillOrder",
+            OrderUnion::ImmediateOrCancelOrder(_) => "ImmediateOrCancelOrder",
+            OrderUnion::MinFillLimitOrder(_) => "MinFillLimitOrder",
+            OrderUnion::StopLimitOrder(_) => "StopLimitOrder",
+            OrderUnion::IcebergOrder(_) => "IcebergOrder",
+            OrderUnion::SlicedOrder(_) => "SlicedOrder",
+            OrderUnion::NftSellOrder(_) => "NftSellOrder",
+            OrderUnion::NftBuyOrder(_) => "NftBuyOrder",
+            OrderUnion::RouteOrder(_) => "RouteOrder",
+            OrderUnion::BrokeredLimitOrder(_) => "BrokeredLimitOrder",
+            OrderUnion::OwnedLimitOrder(_) => "OwnedLimitOrder",
+        }
+    }
+    pub fn as_reader<'r>(&'r self) -> OrderUnionReader<'r> {
+        match self {
+            OrderUnion::LimitOrder(item) => item.as_reader().into(),
+            OrderUnion::LimitOrderWithDeadline(item) => item.as_reader().into(),
+            OrderUnion::MarketOrder(item) => item.as_reader().into(),
+            OrderUnion::MarketOrderWithMinimumAsk(item) => item.as_reader().into(),
+            OrderUnion::FillOrKillOrder(item) => item.as_reader().into(),
+            OrderUnion::ImmediateOrCancelOrder(item) => item.as_reader().into(),
+            OrderUnion::MinFillLimitOrder(item) => item.as_reader().into(),
+            OrderUnion::StopLimitOrder(item) => item.as_reader().into(),
+            OrderUnion::IcebergOrder(item) => item.as_reader().into(),
+            OrderUnion::SlicedOrder(item) => item.as_reader().into(),
+            OrderUnion::NftSellOrder(item) => item.as_reader().into(),
+            OrderUnion::NftBuyOrder(item) => item.as_reader().into(),
+            OrderUnion::RouteOrder(item) => item.as_reader().into(),
+            OrderUnion::BrokeredLimitOrder(item) => item.as_reader().into(),
+            OrderUnion::OwnedLimitOrder(item) => item.as_reader().into(),
+        }
+    }
+}
+impl<'r> OrderUnionReader<'r> {
+    pub const NAME: &'r str = "OrderUnionReader";
+    pub fn as_slice(&self) -> &'r [u8] {
+        match self {
+            OrderUnionReader::LimitOrder(item) => item.as_slice(),
+            OrderUnionReader::LimitOrderWithDeadline(item) => item.as_slice(),
+            OrderUnionReader::MarketOrder(item) => item.as_slice(),
+            OrderUnionReader::MarketOrderWithMinimumAsk(item) => item.as_slice(),
+            OrderUnionReader::FillOrKillOrder(item) => item.as_slice(),
+            OrderUnionReader::ImmediateOrCancelOrder(item) => item.as_slice(),
+            OrderUnionReader::MinFillLimitOrder(item) => item.as_slice(),
+            OrderUnionReader::StopLimitOrder(item) => item.as_slice(),
+            OrderUnionReader::IcebergOrder(item) => item.as_slice(),
+            OrderUnionReader::SlicedOrder(item) => item.as_slice(),
+            OrderUnionReader::NftSellOrder(item) => item.as_slice(),
+            OrderUnionReader::NftBuyOrder(item) => item.as_slice(),
+            OrderUnionReader::RouteOrder(item) => item.as_slice(),
+            OrderUnionReader::BrokeredLimitOrder(item) => item.as_slice(),
+            OrderUnionReader::OwnedLimitOrder(item) => item.as_slice(),
+        }
+    }
+    pub fn item_id(&self) -> molecule::Number {
+        match self {
+            OrderUnionReader::LimitOrder(_) => 0,
+            OrderUnionReader::LimitOrderWithDeadline(_) => 1,
+            OrderUnionReader::MarketOrder(_) => 2,
+            OrderUnionReader::MarketOrderWithMinimumAsk(_) => 3,
+            OrderUnionReader::FillOrKillOrder(_) => 4,
+            OrderUnionReader::ImmediateOrCancelOrder(_) => 5,
+            OrderUnionReader::MinFillLimitOrder(_) => 6,
+            OrderUnionReader::StopLimitOrder(_) => 7,
+            OrderUnionReader::IcebergOrder(_) => 8,
+            OrderUnionReader::SlicedOrder(_) => 9,
+            OrderUnionReader::NftSellOrder(_) => 10,
+            OrderUnionReader::NftBuyOrder(_) => 11,
+            OrderUnionReader::RouteOrder(_) => 12,
+            OrderUnionReader::BrokeredLimitOrder(_) => 13,
+            OrderUnionReader::OwnedLimitOrder(_) => 14,
+        }
+    }
+    pub fn item_name(&self) -> &str {
+        match self {
+            OrderUnionReader::LimitOrder(_) => "LimitOrder",
+            OrderUnionReader::LimitOrderWithDeadline(_) => "LimitOrderWithDeadline",
+            OrderUnionReader::MarketOrder(_) => "MarketOrder",
+            OrderUnionReader::MarketOrderWithMinimumAsk(_) => "MarketOrderWithMinimumAsk",
+            OrderUnionReader::FillOrKillOrder(_) => "FillOrKillOrder",
+            OrderUnionReader::ImmediateOrCancelOrder(_) => "ImmediateOrCancelOrder",
+            OrderUnionReader::MinFillLimitOrder(_) => "MinFillLimitOrder",
+            OrderUnionReader::StopLimitOrder(_) => "StopLimitOrder",
+            OrderUnionReader::IcebergOrder(_) => "IcebergOrder",
+            OrderUnionReader::SlicedOrder(_) => "SlicedOrder",
+            OrderUnionReader::NftSellOrder(_) => "NftSellOrder",
+            OrderUnionReader::NftBuyOrder(_) => "NftBuyOrder",
+            OrderUnionReader::RouteOrder(_) => "RouteOrder",
+            OrderUnionReader::BrokeredLimitOrder(_) => "BrokeredLimitOrder",
+            OrderUnionReader::OwnedLimitOrder(_) => "OwnedLimitOrder",
+        }
+    }
+}
+#[derive(Clone)]
+pub struct Orders(molecule::bytes::Bytes);
+impl ::core::fmt::LowerHex for Orders {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        use molecule::hex_string;
+        if f.alternate() {
+            write!(f, "0x")?;
+        }
+        write!(f, "{}", hex_string(self.as_slice()))
+    }
+}
+impl ::core::fmt::Debug for Orders {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{}({:#x})", Self::NAME, self)
+    }
+}
+impl ::core::fmt::Display for Orders {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{} [", Self::NAME)?;
+        for i in 0..self.len() {
+            if i == 0 {
+                write!(f, "{}", self.get_unchecked(i))?;
+            } else {
+                write!(f, ", {}", self.get_unchecked(i))?;
+            }
+        }
+        write!(f, "]")
+    }
+}
+impl ::core::default::Default for Orders {
+    fn default() -> Self {
+        let v = molecule::bytes::Bytes::from_static(&Self::DEFAULT_VALUE);
+        Orders::new_unchecked(v)
+    }
+}
+impl Orders {
+    const DEFAULT_VALUE: [u8; 4] = [4, 0, 0, 0];
+    pub fn total_size(&self) -> usize {
+        molecule::unpack_number(self.as_slice()) as usize
+    }
+    pub fn item_count(&self) -> usize {
+        if self.total_size() == molecule::NUMBER_SIZE {
+            0
+        } else {
+            (molecule::unpack_number(&self.as_slice()[molecule::NUMBER_SIZE..]) as usize / 4) - 1
+        }
+    }
+    pub fn len(&self) -> usize {
+        self.item_count()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    pub fn get(&self, idx: usize) -> Option<Order> {
+        if idx >= self.len() {
+            None
+        } else {
+            Some(self.get_unchecked(idx))
+        }
+    }
+    pub fn get_unchecked(&self, idx: usize) -> Order {
+        let slice = self.as_slice();
+        let start_idx = molecule::NUMBER_SIZE * (1 + idx);
+        let start = molecule::unpack_number(&slice[start_idx..]) as usize;
+        if idx == self.len() - 1 {
+            Order::new_unchecked(self.0.slice(start..))
+        } else {
+            let end_idx = start_idx + molecule::NUMBER_SIZE;
+            let end = molecule::unpack_number(&slice[end_idx..]) as usize;
+            Order::new_unchecked(self.0.slice(start..end))
+        }
+    }
+    pub fn as_reader<'r>(&'r self) -> OrdersReader<'r> {
+        OrdersReader::new_unchecked(self.as_slice())
+    }
+}
+impl molecule::prelude::Entity for Orders {
+    type Builder = OrdersBuilder;
+    const NAME: &'static str = "Orders";
+    fn new_unchecked(data: molecule::bytes::Bytes) -> Self {
+        Orders(data)
+    }
+    fn as_bytes(&self) -> molecule::bytes::Bytes {
+        self.0.clone()
+    }
+    fn as_slice(&self) -> &[u8] {
+        &self.0[..]
+    }
+    fn from_slice(slice: &[u8]) -> molecule::error::VerificationResult<Self> {
+        OrdersReader::from_slice(slice).map(|reader| reader.to_entity())
+    }
+    fn from_compatible_slice(slice: &[u8]) -> molecule::error::VerificationResult<Self> {
+        OrdersReader::from_compatible_slice(slice).map(|reader| reader.to_entity())
+    }
+    fn new_builder() -> Self::Builder {
+        ::core::default::Default::default()
+    }
+    fn as_builder(self) -> Self::Builder {
+        Self::new_builder().extend(self.into_iter())
+    }
+}
+#[derive(Clone, Copy)]
+pub struct OrdersReader<'r>(&'r [u8]);
+impl<'r> ::core::fmt::LowerHex for OrdersReader<'r> {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        use molecule::hex_string;
+        if f.alternate() {
+            write!(f, "0x")?;
+        }
+        write!(f, "{}", hex_string(self.as_slice()))
+    }
+}
+impl<'r> ::core::fmt::Debug for OrdersReader<'r> {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{}({:#x})", Self::NAME, self)
+    }
+}
+impl<'r> ::core::fmt::Display for OrdersReader<'r> {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{} [", Self::NAME)?;
+        for i in 0..self.len() {
+            if i == 0 {
+                write!(f, "{}", self.get_unchecked(i))?;
+            } else {
+                write!(f, ", {}", self.get_unchecked(i))?;
+            }
+        }
+        write!(f, "]")
+    }
+}
+impl<'r> OrdersReader<'r> {
+    pub fn total_size(&self) -> usize {
+        molecule::unpack_number(self.as_slice()) as usize
+    }
+    pub fn item_count(&self) -> usize {
+        if self.total_size() == molecule::NUMBER_SIZE {
+            0
+        } else {
+            (molecule::unpack_number(&self.as_slice()[molecule::NUMBER_SIZE..]) as usize / 4) - 1
+        }
+    }
+    pub fn len(&self) -> usize {
+        self.item_count()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    pub fn get(&self, idx: usize) -> Option<OrderReader<'r>> {
+        if idx >= self.len() {
+            None
+        } else {
+            Some(self.get_unchecked(idx))
+        }
+    }
+    pub fn get_unchecked(&self, idx: usize) -> OrderReader<'r> {
+        let slice = self.as_slice();
+        let start_idx = molecule::NUMBER_SIZE * (1 + idx);
+        let start = molecule::unpack_number(&slice[start_idx..]) as usize;
+        if idx == self.len() - 1 {
+            OrderReader::new_unchecked(&self.as_slice()[start..])
+        } else {
+            let end_idx = start_idx + molecule::NUMBER_SIZE;
+            let end = molecule::unpack_number(&slice[end_idx..]) as usize;
+            OrderReader::new_unchecked(&self.as_slice()[start..end])
+        }
+    }
+}
+impl<'r> molecule::prelude::Reader<'r> for OrdersReader<'r> {
+    type Entity = Orders;
+    const NAME: &'static str = "OrdersReader";
+    fn to_entity(&self) -> Self::Entity {
+        Self::Entity::new_unchecked(self.as_slice().to_owned().into())
+    }
+    fn new_unchecked(slice: &'r [u8]) -> Self {
+        OrdersReader(slice)
+    }
+    fn as_slice(&self) -> &'r [u8] {
+        self.0
+    }
+    fn verify(slice: &[u8], compatible: bool) -> molecule::error::VerificationResult<()> {
+        use molecule::verification_error as ve;
+        let slice_len = slice.len();
+        if slice_len < molecule::NUMBER_SIZE {
+            return ve!(Self, HeaderIsBroken, molecule::NUMBER_SIZE, slice_len);
+        }
+        let total_size = molecule::unpack_number(slice) as usize;
+        if slice_len != total_size {
+            return ve!(Self, TotalSizeNotMatch, total_size, slice_len);
+        }
+        if slice_len == molecule::NUMBER_SIZE {
+            return Ok(());
+        }
+        if slice_len < molecule::NUMBER_SIZE * 2 {
+            return ve!(
+                Self,
+                TotalSizeNotMatch,
+                molecule::NUMBER_SIZE * 2,
+                slice_len
+            );
+        }
+        let offset_first = molecule::unpack_number(&slice[molecule::NUMBER_SIZE..]) as usize;
+        if offset_first % molecule::NUMBER_SIZE != 0 || offset_first < molecule::NUMBER_SIZE * 2 {
+            return ve!(Self, OffsetsNotMatch);
+        }
+        if slice_len < offset_first {
+            return ve!(Self, HeaderIsBroken, offset_first, slice_len);
+        }
+        let mut offsets: Vec<usize> = slice[molecule::NUMBER_SIZE..offset_first]
+            .chunks_exact(molecule::NUMBER_SIZE)
+            .map(|x| molecule::unpack_number(x) as usize)
+            .collect();
+        offsets.push(total_size);
+        if offsets.windows(2).any(|i| i[0] > i[1]) {
+            return ve!(Self, OffsetsNotMatch);
+        }
+        for pair in offsets.windows(2) {
+            let start = pair[0];
+            let end = pair[1];
+            OrderReader::verify(&slice[start..end], compatible)?;
+        }
+        Ok(())
+    }
+}
+#[derive(Debug, Default)]
+pub struct OrdersBuilder(pub(crate) Vec<Order>);
+impl OrdersBuilder {
+    pub fn set(mut self, v: Vec<Order>) -> Self {
+        self.0 = v;
+        self
+    }
+    pub fn push(mut self, v: Order) -> Self {
+        self.0.push(v);
+        self
+    }
+    pub fn extend<T: ::core::iter::IntoIterator<Item = Order>>(mut self, iter: T) -> Self {
+        for elem in iter {
+            self.0.push(elem);
+        }
+        self
+    }
+    pub fn replace(&mut self, index: usize, v: Order) -> Option<Order> {
+        self.0
+            .get_mut(index)
+            .map(|item| ::core::mem::replace(item, v))
+    }
+}
+impl molecule::prelude::Builder for OrdersBuilder {
+    type Entity = Orders;
+    const NAME: &'static str = "OrdersBuilder";
+    fn expected_length(&self) -> usize {
+        molecule::NUMBER_SIZE * (self.0.len() + 1)
+            + self
+                .0
+                .iter()
+                .map(|inner| inner.as_slice().len())
+                .sum::<usize>()
+    }
+    fn write<W: molecule::io::Write>(&self, writer: &mut W) -> molecule::io::Result<()> {
+        let item_count = self.0.len();
+        if item_count == 0 {
+            writer.write_all(&molecule::pack_number(
+                molecule::NUMBER_SIZE as molecule::Number,
+            ))?;
+        } else {
+            let (total_size, offsets) = self.0.iter().fold(
+                (
+                    molecule::NUMBER_SIZE * (item_count + 1),
+                    Vec::with_capacity(item_count),
+                ),
+                |(start, mut offsets), inner| {
+                    offsets.push(start);
+                    (start + inner.as_slice().len(), offsets)
+                },
+            );
+            writer.write_all(&molecule::pack_number(total_size as molecule::Number))?;
+            for offset in offsets.into_iter() {
+                writer.write_all(&molecule::pack_number(offset as molecule::Number))?;
+            }
+            for inner in self.0.iter() {
+                writer.write_all(inner.as_slice())?;
             }
         }
+        Ok(())
+    }
+    fn build(&self) -> Self::Entity {
+        let mut inner = Vec::with_capacity(self.expected_length());
+        self.write(&mut inner)
+            .unwrap_or_else(|_| panic!("{} build should be ok", Self::NAME));
+        Orders::new_unchecked(inner.into())
+    }
+}
+pub struct OrdersIterator(Orders, usize, usize);
+impl ::core::iter::Iterator for OrdersIterator {
+    type Item = Order;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.1 >= self.2 {
+            None
+        } else {
+            let ret = self.0.get_unchecked(self.1);
+            self.1 += 1;
+            Some(ret)
+        }
+    }
+}
+impl ::core::iter::ExactSizeIterator for OrdersIterator {
+    fn len(&self) -> usize {
+        self.2 - self.1
+    }
+}
+impl ::core::iter::IntoIterator for Orders {
+    type Item = Order;
+    type IntoIter = OrdersIterator;
+    fn into_iter(self) -> Self::IntoIter {
+        let len = self.len();
+        OrdersIterator(self, 0, len)
+    }
+}
+impl<'r> OrdersReader<'r> {
+    pub fn iter<'t>(&'t self) -> OrdersReaderIterator<'t, 'r> {
+        OrdersReaderIterator(&self, 0, self.len())
+    }
+}
+pub struct OrdersReaderIterator<'t, 'r>(&'t OrdersReader<'r>, usize, usize);
+impl<'t: 'r, 'r> ::core::iter::Iterator for OrdersReaderIterator<'t, 'r> {
+    type Item = OrderReader<'t>;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.1 >= self.2 {
+            None
+        } else {
+            let ret = self.0.get_unchecked(self.1);
+            self.1 += 1;
+            Some(ret)
+        }
+    }
+}
+impl<'t: 'r, 'r> ::core::iter::ExactSizeIterator for OrdersReaderIterator<'t, 'r> {
+    fn len(&self) -> usize {
+        self.2 - self.1
+    }
+}
+#[derive(Clone)]
+pub struct Dex1Action(molecule::bytes::Bytes);
+impl ::core::fmt::LowerHex for Dex1Action {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        use molecule::hex_string;
+        if f.alternate() {
+            write!(f, "0x")?;
+        }
+        write!(f, "{}", hex_string(self.as_slice()))
+    }
+}
+impl ::core::fmt::Debug for Dex1Action {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{}({:#x})", Self::NAME, self)
+    }
+}
+impl ::core::fmt::Display for Dex1Action {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{} {{ ", Self::NAME)?;
+        write!(f, "{}: {}", "orders", self.orders())?;
+        let extra_count = self.count_extra_fields();
+        if extra_count != 0 {
+            write!(f, ", .. ({} fields)", extra_count)?;
+        }
+        write!(f, " }}")
+    }
+}
+impl ::core::default::Default for Dex1Action {
+    fn default() -> Self {
+        let v = molecule::bytes::Bytes::from_static(&Self::DEFAULT_VALUE);
+        Dex1Action::new_unchecked(v)
+    }
+}
+impl Dex1Action {
+    const DEFAULT_VALUE: [u8; 12] = [12, 0, 0, 0, 8, 0, 0, 0, 4, 0, 0, 0];
+    pub const FIELD_COUNT: usize = 1;
+    pub fn total_size(&self) -> usize {
+        molecule::unpack_number(self.as_slice()) as usize
+    }
+    pub fn field_count(&self) -> usize {
+        if self.total_size() == molecule::NUMBER_SIZE {
+            0
+        } else {
+            (molecule::unpack_number(&self.as_slice()[molecule::NUMBER_SIZE..]) as usize / 4) - 1
+        }
+    }
+    pub fn count_extra_fields(&self) -> usize {
+        self.field_count() - Self::FIELD_COUNT
+    }
+    pub fn has_extra_fields(&self) -> bool {
+        Self::FIELD_COUNT != self.field_count()
+    }
+    pub fn orders(&self) -> Orders {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[4..]) as usize;
+        if self.has_extra_fields() {
+            let end = molecule::unpack_number(&slice[8..]) as usize;
+            Orders::new_unchecked(self.0.slice(start..end))
+        } else {
+            Orders::new_unchecked(self.0.slice(start..))
+        }
+    }
+    pub fn as_reader<'r>(&'r self) -> Dex1ActionReader<'r> {
+        Dex1ActionReader::new_unchecked(self.as_slice())
+    }
+}
+impl molecule::prelude::Entity for Dex1Action {
+    type Builder = Dex1ActionBuilder;
+    const NAME: &'static str = "Dex1Action";
+    fn new_unchecked(data: molecule::bytes::Bytes) -> Self {
+        Dex1Action(data)
+    }
+    fn as_bytes(&self) -> molecule::bytes::Bytes {
+        self.0.clone()
+    }
+    fn as_slice(&self) -> &[u8] {
+        &self.0[..]
+    }
+    fn from_slice(slice: &[u8]) -> molecule::error::VerificationResult<Self> {
+        Dex1ActionReader::from_slice(slice).map(|reader| reader.to_entity())
+    }
+    fn from_compatible_slice(slice: &[u8]) -> molecule::error::VerificationResult<Self> {
+        Dex1ActionReader::from_compatible_slice(slice).map(|reader| reader.to_entity())
+    }
+    fn new_builder() -> Self::Builder {
+        ::core::default::Default::default()
+    }
+    fn as_builder(self) -> Self::Builder {
+        Self::new_builder().orders(self.orders())
+    }
+}
+#[derive(Clone, Copy)]
+pub struct Dex1ActionReader<'r>(&'r [u8]);
+impl<'r> ::core::fmt::LowerHex for Dex1ActionReader<'r> {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        use molecule::hex_string;
+        if f.alternate() {
+            write!(f, "0x")?;
+        }
+        write!(f, "{}", hex_string(self.as_slice()))
+    }
+}
+impl<'r> ::core::fmt::Debug for Dex1ActionReader<'r> {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{}({:#x})", Self::NAME, self)
+    }
+}
+impl<'r> ::core::fmt::Display for Dex1ActionReader<'r> {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{} {{ ", Self::NAME)?;
+        write!(f, "{}: {}", "orders", self.orders())?;
+        let extra_count = self.count_extra_fields();
+        if extra_count != 0 {
+            write!(f, ", .. ({} fields)", extra_count)?;
+        }
+        write!(f, " }}")
+    }
+}
+impl<'r> Dex1ActionReader<'r> {
+    pub const FIELD_COUNT: usize = 1;
+    pub fn total_size(&self) -> usize {
+        molecule::unpack_number(self.as_slice()) as usize
+    }
+    pub fn field_count(&self) -> usize {
+        if self.total_size() == molecule::NUMBER_SIZE {
+            0
+        } else {
+            (molecule::unpack_number(&self.as_slice()[molecule::NUMBER_SIZE..]) as usize / 4) - 1
+        }
+    }
+    pub fn count_extra_fields(&self) -> usize {
+        self.field_count() - Self::FIELD_COUNT
+    }
+    pub fn has_extra_fields(&self) -> bool {
+        Self::FIELD_COUNT != self.field_count()
+    }
+    pub fn orders(&self) -> OrdersReader<'r> {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[4..]) as usize;
+        if self.has_extra_fields() {
+            let end = molecule::unpack_number(&slice[8..]) as usize;
+            OrdersReader::new_unchecked(&self.as_slice()[start..end])
+        } else {
+            OrdersReader::new_unchecked(&self.as_slice()[start..])
+        }
+    }
+}
+impl<'r> molecule::prelude::Reader<'r> for Dex1ActionReader<'r> {
+    type Entity = Dex1Action;
+    const NAME: &'static str = "Dex1ActionReader";
+    fn to_entity(&self) -> Self::Entity {
+        Self::Entity::new_unchecked(self.as_slice().to_owned().into())
+    }
+    fn new_unchecked(slice: &'r [u8]) -> Self {
+        Dex1ActionReader(slice)
+    }
+    fn as_slice(&self) -> &'r [u8] {
+        self.0
+    }
+    fn verify(slice: &[u8], compatible: bool) -> molecule::error::VerificationResult<()> {
+        use molecule::verification_error as ve;
+        let slice_len = slice.len();
+        if slice_len < molecule::NUMBER_SIZE {
+            return ve!(Self, HeaderIsBroken, molecule::NUMBER_SIZE, slice_len);
+        }
+        let total_size = molecule::unpack_number(slice) as usize;
+        if slice_len != total_size {
+            return ve!(Self, TotalSizeNotMatch, total_size, slice_len);
+        }
+        if slice_len < molecule::NUMBER_SIZE * 2 {
+            return ve!(Self, HeaderIsBroken, molecule::NUMBER_SIZE * 2, slice_len);
+        }
+        let offset_first = molecule::unpack_number(&slice[molecule::NUMBER_SIZE..]) as usize;
+        if offset_first % molecule::NUMBER_SIZE != 0 || offset_first < molecule::NUMBER_SIZE * 2 {
+            return ve!(Self, OffsetsNotMatch);
+        }
+        if slice_len < offset_first {
+            return ve!(Self, HeaderIsBroken, offset_first, slice_len);
+        }
+        let field_count = offset_first / molecule::NUMBER_SIZE - 1;
+        if field_count < Self::FIELD_COUNT {
+            return ve!(Self, FieldCountNotMatch, Self::FIELD_COUNT, field_count);
+        } else if !compatible && field_count > Self::FIELD_COUNT {
+            return ve!(Self, FieldCountNotMatch, Self::FIELD_COUNT, field_count);
+        };
+        let mut offsets: Vec<usize> = slice[molecule::NUMBER_SIZE..offset_first]
+            .chunks_exact(molecule::NUMBER_SIZE)
+            .map(|x| molecule::unpack_number(x) as usize)
+            .collect();
+        offsets.push(total_size);
+        if offsets.windows(2).any(|i| i[0] > i[1]) {
+            return ve!(Self, OffsetsNotMatch);
+        }
+        OrdersReader::verify(&slice[offsets[0]..offsets[1]], compatible)?;
+        Ok(())
+    }
+}
+#[derive(Debug, Default)]
+pub struct Dex1ActionBuilder {
+    pub(crate) orders: Orders,
+}
+impl Dex1ActionBuilder {
+    pub const FIELD_COUNT: usize = 1;
+    pub fn orders(mut self, v: Orders) -> Self {
+        self.orders = v;
+        self
+    }
+}
+impl molecule::prelude::Builder for Dex1ActionBuilder {
+    type Entity = Dex1Action;
+    const NAME: &'static str = "Dex1ActionBuilder";
+    fn expected_length(&self) -> usize {
+        molecule::NUMBER_SIZE * (Self::FIELD_COUNT + 1) + self.orders.as_slice().len()
+    }
+    fn write<W: molecule::io::Write>(&self, writer: &mut W) -> molecule::io::Result<()> {
+        let mut total_size = molecule::NUMBER_SIZE * (Self::FIELD_COUNT + 1);
+        let mut offsets = Vec::with_capacity(Self::FIELD_COUNT);
+        offsets.push(total_size);
+        total_size += self.orders.as_slice().len();
+        writer.write_all(&molecule::pack_number(total_size as molecule::Number))?;
+        for offset in offsets.into_iter() {
+            writer.write_all(&molecule::pack_number(offset as molecule::Number))?;
+        }
+        writer.write_all(self.orders.as_slice())?;
+        Ok(())
+    }
+    fn build(&self) -> Self::Entity {
+        let mut inner = Vec::with_capacity(self.expected_length());
+        self.write(&mut inner)
+            .unwrap_or_else(|_| panic!("{} build should be ok", Self::NAME));
+        Dex1Action::new_unchecked(inner.into())
     }
 }
-impl OrderUnion {
-    pub(crate) fn display_inner(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
-        match self {
-            OrderUnion::LimitOrder(ref item) => write!(f, "{}", item),
-            OrderUnion::LimitOrderWithDeadline(ref item) => write!(f, "{}", item),
-            OrderUnion::MarketOrder(ref item) => write!(f, "{}", item),
-            OrderUnion::MarketOrderWithMinimumAsk(ref item) => write!(f, "{}", item),
+#[derive(Clone)]
+pub struct TradingRule(molecule::bytes::Bytes);
+impl ::core::fmt::LowerHex for TradingRule {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        use molecule::hex_string;
+        if f.alternate() {
+            write!(f, "0x")?;
         }
+        write!(f, "{}", hex_string(self.as_slice()))
     }
 }
-impl<'r> OrderUnionReader<'r> {
-    pub(crate) fn display_inner(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
-        match self {
-            OrderUnionReader::LimitOrder(ref item) => write!(f, "{}", item),
-            OrderUnionReader::LimitOrderWithDeadline(ref item) => write!(f, "{}", item),
-            OrderUnionReader::MarketOrder(ref item) => write!(f, "{}", item),
-            OrderUnionReader::MarketOrderWithMinimumAsk(ref item) => write!(f, "{}", item),
+impl ::core::fmt::Debug for TradingRule {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{}({:#x})", Self::NAME, self)
+    }
+}
+impl ::core::fmt::Display for TradingRule {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{} {{ ", Self::NAME)?;
+        write!(f, "{}: {}", "first_token", self.first_token())?;
+        write!(f, ", {}: {}", "second_token", self.second_token())?;
+        write!(f, ", {}: {}", "min_size", self.min_size())?;
+        write!(f, ", {}: {}", "min_notional", self.min_notional())?;
+        write!(f, ", {}: {}", "price_tick", self.price_tick())?;
+        write!(f, ", {}: {}", "lot_size", self.lot_size())?;
+        let extra_count = self.count_extra_fields();
+        if extra_count != 0 {
+            write!(f, ", .. ({} fields)", extra_count)?;
         }
+        write!(f, " }}")
     }
 }
-impl ::core::convert::From<LimitOrder> for OrderUnion {
-    fn from(item: LimitOrder) -> Self {
-        OrderUnion::LimitOrder(item)
+impl ::core::default::Default for TradingRule {
+    fn default() -> Self {
+        let v = molecule::bytes::Bytes::from_static(&Self::DEFAULT_VALUE);
+        TradingRule::new_unchecked(v)
     }
 }
-impl ::core::convert::From<LimitOrderWithDeadline> for OrderUnion {
-    fn from(item: LimitOrderWithDeadline) -> Self {
-        OrderUnion::LimitOrderWithDeadline(item)
+impl TradingRule {
+    const DEFAULT_VALUE: [u8; 156] = [
+        156, 0, 0, 0, 28, 0, 0, 0, 60, 0, 0, 0, 92, 0, 0, 0, 108, 0, 0, 0, 124, 0, 0, 0, 140, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0,
+    ];
+    pub const FIELD_COUNT: usize = 6;
+    pub fn total_size(&self) -> usize {
+        molecule::unpack_number(self.as_slice()) as usize
+    }
+    pub fn field_count(&self) -> usize {
+        if self.total_size() == molecule::NUMBER_SIZE {
+            0
+        } else {
+            (molecule::unpack_number(&self.as_slice()[molecule::NUMBER_SIZE..]) as usize / 4) - 1
+        }
+    }
+    pub fn count_extra_fields(&self) -> usize {
+        self.field_count() - Self::FIELD_COUNT
+    }
+    pub fn has_extra_fields(&self) -> bool {
+        Self::FIELD_COUNT != self.field_count()
+    }
+    pub fn first_token(&self) -> Byte32 {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[4..]) as usize;
+        let end = molecule::unpack_number(&slice[8..]) as usize;
+        Byte32::new_unchecked(self.0.slice(start..end))
+    }
+    pub fn second_token(&self) -> Byte32 {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[8..]) as usize;
+        let end = molecule::unpack_number(&slice[12..]) as usize;
+        Byte32::new_unchecked(self.0.slice(start..end))
+    }
+    pub fn min_size(&self) -> Uint128 {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[12..]) as usize;
+        let end = molecule::unpack_number(&slice[16..]) as usize;
+        Uint128::new_unchecked(self.0.slice(start..end))
+    }
+    pub fn min_notional(&self) -> Uint128 {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[16..]) as usize;
+        let end = molecule::unpack_number(&slice[20..]) as usize;
+        Uint128::new_unchecked(self.0.slice(start..end))
+    }
+    pub fn price_tick(&self) -> Uint128 {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[20..]) as usize;
+        let end = molecule::unpack_number(&slice[24..]) as usize;
+        Uint128::new_unchecked(self.0.slice(start..end))
+    }
+    pub fn lot_size(&self) -> Uint128 {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[24..]) as usize;
+        if self.has_extra_fields() {
+            let end = molecule::unpack_number(&slice[28..]) as usize;
+            Uint128::new_unchecked(self.0.slice(start..end))
+        } else {
+            Uint128::new_unchecked(self.0.slice(start..))
+        }
+    }
+    pub fn as_reader<'r>(&'r self) -> TradingRuleReader<'r> {
+        TradingRuleReader::new_unchecked(self.as_slice())
     }
 }
-impl ::core::convert::From<MarketOrder> for OrderUnion {
-    fn from(item: MarketOrder) -> Self {
-        OrderUnion::MarketOrder(item)
+impl molecule::prelude::Entity for TradingRule {
+    type Builder = TradingRuleBuilder;
+    const NAME: &'static str = "TradingRule";
+    fn new_unchecked(data: molecule::bytes::Bytes) -> Self {
+        TradingRule(data)
+    }
+    fn as_bytes(&self) -> molecule::bytes::Bytes {
+        self.0.clone()
+    }
+    fn as_slice(&self) -> &[u8] {
+        &self.0[..]
+    }
+    fn from_slice(slice: &[u8]) -> molecule::error::VerificationResult<Self> {
+        TradingRuleReader::from_slice(slice).map(|reader| reader.to_entity())
+    }
+    fn from_compatible_slice(slice: &[u8]) -> molecule::error::VerificationResult<Self> {
+        TradingRuleReader::from_compatible_slice(slice).map(|reader| reader.to_entity())
+    }
+    fn new_builder() -> Self::Builder {
+        ::core::default::Default::default()
+    }
+    fn as_builder(self) -> Self::Builder {
+        Self::new_builder()
+            .first_token(self.first_token())
+            .second_token(self.second_token())
+            .min_size(self.min_size())
+            .min_notional(self.min_notional())
+            .price_tick(self.price_tick())
+            .lot_size(self.lot_size())
     }
 }
-impl ::core::convert::From<MarketOrderWithMinimumAsk> for OrderUnion {
-    fn from(item: MarketOrderWithMinimumAsk) -> Self {
-        OrderUnion::MarketOrderWithMinimumAsk(item)
+#[derive(Clone, Copy)]
+pub struct TradingRuleReader<'r>(&'r [u8]);
+impl<'r> ::core::fmt::LowerHex for TradingRuleReader<'r> {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        use molecule::hex_string;
+        if f.alternate() {
+            write!(f, "0x")?;
+        }
+        write!(f, "{}", hex_string(self.as_slice()))
     }
 }
-impl<'r> ::core::convert::From<LimitOrderReader<'r>> for OrderUnionReader<'r> {
-    fn from(item: LimitOrderReader<'r>) -> Self {
-        OrderUnionReader::LimitOrder(item)
+impl<'r> ::core::fmt::Debug for TradingRuleReader<'r> {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{}({:#x})", Self::NAME, self)
     }
 }
-impl<'r> ::core::convert::From<LimitOrderWithDeadlineReader<'r>> for OrderUnionReader<'r> {
-    fn from(item: LimitOrderWithDeadlineReader<'r>) -> Self {
-        OrderUnionReader::LimitOrderWithDeadline(item)
+impl<'r> ::core::fmt::Display for TradingRuleReader<'r> {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{} {{ ", Self::NAME)?;
+        write!(f, "{}: {}", "first_token", self.first_token())?;
+        write!(f, ", {}: {}", "second_token", self.second_token())?;
+        write!(f, ", {}: {}", "min_size", self.min_size())?;
+        write!(f, ", {}: {}", "min_notional", self.min_notional())?;
+        write!(f, ", {}: {}", "price_tick", self.price_tick())?;
+        write!(f, ", {}: {}", "lot_size", self.lot_size())?;
+        let extra_count = self.count_extra_fields();
+        if extra_count != 0 {
+            write!(f, ", .. ({} fields)", extra_count)?;
+        }
+        write!(f, " }}")
+    }
+}
+impl<'r> TradingRuleReader<'r> {
+    pub const FIELD_COUNT: usize = 6;
+    pub fn total_size(&self) -> usize {
+        molecule::unpack_number(self.as_slice()) as usize
+    }
+    pub fn field_count(&self) -> usize {
+        if self.total_size() == molecule::NUMBER_SIZE {
+            0
+        } else {
+            (molecule::unpack_number(&self.as_slice()[molecule::NUMBER_SIZE..]) as usize / 4) - 1
+        }
+    }
+    pub fn count_extra_fields(&self) -> usize {
+        self.field_count() - Self::FIELD_COUNT
+    }
+    pub fn has_extra_fields(&self) -> bool {
+        Self::FIELD_COUNT != self.field_count()
+    }
+    pub fn first_token(&self) -> Byte32Reader<'r> {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[4..]) as usize;
+        let end = molecule::unpack_number(&slice[8..]) as usize;
+        Byte32Reader::new_unchecked(&self.as_slice()[start..end])
+    }
+    pub fn second_token(&self) -> Byte32Reader<'r> {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[8..]) as usize;
+        let end = molecule::unpack_number(&slice[12..]) as usize;
+        Byte32Reader::new_unchecked(&self.as_slice()[start..end])
+    }
+    pub fn min_size(&self) -> Uint128Reader<'r> {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[12..]) as usize;
+        let end = molecule::unpack_number(&slice[16..]) as usize;
+        Uint128Reader::new_unchecked(&self.as_slice()[start..end])
+    }
+    pub fn min_notional(&self) -> Uint128Reader<'r> {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[16..]) as usize;
+        let end = molecule::unpack_number(&slice[20..]) as usize;
+        Uint128Reader::new_unchecked(&self.as_slice()[start..end])
+    }
+    pub fn price_tick(&self) -> Uint128Reader<'r> {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[20..]) as usize;
+        let end = molecule::unpack_number(&slice[24..]) as usize;
+        Uint128Reader::new_unchecked(&self.as_slice()[start..end])
+    }
+    pub fn lot_size(&self) -> Uint128Reader<'r> {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[24..]) as usize;
+        if self.has_extra_fields() {
+            let end = molecule::unpack_number(&slice[28..]) as usize;
+            Uint128Reader::new_unchecked(&self.as_slice()[start..end])
+        } else {
+            Uint128Reader::new_unchecked(&self.as_slice()[start..])
+        }
+    }
+}
+impl<'r> molecule::prelude::Reader<'r> for TradingRuleReader<'r> {
+    type Entity = TradingRule;
+    const NAME: &'static str = "TradingRuleReader";
+    fn to_entity(&self) -> Self::Entity {
+        Self::Entity::new_unchecked(self.as_slice().to_owned().into())
+    }
+    fn new_unchecked(slice: &'r [u8]) -> Self {
+        TradingRuleReader(slice)
+    }
+    fn as_slice(&self) -> &'r [u8] {
+        self.0
+    }
+    fn verify(slice: &[u8], compatible: bool) -> molecule::error::VerificationResult<()> {
+        use molecule::verification_error as ve;
+        let slice_len = slice.len();
+        if slice_len < molecule::NUMBER_SIZE {
+            return ve!(Self, HeaderIsBroken, molecule::NUMBER_SIZE, slice_len);
+        }
+        let total_size = molecule::unpack_number(slice) as usize;
+        if slice_len != total_size {
+            return ve!(Self, TotalSizeNotMatch, total_size, slice_len);
+        }
+        if slice_len < molecule::NUMBER_SIZE * 2 {
+            return ve!(Self, HeaderIsBroken, molecule::NUMBER_SIZE * 2, slice_len);
+        }
+        let offset_first = molecule::unpack_number(&slice[molecule::NUMBER_SIZE..]) as usize;
+        if offset_first % molecule::NUMBER_SIZE != 0 || offset_first < molecule::NUMBER_SIZE * 2 {
+            return ve!(Self, OffsetsNotMatch);
+        }
+        if slice_len < offset_first {
+            return ve!(Self, HeaderIsBroken, offset_first, slice_len);
+        }
+        let field_count = offset_first / molecule::NUMBER_SIZE - 1;
+        if field_count < Self::FIELD_COUNT {
+            return ve!(Self, FieldCountNotMatch, Self::FIELD_COUNT, field_count);
+        } else if !compatible && field_count > Self::FIELD_COUNT {
+            return ve!(Self, FieldCountNotMatch, Self::FIELD_COUNT, field_count);
+        };
+        let mut offsets: Vec<usize> = slice[molecule::NUMBER_SIZE..offset_first]
+            .chunks_exact(molecule::NUMBER_SIZE)
+            .map(|x| molecule::unpack_number(x) as usize)
+            .collect();
+        offsets.push(total_size);
+        if offsets.windows(2).any(|i| i[0] > i[1]) {
+            return ve!(Self, OffsetsNotMatch);
+        }
+        Byte32Reader::verify(&slice[offsets[0]..offsets[1]], compatible)?;
+        Byte32Reader::verify(&slice[offsets[1]..offsets[2]], compatible)?;
+        Uint128Reader::verify(&slice[offsets[2]..offsets[3]], compatible)?;
+        Uint128Reader::verify(&slice[offsets[3]..offsets[4]], compatible)?;
+        Uint128Reader::verify(&slice[offsets[4]..offsets[5]], compatible)?;
+        Uint128Reader::verify(&slice[offsets[5]..offsets[6]], compatible)?;
+        Ok(())
     }
 }
-impl<'r> ::core::convert::From<MarketOrderReader<'r>> for OrderUnionReader<'r> {
-    fn from(item: MarketOrderReader<'r>) -> Self {
-        OrderUnionReader::MarketOrder(item)
-    }
+#[derive(Debug, Default)]
+pub struct TradingRuleBuilder {
+    pub(crate) first_token: Byte32,
+    pub(crate) second_token: Byte32,
+    pub(crate) min_size: Uint128,
+    pub(crate) min_notional: Uint128,
+    pub(crate) price_tick: Uint128,
+    pub(crate) lot_size: Uint128,
 }
-impl<'r> ::core::convert::From<MarketOrderWithMinimumAskReader<'r>> for OrderUnionReader<'r> {
-    fn from(item: MarketOrderWithMinimumAskReader<'r>) -> Self {
-        OrderUnionReader::MarketOrderWithMinimumAsk(item)
+impl TradingRuleBuilder {
+    pub const FIELD_COUNT: usize = 6;
+    pub fn first_token(mut self, v: Byte32) -> Self {
+        self.first_token = v;
+        self
     }
-}
-impl OrderUnion {
-    pub const NAME: &'static str = "OrderUnion";
-    pub fn as_bytes(&self) -> molecule::bytes::Bytes {
-        match self {
-            OrderUnion::LimitOrder(item) => item.as_bytes(),
-            OrderUnion::LimitOrderWithDeadline(item) => item.as_bytes(),
-            OrderUnion::MarketOrder(item) => item.as_bytes(),
-            OrderUnion::MarketOrderWithMinimumAsk(item) => item.as_bytes(),
-        }
+    pub fn second_token(mut self, v: Byte32) -> Self {
+        self.second_token = v;
+        self
     }
-    pub fn as_slice(&self) -> &[u8] {
-        match self {
-            OrderUnion::LimitOrder(item) => item.as_slice(),
-            OrderUnion::LimitOrderWithDeadline(item) => item.as_slice(),
-            OrderUnion::MarketOrder(item) => item.as_slice(),
-            OrderUnion::MarketOrderWithMinimumAsk(item) => item.as_slice(),
-        }
+    pub fn min_size(mut self, v: Uint128) -> Self {
+        self.min_size = v;
+        self
     }
-    pub fn item_id(&self) -> molecule::Number {
-        match self {
-            OrderUnion::LimitOrder(_) => 0,
-            OrderUnion::LimitOrderWithDeadline(_) => 1,
-            OrderUnion::MarketOrder(_) => 2,
-            OrderUnion::MarketOrderWithMinimumAsk(_) => 3,
-        }
+    pub fn min_notional(mut self, v: Uint128) -> Self {
+        self.min_notional = v;
+        self
     }
-    pub fn item_name(&self) -> &str {
-        match self {
-            OrderUnion::LimitOrder(_) => "LimitOrder",
-            OrderUnion::LimitOrderWithDeadline(_) => "LimitOrderWithDeadline",
-            OrderUnion::MarketOrder(_) => "MarketOrder",
-            OrderUnion::MarketOrderWithMinimumAsk(_) => "MarketOrderWithMinimumAsk",
-        }
+    pub fn price_tick(mut self, v: Uint128) -> Self {
+        self.price_tick = v;
+        self
     }
-    pub fn as_reader<'r>(&'r self) -> OrderUnionReader<'r> {
-        match self {
-            OrderUnion::LimitOrder(item) => item.as_reader().into(),
-            OrderUnion::LimitOrderWithDeadline(item) => item.as_reader().into(),
-            OrderUnion::MarketOrder(item) => item.as_reader().into(),
-            OrderUnion::MarketOrderWithMinimumAsk(item) => item.as_reader().into(),
-        }
+    pub fn lot_size(mut self, v: Uint128) -> Self {
+        self.lot_size = v;
+        self
     }
 }
-impl<'r> OrderUnionReader<'r> {
-    pub const NAME: &'r str = "OrderUnionReader";
-    pub fn as_slice(&self) -> &'r [u8] {
-        match self {
-            OrderUnionReader::LimitOrder(item) => item.as_slice(),
-            OrderUnionReader::LimitOrderWithDeadline(item) => item.as_slice(),
-            OrderUnionReader::MarketOrder(item) => item.as_slice(),
-            OrderUnionReader::MarketOrderWithMinimumAsk(item) => item.as_slice(),
-        }
+impl molecule::prelude::Builder for TradingRuleBuilder {
+    type Entity = TradingRule;
+    const NAME: &'static str = "TradingRuleBuilder";
+    fn expected_length(&self) -> usize {
+        molecule::NUMBER_SIZE * (Self::FIELD_COUNT + 1)
+            + self.first_token.as_slice().len()
+            + self.second_token.as_slice().len()
+            + self.min_size.as_slice().len()
+            + self.min_notional.as_slice().len()
+            + self.price_tick.as_slice().len()
+            + self.lot_size.as_slice().len()
     }
-    pub fn item_id(&self) -> molecule::Number {
-        match self {
-            OrderUnionReader::LimitOrder(_) => 0,
-            OrderUnionReader::LimitOrderWithDeadline(_) => 1,
-            OrderUnionReader::MarketOrder(_) => 2,
-            OrderUnionReader::MarketOrderWithMinimumAsk(_) => 3,
+    fn write<W: molecule::io::Write>(&self, writer: &mut W) -> molecule::io::Result<()> {
+        let mut total_size = molecule::NUMBER_SIZE * (Self::FIELD_COUNT + 1);
+        let mut offsets = Vec::with_capacity(Self::FIELD_COUNT);
+        offsets.push(total_size);
+        total_size += self.first_token.as_slice().len();
+        offsets.push(total_size);
+        total_size += self.second_token.as_slice().len();
+        offsets.push(total_size);
+        total_size += self.min_size.as_slice().len();
+        offsets.push(total_size);
+        total_size += self.min_notional.as_slice().len();
+        offsets.push(total_size);
+        total_size += self.price_tick.as_slice().len();
+        offsets.push(total_size);
+        total_size += self.lot_size.as_slice().len();
+        writer.write_all(&molecule::pack_number(total_size as molecule::Number))?;
+        for offset in offsets.into_iter() {
+            writer.write_all(&molecule::pack_number(offset as molecule::Number))?;
         }
+        writer.write_all(self.first_token.as_slice())?;
+        writer.write_all(self.second_token.as_slice())?;
+        writer.write_all(self.min_size.as_slice())?;
+        writer.write_all(self.min_notional.as_slice())?;
+        writer.write_all(self.price_tick.as_slice())?;
+        writer.write_all(self.lot_size.as_slice())?;
+        Ok(())
     }
-    pub fn item_name(&self) -> &str {
-        match self {
-            OrderUnionReader::LimitOrder(_) => "LimitOrder",
-            OrderUnionReader::LimitOrderWithDeadline(_) => "LimitOrderWithDeadline",
-            OrderUnionReader::MarketOrder(_) => "MarketOrder",
-            OrderUnionReader::MarketOrderWithMinimumAsk(_) => "MarketOrderWithMinimumAsk",
-        }
+    fn build(&self) -> Self::Entity {
+        let mut inner = Vec::with_capacity(self.expected_length());
+        self.write(&mut inner)
+            .unwrap_or_else(|_| panic!("{} build should be ok", Self::NAME));
+        TradingRule::new_unchecked(inner.into())
     }
 }
 #[derive(Clone)]
-pub struct Orders(molecule::bytes::Bytes);
-impl ::core::fmt::LowerHex for Orders {
+pub struct TradingRules(molecule::bytes::Bytes);
+impl ::core::fmt::LowerHex for TradingRules {
     fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
         use molecule::hex_string;
         if f.alternate() {
@@ -1645,12 +6196,12 @@ impl ::core::fmt::LowerHex for Orders {
         write!(f, "{}", hex_string(self.as_slice()))
     }
 }
-impl ::core::fmt::Debug for Orders {
+impl ::core::fmt::Debug for TradingRules {
     fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
         write!(f, "{}({:#x})", Self::NAME, self)
     }
 }
-impl ::core::fmt::Display for Orders {
+impl ::core::fmt::Display for TradingRules {
     fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
         write!(f, "{} [", Self::NAME)?;
         for i in 0..self.len() {
@@ -1663,13 +6214,13 @@ impl ::core::fmt::Display for Orders {
         write!(f, "]")
     }
 }
-impl ::core::default::Default for Orders {
+impl ::core::default::Default for TradingRules {
     fn default() -> Self {
         let v = molecule::bytes::Bytes::from_static(&Self::DEFAULT_VALUE);
-        Orders::new_unchecked(v)
+        TradingRules::new_unchecked(v)
     }
 }
-impl Orders {
+impl TradingRules {
     const DEFAULT_VALUE: [u8; 4] = [4, 0, 0, 0];
     pub fn total_size(&self) -> usize {
         molecule::unpack_number(self.as_slice()) as usize
@@ -1687,34 +6238,34 @@ impl Orders {
     pub fn is_empty(&self) -> bool {
         self.len() == 0
     }
-    pub fn get(&self, idx: usize) -> Option<Order> {
+    pub fn get(&self, idx: usize) -> Option<TradingRule> {
         if idx >= self.len() {
             None
         } else {
             Some(self.get_unchecked(idx))
         }
     }
-    pub fn get_unchecked(&self, idx: usize) -> Order {
+    pub fn get_unchecked(&self, idx: usize) -> TradingRule {
         let slice = self.as_slice();
         let start_idx = molecule::NUMBER_SIZE * (1 + idx);
         let start = molecule::unpack_number(&slice[start_idx..]) as usize;
         if idx == self.len() - 1 {
-            Order::new_unchecked(self.0.slice(start..))
+            TradingRule::new_unchecked(self.0.slice(start..))
         } else {
             let end_idx = start_idx + molecule::NUMBER_SIZE;
             let end = molecule::unpack_number(&slice[end_idx..]) as usize;
-            Order::new_unchecked(self.0.slice(start..end))
+            TradingRule::new_unchecked(self.0.slice(start..end))
         }
     }
-    pub fn as_reader<'r>(&'r self) -> OrdersReader<'r> {
-        OrdersReader::new_unchecked(self.as_slice())
+    pub fn as_reader<'r>(&'r self) -> TradingRulesReader<'r> {
+        TradingRulesReader::new_unchecked(self.as_slice())
     }
 }
-impl molecule::prelude::Entity for Orders {
-    type Builder = OrdersBuilder;
-    const NAME: &'static str = "Orders";
+impl molecule::prelude::Entity for TradingRules {
+    type Builder = TradingRulesBuilder;
+    const NAME: &'static str = "TradingRules";
     fn new_unchecked(data: molecule::bytes::Bytes) -> Self {
-        Orders(data)
+        TradingRules(data)
     }
     fn as_bytes(&self) -> molecule::bytes::Bytes {
         self.0.clone()
@@ -1723,10 +6274,10 @@ impl molecule::prelude::Entity for Orders {
         &self.0[..]
     }
     fn from_slice(slice: &[u8]) -> molecule::error::VerificationResult<Self> {
-        OrdersReader::from_slice(slice).map(|reader| reader.to_entity())
+        TradingRulesReader::from_slice(slice).map(|reader| reader.to_entity())
     }
     fn from_compatible_slice(slice: &[u8]) -> molecule::error::VerificationResult<Self> {
-        OrdersReader::from_compatible_slice(slice).map(|reader| reader.to_entity())
+        TradingRulesReader::from_compatible_slice(slice).map(|reader| reader.to_entity())
     }
     fn new_builder() -> Self::Builder {
         ::core::default::Default::default()
@@ -1736,8 +6287,8 @@ impl molecule::prelude::Entity for Orders {
     }
 }
 #[derive(Clone, Copy)]
-pub struct OrdersReader<'r>(&'r [u8]);
-impl<'r> ::core::fmt::LowerHex for OrdersReader<'r> {
+pub struct TradingRulesReader<'r>(&'r [u8]);
+impl<'r> ::core::fmt::LowerHex for TradingRulesReader<'r> {
     fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
         use molecule::hex_string;
         if f.alternate() {
@@ -1746,12 +6297,12 @@ impl<'r> ::core::fmt::LowerHex for OrdersReader<'r> {
         write!(f, "{}", hex_string(self.as_slice()))
     }
 }
-impl<'r> ::core::fmt::Debug for OrdersReader<'r> {
+impl<'r> ::core::fmt::Debug for TradingRulesReader<'r> {
     fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
         write!(f, "{}({:#x})", Self::NAME, self)
     }
 }
-impl<'r> ::core::fmt::Display for OrdersReader<'r> {
+impl<'r> ::core::fmt::Display for TradingRulesReader<'r> {
     fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
         write!(f, "{} [", Self::NAME)?;
         for i in 0..self.len() {
@@ -1764,7 +6315,7 @@ impl<'r> ::core::fmt::Display for OrdersReader<'r> {
         write!(f, "]")
     }
 }
-impl<'r> OrdersReader<'r> {
+impl<'r> TradingRulesReader<'r> {
     pub fn total_size(&self) -> usize {
         molecule::unpack_number(self.as_slice()) as usize
     }
@@ -1781,34 +6332,34 @@ impl<'r> OrdersReader<'r> {
     pub fn is_empty(&self) -> bool {
         self.len() == 0
     }
-    pub fn get(&self, idx: usize) -> Option<OrderReader<'r>> {
+    pub fn get(&self, idx: usize) -> Option<TradingRuleReader<'r>> {
         if idx >= self.len() {
             None
         } else {
             Some(self.get_unchecked(idx))
         }
     }
-    pub fn get_unchecked(&self, idx: usize) -> OrderReader<'r> {
+    pub fn get_unchecked(&self, idx: usize) -> TradingRuleReader<'r> {
         let slice = self.as_slice();
         let start_idx = molecule::NUMBER_SIZE * (1 + idx);
         let start = molecule::unpack_number(&slice[start_idx..]) as usize;
         if idx == self.len() - 1 {
-            OrderReader::new_unchecked(&self.as_slice()[start..])
+            TradingRuleReader::new_unchecked(&self.as_slice()[start..])
         } else {
             let end_idx = start_idx + molecule::NUMBER_SIZE;
             let end = molecule::unpack_number(&slice[end_idx..]) as usize;
-            OrderReader::new_unchecked(&self.as_slice()[start..end])
+            TradingRuleReader::new_unchecked(&self.as_slice()[start..end])
         }
     }
 }
-impl<'r> molecule::prelude::Reader<'r> for OrdersReader<'r> {
-    type Entity = Orders;
-    const NAME: &'static str = "OrdersReader";
+impl<'r> molecule::prelude::Reader<'r> for TradingRulesReader<'r> {
+    type Entity = TradingRules;
+    const NAME: &'static str = "TradingRulesReader";
     fn to_entity(&self) -> Self::Entity {
         Self::Entity::new_unchecked(self.as_slice().to_owned().into())
     }
     fn new_unchecked(slice: &'r [u8]) -> Self {
-        OrdersReader(slice)
+        TradingRulesReader(slice)
     }
     fn as_slice(&self) -> &'r [u8] {
         self.0
@@ -1852,37 +6403,37 @@ impl<'r> molecule::prelude::Reader<'r> for OrdersReader<'r> {
         for pair in offsets.windows(2) {
             let start = pair[0];
             let end = pair[1];
-            OrderReader::verify(&slice[start..end], compatible)?;
+            TradingRuleReader::verify(&slice[start..end], compatible)?;
         }
         Ok(())
     }
 }
 #[derive(Debug, Default)]
-pub struct OrdersBuilder(pub(crate) Vec<Order>);
-impl OrdersBuilder {
-    pub fn set(mut self, v: Vec<Order>) -> Self {
+pub struct TradingRulesBuilder(pub(crate) Vec<TradingRule>);
+impl TradingRulesBuilder {
+    pub fn set(mut self, v: Vec<TradingRule>) -> Self {
         self.0 = v;
         self
     }
-    pub fn push(mut self, v: Order) -> Self {
+    pub fn push(mut self, v: TradingRule) -> Self {
         self.0.push(v);
         self
     }
-    pub fn extend<T: ::core::iter::IntoIterator<Item = Order>>(mut self, iter: T) -> Self {
+    pub fn extend<T: ::core::iter::IntoIterator<Item = TradingRule>>(mut self, iter: T) -> Self {
         for elem in iter {
             self.0.push(elem);
         }
         self
     }
-    pub fn replace(&mut self, index: usize, v: Order) -> Option<Order> {
+    pub fn replace(&mut self, index: usize, v: TradingRule) -> Option<TradingRule> {
         self.0
             .get_mut(index)
             .map(|item| ::core::mem::replace(item, v))
     }
 }
-impl molecule::prelude::Builder for OrdersBuilder {
-    type Entity = Orders;
-    const NAME: &'static str = "OrdersBuilder";
+impl molecule::prelude::Builder for TradingRulesBuilder {
+    type Entity = TradingRules;
+    const NAME: &'static str = "TradingRulesBuilder";
     fn expected_length(&self) -> usize {
         molecule::NUMBER_SIZE * (self.0.len() + 1)
             + self
@@ -1922,12 +6473,12 @@ impl molecule::prelude::Builder for OrdersBuilder {
         let mut inner = Vec::with_capacity(self.expected_length());
         self.write(&mut inner)
             .unwrap_or_else(|_| panic!("{} build should be ok", Self::NAME));
-        Orders::new_unchecked(inner.into())
+        TradingRules::new_unchecked(inner.into())
     }
 }
-pub struct OrdersIterator(Orders, usize, usize);
-impl ::core::iter::Iterator for OrdersIterator {
-    type Item = Order;
+pub struct TradingRulesIterator(TradingRules, usize, usize);
+impl ::core::iter::Iterator for TradingRulesIterator {
+    type Item = TradingRule;
     fn next(&mut self) -> Option<Self::Item> {
         if self.1 >= self.2 {
             None
@@ -1938,27 +6489,27 @@ impl ::core::iter::Iterator for OrdersIterator {
         }
     }
 }
-impl ::core::iter::ExactSizeIterator for OrdersIterator {
+impl ::core::iter::ExactSizeIterator for TradingRulesIterator {
     fn len(&self) -> usize {
         self.2 - self.1
     }
 }
-impl ::core::iter::IntoIterator for Orders {
-    type Item = Order;
-    type IntoIter = OrdersIterator;
+impl ::core::iter::IntoIterator for TradingRules {
+    type Item = TradingRule;
+    type IntoIter = TradingRulesIterator;
     fn into_iter(self) -> Self::IntoIter {
         let len = self.len();
-        OrdersIterator(self, 0, len)
+        TradingRulesIterator(self, 0, len)
     }
 }
-impl<'r> OrdersReader<'r> {
-    pub fn iter<'t>(&'t self) -> OrdersReaderIterator<'t, 'r> {
-        OrdersReaderIterator(&self, 0, self.len())
+impl<'r> TradingRulesReader<'r> {
+    pub fn iter<'t>(&'t self) -> TradingRulesReaderIterator<'t, 'r> {
+        TradingRulesReaderIterator(&self, 0, self.len())
     }
 }
-pub struct OrdersReaderIterator<'t, 'r>(&'t OrdersReader<'r>, usize, usize);
-impl<'t: 'r, 'r> ::core::iter::Iterator for OrdersReaderIterator<'t, 'r> {
-    type Item = OrderReader<'t>;
+pub struct TradingRulesReaderIterator<'t, 'r>(&'t TradingRulesReader<'r>, usize, usize);
+impl<'t: 'r, 'r> ::core::iter::Iterator for TradingRulesReaderIterator<'t, 'r> {
+    type Item = TradingRuleReader<'t>;
     fn next(&mut self) -> Option<Self::Item> {
         if self.1 >= self.2 {
             None
@@ -1969,14 +6520,14 @@ impl<'t: 'r, 'r> ::core::iter::Iterator for OrdersReaderIterator<'t, 'r> {
         }
     }
 }
-impl<'t: 'r, 'r> ::core::iter::ExactSizeIterator for OrdersReaderIterator<'t, 'r> {
+impl<'t: 'r, 'r> ::core::iter::ExactSizeIterator for TradingRulesReaderIterator<'t, 'r> {
     fn len(&self) -> usize {
         self.2 - self.1
     }
 }
 #[derive(Clone)]
-pub struct Dex1Action(molecule::bytes::Bytes);
-impl ::core::fmt::LowerHex for Dex1Action {
+pub struct OraclePrice(molecule::bytes::Bytes);
+impl ::core::fmt::LowerHex for OraclePrice {
     fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
         use molecule::hex_string;
         if f.alternate() {
@@ -1985,15 +6536,18 @@ impl ::core::fmt::LowerHex for Dex1Action {
         write!(f, "{}", hex_string(self.as_slice()))
     }
 }
-impl ::core::fmt::Debug for Dex1Action {
+impl ::core::fmt::Debug for OraclePrice {
     fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
         write!(f, "{}({:#x})", Self::NAME, self)
     }
 }
-impl ::core::fmt::Display for Dex1Action {
+impl ::core::fmt::Display for OraclePrice {
     fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
         write!(f, "{} {{ ", Self::NAME)?;
-        write!(f, "{}: {}", "orders", self.orders())?;
+        write!(f, "{}: {}", "base_token", self.base_token())?;
+        write!(f, ", {}: {}", "quote_token", self.quote_token())?;
+        write!(f, ", {}: {}", "base_amount", self.base_amount())?;
+        write!(f, ", {}: {}", "quote_amount", self.quote_amount())?;
         let extra_count = self.count_extra_fields();
         if extra_count != 0 {
             write!(f, ", .. ({} fields)", extra_count)?;
@@ -2001,15 +6555,20 @@ impl ::core::fmt::Display for Dex1Action {
         write!(f, " }}")
     }
 }
-impl ::core::default::Default for Dex1Action {
+impl ::core::default::Default for OraclePrice {
     fn default() -> Self {
         let v = molecule::bytes::Bytes::from_static(&Self::DEFAULT_VALUE);
-        Dex1Action::new_unchecked(v)
+        OraclePrice::new_unchecked(v)
     }
 }
-impl Dex1Action {
-    const DEFAULT_VALUE: [u8; 12] = [12, 0, 0, 0, 8, 0, 0, 0, 4, 0, 0, 0];
-    pub const FIELD_COUNT: usize = 1;
+impl OraclePrice {
+    const DEFAULT_VALUE: [u8; 116] = [
+        116, 0, 0, 0, 20, 0, 0, 0, 52, 0, 0, 0, 84, 0, 0, 0, 100, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    ];
+    pub const FIELD_COUNT: usize = 4;
     pub fn total_size(&self) -> usize {
         molecule::unpack_number(self.as_slice()) as usize
     }
@@ -2026,25 +6585,43 @@ impl Dex1Action {
     pub fn has_extra_fields(&self) -> bool {
         Self::FIELD_COUNT != self.field_count()
     }
-    pub fn orders(&self) -> Orders {
+    pub fn base_token(&self) -> Byte32 {
         let slice = self.as_slice();
         let start = molecule::unpack_number(&slice[4..]) as usize;
+        let end = molecule::unpack_number(&slice[8..]) as usize;
+        Byte32::new_unchecked(self.0.slice(start..end))
+    }
+    pub fn quote_token(&self) -> Byte32 {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[8..]) as usize;
+        let end = molecule::unpack_number(&slice[12..]) as usize;
+        Byte32::new_unchecked(self.0.slice(start..end))
+    }
+    pub fn base_amount(&self) -> Uint128 {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[12..]) as usize;
+        let end = molecule::unpack_number(&slice[16..]) as usize;
+        Uint128::new_unchecked(self.0.slice(start..end))
+    }
+    pub fn quote_amount(&self) -> Uint128 {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[16..]) as usize;
         if self.has_extra_fields() {
-            let end = molecule::unpack_number(&slice[8..]) as usize;
-            Orders::new_unchecked(self.0.slice(start..end))
+            let end = molecule::unpack_number(&slice[20..]) as usize;
+            Uint128::new_unchecked(self.0.slice(start..end))
         } else {
-            Orders::new_unchecked(self.0.slice(start..))
+            Uint128::new_unchecked(self.0.slice(start..))
         }
     }
-    pub fn as_reader<'r>(&'r self) -> Dex1ActionReader<'r> {
-        Dex1ActionReader::new_unchecked(self.as_slice())
+    pub fn as_reader<'r>(&'r self) -> OraclePriceReader<'r> {
+        OraclePriceReader::new_unchecked(self.as_slice())
     }
 }
-impl molecule::prelude::Entity for Dex1Action {
-    type Builder = Dex1ActionBuilder;
-    const NAME: &'static str = "Dex1Action";
+impl molecule::prelude::Entity for OraclePrice {
+    type Builder = OraclePriceBuilder;
+    const NAME: &'static str = "OraclePrice";
     fn new_unchecked(data: molecule::bytes::Bytes) -> Self {
-        Dex1Action(data)
+        OraclePrice(data)
     }
     fn as_bytes(&self) -> molecule::bytes::Bytes {
         self.0.clone()
@@ -2053,21 +6630,25 @@ impl molecule::prelude::Entity for Dex1Action {
         &self.0[..]
     }
     fn from_slice(slice: &[u8]) -> molecule::error::VerificationResult<Self> {
-        Dex1ActionReader::from_slice(slice).map(|reader| reader.to_entity())
+        OraclePriceReader::from_slice(slice).map(|reader| reader.to_entity())
     }
     fn from_compatible_slice(slice: &[u8]) -> molecule::error::VerificationResult<Self> {
-        Dex1ActionReader::from_compatible_slice(slice).map(|reader| reader.to_entity())
+        OraclePriceReader::from_compatible_slice(slice).map(|reader| reader.to_entity())
     }
     fn new_builder() -> Self::Builder {
         ::core::default::Default::default()
     }
     fn as_builder(self) -> Self::Builder {
-        Self::new_builder().orders(self.orders())
+        Self::new_builder()
+            .base_token(self.base_token())
+            .quote_token(self.quote_token())
+            .base_amount(self.base_amount())
+            .quote_amount(self.quote_amount())
     }
 }
 #[derive(Clone, Copy)]
-pub struct Dex1ActionReader<'r>(&'r [u8]);
-impl<'r> ::core::fmt::LowerHex for Dex1ActionReader<'r> {
+pub struct OraclePriceReader<'r>(&'r [u8]);
+impl<'r> ::core::fmt::LowerHex for OraclePriceReader<'r> {
     fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
         use molecule::hex_string;
         if f.alternate() {
@@ -2076,15 +6657,18 @@ impl<'r> ::core::fmt::LowerHex for Dex1ActionReader<'r> {
         write!(f, "{}", hex_string(self.as_slice()))
     }
 }
-impl<'r> ::core::fmt::Debug for Dex1ActionReader<'r> {
+impl<'r> ::core::fmt::Debug for OraclePriceReader<'r> {
     fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
         write!(f, "{}({:#x})", Self::NAME, self)
     }
 }
-impl<'r> ::core::fmt::Display for Dex1ActionReader<'r> {
+impl<'r> ::core::fmt::Display for OraclePriceReader<'r> {
     fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
         write!(f, "{} {{ ", Self::NAME)?;
-        write!(f, "{}: {}", "orders", self.orders())?;
+        write!(f, "{}: {}", "base_token", self.base_token())?;
+        write!(f, ", {}: {}", "quote_token", self.quote_token())?;
+        write!(f, ", {}: {}", "base_amount", self.base_amount())?;
+        write!(f, ", {}: {}", "quote_amount", self.quote_amount())?;
         let extra_count = self.count_extra_fields();
         if extra_count != 0 {
             write!(f, ", .. ({} fields)", extra_count)?;
@@ -2092,8 +6676,8 @@ impl<'r> ::core::fmt::Display for Dex1ActionReader<'r> {
         write!(f, " }}")
     }
 }
-impl<'r> Dex1ActionReader<'r> {
-    pub const FIELD_COUNT: usize = 1;
+impl<'r> OraclePriceReader<'r> {
+    pub const FIELD_COUNT: usize = 4;
     pub fn total_size(&self) -> usize {
         molecule::unpack_number(self.as_slice()) as usize
     }
@@ -2110,25 +6694,43 @@ impl<'r> Dex1ActionReader<'r> {
     pub fn has_extra_fields(&self) -> bool {
         Self::FIELD_COUNT != self.field_count()
     }
-    pub fn orders(&self) -> OrdersReader<'r> {
+    pub fn base_token(&self) -> Byte32Reader<'r> {
         let slice = self.as_slice();
         let start = molecule::unpack_number(&slice[4..]) as usize;
+        let end = molecule::unpack_number(&slice[8..]) as usize;
+        Byte32Reader::new_unchecked(&self.as_slice()[start..end])
+    }
+    pub fn quote_token(&self) -> Byte32Reader<'r> {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[8..]) as usize;
+        let end = molecule::unpack_number(&slice[12..]) as usize;
+        Byte32Reader::new_unchecked(&self.as_slice()[start..end])
+    }
+    pub fn base_amount(&self) -> Uint128Reader<'r> {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[12..]) as usize;
+        let end = molecule::unpack_number(&slice[16..]) as usize;
+        Uint128Reader::new_unchecked(&self.as_slice()[start..end])
+    }
+    pub fn quote_amount(&self) -> Uint128Reader<'r> {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[16..]) as usize;
         if self.has_extra_fields() {
-            let end = molecule::unpack_number(&slice[8..]) as usize;
-            OrdersReader::new_unchecked(&self.as_slice()[start..end])
+            let end = molecule::unpack_number(&slice[20..]) as usize;
+            Uint128Reader::new_unchecked(&self.as_slice()[start..end])
         } else {
-            OrdersReader::new_unchecked(&self.as_slice()[start..])
+            Uint128Reader::new_unchecked(&self.as_slice()[start..])
         }
     }
 }
-impl<'r> molecule::prelude::Reader<'r> for Dex1ActionReader<'r> {
-    type Entity = Dex1Action;
-    const NAME: &'static str = "Dex1ActionReader";
+impl<'r> molecule::prelude::Reader<'r> for OraclePriceReader<'r> {
+    type Entity = OraclePrice;
+    const NAME: &'static str = "OraclePriceReader";
     fn to_entity(&self) -> Self::Entity {
         Self::Entity::new_unchecked(self.as_slice().to_owned().into())
     }
     fn new_unchecked(slice: &'r [u8]) -> Self {
-        Dex1ActionReader(slice)
+        OraclePriceReader(slice)
     }
     fn as_slice(&self) -> &'r [u8] {
         self.0
@@ -2167,43 +6769,74 @@ impl<'r> molecule::prelude::Reader<'r> for Dex1ActionReader<'r> {
         if offsets.windows(2).any(|i| i[0] > i[1]) {
             return ve!(Self, OffsetsNotMatch);
         }
-        OrdersReader::verify(&slice[offsets[0]..offsets[1]], compatible)?;
+        Byte32Reader::verify(&slice[offsets[0]..offsets[1]], compatible)?;
+        Byte32Reader::verify(&slice[offsets[1]..offsets[2]], compatible)?;
+        Uint128Reader::verify(&slice[offsets[2]..offsets[3]], compatible)?;
+        Uint128Reader::verify(&slice[offsets[3]..offsets[4]], compatible)?;
         Ok(())
     }
 }
 #[derive(Debug, Default)]
-pub struct Dex1ActionBuilder {
-    pub(crate) orders: Orders,
+pub struct OraclePriceBuilder {
+    pub(crate) base_token: Byte32,
+    pub(crate) quote_token: Byte32,
+    pub(crate) base_amount: Uint128,
+    pub(crate) quote_amount: Uint128,
 }
-impl Dex1ActionBuilder {
-    pub const FIELD_COUNT: usize = 1;
-    pub fn orders(mut self, v: Orders) -> Self {
-        self.orders = v;
+impl OraclePriceBuilder {
+    pub const FIELD_COUNT: usize = 4;
+    pub fn base_token(mut self, v: Byte32) -> Self {
+        self.base_token = v;
+        self
+    }
+    pub fn quote_token(mut self, v: Byte32) -> Self {
+        self.quote_token = v;
+        self
+    }
+    pub fn base_amount(mut self, v: Uint128) -> Self {
+        self.base_amount = v;
+        self
+    }
+    pub fn quote_amount(mut self, v: Uint128) -> Self {
+        self.quote_amount = v;
         self
     }
 }
-impl molecule::prelude::Builder for Dex1ActionBuilder {
-    type Entity = Dex1Action;
-    const NAME: &'static str = "Dex1ActionBuilder";
+impl molecule::prelude::Builder for OraclePriceBuilder {
+    type Entity = OraclePrice;
+    const NAME: &'static str = "OraclePriceBuilder";
     fn expected_length(&self) -> usize {
-        molecule::NUMBER_SIZE * (Self::FIELD_COUNT + 1) + self.orders.as_slice().len()
+        molecule::NUMBER_SIZE * (Self::FIELD_COUNT + 1)
+            + self.base_token.as_slice().len()
+            + self.quote_token.as_slice().len()
+            + self.base_amount.as_slice().len()
+            + self.quote_amount.as_slice().len()
     }
     fn write<W: molecule::io::Write>(&self, writer: &mut W) -> molecule::io::Result<()> {
         let mut total_size = molecule::NUMBER_SIZE * (Self::FIELD_COUNT + 1);
         let mut offsets = Vec::with_capacity(Self::FIELD_COUNT);
         offsets.push(total_size);
-        total_size += self.orders.as_slice().len();
+        total_size += self.base_token.as_slice().len();
+        offsets.push(total_size);
+        total_size += self.quote_token.as_slice().len();
+        offsets.push(total_size);
+        total_size += self.base_amount.as_slice().len();
+        offsets.push(total_size);
+        total_size += self.quote_amount.as_slice().len();
         writer.write_all(&molecule::pack_number(total_size as molecule::Number))?;
         for offset in offsets.into_iter() {
             writer.write_all(&molecule::pack_number(offset as molecule::Number))?;
         }
-        writer.write_all(self.orders.as_slice())?;
+        writer.write_all(self.base_token.as_slice())?;
+        writer.write_all(self.quote_token.as_slice())?;
+        writer.write_all(self.base_amount.as_slice())?;
+        writer.write_all(self.quote_amount.as_slice())?;
         Ok(())
     }
     fn build(&self) -> Self::Entity {
         let mut inner = Vec::with_capacity(self.expected_length());
         self.write(&mut inner)
             .unwrap_or_else(|_| panic!("{} build should be ok", Self::NAME));
-        Dex1Action::new_unchecked(inner.into())
+        OraclePrice::new_unchecked(inner.into())
     }
 }
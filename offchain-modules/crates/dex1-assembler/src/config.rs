@@ -1,6 +1,6 @@
-use ckb_jsonrpc_types::{CellDep, JsonBytes, Script};
+use ckb_jsonrpc_types::{CellDep, JsonBytes, Script, ScriptHashType};
 use ckb_sdk::{traits::DefaultCellDepResolver, CkbRpcClient, ScriptId};
-use ckb_types::{core::BlockView, packed, prelude::*, H256};
+use ckb_types::{core::BlockView, packed, prelude::*, H160, H256};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -14,18 +14,81 @@ pub struct FullScript {
 pub struct TradingPair {
     pub first: FullScript,
     pub second: FullScript,
+    #[serde(default)]
+    pub rules: TradingRules,
+}
+
+/// Trading rules of a pair, orders violating them are rejected. Order size is
+/// measured in the first token, notional is measured in the second token.
+/// Unset rules are not enforced.
+#[derive(Clone, Default, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+#[serde(default)]
+pub struct TradingRules {
+    pub min_size: Option<u128>,
+    pub min_notional: Option<u128>,
+    // Price is expressed as the second token amount per lot
+    pub price_tick: Option<u128>,
+    // Order size must be a multiple of lot size
+    pub lot_size: Option<u128>,
 }
 
 #[derive(Clone, Default, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
 pub struct Config {
     pub dex1_deployment: FullScript,
     pub pairs: Vec<TradingPair>,
+    pub omnilock: FullScript,
+    #[serde(default)]
+    pub matching: MatchingConfig,
+    #[serde(default)]
+    pub surplus: SurplusConfig,
+    #[serde(default)]
+    pub surplus_dust: Option<SurplusDustConfig>,
+    #[serde(default)]
+    pub rings: RingConfig,
+    #[serde(default)]
+    pub packing: PackingConfig,
+    #[serde(default)]
+    pub self_trade: SelfTradeConfig,
+    // Type scripts of oracle cells stop-limit orders can be triggered by
+    #[serde(default)]
+    pub oracles: Vec<Script>,
+    // Lock scripts of brokers brokered limit orders can pay fees to
+    #[serde(default)]
+    pub brokers: Vec<Script>,
+    #[serde(default)]
+    pub tokens: Vec<TokenConfig>,
 }
 
+/// Token standard used by type scripts of a code hash, tokens of type
+/// scripts not configured here are treated as sUDT.
+#[derive(Clone, Default, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+pub struct TokenConfig {
+    pub code_hash: H256,
+    pub hash_type: ScriptHashType,
+    #[serde(flatten)]
+    pub standard: TokenStandard,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+#[serde(tag = "standard", rename_all = "snake_case")]
+pub enum TokenStandard {
+    #[default]
+    Sudt,
+    // Cells created by dex1 carry extension data after the amount
+    Xudt {
+        #[serde(default)]
+        extension_data: JsonBytes,
+    },
+}
+
+/// Trading pairs are configured by token type script hashes, native CKB uses
+/// the all-zero hash, and is represented by a default FullScript.
 #[derive(Clone, Default, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
 pub struct TradingPairHashes {
     pub first: H256,
     pub second: H256,
+    #[serde(default)]
+    pub rules: TradingRules,
 }
 
 #[derive(Clone, Default, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
@@ -34,12 +97,238 @@ pub struct TestUdt {
     name: String,
 }
 
+/// Fee rates here are all measured in shannons per KB, capacities are
+/// measured in shannons.
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+#[serde(default)]
+pub struct FeeConfig {
+    pub min_fee_rate: u64,
+    pub max_fee_rate: u64,
+    // An inflight tx that is still not proposed after this many blocks
+    // will be re-sealed with a higher fee rate.
+    pub bump_after_blocks: u64,
+    // Percentage applied to the previous fee rate when bumping fees.
+    pub bump_percentage: u64,
+    // Extra fee rate CKB requires a replacement tx to pay on top of the
+    // replaced one, used when the node does not report its own.
+    pub min_rbf_rate: u64,
+    // Processor CKB is split into a pool of fee cells of this capacity, each
+    // assembled tx consumes one fee cell.
+    pub fee_cell_capacity: u64,
+    // Number of free fee cells the processor tries to maintain.
+    pub fee_cell_count: usize,
+    // Processor alerts when free fee cells drop below this number, and no
+    // more CKB is available to refill the pool.
+    pub min_fee_cells: usize,
+}
+
+impl Default for FeeConfig {
+    fn default() -> Self {
+        Self {
+            min_fee_rate: 1000,
+            max_fee_rate: 100_000,
+            bump_after_blocks: 10,
+            bump_percentage: 150,
+            min_rbf_rate: 1500,
+            fee_cell_capacity: 100_000_000_000,
+            fee_cell_count: 20,
+            min_fee_cells: 5,
+        }
+    }
+}
+
+impl FeeConfig {
+    /// Fee rate reported by the node, bounded by configured values.
+    pub fn clamp_fee_rate(&self, fee_rate: u64) -> u64 {
+        fee_rate.clamp(self.min_fee_rate, self.max_fee_rate)
+    }
+
+    /// Fee rate replacing a stuck tx sealed with +previous+ fee rate: the
+    /// largest of +current+ fee rate, bump_percentage of the previous one and
+    /// the previous one plus +min_rbf_rate+, bounded by max_fee_rate. The
+    /// replacement has the same size, so CKB accepts it as long as the fee
+    /// rate is raised by at least +min_rbf_rate+, None is returned otherwise.
+    pub fn bumped_fee_rate(&self, current: u64, previous: u64, min_rbf_rate: u64) -> Option<u64> {
+        let min_replacement = previous.saturating_add(min_rbf_rate);
+        let fee_rate = current
+            .max(previous.saturating_mul(self.bump_percentage) / 100)
+            .max(min_replacement)
+            .min(self.max_fee_rate);
+        if fee_rate >= min_replacement {
+            Some(fee_rate)
+        } else {
+            None
+        }
+    }
+}
+
+/// Where signatures for processor's fee cells come from.
+#[derive(Clone, Default, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SignerBackendConfig {
+    // Private keys are read from wallet files passed to the processor.
+    #[default]
+    Local,
+    // Signing requests are forwarded to an external process, either via
+    // HTTP(http://...) or a unix domain socket(unix:///path/to/socket).
+    // Pubkey hashes are the keys held by the external signer.
+    External {
+        url: String,
+        pubkey_hashes: Vec<H160>,
+    },
+}
+
+/// Lock script used by processor's fee cells.
+#[derive(Clone, Default, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum FeeLockConfig {
+    #[default]
+    Sighash,
+    Multisig {
+        sighash_addresses: Vec<H160>,
+        require_first_n: u8,
+        threshold: u8,
+    },
+    // Omnilock in pubkey hash mode
+    Omnilock,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+#[serde(default)]
+pub struct SignerConfig {
+    pub backend: SignerBackendConfig,
+    pub lock: FeeLockConfig,
+}
+
+/// How orders of a trading pair are matched against each other.
+#[derive(Clone, Default, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum MatchingConfig {
+    // Orders are matched as soon as they cross, each order is paid exactly
+    // at its own limit price.
+    #[default]
+    Continuous,
+    // Orders are collected over a window of blocks, all crossing orders in
+    // the window are then settled at a single uniform clearing price.
+    BatchAuction {
+        batch_blocks: u64,
+    },
+}
+
+/// Surplus arises when crossed orders overlap, e.g., a buy order paying up to
+/// 1.05 matches a sell order asking 1.00. Tokens left after paying all
+/// matched orders are routed according to this policy. Maker is the order
+/// arriving first amongst all orders matched in a tx, taker is the one
+/// arriving last.
+#[derive(Clone, Default, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+#[serde(tag = "policy", rename_all = "snake_case")]
+pub enum SurplusConfig {
+    #[default]
+    Maker,
+    Taker,
+    // Maker gets the larger half when surplus cannot be split evenly.
+    Split,
+    // Surplus goes to protocol fee cells using this lock.
+    ProtocolFee {
+        lock: Script,
+    },
+}
+
+/// Surplus that cannot be added to the payment cell of its recipient needs a
+/// cell of its own, funded by the processor. Surplus of a token below
+/// threshold goes to a single cell at lock instead, shared by all such surplus
+/// of the token in a tx.
+#[derive(Clone, Default, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+pub struct SurplusDustConfig {
+    pub threshold: u128,
+    pub lock: Script,
+}
+
+/// Self-trade prevention: a buy order and a sell order of the same owner are
+/// never settled in the same tx. Orders share an owner when they use the
+/// same recipient script, or when any of their input cells share a lock.
+#[derive(Clone, Default, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum SelfTradeConfig {
+    // The order arriving later is cancelled.
+    #[default]
+    CancelNewest,
+    // The order arriving earlier is cancelled.
+    CancelOldest,
+    // The order arriving later sits out the current round, both orders stay
+    // in the order book.
+    Skip,
+}
+
+/// Ring matching settles orders of different pairs forming a cycle, such as
+/// A -> B, B -> C and C -> A, in one tx.
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+#[serde(default)]
+pub struct RingConfig {
+    pub enabled: bool,
+    // Maximum number of orders in a ring, a ring needs at least 3 orders.
+    pub max_length: usize,
+}
+
+impl Default for RingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_length: 4,
+        }
+    }
+}
+
+/// Limits used when packing orders into one tx. Serialized size and script
+/// cycles of a tx are estimated while orders are packed.
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+#[serde(default)]
+pub struct PackingConfig {
+    pub max_tx_size: usize,
+    pub max_cycles: u64,
+    // Cycles assumed for a lock script that has not been verified yet.
+    pub default_lock_cycles: u64,
+}
+
+impl Default for PackingConfig {
+    fn default() -> Self {
+        Self {
+            max_tx_size: 100_000,
+            // CKB tx pool verifies txs under this limit synchronously
+            max_cycles: 70_000_000,
+            default_lock_cycles: 5_000_000,
+        }
+    }
+}
+
 #[derive(Clone, Default, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
 pub struct RunnerConfig {
     pub otx_rpc: String,
     pub ckb_rpc: String,
     pub dex1_deployment: Option<JsonBytes>,
     pub dex1_trading_pair_hashes: Vec<TradingPairHashes>,
+    #[serde(default)]
+    pub fee: FeeConfig,
+    #[serde(default)]
+    pub signer: SignerConfig,
+    #[serde(default)]
+    pub matching: MatchingConfig,
+    #[serde(default)]
+    pub surplus: SurplusConfig,
+    #[serde(default)]
+    pub surplus_dust: Option<SurplusDustConfig>,
+    #[serde(default)]
+    pub rings: RingConfig,
+    #[serde(default)]
+    pub packing: PackingConfig,
+    #[serde(default)]
+    pub self_trade: SelfTradeConfig,
+    #[serde(default)]
+    pub oracles: Vec<Script>,
+    #[serde(default)]
+    pub brokers: Vec<Script>,
+    #[serde(default)]
+    pub tokens: Vec<TokenConfig>,
 
     pub test_udts: Vec<TestUdt>,
     pub omnilock: FullScript,
@@ -67,6 +356,7 @@ impl From<FullScript> for PackedFullScript {
 pub struct PackedTradingPair {
     pub first: PackedFullScript,
     pub second: PackedFullScript,
+    pub rules: TradingRules,
 }
 
 impl From<TradingPair> for PackedTradingPair {
@@ -74,6 +364,7 @@ impl From<TradingPair> for PackedTradingPair {
         PackedTradingPair {
             first: input.first.into(),
             second: input.second.into(),
+            rules: input.rules,
         }
     }
 }
@@ -92,17 +383,19 @@ impl RunnerConfig {
             .dex1_trading_pair_hashes
             .iter()
             .map(|hashes| {
-                let first = known_scripts
-                    .get(&hashes.first)
-                    .expect("first of pair")
-                    .1
-                    .clone();
-                let second = known_scripts
-                    .get(&hashes.second)
-                    .expect("second of pair")
-                    .1
-                    .clone();
-                TradingPair { first, second }
+                let token = |hash: &H256| {
+                    if *hash == H256::default() {
+                        return FullScript::default();
+                    }
+                    known_scripts.get(hash).expect("token of pair").1.clone()
+                };
+                let first = token(&hashes.first);
+                let second = token(&hashes.second);
+                TradingPair {
+                    first,
+                    second,
+                    rules: hashes.rules.clone(),
+                }
             })
             .collect();
 
@@ -114,6 +407,16 @@ impl RunnerConfig {
                 &self.dex1,
             ),
             pairs,
+            omnilock: self.omnilock.clone(),
+            matching: self.matching.clone(),
+            surplus: self.surplus.clone(),
+            surplus_dust: self.surplus_dust.clone(),
+            rings: self.rings.clone(),
+            packing: self.packing.clone(),
+            self_trade: self.self_trade.clone(),
+            oracles: self.oracles.clone(),
+            brokers: self.brokers.clone(),
+            tokens: self.tokens.clone(),
         }
     }
 
@@ -193,3 +496,58 @@ impl RunnerConfig {
         result
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ckb_types::core::FeeRate;
+
+    fn fee_config() -> FeeConfig {
+        FeeConfig {
+            min_fee_rate: 1000,
+            max_fee_rate: 10_000,
+            bump_percentage: 150,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_fee_rate_is_clamped_to_configured_range() {
+        let config = fee_config();
+        assert_eq!(config.clamp_fee_rate(0), 1000);
+        assert_eq!(config.clamp_fee_rate(4000), 4000);
+        assert_eq!(config.clamp_fee_rate(1_000_000), 10_000);
+    }
+
+    #[test]
+    fn test_bumped_fee_rate() {
+        let config = fee_config();
+        // At least bump_percentage of the previous fee rate
+        assert_eq!(config.bumped_fee_rate(1000, 4000, 1000), Some(6000));
+        // Node fee rate wins when it is higher
+        assert_eq!(config.bumped_fee_rate(7000, 2000, 1000), Some(7000));
+        // Raised by at least the minimal replacement fee rate
+        assert_eq!(config.bumped_fee_rate(1000, 2000, 1500), Some(3500));
+        // Bounded by max fee rate
+        assert_eq!(config.bumped_fee_rate(1000, 8000, 1000), Some(10_000));
+        // Nothing to bump at max fee rate
+        assert_eq!(config.bumped_fee_rate(20_000, 10_000, 1000), None);
+        // Max fee rate does not leave room for an acceptable replacement
+        assert_eq!(config.bumped_fee_rate(1000, 9000, 1500), None);
+
+        // Default config bumps a tx at min fee rate to an acceptable
+        // replacement under CKB's default min_rbf_rate.
+        let config = FeeConfig::default();
+        let previous = config.min_fee_rate;
+        let fee_rate = config
+            .bumped_fee_rate(previous, previous, config.min_rbf_rate)
+            .unwrap();
+        assert_eq!(fee_rate, 2500);
+        // CKB requires the replacement to pay min_rbf_rate on top of the fee
+        // of the replaced tx, for any tx size.
+        for size in [1, 999, 1000, 4321, 600_000] {
+            let fee = |rate: u64| FeeRate::from_u64(rate).fee(size).as_u64();
+            assert!(fee(fee_rate) >= fee(previous) + fee(config.min_rbf_rate));
+        }
+    }
+}
@@ -0,0 +1,234 @@
+use crate::config::FeeConfig;
+use ckb_sdk::traits::LiveCell;
+use ckb_types::{
+    core::Capacity,
+    packed::{Byte32, OutPoint},
+    prelude::*,
+};
+use std::collections::HashMap;
+use std::fmt;
+
+/// Minimal capacity for a fee cell to be usable: 61 CKB are occupied by the
+/// change cell itself, the rest is left for tx fees.
+pub fn minimal_fee_cell_capacity() -> u64 {
+    Capacity::bytes(71).expect("overflow").as_u64()
+}
+
+/// Errors sealing a tx with a fee cell from the pool. Both can be recovered
+/// from by refilling the pool, unless +required+ capacity is beyond any fee
+/// cell.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FeeCellError {
+    NoFreeFeeCell,
+    InsufficientCapacity { required: u64 },
+}
+
+impl fmt::Display for FeeCellError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FeeCellError::NoFreeFeeCell => write!(f, "No free fee cell is available!"),
+            FeeCellError::InsufficientCapacity { required } => write!(
+                f,
+                "Fee cell does not have enough capacity, {} shannons are required!",
+                required
+            ),
+        }
+    }
+}
+
+impl std::error::Error for FeeCellError {}
+
+/// Fee cell pool keeps track of empty sighash cells owned by the processor.
+/// Each sealed tx consumes exactly one fee cell, and sends the change back as
+/// a new fee cell. Cells that are too large to be fee cells are used to refill
+/// the pool when it runs low.
+pub struct FeeCellPool {
+    config: FeeConfig,
+
+    // Fee cells consumed by sealed txs that are not yet committed, mapping
+    // to hashes of the consuming txs.
+    locked: HashMap<OutPoint, Byte32>,
+}
+
+impl FeeCellPool {
+    pub fn new(config: &FeeConfig) -> Self {
+        Self {
+            config: config.clone(),
+            locked: HashMap::default(),
+        }
+    }
+
+    pub fn is_fee_cell(&self, cell: &LiveCell) -> bool {
+        let capacity: u64 = cell.output.capacity().unpack();
+        capacity >= minimal_fee_cell_capacity()
+            && capacity < self.config.fee_cell_capacity.saturating_mul(2)
+    }
+
+    pub fn is_locked(&self, cell: &LiveCell) -> bool {
+        self.locked.contains_key(&cell.out_point)
+    }
+
+    /// Free fee cells amongst all cells owned by the processor.
+    pub fn free_fee_cells<'a>(&self, cells: &'a [LiveCell]) -> Vec<&'a LiveCell> {
+        cells
+            .iter()
+            .filter(|cell| self.is_fee_cell(cell) && !self.is_locked(cell))
+            .collect()
+    }
+
+    /// Cells that can be split into new fee cells. Dust cells below the
+    /// minimal fee cell capacity are also merged here.
+    pub fn refill_cells<'a>(&self, cells: &'a [LiveCell]) -> Vec<&'a LiveCell> {
+        cells
+            .iter()
+            .filter(|cell| !self.is_fee_cell(cell) && !self.is_locked(cell))
+            .collect()
+    }
+
+    /// Pick the free fee cell with the most capacity.
+    pub fn pick(&self, cells: &[LiveCell]) -> Option<LiveCell> {
+        self.free_fee_cells(cells)
+            .into_iter()
+            .max_by_key(|cell| Unpack::<u64>::unpack(&cell.output.capacity()))
+            .cloned()
+    }
+
+    /// Number of fee cells to create when refilling the pool of
+    /// +free_fee_cells+, using cells of +total_capacity+. The change cell must
+    /// at least be a valid fee cell, with 1 extra CKB as tx fee.
+    pub fn refill_count(&self, free_fee_cells: usize, total_capacity: u64) -> usize {
+        let reserved_capacity =
+            minimal_fee_cell_capacity() + Capacity::bytes(1).expect("overflow").as_u64();
+        std::cmp::min(
+            self.config.fee_cell_count.saturating_sub(free_fee_cells),
+            (total_capacity.saturating_sub(reserved_capacity) / self.config.fee_cell_capacity)
+                as usize,
+        )
+    }
+
+    pub fn lock(&mut self, out_point: OutPoint, tx_hash: Byte32) {
+        self.locked.insert(out_point, tx_hash);
+    }
+
+    /// Release fee cells consumed by a tx, this shall be called when the tx is
+    /// either committed, or rejected by CKB.
+    pub fn release(&mut self, tx_hash: &Byte32) {
+        self.locked
+            .retain(|_, locking_tx_hash| locking_tx_hash != tx_hash);
+    }
+
+    pub fn locked_count(&self) -> usize {
+        self.locked.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ckb_types::{bytes::Bytes, packed::CellOutput};
+
+    const CKB: u64 = 100_000_000;
+
+    fn pool() -> FeeCellPool {
+        FeeCellPool::new(&FeeConfig {
+            fee_cell_capacity: 1000 * CKB,
+            fee_cell_count: 4,
+            ..Default::default()
+        })
+    }
+
+    fn cell(index: u32, capacity: u64) -> LiveCell {
+        LiveCell {
+            output: CellOutput::new_builder().capacity(capacity.pack()).build(),
+            output_data: Bytes::default(),
+            out_point: OutPoint::new(Byte32::default(), index),
+            block_number: 0,
+            tx_index: 0,
+        }
+    }
+
+    fn tx_hash(n: u8) -> Byte32 {
+        [n; 32].pack()
+    }
+
+    #[test]
+    fn test_fee_cells_are_within_capacity_window() {
+        let pool = pool();
+        // Dust cells cannot even hold the change cell
+        assert!(!pool.is_fee_cell(&cell(0, 70 * CKB)));
+        assert!(pool.is_fee_cell(&cell(0, 71 * CKB)));
+        assert!(pool.is_fee_cell(&cell(0, 1000 * CKB)));
+        // Cells of twice the fee cell capacity are used to refill the pool
+        assert!(pool.is_fee_cell(&cell(0, 2000 * CKB - 1)));
+        assert!(!pool.is_fee_cell(&cell(0, 2000 * CKB)));
+
+        let cells = vec![cell(0, 10 * CKB), cell(1, 900 * CKB), cell(2, 5000 * CKB)];
+        let free: Vec<_> = pool.free_fee_cells(&cells);
+        assert_eq!(free.len(), 1);
+        assert_eq!(free[0].out_point, cells[1].out_point);
+        let refill: Vec<_> = pool
+            .refill_cells(&cells)
+            .into_iter()
+            .map(|cell| cell.out_point.clone())
+            .collect();
+        assert_eq!(
+            refill,
+            vec![cells[0].out_point.clone(), cells[2].out_point.clone()]
+        );
+    }
+
+    #[test]
+    fn test_largest_free_fee_cell_is_picked() {
+        let mut pool = pool();
+        let cells = vec![
+            cell(0, 500 * CKB),
+            cell(1, 1500 * CKB),
+            cell(2, 1000 * CKB),
+            cell(3, 5000 * CKB),
+        ];
+        assert_eq!(pool.pick(&cells).unwrap().out_point, cells[1].out_point);
+
+        // Locked fee cells are skipped
+        pool.lock(cells[1].out_point.clone(), tx_hash(1));
+        assert_eq!(pool.pick(&cells).unwrap().out_point, cells[2].out_point);
+        pool.lock(cells[2].out_point.clone(), tx_hash(2));
+        pool.lock(cells[0].out_point.clone(), tx_hash(2));
+        assert!(pool.pick(&cells).is_none());
+        assert!(pool.refill_cells(&cells).len() == 1);
+    }
+
+    #[test]
+    fn test_fee_cells_are_released_by_consuming_tx() {
+        let mut pool = pool();
+        let cells = vec![cell(0, 500 * CKB), cell(1, 1000 * CKB)];
+        pool.lock(cells[0].out_point.clone(), tx_hash(1));
+        pool.lock(cells[1].out_point.clone(), tx_hash(2));
+        assert_eq!(pool.locked_count(), 2);
+
+        // Committing or rejecting tx 1 only releases its own fee cell
+        pool.release(&tx_hash(1));
+        assert_eq!(pool.locked_count(), 1);
+        assert!(!pool.is_locked(&cells[0]));
+        assert!(pool.is_locked(&cells[1]));
+        assert_eq!(pool.pick(&cells).unwrap().out_point, cells[0].out_point);
+
+        // Releasing an unknown tx changes nothing
+        pool.release(&tx_hash(3));
+        assert_eq!(pool.locked_count(), 1);
+        pool.release(&tx_hash(2));
+        assert_eq!(pool.locked_count(), 0);
+    }
+
+    #[test]
+    fn test_refill_count_is_bounded_by_pool_size_and_capacity() {
+        let pool = pool();
+        // Enough capacity, the pool is filled up
+        assert_eq!(pool.refill_count(1, 100_000 * CKB), 3);
+        assert_eq!(pool.refill_count(4, 100_000 * CKB), 0);
+        assert_eq!(pool.refill_count(5, 100_000 * CKB), 0);
+        // 72 CKB are reserved for the change cell and tx fee
+        assert_eq!(pool.refill_count(0, 2072 * CKB), 2);
+        assert_eq!(pool.refill_count(0, 2071 * CKB), 1);
+        assert_eq!(pool.refill_count(0, 1071 * CKB), 0);
+    }
+}
@@ -0,0 +1,304 @@
+//! Encrypted JSON keystore, following the same format(scrypt + AES-128-CTR,
+//! with keccak256 MAC) as Ethereum's V3 keystore, which is also used by
+//! ckb-cli.
+use aes::Aes128;
+use anyhow::{anyhow, bail, Result};
+use ckb_hash::blake2b_256;
+use ckb_sdk::SECP256K1;
+use ckb_types::{H160, H256};
+use ctr::cipher::{KeyIvInit, StreamCipher};
+use rand::{thread_rng, Rng};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::str::FromStr;
+use tiny_keccak::{Hasher, Keccak};
+
+type Aes128Ctr = ctr::Ctr128BE<Aes128>;
+
+/// Passphrase is read from this environment variable when present, so
+/// processors can be started unattended.
+pub const PASSWORD_ENV: &str = "DEX1_KEYSTORE_PASSWORD";
+
+const SCRYPT_LOG_N: u8 = 18;
+const SCRYPT_R: u32 = 8;
+const SCRYPT_P: u32 = 1;
+const SCRYPT_DKLEN: usize = 32;
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Debug)]
+pub struct Keystore {
+    pub version: u32,
+    pub id: String,
+    // Hex encoded blake160 hash of the public key without 0x prefix, which
+    // is also the lock args of genesis sighash lock. Keystores from other
+    // tools might leave it out.
+    #[serde(default)]
+    pub address: String,
+    pub crypto: Crypto,
+}
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Debug)]
+pub struct Crypto {
+    pub cipher: String,
+    pub cipherparams: CipherParams,
+    pub ciphertext: String,
+    pub kdf: String,
+    pub kdfparams: KdfParams,
+    pub mac: String,
+}
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Debug)]
+pub struct CipherParams {
+    pub iv: String,
+}
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Debug)]
+pub struct KdfParams {
+    pub dklen: usize,
+    pub n: u64,
+    pub p: u32,
+    pub r: u32,
+    pub salt: String,
+}
+
+impl Keystore {
+    pub fn encrypt(private_key: &H256, password: &[u8]) -> Result<Self> {
+        Self::encrypt_with_scrypt_log_n(private_key, password, SCRYPT_LOG_N)
+    }
+
+    fn encrypt_with_scrypt_log_n(private_key: &H256, password: &[u8], log_n: u8) -> Result<Self> {
+        let mut rng = thread_rng();
+        let salt: [u8; 32] = rng.gen();
+        let iv: [u8; 16] = rng.gen();
+        let uuid: [u8; 16] = rng.gen();
+
+        let kdfparams = KdfParams {
+            dklen: SCRYPT_DKLEN,
+            n: 1 << log_n,
+            p: SCRYPT_P,
+            r: SCRYPT_R,
+            salt: hex::encode(salt),
+        };
+        let derived_key = derive_key(password, &kdfparams)?;
+
+        let mut ciphertext = private_key.as_bytes().to_vec();
+        Aes128Ctr::new(derived_key[0..16].into(), &iv.into()).apply_keystream(&mut ciphertext);
+        let mac = keccak256(&[&derived_key[16..32], &ciphertext]);
+
+        Ok(Self {
+            version: 3,
+            id: format_uuid(&uuid),
+            address: format!("{:x}", pubkey_hash(private_key)?),
+            crypto: Crypto {
+                cipher: "aes-128-ctr".to_string(),
+                cipherparams: CipherParams {
+                    iv: hex::encode(iv),
+                },
+                ciphertext: hex::encode(ciphertext),
+                kdf: "scrypt".to_string(),
+                kdfparams,
+                mac: hex::encode(mac),
+            },
+        })
+    }
+
+    pub fn decrypt(&self, password: &[u8]) -> Result<H256> {
+        if self.crypto.cipher != "aes-128-ctr" {
+            bail!("Unsupported cipher: {}", self.crypto.cipher);
+        }
+        if self.crypto.kdf != "scrypt" {
+            bail!("Unsupported kdf: {}", self.crypto.kdf);
+        }
+        let derived_key = derive_key(password, &self.crypto.kdfparams)?;
+        let mut ciphertext = hex::decode(&self.crypto.ciphertext)?;
+        let mac = keccak256(&[&derived_key[16..32], &ciphertext]);
+        if hex::encode(mac) != self.crypto.mac.trim_start_matches("0x") {
+            bail!("Keystore MAC mismatch, the password might be wrong!");
+        }
+
+        let iv = hex::decode(&self.crypto.cipherparams.iv)?;
+        if iv.len() != 16 {
+            bail!("Invalid iv length: {}", iv.len());
+        }
+        Aes128Ctr::new(derived_key[0..16].into(), iv[..].into()).apply_keystream(&mut ciphertext);
+        // ckb-cli keeps a 64-byte master key(private key + chain code), the
+        // first 32 bytes are the private key.
+        if ciphertext.len() != 32 && ciphertext.len() != 64 {
+            bail!("Invalid private key length: {}", ciphertext.len());
+        }
+        Ok(H256::from_slice(&ciphertext[0..32])?)
+    }
+}
+
+/// Wallet files can either contain a raw hex private key, or a JSON keystore.
+/// For keystores, the passphrase is read from environment variable first,
+/// then prompted from terminal.
+pub fn read_wallet(path: &Path) -> Result<H256> {
+    let wallet_content = std::fs::read_to_string(path)?.trim().to_string();
+    if wallet_content.starts_with('{') {
+        let keystore: Keystore = serde_json::from_str(&wallet_content)?;
+        let password = read_password(&format!("Password for {}: ", path.display()))?;
+        keystore.decrypt(password.as_bytes())
+    } else {
+        Ok(H256::from_str(&wallet_content)?)
+    }
+}
+
+pub fn read_password(prompt: &str) -> Result<String> {
+    if let Ok(password) = std::env::var(PASSWORD_ENV) {
+        return Ok(password);
+    }
+    Ok(rpassword::prompt_password(prompt)?)
+}
+
+/// Reads a password for a new keystore, a prompted password is asked twice
+/// for confirmation.
+pub fn read_new_password() -> Result<String> {
+    if let Ok(password) = std::env::var(PASSWORD_ENV) {
+        return Ok(password);
+    }
+    let password = rpassword::prompt_password("Password: ")?;
+    if password != rpassword::prompt_password("Repeat password: ")? {
+        bail!("Passwords do not match!");
+    }
+    Ok(password)
+}
+
+pub fn generate_private_key() -> H256 {
+    let mut rng = thread_rng();
+    loop {
+        let data: [u8; 32] = rng.gen();
+        if secp256k1::SecretKey::from_slice(&data).is_ok() {
+            return H256::from(data);
+        }
+    }
+}
+
+pub fn pubkey_hash(private_key: &H256) -> Result<H160> {
+    let secret_key = secp256k1::SecretKey::from_slice(private_key.as_bytes())?;
+    let pubkey = secp256k1::PublicKey::from_secret_key(&SECP256K1, &secret_key);
+    Ok(H160::from_slice(
+        &blake2b_256(&pubkey.serialize()[..])[0..20],
+    )?)
+}
+
+fn derive_key(password: &[u8], kdfparams: &KdfParams) -> Result<Vec<u8>> {
+    if !kdfparams.n.is_power_of_two() || kdfparams.dklen < 32 {
+        bail!("Invalid scrypt parameters: {:?}", kdfparams);
+    }
+    let params = scrypt::Params::new(
+        kdfparams.n.trailing_zeros() as u8,
+        kdfparams.r,
+        kdfparams.p,
+        kdfparams.dklen,
+    )
+    .map_err(|e| anyhow!("Invalid scrypt parameters: {:?}", e))?;
+    let salt = hex::decode(&kdfparams.salt)?;
+    let mut derived_key = vec![0u8; kdfparams.dklen];
+    scrypt::scrypt(password, &salt, &params, &mut derived_key)
+        .map_err(|e| anyhow!("scrypt error: {:?}", e))?;
+    Ok(derived_key)
+}
+
+fn keccak256(parts: &[&[u8]]) -> [u8; 32] {
+    let mut hasher = Keccak::v256();
+    for part in parts {
+        hasher.update(part);
+    }
+    let mut output = [0u8; 32];
+    hasher.finalize(&mut output);
+    output
+}
+
+// Random(version 4) UUID
+fn format_uuid(data: &[u8; 16]) -> String {
+    let mut data = *data;
+    data[6] = (data[6] & 0x0f) | 0x40;
+    data[8] = (data[8] & 0x3f) | 0x80;
+    let h = hex::encode(data);
+    format!(
+        "{}-{}-{}-{}-{}",
+        &h[0..8],
+        &h[8..12],
+        &h[12..16],
+        &h[16..20],
+        &h[20..32]
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Cheap scrypt parameters so tests stay fast in debug builds
+    const TEST_SCRYPT_LOG_N: u8 = 10;
+
+    // Keystore in ckb-cli format, which encrypts a 64-byte master key(private
+    // key + chain code). It is generated by an independent scrypt, AES and
+    // keccak implementation with password "dex1-fixture", and cheap scrypt
+    // parameters. The private key is the well-known one of dev chain.
+    const CKB_CLI_KEYSTORE: &str = r#"{
+        "crypto": {
+            "cipher": "aes-128-ctr",
+            "cipherparams": {
+                "iv": "0f1e2d3c4b5a69788796a5b4c3d2e1f0"
+            },
+            "ciphertext": "7592f6c1b32b4779b9ee80e907c0bfbd27d8d6d35c08d38acdc0895f737206bb4c7b1d66a15341c221ad1ce10c2aeaa792fc1475a5e067026ed5eb8c90871725",
+            "kdf": "scrypt",
+            "kdfparams": {
+                "dklen": 32,
+                "n": 4096,
+                "p": 1,
+                "r": 8,
+                "salt": "5d9f1c4a27e0b83c6e4f12a9d07b35c8e1f6a2b4c9d03e7f5a1b2c3d4e5f6071"
+            },
+            "mac": "d3215f7bc2fd7ad63f9904eea03ac6ea310946618f17db067fac526be0159a5d"
+        },
+        "id": "a3c1f0e2-7b4d-4e8a-9c6f-2d1b5e8a7c30",
+        "version": 3
+    }"#;
+
+    #[test]
+    fn test_keystore_round_trip() {
+        let private_key = generate_private_key();
+        let keystore =
+            Keystore::encrypt_with_scrypt_log_n(&private_key, b"dex1", TEST_SCRYPT_LOG_N).unwrap();
+        assert_eq!(keystore.version, 3);
+        assert_eq!(keystore.crypto.kdfparams.n, 1 << TEST_SCRYPT_LOG_N);
+        assert_eq!(
+            keystore.address,
+            format!("{:x}", pubkey_hash(&private_key).unwrap())
+        );
+
+        let json = serde_json::to_string(&keystore).unwrap();
+        let keystore: Keystore = serde_json::from_str(&json).unwrap();
+        assert_eq!(keystore.decrypt(b"dex1").unwrap(), private_key);
+    }
+
+    #[test]
+    fn test_decrypt_ckb_cli_keystore() {
+        let keystore: Keystore = serde_json::from_str(CKB_CLI_KEYSTORE).unwrap();
+        let private_key = keystore.decrypt(b"dex1-fixture").unwrap();
+        assert_eq!(
+            private_key,
+            H256::from_str("d00c06bfd800d27397002dca6fb0993d5ba6399b4238b2f29ee9deb97593d2bc")
+                .unwrap()
+        );
+        assert_eq!(
+            pubkey_hash(&private_key).unwrap(),
+            H160::from_str("c8328aabcd9b9e8e64fbc566c4385c3bdeb219d7").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_wrong_password_fails_mac_check() {
+        let private_key = generate_private_key();
+        let keystore =
+            Keystore::encrypt_with_scrypt_log_n(&private_key, b"dex1", TEST_SCRYPT_LOG_N).unwrap();
+        let err = keystore.decrypt(b"dex2").unwrap_err();
+        assert!(err.to_string().contains("MAC mismatch"), "{}", err);
+
+        let keystore: Keystore = serde_json::from_str(CKB_CLI_KEYSTORE).unwrap();
+        let err = keystore.decrypt(b"dex1-fixturf").unwrap_err();
+        assert!(err.to_string().contains("MAC mismatch"), "{}", err);
+    }
+}
@@ -1,12 +1,27 @@
 pub mod config;
+pub mod fee_cells;
+pub mod keystore;
 pub mod schemas;
+pub mod seals;
+pub mod signer;
+pub mod tokens;
+
+#[cfg(test)]
+mod tests;
 
 use crate::{
-    config::{Config, PackedFullScript, PackedTradingPair, RunnerConfig},
+    config::{
+        Config, MatchingConfig, PackedFullScript, PackedTradingPair, PackingConfig, RingConfig,
+        RunnerConfig, SelfTradeConfig, SurplusConfig, SurplusDustConfig, TradingRules,
+    },
+    fee_cells::{FeeCellError, FeeCellPool},
     schemas::{
         basic, dex1,
         top_level::{WitnessLayout, WitnessLayoutUnion},
     },
+    seals::{otx_signing_message, verify_omnilock_seal},
+    signer::FeeCellSigner,
+    tokens::{is_ckb, token_script_hash, token_type, TokenAdapters},
 };
 use anyhow::{anyhow, bail, Result};
 use ckb_hash::blake2b_256;
@@ -16,26 +31,56 @@ use ckb_sdk::{
     traits::{
         CellCollector, CellDepResolver, CellQueryOptions, DefaultCellCollector,
         DefaultCellDepResolver, DefaultTransactionDependencyProvider, LiveCell, MaturityOption,
-        SecpCkbRawKeySigner, ValueRangeOption,
+        ValueRangeOption,
     },
     tx_builder::unlock_tx,
-    unlock::{ScriptUnlocker, SecpSighashUnlocker},
-    CkbRpcClient, ScriptId, SECP256K1,
+    unlock::ScriptUnlocker,
+    CkbRpcClient, RpcError, ScriptId, SECP256K1,
 };
 use ckb_types::{
     bytes::Bytes,
-    core::{Capacity, FeeRate, ScriptHashType, TransactionView},
-    packed::{CellInput, CellOutput, OutPoint, Script, Transaction, WitnessArgs},
+    core::{Capacity, DepType, FeeRate, ScriptHashType, TransactionView},
+    packed::{
+        self, Byte32, CellDep, CellInput, CellOutput, OutPoint, Script, Transaction, WitnessArgs,
+    },
     prelude::*,
     H256,
 };
 use ethnum::U256;
 use num_rational::Ratio;
 use otx_traits::{Assembler, MapEmitter, ReduceEmitter, ReduceSource, Value};
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::{
+    atomic::{AtomicBool, AtomicU64, Ordering as AtomicOrdering},
+    Mutex,
+};
+use std::time::{SystemTime, UNIX_EPOCH};
 
 const LIMIT_BUY: u8 = 'B' as u8;
 const LIMIT_SELL: u8 = 'S' as u8;
+// Key for matching rings across all pairs
+const RING: u8 = b'R';
+// NFT orders are keyed by the NFT type hash followed by the token hash, the
+// NFT key is reduced, while orders are kept under their sides.
+const NFT: u8 = b'N';
+const NFT_BUY: u8 = b'P';
+const NFT_SELL: u8 = b'L';
+// Key of route orders, which are also reduced by this key
+const ROUTE: u8 = b'T';
+// Maximum number of tokens in the path of a route order
+const MAX_ROUTE_LENGTH: usize = 4;
+// Bytes added by sealing a tx: cell deps, the fee cell input, the change
+// output and the signed witness.
+const SEAL_SIZE_OVERHEAD: usize = 1000;
+// Serialized output data of a UDT cell, and offsets in the tx
+const OUTPUT_SIZE_OVERHEAD: usize = 32;
+// Blocks ckb-sdk's cell collector keeps applied txs for
+const APPLIED_TX_BLOCKS: u64 = 13;
+// Freestanding cells of orders without a deadline expire this many blocks
+// after they are created, as enforced by dex1 contract.
+const MAX_ORDER_AGE: u64 = 300_000;
+// Flags of a relative since value in block numbers
+const RELATIVE_BLOCK_NUMBER_SINCE: u64 = 0x80 << 56;
 // const MARKET_BUY: u8 = 'b' as u8;
 // const MARKET_SELL: u8 = 's' as u8;
 
@@ -48,6 +93,17 @@ pub struct RichOtx {
     pub inputs: Vec<(CellOutput, Bytes)>,
 }
 
+/// Base tx matched orders are added to, along with the chain state orders
+/// are validated against.
+#[derive(Debug, Clone)]
+pub struct BaseTx {
+    pub tx: RichOtx,
+    /// Block number of the dex1 entity cell used by base tx
+    pub block_number: u64,
+    /// Orders with deadline before this block number are considered expired
+    pub expired_block_number: u64,
+}
+
 // The environment struct handles impure logic that is pending on
 // actual state of the chain.
 pub struct Dex1Env {
@@ -59,7 +115,12 @@ pub struct Dex1Env {
     tx_dep_provider: DefaultTransactionDependencyProvider,
 
     sender_script: Script,
+    placeholder_witness: WitnessArgs,
     unlockers: HashMap<ScriptId, Box<dyn ScriptUnlocker>>,
+    fee_cells: FeeCellPool,
+    // Txs applied to the cell collector along with the tip block number at
+    // the time, so the collector can be rebuilt without a replaced tx.
+    applied_txs: Vec<(Transaction, u64)>,
 }
 
 pub fn build_genesis_sighash_lock(private_key: &H256) -> (secp256k1::SecretKey, Script) {
@@ -76,28 +137,21 @@ pub fn build_genesis_sighash_lock(private_key: &H256) -> (secp256k1::SecretKey,
 }
 
 impl Dex1Env {
-    pub fn new(config: &RunnerConfig, private_key: H256) -> Self {
+    pub fn new(config: &RunnerConfig, signer: FeeCellSigner) -> Self {
         let client = CkbRpcClient::new(&config.ckb_rpc);
         let dep_resolver = config.build_dep_resolver();
 
-        let (sender_key, sender_script) = build_genesis_sighash_lock(&private_key);
-        let signer = SecpCkbRawKeySigner::new_with_secret_keys(vec![sender_key]);
-        let sighash_unlocker = SecpSighashUnlocker::from(Box::new(signer) as Box<_>);
-        let sighash_script_id = ScriptId::new_type(SIGHASH_TYPE_HASH.clone());
-        let mut unlockers = HashMap::default();
-        unlockers.insert(
-            sighash_script_id,
-            Box::new(sighash_unlocker) as Box<dyn ScriptUnlocker>,
-        );
-
         Self {
             client,
             config: config.clone(),
             cell_collector: DefaultCellCollector::new(&config.ckb_rpc),
             dep_resolver,
             tx_dep_provider: DefaultTransactionDependencyProvider::new(&config.ckb_rpc, 10),
-            sender_script,
-            unlockers,
+            sender_script: signer.lock_script,
+            placeholder_witness: signer.placeholder_witness,
+            unlockers: signer.unlockers,
+            fee_cells: FeeCellPool::new(&config.fee),
+            applied_txs: Vec::new(),
         }
     }
 
@@ -105,6 +159,35 @@ impl Dex1Env {
         self.cell_collector = DefaultCellCollector::new(&self.config.ckb_rpc);
         self.dep_resolver = self.config.build_dep_resolver();
         self.tx_dep_provider = DefaultTransactionDependencyProvider::new(&self.config.ckb_rpc, 10);
+        self.applied_txs.clear();
+    }
+
+    // Update providers to recognize a newly sealed tx
+    fn apply_tx(&mut self, tx: Transaction) {
+        let tip_block_number = self.client.get_tip_block_number().expect("rpc").value();
+        self.cell_collector
+            .apply_tx(tx.clone(), tip_block_number)
+            .expect("apply tx to cell collector");
+        self.tx_dep_provider
+            .apply_tx(tx.clone(), tip_block_number)
+            .expect("apply tx to tx dep provider");
+        // Cell collector forgets applied txs after a while as well
+        self.applied_txs
+            .retain(|(_, block_number)| block_number + APPLIED_TX_BLOCKS >= tip_block_number);
+        self.applied_txs.push((tx, tip_block_number));
+    }
+
+    // Cell collector can only be reset as a whole, all applied txs but the
+    // rolled back one are applied again.
+    fn rollback_tx(&mut self, tx_hash: &Byte32) {
+        self.applied_txs
+            .retain(|(tx, _)| tx.calc_tx_hash() != *tx_hash);
+        self.cell_collector.reset();
+        for (tx, block_number) in &self.applied_txs {
+            self.cell_collector
+                .apply_tx(tx.clone(), *block_number)
+                .expect("apply tx to cell collector");
+        }
     }
 
     pub fn dex1_script(&self) -> Script {
@@ -163,7 +246,24 @@ impl Dex1Env {
         Ok(cell)
     }
 
-    pub fn base_tx(&mut self) -> Result<(RichOtx, u64)> {
+    /// Live cells of all configured oracles, including those created by
+    /// pending txs.
+    pub fn oracle_cells(&mut self) -> Result<Vec<LiveCell>> {
+        let mut oracle_cells = Vec::new();
+        for oracle in &self.config.oracles {
+            let mut query = CellQueryOptions::new_type(oracle.clone().into());
+            query.with_data = Some(true);
+            query.script_search_mode = Some(SearchMode::Exact);
+            let (cells, _) = match self.cell_collector.collect_live_cells(&query, false) {
+                Ok(data) => data,
+                Err(e) => bail!("Cell collector error: {:?}", e),
+            };
+            oracle_cells.extend(cells);
+        }
+        Ok(oracle_cells)
+    }
+
+    pub fn base_tx(&mut self) -> Result<BaseTx> {
         let cell = self.latest_dex1_cell(false)?;
         let tx = TransactionView::new_advanced_builder()
             // We will find the right input cell to use at sealing time.
@@ -177,20 +277,57 @@ impl Dex1Env {
         // to detect against expired otxs at sealing time.
         let expired_block = cell.block_number + 100;
 
-        Ok((self.fulfill_otx(tx.data())?, expired_block))
+        Ok(BaseTx {
+            tx: self.fulfill_otx(tx.data())?,
+            block_number: cell.block_number,
+            expired_block_number: expired_block,
+        })
+    }
+
+    /// Block number of the committed dex1 entity cell, which the next tx is
+    /// sealed with.
+    pub fn dex1_block_number(&mut self) -> Result<u64> {
+        Ok(self.latest_dex1_cell(true)?.block_number)
+    }
+
+    /// Fee rate to use for newly sealed txs, the median reported by the CKB
+    /// node is used, bounded by configured values.
+    pub fn fee_rate(&self) -> u64 {
+        let fee_config = &self.config.fee;
+        let fee_rate = match self.client.get_fee_rate_statistics(None) {
+            Ok(Some(statistics)) => statistics.median.value(),
+            Ok(None) => fee_config.min_fee_rate,
+            Err(e) => {
+                log::warn!("Error fetching fee rate statistics: {:?}", e);
+                fee_config.min_fee_rate
+            }
+        };
+        fee_config.clamp_fee_rate(fee_rate)
+    }
+
+    /// Extra fee rate a replacement tx must pay on top of the replaced one,
+    /// as reported by the CKB node, or the configured fallback.
+    pub fn min_rbf_rate(&self) -> u64 {
+        match self.client.tx_pool_info() {
+            Ok(info) => info.min_rbf_rate.value(),
+            Err(e) => {
+                log::warn!("Error fetching tx pool info: {:?}", e);
+                self.config.fee.min_rbf_rate
+            }
+        }
     }
 
-    pub fn seal_tx(&mut self, tx: &Transaction) -> Result<Transaction> {
+    pub fn seal_tx(&mut self, tx: &Transaction, fee_rate: u64) -> Result<Transaction> {
         // For each pending tx:
         let tx = tx.clone().into_view();
         // * Find the latest dex1 cell input with its header, then update
-        // current tx with latest values.
-        let tx = {
-            let input_position = tx
-                .inputs()
-                .into_iter()
-                .position(|cell_input| cell_input == placeholder_dex1_cell_input())
-                .expect("dex1 cell is missing!");
+        // current tx with latest values. Txs sweeping expired freestanding
+        // cells do not touch the dex1 cell.
+        let input_position = tx
+            .inputs()
+            .into_iter()
+            .position(|cell_input| cell_input == placeholder_dex1_cell_input());
+        let tx = if let Some(input_position) = input_position {
             let dex1_cell = self.latest_dex1_cell(true)?;
 
             assert!(dex1_cell.block_number > 0);
@@ -209,47 +346,36 @@ impl Dex1Env {
                 .set_inputs(inputs)
                 .header_dep(header.hash.pack())
                 .build()
+        } else {
+            tx
         };
-        // * Add sighash cell for both providing tx fees, and sealing the whole tx
+        // * Add a fee cell for both providing tx fees, and sealing the whole tx
         let mut witnesses: Vec<_> = tx.witnesses().into_iter().collect();
 
-        let mut query = CellQueryOptions::new_lock(self.sender_script.clone());
-        query.secondary_script_len_range = Some(ValueRangeOption::new_exact(0));
-        query.data_len_range = Some(ValueRangeOption::new_exact(0));
-        query.maturity = MaturityOption::Both;
-        query.min_total_capacity = Capacity::bytes(71).expect("overflow").as_u64();
+        let sender_cells = self.sender_cells()?;
+        let fee_cell = match self.fee_cells.pick(&sender_cells) {
+            Some(cell) => cell,
+            None => return Err(FeeCellError::NoFreeFeeCell.into()),
+        };
+        let total_capacity: u64 = fee_cell.output.capacity().unpack();
 
         let first_input_cell_index = tx.inputs().len();
         let fee_change_output_cell_index = tx.outputs().len();
-        let (live_cells, total_capacity) = self
-            .cell_collector
-            .collect_live_cells(&query, false)
-            .expect("collect cells");
-        assert!(total_capacity >= query.min_total_capacity);
-        let placeholder_witness = WitnessArgs::new_builder()
-            .lock(Some(Bytes::from(vec![0u8; 65])).pack())
-            .build()
-            .as_bytes()
-            .pack();
+        let placeholder_witness = self.placeholder_witness.as_bytes().pack();
         if witnesses[first_input_cell_index].len() > 0 {
             witnesses.insert(first_input_cell_index, placeholder_witness);
         } else {
             witnesses[first_input_cell_index] = placeholder_witness;
         }
-        for i in 1..live_cells.len() {
-            if witnesses[first_input_cell_index + i].len() > 0 {
-                witnesses.insert(first_input_cell_index + i, Bytes::default().pack());
-            }
-        }
 
         let tx_without_deps = tx
             .as_advanced_builder()
             .set_witnesses(witnesses)
-            .inputs(live_cells.iter().map(|live_cell| {
+            .input(
                 CellInput::new_builder()
-                    .previous_output(live_cell.out_point.clone())
-                    .build()
-            }))
+                    .previous_output(fee_cell.out_point.clone())
+                    .build(),
+            )
             .output(
                 CellOutput::new_builder()
                     .capacity(total_capacity.pack())
@@ -259,8 +385,9 @@ impl Dex1Env {
             .output_data(Bytes::default().pack())
             .build();
 
-        // * Fill in missing cell deps
-        let mut cell_deps = HashSet::new();
+        // * Fill in missing cell deps, keeping oracle cell deps added by the
+        // assembler
+        let mut cell_deps: HashSet<CellDep> = tx_without_deps.cell_deps().into_iter().collect();
         for input in tx_without_deps.inputs() {
             let (output, _) = self
                 .tx_dep_provider
@@ -289,11 +416,30 @@ impl Dex1Env {
             .set_cell_deps(cell_deps.into_iter().collect())
             .build();
 
-        let fee_rate = FeeRate::from_u64(1000);
+        // Fee cell also funds outputs not covered by otxs, such as surplus cells.
+        let fee_rate = FeeRate::from_u64(fee_rate);
         let fee = fee_rate
             .fee(tx_before_fee.data().as_reader().serialized_size_in_block() as u64)
             .as_u64();
-        let updated_capacity = total_capacity.checked_sub(fee).expect("fee overflow");
+        // The change cell must stay a valid cell on its own.
+        let change_capacity = tx_before_fee
+            .output(fee_change_output_cell_index)
+            .expect("change output")
+            .occupied_capacity(Capacity::zero())
+            .expect("overflow")
+            .as_u64();
+        let input_capacity = self.input_capacity(&tx_before_fee)?;
+        let spent_capacity = output_capacity(&tx_before_fee, fee_change_output_cell_index)?
+            .checked_add(fee)
+            .ok_or_else(|| anyhow!("overflow!"))?;
+        let updated_capacity = input_capacity
+            .checked_sub(spent_capacity)
+            .filter(|c| *c >= change_capacity)
+            .ok_or_else(|| FeeCellError::InsufficientCapacity {
+                required: spent_capacity
+                    .saturating_add(change_capacity)
+                    .saturating_sub(input_capacity - total_capacity),
+            })?;
 
         let mut outputs: Vec<_> = tx_before_fee.outputs().into_iter().collect();
         let updated_change_output = outputs[fee_change_output_cell_index]
@@ -310,18 +456,207 @@ impl Dex1Env {
         // * Sign an input cell using sighash to seal it
         let (new_tx, _) =
             unlock_tx(locked_tx, &self.tx_dep_provider, &self.unlockers).expect("unlock tx");
+        self.fee_cells.lock(fee_cell.out_point, new_tx.hash());
+        self.check_fee_cells(&sender_cells);
 
         // Update providers to recognize this new transaction
-        let tip_block_number = self.client.get_tip_block_number().expect("rpc").value();
-        self.cell_collector
-            .apply_tx(new_tx.data(), tip_block_number)
-            .expect("apply tx to cell collector");
-        self.tx_dep_provider
-            .apply_tx(new_tx.data(), tip_block_number)
-            .expect("apply tx to tx dep provider");
+        self.apply_tx(new_tx.data());
+
+        Ok(new_tx.data())
+    }
+
+    /// Re-seal an already sealed tx using a higher fee rate. The same fee cell
+    /// inputs are kept, so the new tx can replace the old one in CKB's tx pool.
+    /// Env state is left untouched, see `commit_replacement`.
+    pub fn bump_fee(&self, tx: &Transaction, fee_rate: u64) -> Result<Transaction> {
+        let tx = tx.clone().into_view();
+        // Fee change cell is always appended as the last output at sealing time.
+        let fee_change_output_cell_index = tx.outputs().len() - 1;
+
+        let input_capacity = self.input_capacity(&tx)?;
+        let output_capacity = output_capacity(&tx, fee_change_output_cell_index)?;
+
+        // Only capacity of the change cell is altered, tx size stays the same.
+        let fee = FeeRate::from_u64(fee_rate)
+            .fee(tx.data().as_reader().serialized_size_in_block() as u64)
+            .as_u64();
+        let updated_capacity = input_capacity
+            .checked_sub(output_capacity)
+            .and_then(|c| c.checked_sub(fee))
+            .ok_or_else(|| anyhow!("Not enough capacity to pay fee rate {}!", fee_rate))?;
 
+        let mut outputs: Vec<_> = tx.outputs().into_iter().collect();
+        outputs[fee_change_output_cell_index] = outputs[fee_change_output_cell_index]
+            .clone()
+            .as_builder()
+            .capacity(updated_capacity.pack())
+            .build();
+        let locked_tx = tx.as_advanced_builder().set_outputs(outputs).build();
+        let (new_tx, _) =
+            unlock_tx(locked_tx, &self.tx_dep_provider, &self.unlockers).expect("unlock tx");
         Ok(new_tx.data())
     }
+
+    /// Track +new_tx+ built by `bump_fee` in place of the tx it replaces. This
+    /// shall only be called once CKB accepts +new_tx+, otherwise the replaced
+    /// tx is still the one in the tx pool.
+    pub fn commit_replacement(&mut self, replaced_tx_hash: &Byte32, new_tx: &Transaction) {
+        let new_tx = new_tx.clone().into_view();
+        // Fee cell is always the last input, now locked by the new tx.
+        self.fee_cells.release(replaced_tx_hash);
+        let fee_cell_input = new_tx.inputs().get(new_tx.inputs().len() - 1).unwrap();
+        self.fee_cells
+            .lock(fee_cell_input.previous_output(), new_tx.hash());
+
+        // The replaced tx is forgotten, other txs such as fee cell refilling
+        // ones are kept.
+        self.rollback_tx(replaced_tx_hash);
+        self.apply_tx(new_tx.data());
+    }
+
+    fn input_capacity(&self, tx: &TransactionView) -> Result<u64> {
+        let mut input_capacity: u64 = 0;
+        for output in self.input_cells(tx)? {
+            input_capacity = input_capacity
+                .checked_add(output.capacity().unpack())
+                .ok_or_else(|| anyhow!("overflow!"))?;
+        }
+        Ok(input_capacity)
+    }
+
+    /// Resolve the cells consumed by a tx.
+    pub fn input_cells(&self, tx: &TransactionView) -> Result<Vec<CellOutput>> {
+        let mut cells = Vec::with_capacity(tx.inputs().len());
+        for input in tx.inputs() {
+            let (output, _) = match self
+                .tx_dep_provider
+                .get_cell_with_data(&input.previous_output())
+            {
+                Ok(data) => data,
+                Err(e) => bail!("Dep provider error: {:?}", e),
+            };
+            cells.push(output);
+        }
+        Ok(cells)
+    }
+
+    /// Dry-run a sealed tx against CKB's tx pool without broadcasting it, all
+    /// scripts are executed. Errors can be parsed the same way as errors from
+    /// `send_transaction`, pinpointing the failing cell.
+    pub fn verify_tx(&self, tx: &Transaction) -> Result<u64, RpcError> {
+        let entry = self.client.test_tx_pool_accept(
+            tx.clone().into(),
+            Some(ckb_jsonrpc_types::OutputsValidator::Passthrough),
+        )?;
+        Ok(entry.cycles.value())
+    }
+
+    /// Release the fee cell consumed by a sealed tx, once the tx is either
+    /// committed or rejected.
+    pub fn release_fee_cell(&mut self, tx_hash: &Byte32) {
+        self.fee_cells.release(tx_hash);
+    }
+
+    /// Split large processor cells into fixed-size fee cells, when free fee
+    /// cells in the pool run below the configured count. The returned tx is
+    /// sealed, and ready for submission.
+    pub fn refill_fee_cells(&mut self) -> Result<Option<Transaction>> {
+        let fee_config = self.config.fee.clone();
+        let sender_cells = self.sender_cells()?;
+        let free_fee_cells = self.fee_cells.free_fee_cells(&sender_cells).len();
+        if free_fee_cells >= fee_config.fee_cell_count {
+            return Ok(None);
+        }
+        let refill_cells = self.fee_cells.refill_cells(&sender_cells);
+        let total_capacity = refill_cells.iter().try_fold(0u64, |acc, cell| {
+            acc.checked_add(cell.output.capacity().unpack())
+                .ok_or_else(|| anyhow!("overflow!"))
+        })?;
+        let count = self.fee_cells.refill_count(free_fee_cells, total_capacity);
+        if count == 0 {
+            self.check_fee_cells(&sender_cells);
+            return Ok(None);
+        }
+
+        let fee_cell_output = CellOutput::new_builder()
+            .capacity(fee_config.fee_cell_capacity.pack())
+            .lock(self.sender_script.clone())
+            .build();
+        let change_capacity = total_capacity - fee_config.fee_cell_capacity * count as u64;
+        let mut witnesses = vec![Bytes::default().pack(); refill_cells.len()];
+        witnesses[0] = self.placeholder_witness.as_bytes().pack();
+        let tx_before_fee = TransactionView::new_advanced_builder()
+            .cell_dep(
+                self.dep_resolver
+                    .resolve(&self.sender_script)
+                    .expect("resolving cell dep"),
+            )
+            .inputs(refill_cells.iter().map(|cell| {
+                CellInput::new_builder()
+                    .previous_output(cell.out_point.clone())
+                    .build()
+            }))
+            .outputs(vec![fee_cell_output; count])
+            .outputs_data(vec![Bytes::default().pack(); count])
+            .output(
+                CellOutput::new_builder()
+                    .capacity(change_capacity.pack())
+                    .lock(self.sender_script.clone())
+                    .build(),
+            )
+            .output_data(Bytes::default().pack())
+            .set_witnesses(witnesses)
+            .build();
+        let fee = FeeRate::from_u64(self.fee_rate())
+            .fee(tx_before_fee.data().as_reader().serialized_size_in_block() as u64)
+            .as_u64();
+        let mut outputs: Vec<_> = tx_before_fee.outputs().into_iter().collect();
+        outputs[count] = outputs[count]
+            .clone()
+            .as_builder()
+            .capacity((change_capacity - fee).pack())
+            .build();
+        let locked_tx = tx_before_fee
+            .as_advanced_builder()
+            .set_outputs(outputs)
+            .build();
+        let (new_tx, _) =
+            unlock_tx(locked_tx, &self.tx_dep_provider, &self.unlockers).expect("unlock tx");
+
+        self.apply_tx(new_tx.data());
+        log::info!(
+            "Refilling fee cell pool with {} new fee cells in tx {:x}",
+            count,
+            new_tx.hash()
+        );
+
+        Ok(Some(new_tx.data()))
+    }
+
+    // All empty sighash cells owned by the processor
+    fn sender_cells(&mut self) -> Result<Vec<LiveCell>> {
+        let mut query = CellQueryOptions::new_lock(self.sender_script.clone());
+        query.secondary_script_len_range = Some(ValueRangeOption::new_exact(0));
+        query.data_len_range = Some(ValueRangeOption::new_exact(0));
+        query.maturity = MaturityOption::Both;
+        query.min_total_capacity = u64::MAX;
+
+        match self.cell_collector.collect_live_cells(&query, false) {
+            Ok((cells, _)) => Ok(cells),
+            Err(e) => bail!("Cell collector error: {:?}", e),
+        }
+    }
+
+    fn check_fee_cells(&self, sender_cells: &[LiveCell]) {
+        let free_fee_cells = self.fee_cells.free_fee_cells(sender_cells).len();
+        if free_fee_cells < self.config.fee.min_fee_cells {
+            log::warn!(
+                "Fee cell pool is running low: {} free fee cells, {} locked by inflight txs!",
+                free_fee_cells,
+                self.fee_cells.locked_count()
+            );
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -333,33 +668,71 @@ pub struct ParsedData {
     pub price: Ratio<u128>,
     pub order: dex1::Order,
     pub freestanding_cell: bool,
+    // Arrival sequence of the order, see OrderPriority for details.
+    pub sequence: u64,
+    // Lock script of the separate owner of an order, when the otx carries
+    // it. Expired freestanding cells are returned to it.
+    pub owner_script: Option<Script>,
 }
 
 impl ParsedData {
-    fn minimal_ckbytes(&self) -> u64 {
+    fn priority(&self) -> OrderPriority {
+        OrderPriority {
+            price: self.price,
+            sequence: self.sequence,
+        }
+    }
+
+    // Cells created when settling the order: a freestanding cell keeping the
+    // remaining bid tokens in case of a partial fill, or the cell refunding
+    // them to the recipient for immediate-or-cancel orders, and the cell paying
+    // ask tokens to the recipient.
+    fn settlement_cells(&self) -> (CellOutput, CellOutput) {
+        let remaining_lock = match self.order.to_enum() {
+            dex1::OrderUnion::ImmediateOrCancelOrder(_) => self.recipient_script.clone(),
+            _ => Script::new_builder()
+                .args(Bytes::from(vec![0; 96]).pack())
+                .build(),
+        };
         let dummy_freestanding_cell = CellOutput::new_builder()
-            .lock(
-                Script::new_builder()
-                    .args(Bytes::from(vec![0; 96]).pack())
-                    .build(),
-            )
-            .type_(Some(self.bid_token_script.clone()).pack())
+            .lock(remaining_lock)
+            .type_(token_type(&self.bid_token_script))
             .build();
-        let dummy_cell_capacity = dummy_freestanding_cell
-            .occupied_capacity(Capacity::bytes(16).expect("overflow"))
-            .expect("overflow");
         let return_cell = CellOutput::new_builder()
             .lock(self.recipient_script.clone())
-            .type_(Some(self.ask_token_script.clone()).pack())
+            .type_(token_type(&self.ask_token_script))
             .build();
+        (dummy_freestanding_cell, return_cell)
+    }
+
+    fn minimal_ckbytes(&self, tokens: &TokenAdapters) -> u64 {
+        let (dummy_freestanding_cell, return_cell) = self.settlement_cells();
+        let dummy_cell_capacity = dummy_freestanding_cell
+            .occupied_capacity(
+                Capacity::bytes(tokens.data_size(&self.bid_token_script)).expect("overflow"),
+            )
+            .expect("overflow");
         let return_cell_capacity = return_cell
-            .occupied_capacity(Capacity::bytes(16).expect("overflow"))
+            .occupied_capacity(
+                Capacity::bytes(tokens.data_size(&self.ask_token_script)).expect("overflow"),
+            )
             .expect("overflow");
         dummy_cell_capacity
             .safe_add(return_cell_capacity)
             .expect("overflow")
             .as_u64()
     }
+
+    // Upper bound of the bytes the order adds to a settlement tx.
+    fn estimated_size(&self, tokens: &TokenAdapters) -> usize {
+        let (dummy_freestanding_cell, return_cell) = self.settlement_cells();
+        self.tx.tx.as_slice().len()
+            + dummy_freestanding_cell.as_slice().len()
+            + return_cell.as_slice().len()
+            + tokens.data_size(&self.bid_token_script)
+            + tokens.data_size(&self.ask_token_script)
+            + 2 * OUTPUT_SIZE_OVERHEAD
+    }
 }
 
 impl Value for ParsedData {
@@ -368,10 +741,63 @@ impl Value for ParsedData {
     }
 }
 
+/// Orders are prioritized first by price, then by arrival sequence, so orders
+/// at the same price level are filled in FIFO order. Sequence numbers are
+/// assigned in the order otxs are mapped, derived from the wall clock in
+/// microseconds so they keep increasing across processor restarts. When an
+/// order is partially filled, the remainder re-enters as a freestanding order
+/// keeping the sequence of the original order, so it does not lose its time
+/// priority, except the hidden part of an iceberg order, which takes a new
+/// sequence. An otx submitted again after a restart is a new arrival and
+/// takes a new sequence as well.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct OrderPriority {
+    pub price: Ratio<u128>,
+    pub sequence: u64,
+}
+
 // An assembler here is pure logic for assembling transactions.
 pub struct Dex1 {
     dex1_script: PackedFullScript,
     pairs: HashMap<[u8; 64], PackedTradingPair>,
+    omnilock_script: PackedFullScript,
+    matching: MatchingConfig,
+    surplus: SurplusConfig,
+    surplus_dust: Option<SurplusDustConfig>,
+    rings: RingConfig,
+    packing: PackingConfig,
+    self_trade: SelfTradeConfig,
+    // Last arrival sequence assigned, see OrderPriority for details.
+    last_sequence: AtomicU64,
+    // Cycles recently consumed by each kind of lock script, keyed by code
+    // hash and hash type.
+    lock_cycles: Mutex<HashMap<(Byte32, u8), u64>>,
+    // Latest oracle cells, keyed by type script hash. Only configured oracles
+    // can be used by stop-limit orders.
+    oracle_hashes: HashSet<Byte32>,
+    oracles: Mutex<HashMap<Byte32, OracleCell>>,
+    tokens: TokenAdapters,
+    // Lock scripts of configured brokers, keyed by lock script hash
+    brokers: HashMap<Byte32, Script>,
+    // NFT & token pairs of all mapped NFT orders
+    nft_pairs: Mutex<HashSet<[u8; 64]>>,
+    // Whether any route order has been mapped
+    routes: AtomicBool,
+}
+
+// Latest live cell of an oracle, along with the price it reports
+#[derive(Debug, Clone)]
+struct OracleCell {
+    out_point: OutPoint,
+    price: dex1::OraclePrice,
+}
+
+// Chain state orders are validated against while matching a tx
+#[derive(Debug, Clone)]
+struct MatchConditions {
+    block_number: u64,
+    expired_block_number: u64,
+    oracles: HashMap<Byte32, OracleCell>,
 }
 
 impl Dex1 {
@@ -384,31 +810,211 @@ impl Dex1 {
                 .map(|pair| {
                     let packed: PackedTradingPair = pair.clone().into();
                     let mut key = [0u8; 64];
-                    key[0..32].copy_from_slice(&packed.first.script.calc_script_hash().raw_data());
+                    key[0..32].copy_from_slice(&token_script_hash(&packed.first.script).raw_data());
                     key[32..64]
-                        .copy_from_slice(&packed.second.script.calc_script_hash().raw_data());
+                        .copy_from_slice(&token_script_hash(&packed.second.script).raw_data());
                     (key, packed)
                 })
                 .collect(),
+            omnilock_script: config.omnilock.clone().into(),
+            matching: config.matching.clone(),
+            surplus: config.surplus.clone(),
+            surplus_dust: config.surplus_dust.clone(),
+            rings: config.rings.clone(),
+            packing: config.packing.clone(),
+            self_trade: config.self_trade.clone(),
+            last_sequence: AtomicU64::new(0),
+            lock_cycles: Mutex::new(HashMap::new()),
+            oracle_hashes: config
+                .oracles
+                .iter()
+                .map(|oracle| Script::from(oracle.clone()).calc_script_hash())
+                .collect(),
+            oracles: Mutex::new(HashMap::new()),
+            tokens: TokenAdapters::new(&config.tokens),
+            brokers: config
+                .brokers
+                .iter()
+                .map(|broker| {
+                    let script: Script = broker.clone().into();
+                    (script.calc_script_hash(), script)
+                })
+                .collect(),
+            nft_pairs: Mutex::new(HashSet::new()),
+            routes: AtomicBool::new(false),
+        }
+    }
+
+    /// Update prices reported by oracle cells, stop-limit orders are
+    /// triggered against the latest prices. Cells of oracles not configured,
+    /// or with malformed data are ignored.
+    pub fn update_oracles(&self, cells: &[LiveCell]) {
+        let mut oracles = self.oracles.lock().expect("lock");
+        for cell in cells {
+            let Some(type_hash) = cell
+                .output
+                .type_()
+                .to_opt()
+                .map(|script| script.calc_script_hash())
+                .filter(|type_hash| self.oracle_hashes.contains(type_hash))
+            else {
+                continue;
+            };
+            match dex1::OraclePrice::from_slice(&cell.output_data) {
+                Ok(price) => {
+                    oracles.insert(
+                        type_hash,
+                        OracleCell {
+                            out_point: cell.out_point.clone(),
+                            price,
+                        },
+                    );
+                }
+                Err(e) => log::warn!("Error parsing oracle data of {:x}: {:?}", type_hash, e),
+            }
+        }
+    }
+
+    // Snapshot of chain state used to validate orders matched into +base_tx+
+    fn match_conditions(&self, base_tx: &BaseTx) -> MatchConditions {
+        MatchConditions {
+            block_number: base_tx.block_number,
+            expired_block_number: base_tx.expired_block_number,
+            oracles: self.oracles.lock().expect("lock").clone(),
+        }
+    }
+
+    /// Record cycles consumed by a verified tx, so later txs can be packed
+    /// with better cycle estimations. Cycles are attributed evenly to each
+    /// lock script group of +inputs+, type scripts are not separated out,
+    /// which only makes estimations more conservative.
+    pub fn record_cycles(&self, inputs: &[CellOutput], cycles: u64) {
+        let groups: HashSet<Script> = inputs.iter().map(|cell| cell.lock()).collect();
+        if groups.is_empty() {
+            return;
+        }
+        let group_cycles = cycles / groups.len() as u64;
+        let mut lock_cycles = self.lock_cycles.lock().expect("lock");
+        for lock in groups {
+            lock_cycles.insert(lock_kind(&lock), group_cycles);
+        }
+    }
+
+    // Each distinct lock script among +inputs+ is executed once.
+    fn estimated_cycles(&self, inputs: &[(CellOutput, Bytes)]) -> u64 {
+        let groups: HashSet<Script> = inputs.iter().map(|(cell, _)| cell.lock()).collect();
+        let lock_cycles = self.lock_cycles.lock().expect("lock");
+        groups
+            .iter()
+            .map(|lock| {
+                lock_cycles
+                    .get(&lock_kind(lock))
+                    .copied()
+                    .unwrap_or(self.packing.default_lock_cycles)
+            })
+            .sum()
+    }
+
+    fn packing_budget(&self, base_tx: &RichOtx) -> PackingBudget<'_> {
+        PackingBudget {
+            dex1: self,
+            size: base_tx.tx.as_slice().len() + SEAL_SIZE_OVERHEAD,
+            // The fee cell lock as well as dex1 & UDT type scripts
+            cycles: self.estimated_cycles(&base_tx.inputs) + self.packing.default_lock_cycles,
+        }
+    }
+
+    // Key of the order book +limit_order+ belongs to
+    fn order_key(&self, limit_order: &dex1::LimitOrder) -> Option<[u8; 65]> {
+        let bid_token = token_hash(&limit_order.bid_token());
+        let ask_token = token_hash(&limit_order.ask_token());
+        for (suffix, first, second) in [
+            (LIMIT_SELL, bid_token, ask_token),
+            (LIMIT_BUY, ask_token, bid_token),
+        ] {
+            let mut pair = [0u8; 64];
+            pair[0..32].copy_from_slice(&first);
+            pair[32..64].copy_from_slice(&second);
+            if self.pairs.contains_key(&pair) {
+                let mut key = [suffix; 65];
+                key[0..64].copy_from_slice(&pair);
+                return Some(key);
+            }
         }
+        None
     }
 
     pub fn keys(&self) -> Vec<[u8; 65]> {
-        self.pairs
+        let mut keys: Vec<[u8; 65]> = self
+            .pairs
             .keys()
             .map(|key| {
                 let mut data = [0u8; 65];
                 data[0..64].copy_from_slice(key);
                 data
             })
-            .collect()
+            .collect();
+        let mut nft_pairs: Vec<[u8; 64]> = self
+            .nft_pairs
+            .lock()
+            .expect("lock")
+            .iter()
+            .cloned()
+            .collect();
+        nft_pairs.sort();
+        keys.extend(nft_pairs.into_iter().map(|pair| {
+            let mut key = [NFT; 65];
+            key[0..64].copy_from_slice(&pair);
+            key
+        }));
+        // Routes are reduced after all pairs, taking liquidity left by them
+        if self.routes.load(AtomicOrdering::SeqCst) {
+            keys.push([ROUTE; 65]);
+        }
+        // Rings are reduced after all pairs, so only orders that cannot be
+        // filled within their own pairs are left.
+        if self.rings.enabled && self.pairs.len() >= 3 {
+            keys.push([RING; 65]);
+        }
+        keys
+    }
+
+    fn verify_seals(&self, otx: &basic::Otx, tx: &RichOtx) -> Result<()> {
+        let message = otx_signing_message(otx, &tx.tx, &tx.inputs);
+        let mut verified_locks = HashSet::new();
+        for (cell_output, _) in &tx.inputs {
+            let lock = cell_output.lock();
+            let lock_hash = lock.calc_script_hash();
+            if !verified_locks.insert(lock_hash.clone()) {
+                continue;
+            }
+            if lock.code_hash() == SIGHASH_TYPE_HASH.pack()
+                && lock.hash_type() == ScriptHashType::Type.into()
+            {
+                bail!("Genesis sighash lock cannot be unlocked in an otx!");
+            }
+            if lock.code_hash() == self.omnilock_script.script.code_hash()
+                && lock.hash_type() == self.omnilock_script.script.hash_type()
+            {
+                let seal = otx
+                    .seals()
+                    .into_iter()
+                    .find(|seal| seal.script_hash() == lock_hash)
+                    .ok_or_else(|| anyhow!("Missing seal for lock {:x}!", lock_hash))?;
+                verify_omnilock_seal(&lock, &seal.seal().raw_data(), &message)?;
+            }
+        }
+        Ok(())
     }
 
+    /// Lock of the freestanding cell keeping +order+, which can be unlocked
+    /// by dex1, or by the owner of the order to cancel it.
     pub fn freestanding_lock(&self, recipient_script: &Script, order: &dex1::Order) -> Script {
+        let owner = order_owner(order).unwrap_or_else(|| recipient_script.calc_script_hash());
         let freestanding_args = {
             let mut data = [0u8; 96];
             data[0..32].copy_from_slice(&self.dex1_script.script.args().raw_data().slice(0..32));
-            data[32..64].copy_from_slice(&recipient_script.calc_script_hash().raw_data());
+            data[32..64].copy_from_slice(&owner.raw_data());
             data[64..96].copy_from_slice(&hash_order(order));
             Bytes::from(data.to_vec()).pack()
         };
@@ -419,512 +1025,505 @@ impl Dex1 {
             .args(freestanding_args)
             .build()
     }
-}
 
-impl Assembler for Dex1 {
-    type Otx = RichOtx;
-    type BaseTx = (RichOtx, u64);
-    type Key = [u8; 65];
-    type Order = Ratio<u128>;
-    type Value = ParsedData;
-    type PostValue = Option<([u8; 65], ParsedData)>;
+    /// Pending txs might be sealed with a later dex1 entity cell than the one
+    /// they are assembled against. Sliced remainders of +tx+ are rescheduled
+    /// to be due interval blocks after +block_number+, the block of the dex1
+    /// entity cell +tx+ is sealed with, as the contract requires.
+    pub fn reschedule_slices(
+        &self,
+        tx: &Transaction,
+        post_value: <Dex1 as Assembler>::PostValue,
+        block_number: u64,
+    ) -> (Transaction, <Dex1 as Assembler>::PostValue) {
+        let mut outputs: Vec<_> = tx.raw().outputs().into_iter().collect();
+        let post_value = post_value
+            .into_iter()
+            .map(|(key, mut parsed_data)| {
+                let dex1::OrderUnion::SlicedOrder(o) = parsed_data.order.to_enum() else {
+                    return (key, parsed_data);
+                };
+                let not_before: u64 = o.not_before().unpack();
+                let interval: u64 = o.interval().unpack();
+                let next = block_number.saturating_add(interval);
+                if interval == 0 || not_before >= next {
+                    return (key, parsed_data);
+                }
+                let old_lock =
+                    self.freestanding_lock(&parsed_data.recipient_script, &parsed_data.order);
+                parsed_data.order = dex1::Order::new_builder()
+                    .set(o.as_builder().not_before(next.pack()).build())
+                    .build();
+                let new_lock =
+                    self.freestanding_lock(&parsed_data.recipient_script, &parsed_data.order);
+                for output in outputs
+                    .iter_mut()
+                    .filter(|output| output.lock() == old_lock)
+                {
+                    *output = output.clone().as_builder().lock(new_lock.clone()).build();
+                }
+                (key, parsed_data)
+            })
+            .collect();
+        let tx = tx
+            .clone()
+            .as_advanced_builder()
+            .set_outputs(outputs)
+            .build()
+            .data();
+        (tx, post_value)
+    }
 
-    fn map<E>(&self, tx: RichOtx, emitter: &mut E) -> Result<()>
+    /// Build a tx returning freestanding cells of orders expired at
+    /// +tip_block_number+ to their owners, which are the recipients unless
+    /// orders specify separate owners. Each cell is unlocked via the
+    /// expiry clause of the freestanding lock, the tx still needs to be sealed
+    /// by Dex1Env with a fee cell. Orders with a deadline expire at the
+    /// deadline, other orders expire MAX_ORDER_AGE blocks after their cell is
+    /// created, which +created_block_number+ tells by the creating tx hash.
+    pub fn sweep_expired_orders<'a, I, F>(
+        &self,
+        orders: I,
+        tip_block_number: u64,
+        created_block_number: F,
+    ) -> Option<Transaction>
     where
-        E: MapEmitter<Self::Key, Self::Order, Self::Value>,
+        I: IntoIterator<Item = &'a ParsedData>,
+        F: Fn(&Byte32) -> Option<u64>,
     {
-        let raw = tx.tx.raw();
-        if raw.cell_deps().len() > 0 {
-            bail!("Invalid number of cell deps!");
-        }
-        if raw.header_deps().len() > 0 {
-            bail!("Invalid number of header deps!");
-        }
-        if tx.tx.witnesses().len() != 1 {
-            bail!("Invalid number of witnesses!");
-        }
-        let witness = tx.tx.witnesses().get(0).unwrap();
-        let witness_layout = WitnessLayout::from_slice(&witness.raw_data())
-            .map_err(|e| anyhow!("Error parsing witness: {:?}", e))?;
-        let WitnessLayoutUnion::Otx(otx) = witness_layout.to_enum() else {
-            bail!("Invalid witness layout type!");
-        };
-        {
-            let input_cells: usize = otx.input_cells().unpack();
-            let output_cells: usize = otx.output_cells().unpack();
-            let cell_deps: usize = otx.cell_deps().unpack();
-            let header_deps: usize = otx.header_deps().unpack();
-            if raw.inputs().len() != input_cells
-                || raw.outputs().len() != output_cells
-                || raw.cell_deps().len() != cell_deps
-                || raw.header_deps().len() != header_deps
+        let mut out_points = HashSet::new();
+        let mut builder = TransactionView::new_advanced_builder();
+        for parsed_data in orders {
+            if !parsed_data.freestanding_cell {
+                continue;
+            }
+            let Some(cell_input) = parsed_data.tx.tx.raw().inputs().get(0) else {
+                continue;
+            };
+            // Cells of owned orders are returned to the owner, they can only
+            // be swept when the otx carried the owner lock.
+            let owner_script = match order_owner(&parsed_data.order) {
+                Some(_) => match &parsed_data.owner_script {
+                    Some(owner_script) => owner_script.clone(),
+                    None => continue,
+                },
+                None => parsed_data.recipient_script.clone(),
+            };
+            let (expiry, since) = match parsed_data.order.to_enum() {
+                dex1::OrderUnion::LimitOrderWithDeadline(o) => {
+                    let deadline: u64 = o.deadline().unpack();
+                    (Some(deadline), deadline)
+                }
+                _ => (
+                    created_block_number(&cell_input.previous_output().tx_hash())
+                        .map(|block_number| block_number.saturating_add(MAX_ORDER_AGE)),
+                    RELATIVE_BLOCK_NUMBER_SINCE | MAX_ORDER_AGE,
+                ),
+            };
+            if expiry.is_none_or(|expiry| expiry > tip_block_number)
+                || !out_points.insert(cell_input.previous_output())
             {
-                bail!("Invalid otx format!");
+                continue;
             }
+            let (cell_output, cell_data) = &parsed_data.tx.inputs[0];
+            let witness = WitnessArgs::new_builder()
+                .lock(Some(parsed_data.order.as_bytes()).pack())
+                .build();
+            builder = builder
+                .input(cell_input.as_builder().since(since.pack()).build())
+                .output(cell_output.clone().as_builder().lock(owner_script).build())
+                .output_data(cell_data.pack())
+                .witness(witness.as_bytes().pack());
         }
-        let dex1_script_hash = self.dex1_script.script.calc_script_hash();
-        let action = otx
-            .message()
-            .actions()
-            .into_iter()
-            .find(|action| action.script_hash() == dex1_script_hash)
-            .ok_or_else(|| anyhow!("Missing cobuild action for dex1!"))?;
-        let dex1_action = dex1::Dex1Action::from_slice(&action.data().raw_data())
-            .map_err(|e| anyhow!("Error parsing dex1 action data: {:?}", e))?;
-        if dex1_action.orders().len() != 1 {
-            bail!("For now, we only process otx with exact one order");
+        if out_points.is_empty() {
+            return None;
         }
-        let order = dex1_action.orders().get(0).unwrap();
-        let limit_order = match order.to_enum() {
-            dex1::OrderUnion::LimitOrder(limit_order) => limit_order,
-            // Deadline will be processed at reduce time
-            dex1::OrderUnion::LimitOrderWithDeadline(o) => o.order(),
-            _ => bail!("For now, we only support basic limit orders, later we shall add support for market orders"),
-        };
-        // Check if current trading pair is supported
-        let (key, bid_token_script, ask_token_script) = {
-            let buy_pair = {
-                let mut pair = [0u8; 64];
-                pair[0..32].copy_from_slice(&limit_order.ask_token().raw_data());
-                pair[32..64].copy_from_slice(&limit_order.bid_token().raw_data());
-                pair
-            };
-            let sell_pair = {
-                let mut pair = [0u8; 64];
-                pair[0..32].copy_from_slice(&limit_order.bid_token().raw_data());
-                pair[32..64].copy_from_slice(&limit_order.ask_token().raw_data());
-                pair
-            };
-            if let Some(pair_data) = self.pairs.get(&buy_pair) {
-                let mut key = [LIMIT_BUY; 65];
-                key[0..64].copy_from_slice(&buy_pair);
-                (key, &pair_data.second, &pair_data.first)
-            } else if let Some(pair_data) = self.pairs.get(&sell_pair) {
-                let mut key = [LIMIT_SELL; 65];
-                key[0..64].copy_from_slice(&sell_pair);
-                (key, &pair_data.first, &pair_data.second)
-            } else {
-                bail!("Trading pair is not supported!");
-            }
-        };
-        // Check if enough tokens are provided by the otx as claimed in bid_amount
-        // Check if claimed_ckbytes can be claimed
-        let bid_amount: u128 = limit_order.bid_amount().unpack();
-        let ask_amount: u128 = limit_order.ask_amount().unpack();
-        let claimed_ckbytes: u64 = limit_order.claimed_ckbytes().unpack();
-        {
-            let mut tokens: u128 = 0;
-            let mut ckbytes: u64 = 0;
-            for (cell_output, cell_data) in &tx.inputs {
-                if cell_output
-                    .type_()
-                    .to_opt()
-                    .map(|s| s == bid_token_script.script)
-                    .unwrap_or(false)
-                {
-                    if cell_data.len() < 16 {
-                        bail!("Invalid udt data format!");
-                    }
-                    let mut data = [0u8; 16];
-                    data.copy_from_slice(&cell_data[0..16]);
-                    let current_tokens = u128::from_le_bytes(data);
-                    tokens = tokens
-                        .checked_add(current_tokens)
-                        .ok_or_else(|| anyhow!("overflow!"))?;
-                }
-                ckbytes = ckbytes
-                    .checked_add(cell_output.capacity().unpack())
-                    .ok_or_else(|| anyhow!("overflow!"))?;
-            }
-            for (i, cell_output) in raw.outputs().into_iter().enumerate() {
-                if cell_output
-                    .type_()
-                    .to_opt()
-                    .map(|s| s == bid_token_script.script)
-                    .unwrap_or(false)
-                {
-                    let cell_data = raw.outputs_data().get(i).unwrap();
-                    let mut data = [0u8; 16];
-                    data.copy_from_slice(&cell_data.raw_data()[0..16]);
-                    let current_tokens = u128::from_le_bytes(data);
-                    tokens = tokens
-                        .checked_sub(current_tokens)
-                        .ok_or_else(|| anyhow!("overflow!"))?;
+        // Reserved for the fee cell witness added at sealing time
+        Some(builder.witness(Bytes::default().pack()).build().data())
+    }
+
+    // Ring matching: the best order in each direction of each pair forms an
+    // edge from its bid token to its ask token, a cycle of such edges can be
+    // settled together even when none of the orders crosses within its own
+    // pair.
+    fn reduce_rings<E, S>(&self, base_tx: BaseTx, emitter: &mut E, source: &S) -> Result<()>
+    where
+        E: ReduceEmitter<
+            <Dex1 as Assembler>::Key,
+            <Dex1 as Assembler>::Order,
+            <Dex1 as Assembler>::Value,
+            <Dex1 as Assembler>::PostValue,
+        >,
+        S: ReduceSource<<Dex1 as Assembler>::Key, <Dex1 as Assembler>::Value>,
+    {
+        let conditions = self.match_conditions(&base_tx);
+        let base_tx = base_tx.tx;
+
+        let mut pair_keys: Vec<&[u8; 64]> = self.pairs.keys().collect();
+        pair_keys.sort();
+        let mut orders = Vec::new();
+        let mut edges: BTreeMap<[u8; 32], Vec<([u8; 32], usize)>> = BTreeMap::new();
+        for pair_key in pair_keys {
+            for suffix in [LIMIT_BUY, LIMIT_SELL] {
+                let mut key = [suffix; 65];
+                key[0..64].copy_from_slice(pair_key);
+                let mut otxs = source.otxs(key);
+                if let Some(order) = locate_next_valid_order(&mut otxs, emitter, &conditions)? {
+                    edges
+                        .entry(token_hash(&order.1.bid_token()))
+                        .or_default()
+                        .push((token_hash(&order.1.ask_token()), orders.len()));
+                    orders.push((order, key));
                 }
-                ckbytes = ckbytes
-                    .checked_sub(cell_output.capacity().unpack())
-                    .ok_or_else(|| anyhow!("overflow!"))?;
-            }
-            if bid_amount == 0 || bid_amount != tokens {
-                bail!("Invalid bid amount!");
-            }
-            if claimed_ckbytes > ckbytes {
-                bail!("Not enough ckbytes to claim!");
             }
         }
-        // Check if recipient hash is used in current otx. Note this is a shortcut,
-        // it's also possible to provide recipient script via OTX RPC.
-        let Some(recipient_script) = tx
-            .inputs
-            .iter()
-            .map(|(cell_output, _)| cell_output.lock())
-            .chain(
-                raw.outputs()
-                    .into_iter()
-                    .map(|cell_output| cell_output.lock()),
-            )
-            .find(|s| s.calc_script_hash() == limit_order.recipient())
-        else {
-            bail!("Recipient script is missing!");
-        };
-        // Claimed ckbytes must be enough for partial fills
-        {
-            let dummy_freestanding_cell = CellOutput::new_builder()
-                .lock(
-                    Script::new_builder()
-                        .args(Bytes::from(vec![0; 96]).pack())
-                        .build(),
-                )
-                .type_(Some(bid_token_script.script.clone()).pack())
-                .build();
-            let dummy_cell_capacity = dummy_freestanding_cell
-                .occupied_capacity(Capacity::bytes(16).expect("overflow"))
-                .expect("overflow");
-            let return_cell = CellOutput::new_builder()
-                .lock(recipient_script.clone())
-                .type_(Some(ask_token_script.script.clone()).pack())
-                .build();
-            let return_cell_capacity = return_cell
-                .occupied_capacity(Capacity::bytes(16).expect("overflow"))
-                .expect("overflow");
-            let total_capacity = dummy_cell_capacity
-                .safe_add(return_cell_capacity)
-                .expect("overflow");
-            if total_capacity.as_u64() > claimed_ckbytes {
-                bail!("Claimed ckbytes are not enough in the partial fill worse case!");
+
+        for start in edges.keys() {
+            let mut path = Vec::new();
+            if let Some(fills) = search_ring(
+                start,
+                start,
+                &edges,
+                &orders,
+                &mut path,
+                self.rings.max_length,
+            ) {
+                let mut budget = self.packing_budget(&base_tx);
+                if !fills.iter().all(|fill| budget.reserve(&fill.order.0)) {
+                    continue;
+                }
+                log::debug!("Settling a ring of {} orders", fills.len());
+                let (tx, post_value) = self.settle(&base_tx, fills, &conditions)?;
+                return emitter.emit_tx(tx, post_value);
             }
         }
-        // Calculate the price, now we can build parsed data
-        let price = Ratio::new_raw(ask_amount, bid_amount);
-        let parsed_data = ParsedData {
-            tx,
-            recipient_script,
-            ask_token_script: ask_token_script.script.clone(),
-            bid_token_script: bid_token_script.script.clone(),
-            price,
-            order,
-            freestanding_cell: false,
-        };
-        // Claimed ckbytes must be enough for partial fills
-        if parsed_data.minimal_ckbytes() > claimed_ckbytes {
-            bail!("Claimed ckbytes are not enough in the partial fill worse case!");
-        }
-
-        log::debug!(
-            "Emitting {} order of price {}, bid amount: {}, ask amount: {}",
-            if key[64] == LIMIT_SELL {
-                "limit sell"
-            } else {
-                "limit buy"
-            },
-            price,
-            bid_amount,
-            ask_amount
-        );
-        emitter.emit(key, price, parsed_data)
+        Ok(())
     }
 
-    fn reduce<E, S>(
-        &self,
-        base_tx: (RichOtx, u64),
-        key: Self::Key,
-        emitter: &mut E,
-        source: &S,
-    ) -> Result<()>
-    where
-        E: ReduceEmitter<Self::Key, Self::Order, Self::Value, Self::PostValue>,
-        S: ReduceSource<Self::Key, Self::Value>,
-    {
-        let (base_tx, expired_block_number) = base_tx;
-
-        let dex1_cell_input = base_tx.tx.raw().inputs().get(0).unwrap();
-        let dex1_cell_output = base_tx.tx.raw().outputs().get(0).unwrap();
-        let dex1_cell_data = base_tx.tx.raw().outputs_data().get(0).unwrap();
-        // Create iterators of buy orders & sell orders
-        let limit_buy_key = {
-            let mut key = key.clone();
-            key[64] = LIMIT_BUY;
-            key
-        };
-        let mut limit_buys = source.otxs(limit_buy_key);
-        let limit_sell_key = {
-            let mut key = key.clone();
-            key[64] = LIMIT_SELL;
-            key
-        };
-        let mut limit_sells = source.otxs(limit_sell_key);
-        // For each top buy order, look for sell orders it can fulfill
-        // For simplicity, we are packing at most 20 limit buy otxs here,
-        // but this depends on your strategy, it can and should be altered.
-        let mut fulfilled_orders: Vec<(ParsedData, dex1::LimitOrder)> = vec![];
-        let mut unfinished_buy_order =
-            locate_next_valid_order(&mut limit_buys, emitter, expired_block_number)?;
-        let mut pending_ask_amount: u128 = match &unfinished_buy_order {
-            Some((_, limit_order)) => limit_order.ask_amount().unpack(),
-            // Terminate when we don't have at least one buy order.
-            None => return Ok(()),
-        };
-        let mut unfinished_sell_order: Option<(ParsedData, dex1::LimitOrder)> = None;
-        while fulfilled_orders.len() < 20 {
-            // Loop invariant
-            assert!(unfinished_buy_order.is_none() || unfinished_sell_order.is_none());
-            let mut advanced = false;
-            {
-                let mut finish_buy_order = false;
-                if let Some(buy_order) = &unfinished_buy_order {
-                    if let Some(sell_order) =
-                        locate_next_valid_order(&mut limit_sells, emitter, expired_block_number)?
-                    {
-                        if !matchable_order(&buy_order.0, &sell_order.0) {
-                            // No more sell orders can be processed
-                            break;
-                        }
-                        let seller_bid_amount: u128 = sell_order.1.bid_amount().unpack();
-                        match pending_ask_amount.cmp(&seller_bid_amount) {
-                            std::cmp::Ordering::Less => {
-                                // pending_ask_amount should now hold value for the unfinished sell order.
-                                pending_ask_amount = seller_bid_amount - pending_ask_amount;
-                                finish_buy_order = true;
-                                unfinished_sell_order = Some(sell_order);
-                            }
-                            std::cmp::Ordering::Equal => {
-                                pending_ask_amount = 0;
-                                finish_buy_order = true;
-                                fulfilled_orders.push(sell_order);
-                            }
-                            std::cmp::Ordering::Greater => {
-                                pending_ask_amount -= seller_bid_amount;
-                                fulfilled_orders.push(sell_order);
-                            }
-                        };
-                        advanced = true;
-                    }
+    // Routes surplus of each token according to the surplus policy. Surplus
+    // paid to an order receiving the token is added to its payment cell,
+    // otherwise a separate surplus cell funded by the processor is created.
+    // Surplus below the dust threshold goes to a single dust cell per token
+    // instead of such cells.
+    fn distribute_surplus(&self, fills: &mut [Fill]) -> Result<Vec<(CellOutput, packed::Bytes)>> {
+        let mut tokens: Vec<Script> = Vec::new();
+        for fill in fills.iter() {
+            for token in [
+                &fill.order.0.bid_token_script,
+                &fill.order.0.ask_token_script,
+            ] {
+                if !tokens.contains(token) {
+                    tokens.push(token.clone());
                 }
-                if finish_buy_order {
-                    fulfilled_orders.push(unfinished_buy_order.unwrap());
-                    if unfinished_sell_order.is_none() {
-                        unfinished_buy_order = locate_next_valid_order(
-                            &mut limit_buys,
-                            emitter,
-                            expired_block_number,
-                        )?;
-                    } else {
-                        unfinished_buy_order = None;
-                    }
+            }
+        }
+        let maker = (0..fills.len())
+            .min_by_key(|i| fills[*i].order.0.sequence)
+            .expect("no fills");
+        let taker = (0..fills.len())
+            .max_by_key(|i| fills[*i].order.0.sequence)
+            .expect("no fills");
+
+        let mut surplus_cells = Vec::new();
+        for token in tokens {
+            let mut provided_amount: u128 = 0;
+            let mut paid_amount: u128 = 0;
+            for fill in fills.iter() {
+                let (parsed_data, limit_order) = &fill.order;
+                if parsed_data.bid_token_script == token {
+                    let amount = match fill.partial {
+                        Some((amount, _)) => amount,
+                        None => limit_order.bid_amount().unpack(),
+                    };
+                    provided_amount = provided_amount
+                        .checked_add(amount)
+                        .ok_or_else(|| anyhow!("overflow!"))?;
+                }
+                if parsed_data.ask_token_script == token {
+                    paid_amount = paid_amount
+                        .checked_add(fill.paid_amount)
+                        .ok_or_else(|| anyhow!("overflow!"))?;
                 }
             }
-            {
-                let mut finish_sell_order = false;
-                if let Some(sell_order) = &unfinished_sell_order {
-                    if let Some(buy_order) =
-                        locate_next_valid_order(&mut limit_buys, emitter, expired_block_number)?
-                    {
-                        if !matchable_order(&buy_order.0, &sell_order.0) {
-                            // No more buy orders can be processed
-                            break;
-                        }
-                        let buyer_bid_amount: u128 = buy_order.1.bid_amount().unpack();
-                        match pending_ask_amount.cmp(&buyer_bid_amount) {
-                            std::cmp::Ordering::Less => {
-                                // pending_ask_amount should now hold value for the unfinished buy order.
-                                pending_ask_amount = buyer_bid_amount - pending_ask_amount;
-                                finish_sell_order = true;
-                                unfinished_buy_order = Some(buy_order);
-                            }
-                            std::cmp::Ordering::Equal => {
-                                pending_ask_amount = 0;
-                                finish_sell_order = true;
-                                fulfilled_orders.push(buy_order);
-                            }
-                            std::cmp::Ordering::Greater => {
-                                pending_ask_amount -= buyer_bid_amount;
-                                fulfilled_orders.push(buy_order);
-                            }
-                        }
-                        advanced = true;
-                    }
+            let surplus = provided_amount.checked_sub(paid_amount).ok_or_else(|| {
+                anyhow!(
+                    "Token {:x} is paid more than provided!",
+                    token_script_hash(&token)
+                )
+            })?;
+            if surplus == 0 {
+                continue;
+            }
+            let shares = match &self.surplus {
+                SurplusConfig::Maker => vec![(maker, surplus)],
+                SurplusConfig::Taker => vec![(taker, surplus)],
+                SurplusConfig::Split => {
+                    vec![(maker, surplus - surplus / 2), (taker, surplus / 2)]
                 }
-                if finish_sell_order {
-                    fulfilled_orders.push(unfinished_sell_order.unwrap());
-                    if unfinished_buy_order.is_none() {
-                        unfinished_sell_order = locate_next_valid_order(
-                            &mut limit_sells,
-                            emitter,
-                            expired_block_number,
-                        )?;
-                    } else {
-                        unfinished_sell_order = None;
-                    }
+                SurplusConfig::ProtocolFee { lock } => {
+                    surplus_cells.push(surplus_cell(
+                        &self.tokens,
+                        lock.clone().into(),
+                        &token,
+                        surplus,
+                    )?);
+                    continue;
+                }
+            };
+            let mut dust: u128 = 0;
+            for (i, amount) in shares {
+                if amount == 0 {
+                    continue;
+                }
+                let fill = &mut fills[i];
+                let ask_amount: u128 = fill.order.1.ask_amount().unpack();
+                // A partial fill still leaves ask tokens to the remaining order
+                let foldable = fill.order.0.ask_token_script == token
+                    && (fill.partial.is_none()
+                        || fill.paid_amount.saturating_add(amount) < ask_amount);
+                let is_dust = self
+                    .surplus_dust
+                    .as_ref()
+                    .is_some_and(|dust_config| amount < dust_config.threshold);
+                if foldable {
+                    fill.paid_amount += amount;
+                } else if is_dust {
+                    dust += amount;
+                } else {
+                    surplus_cells.push(surplus_cell(
+                        &self.tokens,
+                        fill.order.0.recipient_script.clone(),
+                        &token,
+                        amount,
+                    )?);
                 }
             }
-            if !advanced {
-                break;
+            if let Some(dust_config) = self.surplus_dust.as_ref().filter(|_| dust > 0) {
+                surplus_cells.push(surplus_cell(
+                    &self.tokens,
+                    dust_config.lock.clone().into(),
+                    &token,
+                    dust,
+                )?);
             }
         }
-        if fulfilled_orders.is_empty() {
-            return Ok(());
+        Ok(surplus_cells)
+    }
+
+    // Builds the assembled tx paying all matched orders.
+    fn settle(
+        &self,
+        base_tx: &RichOtx,
+        fills: Vec<Fill>,
+        conditions: &MatchConditions,
+    ) -> Result<(Transaction, <Dex1 as Assembler>::PostValue)> {
+        let mut fills = fills;
+        // Iceberg orders are matched by their displayed part, the hidden part
+        // is kept in a freestanding cell just like a partial fill.
+        for fill in fills.iter_mut() {
+            // Route orders are always fully filled
+            let Some(full_order) = limit_order(&fill.order.0.order) else {
+                continue;
+            };
+            if full_order.as_slice() == fill.order.1.as_slice() {
+                continue;
+            }
+            if fill.partial.is_none() {
+                let key = self
+                    .order_key(&full_order)
+                    .ok_or_else(|| anyhow!("Trading pair is not supported!"))?;
+                fill.partial = Some((fill.order.1.bid_amount().unpack(), key));
+            }
+            fill.order.1 = full_order;
         }
-        // Create payment cells for all fulfilled orders
-        let mut freestanding_orders = Vec::new();
+        let surplus_cells = self.distribute_surplus(&mut fills)?;
+        // Create payment cells for all matched orders
         let mut otx_payment_cells = Vec::new();
         let mut freestanding_payment_cells = Vec::new();
-        fulfilled_orders
-            .iter()
-            .for_each(|(parsed_data, limit_order)| {
-                let cell_output = CellOutput::new_builder()
-                    .lock(parsed_data.recipient_script.clone())
-                    .type_(Some(parsed_data.ask_token_script.clone()).pack())
-                    .capacity(limit_order.claimed_ckbytes())
-                    .build();
-                let cell_data = limit_order.ask_amount().raw_data().pack();
-
-                if parsed_data.freestanding_cell {
-                    freestanding_orders.push(parsed_data.order.clone());
-                    freestanding_payment_cells.push((cell_output, cell_data));
+        let mut post_value = Vec::new();
+        for fill in &fills {
+            let (parsed_data, limit_order) = &fill.order;
+            let (paid_amount, broker_cell) = self.broker_fee(parsed_data, fill.paid_amount)?;
+            // The broker cell is funded by claimed ckbytes as well
+            let claimed_ckbytes: u64 = limit_order.claimed_ckbytes().unpack();
+            let claimed_ckbytes = claimed_ckbytes
+                .checked_sub(if broker_cell.is_some() {
+                    self.broker_ckbytes(parsed_data)
                 } else {
-                    otx_payment_cells.push((cell_output, cell_data));
-                }
-            });
+                    0
+                })
+                .ok_or_else(|| anyhow!("Claimed ckbytes are not enough for the broker cell!"))?;
+            let mut payment_cells = match fill.partial {
+                None => vec![self.tokens.cell(
+                    parsed_data.recipient_script.clone(),
+                    &parsed_data.ask_token_script,
+                    paid_amount,
+                    claimed_ckbytes,
+                )?],
+                // The partial order will ask +required_ask_amount+ ask token, while
+                // providing +required_bid_amount+ bid token.
+                Some((required_bid_amount, key)) => {
+                    let required_ask_amount = fill.paid_amount;
+                    let ask_amount: u128 = limit_order.ask_amount().unpack();
+                    let bid_amount: u128 = limit_order.bid_amount().unpack();
+                    assert!(required_bid_amount < bid_amount);
+                    assert!(required_ask_amount < ask_amount);
+                    assert!(
+                        U256::from(required_ask_amount) * U256::from(bid_amount)
+                            >= U256::from(required_bid_amount) * U256::from(ask_amount),
+                    );
+
+                    // Create paid cell first, so we know how much capacity freestanding cell has
+                    let (paid_cell, paid_data, paid_capacity) = {
+                        let capacity = self.tokens.occupied_capacity(
+                            parsed_data.recipient_script.clone(),
+                            &parsed_data.ask_token_script,
+                        );
+                        let (output, data) = self.tokens.cell(
+                            parsed_data.recipient_script.clone(),
+                            &parsed_data.ask_token_script,
+                            paid_amount,
+                            capacity,
+                        )?;
+                        (output, data, capacity)
+                    };
 
-        // Create payment cells for partially filled order if needed
-        assert!(unfinished_buy_order.is_none() || unfinished_sell_order.is_none());
-        let mut post_value = None;
-        if unfinished_buy_order.is_some() || unfinished_sell_order.is_some() {
-            let (order, key) = if unfinished_buy_order.is_some() {
-                (unfinished_buy_order.unwrap(), limit_buy_key)
-            } else {
-                (unfinished_sell_order.unwrap(), limit_sell_key)
-            };
+                    let freestanding_capacity = claimed_ckbytes - paid_capacity;
+                    let new_ask_amount = ask_amount - required_ask_amount;
+                    let new_bid_amount = bid_amount - required_bid_amount;
 
-            // The partial order will ask +required_ask_amount+ ask token, while
-            // providing +required_bid_amount+ bid token.
-            let (required_bid_amount, required_ask_amount) = {
-                let mut input_bid_amount: u128 = 0;
-                let mut output_bid_amount: u128 = 0;
-                let mut input_ask_amount: u128 = 0;
-                let mut output_ask_amount: u128 = 0;
-
-                let ask_token = order.1.ask_token();
-                fulfilled_orders.iter().for_each(|(_, limit_order)| {
-                    let current_bid_amount: u128 = limit_order.bid_amount().unpack();
-                    let current_ask_amount: u128 = limit_order.ask_amount().unpack();
-
-                    if limit_order.ask_token() == ask_token {
-                        // Current order is of the same direction as partial order
-                        input_bid_amount += current_bid_amount;
-                        output_ask_amount += current_ask_amount;
+                    // Immediate-or-cancel orders get remaining bid tokens back
+                    // instead of a freestanding cell
+                    if let dex1::OrderUnion::ImmediateOrCancelOrder(_) = parsed_data.order.to_enum()
+                    {
+                        let refund = self.tokens.cell(
+                            parsed_data.recipient_script.clone(),
+                            &parsed_data.bid_token_script,
+                            new_bid_amount,
+                            freestanding_capacity,
+                        )?;
+                        vec![refund, (paid_cell, paid_data)]
                     } else {
-                        input_ask_amount += current_bid_amount;
-                        output_bid_amount += current_ask_amount;
-                    }
-                });
-
-                assert!(output_bid_amount > input_bid_amount);
-                assert!(input_ask_amount > output_ask_amount);
-                (
-                    (output_bid_amount - input_bid_amount),
-                    (input_ask_amount - output_ask_amount),
-                )
-            };
-
-            let ask_amount: u128 = order.1.ask_amount().unpack();
-            let bid_amount: u128 = order.1.bid_amount().unpack();
-            assert!(required_bid_amount < bid_amount);
-            assert!(required_ask_amount < ask_amount);
-            assert!(
-                U256::from(required_ask_amount) * U256::from(bid_amount)
-                    >= U256::from(required_bid_amount) * U256::from(ask_amount),
-            );
-
-            let claimed_ckbytes: u64 = order.1.claimed_ckbytes().unpack();
-
-            // Create paid cell first, so we know how much capacity freestanding cell has
-            let (paid_cell, paid_data, paid_capacity) = {
-                let dummy = CellOutput::new_builder()
-                    .lock(order.0.recipient_script.clone())
-                    .type_(Some(order.0.ask_token_script.clone()).pack())
-                    .build();
-                let capacity = dummy
-                    .occupied_capacity(Capacity::bytes(16).expect("overflow"))
-                    .expect("overflow");
-                let output = dummy
-                    .as_builder()
-                    .capacity(capacity.as_u64().pack())
-                    .build();
-                (
-                    output,
-                    Bytes::from(required_ask_amount.to_le_bytes().to_vec()).pack(),
-                    capacity.as_u64(),
-                )
-            };
-
-            let freestanding_capacity = claimed_ckbytes - paid_capacity;
-            let new_ask_amount = ask_amount - required_ask_amount;
-            let new_bid_amount = bid_amount - required_bid_amount;
-            let new_price = Ratio::new_raw(new_ask_amount, new_bid_amount);
-            let new_order = dex1::Order::new_builder()
-                .set(
-                    order
-                        .1
-                        .clone()
-                        .as_builder()
-                        .ask_amount(new_ask_amount.pack())
-                        .bid_amount(new_bid_amount.pack())
-                        .claimed_ckbytes(freestanding_capacity.pack())
-                        .build(),
-                )
-                .build();
+                        let new_price = Ratio::new_raw(new_ask_amount, new_bid_amount);
+                        let new_order = carve_limit_order(
+                            &parsed_data.order,
+                            limit_order
+                                .clone()
+                                .as_builder()
+                                .ask_amount(new_ask_amount.pack())
+                                .bid_amount(new_bid_amount.pack())
+                                .claimed_ckbytes(freestanding_capacity.pack())
+                                .build(),
+                            conditions.block_number,
+                        );
+
+                        // Create freestanding cell, remaining CKB is also kept
+                        // in cell data so it can be told apart from CKBytes.
+                        let (freestanding_cell, freestanding_data) = {
+                            let (cell, data) = self.tokens.cell(
+                                self.freestanding_lock(&parsed_data.recipient_script, &new_order),
+                                &parsed_data.bid_token_script,
+                                new_bid_amount,
+                                freestanding_capacity,
+                            )?;
+                            if is_ckb(&parsed_data.bid_token_script) {
+                                (
+                                    cell,
+                                    Bytes::from(new_bid_amount.to_le_bytes().to_vec()).pack(),
+                                )
+                            } else {
+                                (cell, data)
+                            }
+                        };
 
-            // Create freestanding cell
-            let (freestanding_cell, freestanding_data) = {
-                (
-                    CellOutput::new_builder()
-                        .lock(self.freestanding_lock(&order.0.recipient_script, &new_order))
-                        .type_(Some(order.0.bid_token_script.clone()).pack())
-                        .capacity(freestanding_capacity.pack())
-                        .build(),
-                    Bytes::from((bid_amount - required_bid_amount).to_le_bytes().to_vec()).pack(),
-                )
+                        // The hidden part of an iceberg order joins the back of
+                        // its price level.
+                        let sequence = match parsed_data.order.to_enum() {
+                            dex1::OrderUnion::IcebergOrder(_) => self.next_sequence(),
+                            _ => parsed_data.sequence,
+                        };
+                        post_value.push((
+                            key,
+                            ParsedData {
+                                tx: RichOtx {
+                                    tx: Transaction::default(),
+                                    inputs: Vec::new(),
+                                },
+                                recipient_script: parsed_data.recipient_script.clone(),
+                                ask_token_script: parsed_data.ask_token_script.clone(),
+                                bid_token_script: parsed_data.bid_token_script.clone(),
+                                price: new_price,
+                                order: new_order,
+                                freestanding_cell: true,
+                                sequence,
+                                owner_script: parsed_data.owner_script.clone(),
+                            },
+                        ));
+                        vec![
+                            (freestanding_cell, freestanding_data),
+                            (paid_cell, paid_data),
+                        ]
+                    }
+                }
             };
+            payment_cells.extend(broker_cell);
 
-            post_value = Some((
-                key,
-                ParsedData {
-                    tx: RichOtx {
-                        tx: Transaction::default(),
-                        inputs: Vec::new(),
-                    },
-                    recipient_script: order.0.recipient_script.clone(),
-                    ask_token_script: order.0.ask_token_script.clone(),
-                    bid_token_script: order.0.bid_token_script.clone(),
-                    price: new_price,
-                    order: new_order,
-                    freestanding_cell: true,
-                },
-            ));
-
-            if order.0.freestanding_cell {
-                freestanding_orders.push(order.0.order.clone());
-                freestanding_payment_cells.push((freestanding_cell, freestanding_data));
-                freestanding_payment_cells.push((paid_cell, paid_data));
+            if parsed_data.freestanding_cell {
+                freestanding_payment_cells.extend(payment_cells);
             } else {
-                otx_payment_cells.push((freestanding_cell, freestanding_data));
-                otx_payment_cells.push((paid_cell, paid_data));
+                otx_payment_cells.extend(payment_cells);
             }
-            fulfilled_orders.push(order);
         }
 
-        // Assemble the final transaction.
+        // Payment cells of otx orders come first, as otxs are put ahead of
+        // freestanding cells. Surplus cells come last, they are not bound to
+        // any order.
+        let outputs = otx_payment_cells
+            .into_iter()
+            .chain(freestanding_payment_cells)
+            .chain(surplus_cells)
+            .collect();
+        // The outer processor infrastructure shall take care of locating cell deps,
+        // reducer here only adds oracle cells triggering stop-limit orders.
+        // We will fill in dex1 header dep at sealing time
+        let cell_deps: HashSet<CellDep> = {
+            let oracles = self.oracles.lock().expect("lock");
+            fills
+                .iter()
+                .filter_map(|fill| match fill.order.0.order.to_enum() {
+                    dex1::OrderUnion::StopLimitOrder(o) => oracles.get(&o.oracle()),
+                    _ => None,
+                })
+                .map(|oracle| {
+                    CellDep::new_builder()
+                        .out_point(oracle.out_point.clone())
+                        .dep_type(DepType::Code.into())
+                        .build()
+                })
+                .collect()
+        };
+        let orders: Vec<&ParsedData> = fills.iter().map(|fill| &fill.order.0).collect();
+        let tx = self.assemble_tx(base_tx, &orders, outputs, cell_deps);
+        Ok((tx, post_value))
+    }
+
+    // Assembles the final transaction settling +orders+, +payment_cells+ are
+    // created in the order orders are validated by dex1.
+    fn assemble_tx(
+        &self,
+        base_tx: &RichOtx,
+        orders: &[&ParsedData],
+        payment_cells: Vec<(CellOutput, packed::Bytes)>,
+        cell_deps: HashSet<CellDep>,
+    ) -> Transaction {
+        let dex1_cell_input = base_tx.tx.raw().inputs().get(0).unwrap();
+        let dex1_cell_output = base_tx.tx.raw().outputs().get(0).unwrap();
+        let dex1_cell_data = base_tx.tx.raw().outputs_data().get(0).unwrap();
+        let freestanding_orders: Vec<dex1::Order> = orders
+            .iter()
+            .filter(|parsed_data| parsed_data.freestanding_cell)
+            .map(|parsed_data| parsed_data.order.clone())
+            .collect();
         let sighash_witness = {
             let orders = dex1::Orders::new_builder()
                 .extend(freestanding_orders)
@@ -960,10 +1559,10 @@ impl Assembler for Dex1 {
         let mut outputs_data = vec![];
         // Our assembler here will create tx of the following layouts:
         // * All the otx cells come at the very first
-        fulfilled_orders
+        orders
             .iter()
-            .filter(|(parsed_data, _)| !parsed_data.freestanding_cell)
-            .for_each(|(parsed_data, _)| {
+            .filter(|parsed_data| !parsed_data.freestanding_cell)
+            .for_each(|parsed_data| {
                 inputs.extend(parsed_data.tx.tx.raw().inputs());
                 outputs.extend(parsed_data.tx.tx.raw().outputs());
                 outputs_data.extend(parsed_data.tx.tx.raw().outputs_data());
@@ -974,18 +1573,14 @@ impl Assembler for Dex1 {
         outputs.push(dex1_cell_output);
         outputs_data.push(dex1_cell_data);
         // * Freestanding input cells are appended to inputs
-        fulfilled_orders
+        orders
             .iter()
-            .filter(|(parsed_data, _)| parsed_data.freestanding_cell)
-            .for_each(|(parsed_data, _)| {
+            .filter(|parsed_data| parsed_data.freestanding_cell)
+            .for_each(|parsed_data| {
                 inputs.extend(parsed_data.tx.tx.raw().inputs());
             });
         // * Finally, we have a series of payment output cells
-        for (output, data) in otx_payment_cells {
-            outputs.push(output);
-            outputs_data.push(data);
-        }
-        for (output, data) in freestanding_payment_cells {
+        for (output, data) in payment_cells {
             outputs.push(output);
             outputs_data.push(data);
         }
@@ -997,81 +1592,1214 @@ impl Assembler for Dex1 {
             w.extend(cobuild_witnesses);
             w
         };
-        // The outer processor infrastructure shall take care of locating cell deps,
-        // reducer here does nothing.
-        // We will fill in dex1 header dep at sealing time
-        let tx = TransactionView::new_advanced_builder()
+        TransactionView::new_advanced_builder()
+            .cell_deps(cell_deps)
             .inputs(inputs)
             .outputs(outputs)
             .outputs_data(outputs_data)
             .witnesses(witnesses)
-            .build();
-        emitter.emit_tx(tx.data(), post_value)
+            .build()
+            .data()
     }
 
-    fn postprocess<E>(&self, tx: Transaction, value: Self::PostValue, emitter: &mut E) -> Result<()>
-    where
-        E: MapEmitter<Self::Key, Self::Order, Self::Value>,
-    {
-        if let Some((key, mut parsed_data)) = value {
-            let tx = tx.into_view();
-            let freestanding_lock =
-                self.freestanding_lock(&parsed_data.recipient_script, &parsed_data.order);
-            let Some(output_index) = tx
-                .outputs()
-                .into_iter()
-                .position(|cell_output| cell_output.lock() == freestanding_lock)
-            else {
-                bail!(
-                    "Freestanding lock is missing in transaction {:x}!",
-                    tx.hash()
-                );
-            };
-            let output = tx.outputs().get(output_index).unwrap();
-            let data = tx.outputs_data().get(output_index).unwrap();
-            if parsed_data.minimal_ckbytes() > output.capacity().unpack() {
-                bail!(
-                    "Freestanding cell in {:x} does not have enough capacity for later orders!",
-                    tx.hash()
-                );
-            }
-            parsed_data.tx = RichOtx {
-                tx: TransactionView::new_advanced_builder()
-                    .input(
-                        CellInput::new_builder()
-                            .previous_output(OutPoint::new(tx.hash(), output_index as u32))
-                            .build(),
-                    )
-                    .build()
-                    .data(),
-                inputs: vec![(output, data.unpack())],
-            };
-            emitter.emit(key, parsed_data.price, parsed_data)
-        } else {
-            Ok(())
-        }
+    // Assigns the next arrival sequence, the current time in microseconds
+    // unless an order has already taken it.
+    fn next_sequence(&self) -> u64 {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_micros() as u64)
+            .unwrap_or(0);
+        let next = |last: u64| last.saturating_add(1).max(now);
+        let last = self
+            .last_sequence
+            .fetch_update(AtomicOrdering::SeqCst, AtomicOrdering::SeqCst, |last| {
+                Some(next(last))
+            })
+            .expect("sequence update");
+        next(last)
     }
-}
 
-fn validate_limit_order(order: &ParsedData, expired_block_number: u64) -> Option<dex1::LimitOrder> {
-    match order.order.to_enum() {
-        dex1::OrderUnion::LimitOrder(o) => Some(o),
-        dex1::OrderUnion::LimitOrderWithDeadline(o) => {
-            let deadline: u64 = o.deadline().unpack();
-            if deadline <= expired_block_number {
-                Some(o.order())
-            } else {
-                None
-            }
+    // Brokered orders pay fee_bps of ask tokens received to the broker, in a
+    // cell right after the payment cell, keeping broker_ckbytes of claimed
+    // ckbytes. Returns ask tokens left to the order, along with the broker
+    // cell. The fee must be exactly fee_bps of the total rounded down, dex1
+    // contract rejects both underpaid and overpaid brokers. No broker cell is
+    // created when the fee rounds down to 0.
+    fn broker_fee(
+        &self,
+        parsed_data: &ParsedData,
+        paid_amount: u128,
+    ) -> Result<(u128, Option<(CellOutput, packed::Bytes)>)> {
+        let dex1::OrderUnion::BrokeredLimitOrder(o) = parsed_data.order.to_enum() else {
+            return Ok((paid_amount, None));
+        };
+        let broker = self
+            .brokers
+            .get(&o.broker())
+            .ok_or_else(|| anyhow!("Broker is not supported!"))?;
+        let fee_bps: u32 = o.fee_bps().unpack();
+        let fee = (U256::from(paid_amount) * U256::from(fee_bps) / U256::from(10000u32)).as_u128();
+        if fee == 0 {
+            return Ok((paid_amount, None));
         }
-        _ => unreachable!(),
+        let broker_cell = self.tokens.cell(
+            broker.clone(),
+            &parsed_data.ask_token_script,
+            fee,
+            self.broker_ckbytes(parsed_data),
+        )?;
+        Ok((paid_amount - fee, Some(broker_cell)))
     }
-}
 
-fn locate_next_valid_order<I, E>(
-    iter: &mut I,
-    emitter: &mut E,
-    expired_block_number: u64,
+    // Ckbytes a brokered order must claim on top of its settlement cells, to
+    // fund the broker cell.
+    fn broker_ckbytes(&self, parsed_data: &ParsedData) -> u64 {
+        let dex1::OrderUnion::BrokeredLimitOrder(o) = parsed_data.order.to_enum() else {
+            return 0;
+        };
+        self.brokers
+            .get(&o.broker())
+            .map(|broker| {
+                self.tokens
+                    .occupied_capacity(broker.clone(), &parsed_data.ask_token_script)
+            })
+            .unwrap_or(0)
+    }
+
+    // Locates the freestanding cell created for the remaining order of a
+    // partial fill in +tx+, so the remaining order can be matched again.
+    fn freestanding_order(
+        &self,
+        tx: &TransactionView,
+        mut parsed_data: ParsedData,
+    ) -> Result<ParsedData> {
+        let freestanding_lock =
+            self.freestanding_lock(&parsed_data.recipient_script, &parsed_data.order);
+        let Some(output_index) = tx
+            .outputs()
+            .into_iter()
+            .position(|cell_output| cell_output.lock() == freestanding_lock)
+        else {
+            bail!(
+                "Freestanding lock is missing in transaction {:x}!",
+                tx.hash()
+            );
+        };
+        let output = tx.outputs().get(output_index).unwrap();
+        let data = tx.outputs_data().get(output_index).unwrap();
+        let mut ckbytes: u64 = output.capacity().unpack();
+        if is_ckb(&parsed_data.bid_token_script) {
+            let bid_amount: u128 = limit_order(&parsed_data.order)
+                .expect("limit order")
+                .bid_amount()
+                .unpack();
+            ckbytes = u64::try_from(bid_amount)
+                .ok()
+                .and_then(|amount| ckbytes.checked_sub(amount))
+                .ok_or_else(|| anyhow!("overflow!"))?;
+        }
+        if parsed_data.minimal_ckbytes(&self.tokens) + self.broker_ckbytes(&parsed_data) > ckbytes {
+            bail!(
+                "Freestanding cell in {:x} does not have enough capacity for later orders!",
+                tx.hash()
+            );
+        }
+        parsed_data.tx = RichOtx {
+            tx: TransactionView::new_advanced_builder()
+                .input(
+                    CellInput::new_builder()
+                        .previous_output(OutPoint::new(tx.hash(), output_index as u32))
+                        .build(),
+                )
+                .build()
+                .data(),
+            inputs: vec![(output, data.unpack())],
+        };
+        Ok(parsed_data)
+    }
+    // Checks the otx provides exactly +bid_amount+ of +token+, returning
+    // CKBytes left in the otx. Bid CKB is provided on top of the CKBytes.
+    fn provided_bid(&self, tx: &RichOtx, token: &Script, bid_amount: u128) -> Result<u64> {
+        let raw = tx.tx.raw();
+        let bid_ckb = is_ckb(token);
+        let adapter = self.tokens.get(token);
+        let mut tokens: u128 = 0;
+        let mut ckbytes: u64 = 0;
+        for (cell_output, cell_data) in &tx.inputs {
+            if !bid_ckb
+                && cell_output
+                    .type_()
+                    .to_opt()
+                    .map(|s| &s == token)
+                    .unwrap_or(false)
+            {
+                let current_tokens = adapter
+                    .amount(cell_data)
+                    .ok_or_else(|| anyhow!("Invalid udt data format!"))?;
+                tokens = tokens
+                    .checked_add(current_tokens)
+                    .ok_or_else(|| anyhow!("overflow!"))?;
+            }
+            ckbytes = ckbytes
+                .checked_add(cell_output.capacity().unpack())
+                .ok_or_else(|| anyhow!("overflow!"))?;
+        }
+        for (i, cell_output) in raw.outputs().into_iter().enumerate() {
+            if !bid_ckb
+                && cell_output
+                    .type_()
+                    .to_opt()
+                    .map(|s| &s == token)
+                    .unwrap_or(false)
+            {
+                let cell_data = raw.outputs_data().get(i).unwrap();
+                let current_tokens = adapter
+                    .amount(&cell_data.raw_data())
+                    .ok_or_else(|| anyhow!("Invalid udt data format!"))?;
+                tokens = tokens
+                    .checked_sub(current_tokens)
+                    .ok_or_else(|| anyhow!("overflow!"))?;
+            }
+            ckbytes = ckbytes
+                .checked_sub(cell_output.capacity().unpack())
+                .ok_or_else(|| anyhow!("overflow!"))?;
+        }
+        if bid_ckb {
+            let bid_ckbytes =
+                u64::try_from(bid_amount).map_err(|_| anyhow!("Invalid bid amount!"))?;
+            ckbytes = ckbytes
+                .checked_sub(bid_ckbytes)
+                .ok_or_else(|| anyhow!("Invalid bid amount!"))?;
+            tokens = bid_amount;
+        }
+        if bid_amount == 0 || bid_amount != tokens {
+            bail!("Invalid bid amount!");
+        }
+        Ok(ckbytes)
+    }
+
+    // NFTs can be traded for any token of configured pairs
+    fn pair_token(&self, token: &Byte32) -> Result<Script> {
+        self.pairs
+            .values()
+            .flat_map(|pair| [&pair.first.script, &pair.second.script])
+            .find(|script| token_script_hash(script) == *token)
+            .cloned()
+            .ok_or_else(|| anyhow!("Token is not supported!"))
+    }
+
+    fn map_nft_sell_order<E>(
+        &self,
+        tx: RichOtx,
+        nft_order: dex1::NftSellOrder,
+        emitter: &mut E,
+    ) -> Result<()>
+    where
+        E: MapEmitter<
+            <Dex1 as Assembler>::Key,
+            <Dex1 as Assembler>::Order,
+            <Dex1 as Assembler>::Value,
+        >,
+    {
+        let ask_token_script = self.pair_token(&nft_order.ask_token())?;
+        let ask_amount: u128 = nft_order.ask_amount().unpack();
+        let claimed_ckbytes: u64 = nft_order.claimed_ckbytes().unpack();
+        if ask_amount == 0 {
+            bail!("Invalid ask amount!");
+        }
+        // The NFT cell must be given up by the otx
+        let is_nft = |cell_output: &CellOutput| {
+            cell_output
+                .type_()
+                .to_opt()
+                .map(|s| s.calc_script_hash() == nft_order.nft())
+                .unwrap_or(false)
+        };
+        let Some(nft_script) = tx
+            .inputs
+            .iter()
+            .find(|(cell_output, _)| is_nft(cell_output))
+            .and_then(|(cell_output, _)| cell_output.type_().to_opt())
+        else {
+            bail!("NFT cell is missing!");
+        };
+        if tx.tx.raw().outputs().into_iter().any(|o| is_nft(&o)) {
+            bail!("NFT cell is kept by the otx!");
+        }
+        let mut ckbytes: u64 = 0;
+        for (cell_output, _) in &tx.inputs {
+            ckbytes = ckbytes
+                .checked_add(cell_output.capacity().unpack())
+                .ok_or_else(|| anyhow!("overflow!"))?;
+        }
+        for cell_output in tx.tx.raw().outputs() {
+            ckbytes = ckbytes
+                .checked_sub(cell_output.capacity().unpack())
+                .ok_or_else(|| anyhow!("overflow!"))?;
+        }
+        if claimed_ckbytes > ckbytes {
+            bail!("Not enough ckbytes to claim!");
+        }
+        let recipient_script = otx_recipient_script(&tx, &nft_order.recipient())?;
+        if self
+            .tokens
+            .occupied_capacity(recipient_script.clone(), &ask_token_script)
+            > claimed_ckbytes
+        {
+            bail!("Claimed ckbytes are not enough for the payment cell!");
+        }
+
+        let mut pair = [0u8; 64];
+        pair[0..32].copy_from_slice(&nft_order.nft().raw_data());
+        pair[32..64].copy_from_slice(&nft_order.ask_token().raw_data());
+        self.nft_pairs.lock().expect("lock").insert(pair);
+        let mut key = [NFT_SELL; 65];
+        key[0..64].copy_from_slice(&pair);
+        // Lower asks come first
+        let price = Ratio::new_raw(ask_amount, 1);
+        let parsed_data = ParsedData {
+            tx,
+            recipient_script,
+            ask_token_script,
+            bid_token_script: nft_script,
+            price,
+            order: dex1::Order::new_builder().set(nft_order).build(),
+            freestanding_cell: false,
+            sequence: self.next_sequence(),
+            owner_script: None,
+        };
+        log::debug!(
+            "Emitting NFT sell order, ask amount: {}, sequence: {}",
+            ask_amount,
+            parsed_data.sequence
+        );
+        emitter.emit(key, parsed_data.priority(), parsed_data)
+    }
+
+    fn map_nft_buy_order<E>(
+        &self,
+        tx: RichOtx,
+        nft_order: dex1::NftBuyOrder,
+        emitter: &mut E,
+    ) -> Result<()>
+    where
+        E: MapEmitter<
+            <Dex1 as Assembler>::Key,
+            <Dex1 as Assembler>::Order,
+            <Dex1 as Assembler>::Value,
+        >,
+    {
+        let bid_token_script = self.pair_token(&nft_order.bid_token())?;
+        let bid_amount: u128 = nft_order.bid_amount().unpack();
+        let claimed_ckbytes: u64 = nft_order.claimed_ckbytes().unpack();
+        let ckbytes = self.provided_bid(&tx, &bid_token_script, bid_amount)?;
+        if claimed_ckbytes > ckbytes {
+            bail!("Not enough ckbytes to claim!");
+        }
+        let recipient_script = otx_recipient_script(&tx, &nft_order.recipient())?;
+        // The NFT cell is only known once matched, the refund cell must fit
+        // in claimed ckbytes either way.
+        if self
+            .tokens
+            .occupied_capacity(recipient_script.clone(), &bid_token_script)
+            > claimed_ckbytes
+        {
+            bail!("Claimed ckbytes are not enough for the refund cell!");
+        }
+
+        let mut pair = [0u8; 64];
+        pair[0..32].copy_from_slice(&nft_order.nft().raw_data());
+        pair[32..64].copy_from_slice(&nft_order.bid_token().raw_data());
+        self.nft_pairs.lock().expect("lock").insert(pair);
+        let mut key = [NFT_BUY; 65];
+        key[0..64].copy_from_slice(&pair);
+        // Higher bids come first
+        let price = Ratio::new_raw(1, bid_amount);
+        let parsed_data = ParsedData {
+            tx,
+            recipient_script,
+            // The NFT type script is only known once matched to a sell order
+            ask_token_script: Script::default(),
+            bid_token_script,
+            price,
+            order: dex1::Order::new_builder().set(nft_order).build(),
+            freestanding_cell: false,
+            sequence: self.next_sequence(),
+            owner_script: None,
+        };
+        log::debug!(
+            "Emitting NFT buy order, bid amount: {}, sequence: {}",
+            bid_amount,
+            parsed_data.sequence
+        );
+        emitter.emit(key, parsed_data.priority(), parsed_data)
+    }
+
+    // An NFT is sold to the highest bid no lower than the lowest ask, at the
+    // ask price. The rest of the bid is refunded to the buyer.
+    fn reduce_nft<E, S>(
+        &self,
+        base_tx: BaseTx,
+        key: <Dex1 as Assembler>::Key,
+        emitter: &mut E,
+        source: &S,
+    ) -> Result<()>
+    where
+        E: ReduceEmitter<
+            <Dex1 as Assembler>::Key,
+            <Dex1 as Assembler>::Order,
+            <Dex1 as Assembler>::Value,
+            <Dex1 as Assembler>::PostValue,
+        >,
+        S: ReduceSource<<Dex1 as Assembler>::Key, <Dex1 as Assembler>::Value>,
+    {
+        let base_tx = base_tx.tx;
+        let mut sell_key = key;
+        sell_key[64] = NFT_SELL;
+        let mut buy_key = key;
+        buy_key[64] = NFT_BUY;
+
+        // Only one sell order can be settled, since the NFT cell is spent.
+        let Some((sell, sell_order, (nft_cell, nft_data))) =
+            locate_next_valid_nft_sell(source.otxs(sell_key), emitter)?
+        else {
+            return Ok(());
+        };
+        let ask_amount: u128 = sell_order.ask_amount().unpack();
+        let sell_owners = order_owners(&sell);
+        for buy in source.otxs(buy_key) {
+            let dex1::OrderUnion::NftBuyOrder(buy_order) = buy.order.to_enum() else {
+                if let Err(e) = emitter.reject_otx(buy.tx.tx) {
+                    bail!("Reject otx encounters error: {:?}", e);
+                }
+                continue;
+            };
+            let bid_amount: u128 = buy_order.bid_amount().unpack();
+            if bid_amount < ask_amount {
+                break;
+            }
+            if !order_owners(&buy).is_disjoint(&sell_owners) {
+                continue;
+            }
+            // Claimed ckbytes of the buyer fund the refund cell first, the
+            // NFT cell takes the rest. Nothing is refunded when the bid
+            // matches the ask, the refund cell is left out then.
+            let claimed_ckbytes: u64 = buy_order.claimed_ckbytes().unpack();
+            let refund = bid_amount - ask_amount;
+            let refund_ckbytes = if refund > 0 {
+                self.tokens
+                    .occupied_capacity(buy.recipient_script.clone(), &buy.bid_token_script)
+            } else {
+                0
+            };
+            let nft_ckbytes = claimed_ckbytes.saturating_sub(refund_ckbytes);
+            let bought_cell = nft_cell
+                .clone()
+                .as_builder()
+                .lock(buy.recipient_script.clone())
+                .capacity(nft_ckbytes.pack())
+                .build();
+            // The buyer cannot hold the NFT cell with claimed ckbytes
+            let occupied_capacity = bought_cell
+                .occupied_capacity(Capacity::bytes(nft_data.len()).expect("overflow"))
+                .expect("overflow")
+                .as_u64();
+            if occupied_capacity > nft_ckbytes {
+                if let Err(e) = emitter.reject_otx(buy.tx.tx) {
+                    bail!("Reject otx encounters error: {:?}", e);
+                }
+                continue;
+            }
+            let mut budget = self.packing_budget(&base_tx);
+            if !budget.reserve(&sell) || !budget.reserve(&buy) {
+                return Ok(());
+            }
+            // The refund cell comes right after the NFT cell
+            let mut payment_cells = vec![
+                self.tokens.cell(
+                    sell.recipient_script.clone(),
+                    &sell.ask_token_script,
+                    ask_amount,
+                    sell_order.claimed_ckbytes().unpack(),
+                )?,
+                (bought_cell, nft_data.pack()),
+            ];
+            if refund > 0 {
+                payment_cells.push(self.tokens.cell(
+                    buy.recipient_script.clone(),
+                    &buy.bid_token_script,
+                    refund,
+                    refund_ckbytes,
+                )?);
+            }
+            log::debug!(
+                "Settling NFT sell order of ask amount {} with bid amount {}",
+                ask_amount,
+                bid_amount
+            );
+            let tx = self.assemble_tx(&base_tx, &[&sell, &buy], payment_cells, HashSet::new());
+            return emitter.emit_tx(tx, Vec::new());
+        }
+        Ok(())
+    }
+    fn map_route_order<E>(
+        &self,
+        tx: RichOtx,
+        route_order: dex1::RouteOrder,
+        emitter: &mut E,
+    ) -> Result<()>
+    where
+        E: MapEmitter<
+            <Dex1 as Assembler>::Key,
+            <Dex1 as Assembler>::Order,
+            <Dex1 as Assembler>::Value,
+        >,
+    {
+        let path: Vec<Byte32> = route_order.path().into_iter().collect();
+        if path.len() < 2 || path.len() > MAX_ROUTE_LENGTH {
+            bail!("Invalid route length!");
+        }
+        if path.iter().collect::<HashSet<_>>().len() != path.len() {
+            bail!("Route visits a token more than once!");
+        }
+        for hop in path.windows(2) {
+            let counterpart = dex1::LimitOrder::new_builder()
+                .bid_token(hop[1].clone())
+                .ask_token(hop[0].clone())
+                .build();
+            if self.order_key(&counterpart).is_none() {
+                bail!("Trading pair is not supported!");
+            }
+        }
+        let bid_token_script = self.pair_token(&path[0])?;
+        let ask_token_script = self.pair_token(&path[path.len() - 1])?;
+        let bid_amount: u128 = route_order.bid_amount().unpack();
+        let claimed_ckbytes: u64 = route_order.claimed_ckbytes().unpack();
+        let ckbytes = self.provided_bid(&tx, &bid_token_script, bid_amount)?;
+        if claimed_ckbytes > ckbytes {
+            bail!("Not enough ckbytes to claim!");
+        }
+        let recipient_script = otx_recipient_script(&tx, &route_order.recipient())?;
+        if self
+            .tokens
+            .occupied_capacity(recipient_script.clone(), &ask_token_script)
+            > claimed_ckbytes
+        {
+            bail!("Claimed ckbytes are not enough for the payment cell!");
+        }
+
+        let parsed_data = ParsedData {
+            tx,
+            recipient_script,
+            ask_token_script,
+            bid_token_script,
+            // Route orders are settled in arrival order
+            price: Ratio::new_raw(0, 1),
+            order: dex1::Order::new_builder().set(route_order).build(),
+            freestanding_cell: false,
+            sequence: self.next_sequence(),
+            owner_script: None,
+        };
+        log::debug!(
+            "Emitting route order of {} hops, bid amount: {}, sequence: {}",
+            path.len() - 1,
+            bid_amount,
+            parsed_data.sequence
+        );
+        self.routes.store(true, AtomicOrdering::SeqCst);
+        emitter.emit([ROUTE; 65], parsed_data.priority(), parsed_data)
+    }
+
+    // Route orders take liquidity left in pairs, the first route that can be
+    // filled along its whole path is settled.
+    fn reduce_routes<E, S>(&self, base_tx: BaseTx, emitter: &mut E, source: &S) -> Result<()>
+    where
+        E: ReduceEmitter<
+            <Dex1 as Assembler>::Key,
+            <Dex1 as Assembler>::Order,
+            <Dex1 as Assembler>::Value,
+            <Dex1 as Assembler>::PostValue,
+        >,
+        S: ReduceSource<<Dex1 as Assembler>::Key, <Dex1 as Assembler>::Value>,
+    {
+        let conditions = self.match_conditions(&base_tx);
+        let base_tx = base_tx.tx;
+
+        for route in source.otxs([ROUTE; 65]) {
+            let Some(fills) = self.route_fills(&route, emitter, source, &conditions)? else {
+                continue;
+            };
+            let mut budget = self.packing_budget(&base_tx);
+            if !fills.iter().all(|fill| budget.reserve(&fill.order.0)) {
+                continue;
+            }
+            log::debug!("Settling a route order with {} fills", fills.len() - 1);
+            let (tx, post_value) = self.settle(&base_tx, fills, &conditions)?;
+            return emitter.emit_tx(tx, post_value);
+        }
+        Ok(())
+    }
+
+    // Each hop of a route takes all tokens received from the previous hop,
+    // filling orders of the hop pair by priority. Only the last order of a
+    // hop might be partially filled, it is paid all remaining tokens, which is
+    // no worse than its limit price.
+    fn route_fills<E, S>(
+        &self,
+        route: &ParsedData,
+        emitter: &mut E,
+        source: &S,
+        conditions: &MatchConditions,
+    ) -> Result<Option<Vec<Fill>>>
+    where
+        E: ReduceEmitter<
+            <Dex1 as Assembler>::Key,
+            <Dex1 as Assembler>::Order,
+            <Dex1 as Assembler>::Value,
+            <Dex1 as Assembler>::PostValue,
+        >,
+        S: ReduceSource<<Dex1 as Assembler>::Key, <Dex1 as Assembler>::Value>,
+    {
+        let dex1::OrderUnion::RouteOrder(route_order) = route.order.to_enum() else {
+            bail!("Invalid route order!");
+        };
+        let path: Vec<Byte32> = route_order.path().into_iter().collect();
+        let owners = order_owners(route);
+        let mut fills = Vec::new();
+        let mut amount: u128 = route_order.bid_amount().unpack();
+        for hop in path.windows(2) {
+            // Orders of the hop bid the next token, asking for current one
+            let counterpart = dex1::LimitOrder::new_builder()
+                .bid_token(hop[1].clone())
+                .ask_token(hop[0].clone())
+                .build();
+            let key = self
+                .order_key(&counterpart)
+                .ok_or_else(|| anyhow!("Trading pair is not supported!"))?;
+            let mut otxs = source
+                .otxs(key)
+                .filter(|parsed_data| order_owners(parsed_data).is_disjoint(&owners));
+            let mut received: u128 = 0;
+            while amount > 0 {
+                let Some(order) = locate_next_valid_order(&mut otxs, emitter, conditions)? else {
+                    return Ok(None);
+                };
+                let ask_amount: u128 = order.1.ask_amount().unpack();
+                let bid_amount: u128 = order.1.bid_amount().unpack();
+                let (paid_amount, provided_amount, partial) = if ask_amount <= amount {
+                    (ask_amount, bid_amount, None)
+                } else {
+                    let provided_amount = (U256::from(amount) * U256::from(bid_amount)
+                        / U256::from(ask_amount))
+                    .as_u128();
+                    if provided_amount == 0
+                        || !partial_fill_allowed(&order.0.order, provided_amount)
+                    {
+                        return Ok(None);
+                    }
+                    (amount, provided_amount, Some((provided_amount, key)))
+                };
+                amount -= paid_amount;
+                received = received
+                    .checked_add(provided_amount)
+                    .ok_or_else(|| anyhow!("overflow!"))?;
+                fills.push(Fill {
+                    order,
+                    paid_amount,
+                    partial,
+                });
+            }
+            if received == 0 {
+                return Ok(None);
+            }
+            amount = received;
+        }
+        let minimum_ask: u128 = route_order.minimum_ask().unpack();
+        if amount < minimum_ask {
+            return Ok(None);
+        }
+        // The route order is settled like a fully filled limit order
+        let limit_order = dex1::LimitOrder::new_builder()
+            .bid_token(path[0].clone())
+            .bid_amount(route_order.bid_amount())
+            .ask_token(path[path.len() - 1].clone())
+            .ask_amount(route_order.minimum_ask())
+            .recipient(route_order.recipient())
+            .claimed_ckbytes(route_order.claimed_ckbytes())
+            .build();
+        fills.insert(
+            0,
+            Fill {
+                order: (route.clone(), limit_order),
+                paid_amount: amount,
+                partial: None,
+            },
+        );
+        Ok(Some(fills))
+    }
+}
+
+impl Assembler for Dex1 {
+    type Otx = RichOtx;
+    type BaseTx = BaseTx;
+    type Key = [u8; 65];
+    type Order = OrderPriority;
+    type Value = ParsedData;
+    // Remaining orders kept in freestanding cells
+    type PostValue = Vec<([u8; 65], ParsedData)>;
+
+    fn map<E>(&self, tx: RichOtx, emitter: &mut E) -> Result<()>
+    where
+        E: MapEmitter<Self::Key, Self::Order, Self::Value>,
+    {
+        let raw = tx.tx.raw();
+        if raw.cell_deps().len() > 0 {
+            bail!("Invalid number of cell deps!");
+        }
+        if raw.header_deps().len() > 0 {
+            bail!("Invalid number of header deps!");
+        }
+        if tx.tx.witnesses().len() != 1 {
+            bail!("Invalid number of witnesses!");
+        }
+        let witness = tx.tx.witnesses().get(0).unwrap();
+        let witness_layout = WitnessLayout::from_slice(&witness.raw_data())
+            .map_err(|e| anyhow!("Error parsing witness: {:?}", e))?;
+        let WitnessLayoutUnion::Otx(otx) = witness_layout.to_enum() else {
+            bail!("Invalid witness layout type!");
+        };
+        {
+            let input_cells: usize = otx.input_cells().unpack();
+            let output_cells: usize = otx.output_cells().unpack();
+            let cell_deps: usize = otx.cell_deps().unpack();
+            let header_deps: usize = otx.header_deps().unpack();
+            if raw.inputs().len() != input_cells
+                || raw.outputs().len() != output_cells
+                || raw.cell_deps().len() != cell_deps
+                || raw.header_deps().len() != header_deps
+            {
+                bail!("Invalid otx format!");
+            }
+        }
+        // A single badly-signed otx would fail the whole assembled tx
+        self.verify_seals(&otx, &tx)?;
+        let dex1_script_hash = self.dex1_script.script.calc_script_hash();
+        let action = otx
+            .message()
+            .actions()
+            .into_iter()
+            .find(|action| action.script_hash() == dex1_script_hash)
+            .ok_or_else(|| anyhow!("Missing cobuild action for dex1!"))?;
+        let dex1_action = dex1::Dex1Action::from_slice(&action.data().raw_data())
+            .map_err(|e| anyhow!("Error parsing dex1 action data: {:?}", e))?;
+        if dex1_action.orders().len() != 1 {
+            bail!("For now, we only process otx with exact one order");
+        }
+        let order = dex1_action.orders().get(0).unwrap();
+        match order.to_enum() {
+            dex1::OrderUnion::NftSellOrder(o) => return self.map_nft_sell_order(tx, o, emitter),
+            dex1::OrderUnion::NftBuyOrder(o) => return self.map_nft_buy_order(tx, o, emitter),
+            dex1::OrderUnion::RouteOrder(o) => return self.map_route_order(tx, o, emitter),
+            _ => (),
+        }
+        // Deadline, fill limits and stop triggers will be processed at reduce time
+        let Some(limit_order) = limit_order(&order) else {
+            bail!("For now, we only support limit orders, later we shall add support for market orders");
+        };
+        match order.to_enum() {
+            dex1::OrderUnion::StopLimitOrder(o) if !self.oracle_hashes.contains(&o.oracle()) => {
+                bail!("Oracle is not supported!");
+            }
+            dex1::OrderUnion::IcebergOrder(o) => {
+                let display_amount: u128 = o.display_amount().unpack();
+                if display_amount == 0 {
+                    bail!("Invalid display amount!");
+                }
+            }
+            dex1::OrderUnion::SlicedOrder(o) => {
+                let slice_amount: u128 = o.slice_amount().unpack();
+                if slice_amount == 0 {
+                    bail!("Invalid slice amount!");
+                }
+            }
+            dex1::OrderUnion::BrokeredLimitOrder(o) => {
+                let fee_bps: u32 = o.fee_bps().unpack();
+                if fee_bps > 10000 {
+                    bail!("Invalid broker fee!");
+                }
+                if !self.brokers.contains_key(&o.broker()) {
+                    bail!("Broker is not supported!");
+                }
+            }
+            _ => (),
+        }
+        // Check if current trading pair is supported
+        let (key, bid_token_script, ask_token_script, rules) = {
+            let buy_pair = {
+                let mut pair = [0u8; 64];
+                pair[0..32].copy_from_slice(&limit_order.ask_token().raw_data());
+                pair[32..64].copy_from_slice(&limit_order.bid_token().raw_data());
+                pair
+            };
+            let sell_pair = {
+                let mut pair = [0u8; 64];
+                pair[0..32].copy_from_slice(&limit_order.bid_token().raw_data());
+                pair[32..64].copy_from_slice(&limit_order.ask_token().raw_data());
+                pair
+            };
+            if let Some(pair_data) = self.pairs.get(&buy_pair) {
+                let mut key = [LIMIT_BUY; 65];
+                key[0..64].copy_from_slice(&buy_pair);
+                (key, &pair_data.second, &pair_data.first, &pair_data.rules)
+            } else if let Some(pair_data) = self.pairs.get(&sell_pair) {
+                let mut key = [LIMIT_SELL; 65];
+                key[0..64].copy_from_slice(&sell_pair);
+                (key, &pair_data.first, &pair_data.second, &pair_data.rules)
+            } else {
+                bail!("Trading pair is not supported!");
+            }
+        };
+        // Check if enough tokens are provided by the otx as claimed in bid_amount
+        // Check if claimed_ckbytes can be claimed
+        let bid_amount: u128 = limit_order.bid_amount().unpack();
+        let ask_amount: u128 = limit_order.ask_amount().unpack();
+        let claimed_ckbytes: u64 = limit_order.claimed_ckbytes().unpack();
+        // Bid CKB is provided on top of claimed ckbytes
+        {
+            let ckbytes = self.provided_bid(&tx, &bid_token_script.script, bid_amount)?;
+            if claimed_ckbytes > ckbytes {
+                bail!("Not enough ckbytes to claim!");
+            }
+        }
+        if key[64] == LIMIT_SELL {
+            check_trading_rules(rules, bid_amount, ask_amount)?;
+        } else {
+            check_trading_rules(rules, ask_amount, bid_amount)?;
+        }
+        let recipient_script = otx_recipient_script(&tx, &limit_order.recipient())?;
+        let owner_script =
+            order_owner(&order).and_then(|owner| otx_recipient_script(&tx, &owner).ok());
+        // Calculate the price, now we can build parsed data
+        let price = Ratio::new_raw(ask_amount, bid_amount);
+        let parsed_data = ParsedData {
+            tx,
+            recipient_script,
+            ask_token_script: ask_token_script.script.clone(),
+            bid_token_script: bid_token_script.script.clone(),
+            price,
+            order,
+            freestanding_cell: false,
+            sequence: self.next_sequence(),
+            owner_script,
+        };
+        // Claimed ckbytes must be enough for partial fills
+        if parsed_data.minimal_ckbytes(&self.tokens) > claimed_ckbytes {
+            bail!("Claimed ckbytes are not enough in the partial fill worse case!");
+        }
+        if parsed_data.minimal_ckbytes(&self.tokens) + self.broker_ckbytes(&parsed_data)
+            > claimed_ckbytes
+        {
+            bail!("Claimed ckbytes are not enough for the broker cell!");
+        }
+
+        let priority = OrderPriority {
+            price,
+            sequence: parsed_data.sequence,
+        };
+        log::debug!(
+            "Emitting {} order of price {}, bid amount: {}, ask amount: {}, sequence: {}",
+            if key[64] == LIMIT_SELL {
+                "limit sell"
+            } else {
+                "limit buy"
+            },
+            price,
+            bid_amount,
+            ask_amount,
+            priority.sequence
+        );
+        emitter.emit(key, priority, parsed_data)
+    }
+
+    fn reduce<E, S>(
+        &self,
+        base_tx: BaseTx,
+        key: Self::Key,
+        emitter: &mut E,
+        source: &S,
+    ) -> Result<()>
+    where
+        E: ReduceEmitter<Self::Key, Self::Order, Self::Value, Self::PostValue>,
+        S: ReduceSource<Self::Key, Self::Value>,
+    {
+        if key[64] == RING {
+            return self.reduce_rings(base_tx, emitter, source);
+        }
+        if key[64] == NFT {
+            return self.reduce_nft(base_tx, key, emitter, source);
+        }
+        if key[64] == ROUTE {
+            return self.reduce_routes(base_tx, emitter, source);
+        }
+        let conditions = self.match_conditions(&base_tx);
+        let base_tx = base_tx.tx;
+
+        let limit_buy_key = {
+            let mut key = key.clone();
+            key[64] = LIMIT_BUY;
+            key
+        };
+        let limit_sell_key = {
+            let mut key = key.clone();
+            key[64] = LIMIT_SELL;
+            key
+        };
+        // Matching goes on until the book no longer crosses, each tx takes as
+        // many orders as the packing budget allows. Later txs skip otxs
+        // settled by earlier ones, as well as orders left out by self-trade
+        // prevention. Remaining orders of partial fills are merged back into
+        // their sides by priority, so the next tx spends freestanding cells
+        // created by the previous tx.
+        let mut excluded_out_points = HashSet::new();
+        let mut settled_out_points = HashSet::new();
+        let mut remaining_orders: Vec<([u8; 65], ParsedData)> = Vec::new();
+        let mut locator = OrderLocator::new(&self.self_trade, conditions);
+        loop {
+            excluded_out_points.extend(locator.excluded_out_points.drain());
+            locator.reset();
+            let (remaining_buys, remaining_sells): (Vec<_>, Vec<_>) = remaining_orders
+                .iter()
+                .filter(|(_, parsed_data)| !parsed_data.spent(&excluded_out_points))
+                .cloned()
+                .partition(|(key, _)| *key == limit_buy_key);
+            let fills = {
+                // Create iterators of buy orders & sell orders
+                let mut limit_buys = merge_by_priority(
+                    remaining_buys
+                        .into_iter()
+                        .map(|(_, parsed_data)| parsed_data),
+                    source
+                        .otxs(limit_buy_key)
+                        .filter(|value| !value.spent(&excluded_out_points)),
+                );
+                let mut limit_sells = merge_by_priority(
+                    remaining_sells
+                        .into_iter()
+                        .map(|(_, parsed_data)| parsed_data),
+                    source
+                        .otxs(limit_sell_key)
+                        .filter(|value| !value.spent(&excluded_out_points)),
+                );
+                let mut budget = self.packing_budget(&base_tx);
+                match &self.matching {
+                    MatchingConfig::Continuous => match_continuous(
+                        &mut limit_buys,
+                        &mut limit_sells,
+                        limit_buy_key,
+                        limit_sell_key,
+                        emitter,
+                        &mut budget,
+                        &mut locator,
+                    )?,
+                    MatchingConfig::BatchAuction { .. } => match_batch_auction(
+                        &mut limit_buys,
+                        &mut limit_sells,
+                        limit_buy_key,
+                        limit_sell_key,
+                        emitter,
+                        &mut budget,
+                        &mut locator,
+                    )?,
+                }
+            };
+            // An order already matched is left out, the tx is matched again
+            // without it.
+            if locator.restart {
+                continue;
+            }
+            // So is an order that does not allow the partial fill, it is left
+            // out for the rest of the round.
+            if let Some(fill) = fills.iter().find(|fill| match fill.partial {
+                Some((amount, _)) => !partial_fill_allowed(&fill.order.0.order, amount),
+                None => false,
+            }) {
+                excluded_out_points.extend(
+                    fill.order
+                        .0
+                        .tx
+                        .tx
+                        .raw()
+                        .inputs()
+                        .into_iter()
+                        .map(|cell_input| cell_input.previous_output()),
+                );
+                continue;
+            }
+            // Each tx must fully fill at least one order to make progress
+            if fills.iter().all(|fill| fill.partial.is_some()) {
+                break;
+            }
+            let (tx, post_value) = self.settle(&base_tx, fills, &locator.conditions)?;
+            remaining_orders.clear();
+            settled_out_points.extend(
+                tx.raw()
+                    .inputs()
+                    .into_iter()
+                    .map(|cell_input| cell_input.previous_output()),
+            );
+            excluded_out_points.extend(settled_out_points.iter().cloned());
+            let tx_view = tx.clone().into_view();
+            let mut matchable = true;
+            for (key, parsed_data) in &post_value {
+                match self.freestanding_order(&tx_view, parsed_data.clone()) {
+                    Ok(parsed_data) => remaining_orders.push((*key, parsed_data)),
+                    Err(e) => {
+                        log::warn!("Remaining order cannot be matched in this round: {:?}", e);
+                        matchable = false;
+                    }
+                }
+            }
+            emitter.emit_tx(tx, post_value)?;
+            if !matchable {
+                break;
+            }
+        }
+        // Immediate orders not settled in this round are cancelled
+        for key in [limit_buy_key, limit_sell_key] {
+            for parsed_data in source
+                .otxs(key)
+                .filter(|value| immediate_order(&value.order) && !value.spent(&settled_out_points))
+            {
+                if let Err(e) = emitter.reject_otx(parsed_data.tx.tx) {
+                    bail!("Reject otx encounters error: {:?}", e);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn postprocess<E>(&self, tx: Transaction, value: Self::PostValue, emitter: &mut E) -> Result<()>
+    where
+        E: MapEmitter<Self::Key, Self::Order, Self::Value>,
+    {
+        let tx = tx.into_view();
+        for (key, parsed_data) in value {
+            let parsed_data = self.freestanding_order(&tx, parsed_data)?;
+            emitter.emit(key, parsed_data.priority(), parsed_data)?;
+        }
+        Ok(())
+    }
+}
+
+// Limit order wrapped in any of the limit order variants
+fn limit_order(order: &dex1::Order) -> Option<dex1::LimitOrder> {
+    match order.to_enum() {
+        dex1::OrderUnion::LimitOrder(o) => Some(o),
+        dex1::OrderUnion::LimitOrderWithDeadline(o) => Some(o.order()),
+        dex1::OrderUnion::FillOrKillOrder(o) => Some(o.order()),
+        dex1::OrderUnion::ImmediateOrCancelOrder(o) => Some(o.order()),
+        dex1::OrderUnion::MinFillLimitOrder(o) => Some(o.order()),
+        dex1::OrderUnion::StopLimitOrder(o) => Some(o.order()),
+        dex1::OrderUnion::IcebergOrder(o) => Some(o.order()),
+        dex1::OrderUnion::SlicedOrder(o) => Some(o.order()),
+        dex1::OrderUnion::BrokeredLimitOrder(o) => Some(o.order()),
+        dex1::OrderUnion::OwnedLimitOrder(o) => Some(o.order()),
+        _ => None,
+    }
+}
+
+// Separate owner of an order, who can cancel it instead of the recipient
+fn order_owner(order: &dex1::Order) -> Option<Byte32> {
+    match order.to_enum() {
+        dex1::OrderUnion::OwnedLimitOrder(o) => Some(o.owner()),
+        _ => None,
+    }
+}
+
+// Builds the remaining order of a partial fill, keeping the variant of
+// +order+ so the contract derives the same freestanding lock. The next slice
+// of a sliced order is scheduled interval blocks after the fill, which
+// happens at +block_number+ of the dex1 entity cell.
+fn carve_limit_order(
+    order: &dex1::Order,
+    limit_order: dex1::LimitOrder,
+    block_number: u64,
+) -> dex1::Order {
+    match order.to_enum() {
+        dex1::OrderUnion::LimitOrderWithDeadline(o) => dex1::Order::new_builder()
+            .set(o.as_builder().order(limit_order).build())
+            .build(),
+        dex1::OrderUnion::MinFillLimitOrder(o) => dex1::Order::new_builder()
+            .set(o.as_builder().order(limit_order).build())
+            .build(),
+        dex1::OrderUnion::StopLimitOrder(o) => dex1::Order::new_builder()
+            .set(o.as_builder().order(limit_order).build())
+            .build(),
+        dex1::OrderUnion::IcebergOrder(o) => dex1::Order::new_builder()
+            .set(o.as_builder().order(limit_order).build())
+            .build(),
+        dex1::OrderUnion::BrokeredLimitOrder(o) => dex1::Order::new_builder()
+            .set(o.as_builder().order(limit_order).build())
+            .build(),
+        dex1::OrderUnion::OwnedLimitOrder(o) => dex1::Order::new_builder()
+            .set(o.as_builder().order(limit_order).build())
+            .build(),
+        dex1::OrderUnion::SlicedOrder(o) => {
+            let not_before: u64 = o.not_before().unpack();
+            let interval: u64 = o.interval().unpack();
+            let not_before = if interval > 0 {
+                std::cmp::max(not_before, block_number).saturating_add(interval)
+            } else {
+                not_before
+            };
+            dex1::Order::new_builder()
+                .set(
+                    o.as_builder()
+                        .order(limit_order)
+                        .not_before(not_before.pack())
+                        .build(),
+                )
+                .build()
+        }
+        _ => dex1::Order::new_builder().set(limit_order).build(),
+    }
+}
+
+// Whether +order+ can be partially filled, providing +amount+ bid token.
+fn partial_fill_allowed(order: &dex1::Order, amount: u128) -> bool {
+    match order.to_enum() {
+        dex1::OrderUnion::FillOrKillOrder(_) => false,
+        dex1::OrderUnion::MinFillLimitOrder(o) => {
+            let min_fill: u128 = o.min_fill().unpack();
+            amount >= min_fill
+        }
+        _ => true,
+    }
+}
+
+// Whether the oracle values the bid token of +order+ at or below the trigger
+// price in the ask token, this mirrors the check done by dex1 contract.
+fn stop_triggered(order: &dex1::StopLimitOrder, price: &dex1::OraclePrice) -> bool {
+    let limit_order = order.order();
+    let base_amount: u128 = price.base_amount().unpack();
+    let quote_amount: u128 = price.quote_amount().unpack();
+    let (bid_value, ask_value) = if price.base_token() == limit_order.bid_token()
+        && price.quote_token() == limit_order.ask_token()
+    {
+        (base_amount, quote_amount)
+    } else if price.base_token() == limit_order.ask_token()
+        && price.quote_token() == limit_order.bid_token()
+    {
+        (quote_amount, base_amount)
+    } else {
+        return false;
+    };
+    if bid_value == 0 {
+        return false;
+    }
+    let trigger_bid_amount: u128 = order.trigger_bid_amount().unpack();
+    let trigger_ask_amount: u128 = order.trigger_ask_amount().unpack();
+    U256::from(ask_value) * U256::from(trigger_bid_amount)
+        <= U256::from(trigger_ask_amount) * U256::from(bid_value)
+}
+
+// Fill-or-kill and immediate-or-cancel orders are cancelled when they are
+// not filled in the first round they take part in.
+fn immediate_order(order: &dex1::Order) -> bool {
+    matches!(
+        order.to_enum(),
+        dex1::OrderUnion::FillOrKillOrder(_) | dex1::OrderUnion::ImmediateOrCancelOrder(_)
+    )
+}
+
+// Iceberg orders are only matched by their displayed part, and sliced orders
+// by their current slice, priced no better than the full order. Other orders
+// are matched as is.
+fn displayed_order(
+    mut parsed_data: ParsedData,
+    limit_order: dex1::LimitOrder,
+) -> (ParsedData, dex1::LimitOrder) {
+    let display_amount: u128 = match parsed_data.order.to_enum() {
+        dex1::OrderUnion::IcebergOrder(o) => o.display_amount().unpack(),
+        dex1::OrderUnion::SlicedOrder(o) => o.slice_amount().unpack(),
+        _ => return (parsed_data, limit_order),
+    };
+    let bid_amount: u128 = limit_order.bid_amount().unpack();
+    let ask_amount: u128 = limit_order.ask_amount().unpack();
+    if display_amount >= bid_amount {
+        return (parsed_data, limit_order);
+    }
+    let displayed_ask_amount =
+        ((U256::from(display_amount) * U256::from(ask_amount) + U256::from(bid_amount) - 1)
+            / U256::from(bid_amount))
+        .as_u128();
+    parsed_data.price = Ratio::new_raw(displayed_ask_amount, display_amount);
+    let displayed = limit_order
+        .as_builder()
+        .bid_amount(display_amount.pack())
+        .ask_amount(displayed_ask_amount.pack())
+        .build();
+    (parsed_data, displayed)
+}
+
+// Merges remaining orders of earlier txs into the order book, both are
+// expected in priority order.
+fn merge_by_priority<R, B>(remaining: R, book: B) -> impl Iterator<Item = ParsedData>
+where
+    R: Iterator<Item = ParsedData>,
+    B: Iterator<Item = ParsedData>,
+{
+    let mut remaining = remaining.peekable();
+    let mut book = book.peekable();
+    std::iter::from_fn(move || match (remaining.peek(), book.peek()) {
+        (Some(r), Some(b)) if b.priority() < r.priority() => book.next(),
+        (Some(_), _) => remaining.next(),
+        (None, _) => book.next(),
+    })
+}
+
+// Stop-limit orders not yet triggered, as well as sliced orders whose next
+// slice is not yet due are skipped, they stay in the order book.
+// NFT sell order along with the NFT cell it sells
+type NftListing = (ParsedData, dex1::NftSellOrder, (CellOutput, Bytes));
+
+// Skips to the first NFT sell order still holding its NFT cell, rejecting
+// invalid ones along the way.
+fn locate_next_valid_nft_sell<I, E>(iter: I, emitter: &mut E) -> Result<Option<NftListing>>
+where
+    I: Iterator<Item = ParsedData>,
+    E: ReduceEmitter<
+        <Dex1 as Assembler>::Key,
+        <Dex1 as Assembler>::Order,
+        <Dex1 as Assembler>::Value,
+        <Dex1 as Assembler>::PostValue,
+    >,
+{
+    for parsed_data in iter {
+        if let dex1::OrderUnion::NftSellOrder(sell_order) = parsed_data.order.to_enum() {
+            let nft = parsed_data
+                .tx
+                .inputs
+                .iter()
+                .find(|(cell_output, _)| {
+                    cell_output.type_().to_opt().as_ref() == Some(&parsed_data.bid_token_script)
+                })
+                .cloned();
+            if let Some(nft) = nft {
+                return Ok(Some((parsed_data, sell_order, nft)));
+            }
+        }
+        if let Err(e) = emitter.reject_otx(parsed_data.tx.tx) {
+            bail!("Reject otx encounters error: {:?}", e);
+        }
+    }
+    Ok(None)
+}
+
+fn locate_next_valid_order<I, E>(
+    iter: &mut I,
+    emitter: &mut E,
+    conditions: &MatchConditions,
 ) -> Result<Option<(ParsedData, dex1::LimitOrder)>>
 where
     I: Iterator<Item = ParsedData>,
@@ -1083,8 +2811,43 @@ where
     >,
 {
     while let Some(parsed_data) = iter.next() {
-        if let Some(limit_order) = validate_limit_order(&parsed_data, expired_block_number) {
-            return Ok(Some((parsed_data, limit_order)));
+        match parsed_data.order.to_enum() {
+            dex1::OrderUnion::StopLimitOrder(o) => {
+                let triggered = conditions
+                    .oracles
+                    .get(&o.oracle())
+                    .map(|oracle| stop_triggered(&o, &oracle.price))
+                    .unwrap_or(false);
+                if !triggered {
+                    continue;
+                }
+            }
+            dex1::OrderUnion::SlicedOrder(o) => {
+                let not_before: u64 = o.not_before().unpack();
+                if not_before > conditions.block_number {
+                    continue;
+                }
+            }
+            // The contract requires the deadline to be after the block of
+            // the dex1 entity cell the tx is sealed with, orders the tx might
+            // land after are skipped in this round. Only otxs past their
+            // deadline are rejected, expired freestanding cells are left for
+            // sweeping.
+            dex1::OrderUnion::LimitOrderWithDeadline(o) => {
+                let deadline: u64 = o.deadline().unpack();
+                if deadline <= conditions.expired_block_number {
+                    if deadline <= conditions.block_number && !parsed_data.freestanding_cell {
+                        if let Err(e) = emitter.reject_otx(parsed_data.tx.tx) {
+                            bail!("Reject otx encounters error: {:?}", e);
+                        }
+                    }
+                    continue;
+                }
+            }
+            _ => (),
+        }
+        if let Some(limit_order) = limit_order(&parsed_data.order) {
+            return Ok(Some(displayed_order(parsed_data, limit_order)));
         } else {
             if let Err(e) = emitter.reject_otx(parsed_data.tx.tx) {
                 bail!("Reject otx encounters error: {:?}", e);
@@ -1094,6 +2857,779 @@ where
     Ok(None)
 }
 
+// Estimated serialized size and script cycles of a tx being packed, orders
+// are only added while the tx stays within packing limits.
+struct PackingBudget<'a> {
+    dex1: &'a Dex1,
+    size: usize,
+    cycles: u64,
+}
+
+impl PackingBudget<'_> {
+    // Reserves room for +parsed_data+, returns false when it does not fit.
+    fn reserve(&mut self, parsed_data: &ParsedData) -> bool {
+        let size = self.size + parsed_data.estimated_size(&self.dex1.tokens);
+        let cycles = self.cycles + self.dex1.estimated_cycles(&parsed_data.tx.inputs);
+        if size > self.dex1.packing.max_tx_size || cycles > self.dex1.packing.max_cycles {
+            return false;
+        }
+        self.size = size;
+        self.cycles = cycles;
+        true
+    }
+}
+
+fn lock_kind(lock: &Script) -> (Byte32, u8) {
+    (lock.code_hash(), lock.hash_type().into())
+}
+
+// Locates valid orders for the tx being matched. Owners of orders considered
+// for the tx are tracked, so that a buy order and a sell order of the same
+// owner never end up in the same tx.
+struct OrderLocator<'a> {
+    self_trade: &'a SelfTradeConfig,
+    conditions: MatchConditions,
+    buys: Vec<(HashSet<Byte32>, ParsedData)>,
+    sells: Vec<(HashSet<Byte32>, ParsedData)>,
+    // Out points of orders left out for the rest of the round
+    excluded_out_points: HashSet<OutPoint>,
+    // Set when an order already considered for the tx is left out, the tx
+    // needs to be matched again.
+    restart: bool,
+}
+
+impl<'a> OrderLocator<'a> {
+    fn new(self_trade: &'a SelfTradeConfig, conditions: MatchConditions) -> Self {
+        Self {
+            self_trade,
+            conditions,
+            buys: Vec::new(),
+            sells: Vec::new(),
+            excluded_out_points: HashSet::new(),
+            restart: false,
+        }
+    }
+
+    // Starts matching a new tx
+    fn reset(&mut self) {
+        self.buys.clear();
+        self.sells.clear();
+        self.restart = false;
+    }
+
+    // Locates the next valid order from +iter+, resolving conflicts with
+    // orders of the same owner on the other side according to the self-trade
+    // prevention mode. None is also returned when the tx needs to be matched
+    // again.
+    fn locate<I, E>(
+        &mut self,
+        iter: &mut I,
+        buy: bool,
+        emitter: &mut E,
+    ) -> Result<Option<(ParsedData, dex1::LimitOrder)>>
+    where
+        I: Iterator<Item = ParsedData>,
+        E: ReduceEmitter<
+            <Dex1 as Assembler>::Key,
+            <Dex1 as Assembler>::Order,
+            <Dex1 as Assembler>::Value,
+            <Dex1 as Assembler>::PostValue,
+        >,
+    {
+        if self.restart {
+            return Ok(None);
+        }
+        while let Some(order) = locate_next_valid_order(iter, emitter, &self.conditions)? {
+            let owners = order_owners(&order.0);
+            let (same_side, other_side) = if buy {
+                (&mut self.buys, &self.sells)
+            } else {
+                (&mut self.sells, &self.buys)
+            };
+            let Some(other) = other_side
+                .iter()
+                .find(|(other_owners, _)| !other_owners.is_disjoint(&owners))
+                .map(|(_, parsed_data)| parsed_data.clone())
+            else {
+                same_side.push((owners, order.0.clone()));
+                return Ok(Some(order));
+            };
+            let order_is_newer = order.0.sequence > other.sequence;
+            let (leave_out_order, cancel) = match self.self_trade {
+                SelfTradeConfig::CancelNewest => (order_is_newer, true),
+                SelfTradeConfig::CancelOldest => (!order_is_newer, true),
+                SelfTradeConfig::Skip => (order_is_newer, false),
+            };
+            let left_out = if leave_out_order { order.0 } else { other };
+            log::info!(
+                "Self-trade prevented, order {} is {}",
+                left_out.sequence,
+                if cancel { "cancelled" } else { "skipped" }
+            );
+            self.excluded_out_points.extend(
+                left_out
+                    .tx
+                    .tx
+                    .raw()
+                    .inputs()
+                    .into_iter()
+                    .map(|cell_input| cell_input.previous_output()),
+            );
+            // Freestanding cells are not backed by otxs, they always stay
+            // in the order book.
+            if cancel && !left_out.freestanding_cell {
+                if let Err(e) = emitter.reject_otx(left_out.tx.tx) {
+                    bail!("Reject otx encounters error: {:?}", e);
+                }
+            }
+            if !leave_out_order {
+                self.restart = true;
+                return Ok(None);
+            }
+        }
+        Ok(None)
+    }
+}
+
+// Owners of an order are identified by the recipient script hash, the
+// separate owner if any, as well as lock hashes of input cells.
+fn order_owners(parsed_data: &ParsedData) -> HashSet<Byte32> {
+    let mut owners: HashSet<Byte32> = parsed_data
+        .tx
+        .inputs
+        .iter()
+        .map(|(cell_output, _)| cell_output.lock().calc_script_hash())
+        .collect();
+    owners.insert(parsed_data.recipient_script.calc_script_hash());
+    owners.extend(order_owner(&parsed_data.order));
+    owners
+}
+
+/// A matched order, along with the ask token amount paid to it. Partially
+/// filled orders also keep the bid token amount actually provided, and the
+/// key used to emit the remaining order.
+struct Fill {
+    order: (ParsedData, dex1::LimitOrder),
+    paid_amount: u128,
+    partial: Option<(u128, [u8; 65])>,
+}
+
+// Continuous matching: the top buy order is matched against sell orders it
+// can fulfill, each order is paid exactly at its own limit price.
+fn match_continuous<B, S, E>(
+    limit_buys: &mut B,
+    limit_sells: &mut S,
+    limit_buy_key: [u8; 65],
+    limit_sell_key: [u8; 65],
+    emitter: &mut E,
+    budget: &mut PackingBudget,
+    locator: &mut OrderLocator,
+) -> Result<Vec<Fill>>
+where
+    B: Iterator<Item = ParsedData>,
+    S: Iterator<Item = ParsedData>,
+    E: ReduceEmitter<
+        <Dex1 as Assembler>::Key,
+        <Dex1 as Assembler>::Order,
+        <Dex1 as Assembler>::Value,
+        <Dex1 as Assembler>::PostValue,
+    >,
+{
+    // For each top buy order, look for sell orders it can fulfill
+    // Orders are packed as long as the estimated size and cycles of the tx
+    // stay within packing limits.
+    let mut fulfilled_orders: Vec<(ParsedData, dex1::LimitOrder)> = vec![];
+    let mut unfinished_buy_order = locator.locate(limit_buys, true, emitter)?;
+    let mut pending_ask_amount: u128 = match &unfinished_buy_order {
+        Some((parsed_data, limit_order)) if budget.reserve(parsed_data) => {
+            limit_order.ask_amount().unpack()
+        }
+        // Terminate when we don't have at least one buy order.
+        _ => return Ok(Vec::new()),
+    };
+    let mut unfinished_sell_order: Option<(ParsedData, dex1::LimitOrder)> = None;
+    loop {
+        // Loop invariant
+        assert!(unfinished_buy_order.is_none() || unfinished_sell_order.is_none());
+        let mut advanced = false;
+        {
+            let mut finish_buy_order = false;
+            if let Some(buy_order) = &unfinished_buy_order {
+                if let Some(sell_order) = locator.locate(limit_sells, false, emitter)? {
+                    if !matchable_order(&buy_order.0, &sell_order.0)
+                        || !budget.reserve(&sell_order.0)
+                    {
+                        // No more sell orders can be processed
+                        break;
+                    }
+                    let seller_bid_amount: u128 = sell_order.1.bid_amount().unpack();
+                    match pending_ask_amount.cmp(&seller_bid_amount) {
+                        std::cmp::Ordering::Less => {
+                            // pending_ask_amount should now hold value for the unfinished sell order.
+                            pending_ask_amount = seller_bid_amount - pending_ask_amount;
+                            finish_buy_order = true;
+                            unfinished_sell_order = Some(sell_order);
+                        }
+                        std::cmp::Ordering::Equal => {
+                            pending_ask_amount = 0;
+                            finish_buy_order = true;
+                            fulfilled_orders.push(sell_order);
+                        }
+                        std::cmp::Ordering::Greater => {
+                            pending_ask_amount -= seller_bid_amount;
+                            fulfilled_orders.push(sell_order);
+                        }
+                    };
+                    advanced = true;
+                }
+            }
+            if finish_buy_order {
+                fulfilled_orders.push(unfinished_buy_order.unwrap());
+                if unfinished_sell_order.is_none() {
+                    unfinished_buy_order = locator.locate(limit_buys, true, emitter)?;
+                } else {
+                    unfinished_buy_order = None;
+                }
+            }
+        }
+        {
+            let mut finish_sell_order = false;
+            if let Some(sell_order) = &unfinished_sell_order {
+                if let Some(buy_order) = locator.locate(limit_buys, true, emitter)? {
+                    if !matchable_order(&buy_order.0, &sell_order.0)
+                        || !budget.reserve(&buy_order.0)
+                    {
+                        // No more buy orders can be processed
+                        break;
+                    }
+                    let buyer_bid_amount: u128 = buy_order.1.bid_amount().unpack();
+                    match pending_ask_amount.cmp(&buyer_bid_amount) {
+                        std::cmp::Ordering::Less => {
+                            // pending_ask_amount should now hold value for the unfinished buy order.
+                            pending_ask_amount = buyer_bid_amount - pending_ask_amount;
+                            finish_sell_order = true;
+                            unfinished_buy_order = Some(buy_order);
+                        }
+                        std::cmp::Ordering::Equal => {
+                            pending_ask_amount = 0;
+                            finish_sell_order = true;
+                            fulfilled_orders.push(buy_order);
+                        }
+                        std::cmp::Ordering::Greater => {
+                            pending_ask_amount -= buyer_bid_amount;
+                            fulfilled_orders.push(buy_order);
+                        }
+                    }
+                    advanced = true;
+                }
+            }
+            if finish_sell_order {
+                fulfilled_orders.push(unfinished_sell_order.unwrap());
+                if unfinished_buy_order.is_none() {
+                    unfinished_sell_order = locator.locate(limit_sells, false, emitter)?;
+                } else {
+                    unfinished_sell_order = None;
+                }
+            }
+        }
+        if !advanced {
+            break;
+        }
+    }
+    if fulfilled_orders.is_empty() {
+        return Ok(Vec::new());
+    }
+    let mut fills: Vec<Fill> = fulfilled_orders
+        .into_iter()
+        .map(|order| {
+            let paid_amount = order.1.ask_amount().unpack();
+            Fill {
+                order,
+                paid_amount,
+                partial: None,
+            }
+        })
+        .collect();
+
+    // Locate partially filled order if needed
+    assert!(unfinished_buy_order.is_none() || unfinished_sell_order.is_none());
+    if unfinished_buy_order.is_some() || unfinished_sell_order.is_some() {
+        let (order, key) = if unfinished_buy_order.is_some() {
+            (unfinished_buy_order.unwrap(), limit_buy_key)
+        } else {
+            (unfinished_sell_order.unwrap(), limit_sell_key)
+        };
+
+        // The partial order needs to provide +required_bid_amount+ bid token,
+        // while at most +available_ask_amount+ ask token is left for it.
+        let (required_bid_amount, available_ask_amount) = {
+            let mut input_bid_amount: u128 = 0;
+            let mut output_bid_amount: u128 = 0;
+            let mut input_ask_amount: u128 = 0;
+            let mut output_ask_amount: u128 = 0;
+
+            let ask_token = order.1.ask_token();
+            fills.iter().for_each(|fill| {
+                let limit_order = &fill.order.1;
+                let current_bid_amount: u128 = limit_order.bid_amount().unpack();
+                let current_ask_amount: u128 = limit_order.ask_amount().unpack();
+
+                if limit_order.ask_token() == ask_token {
+                    // Current order is of the same direction as partial order
+                    input_bid_amount += current_bid_amount;
+                    output_ask_amount += current_ask_amount;
+                } else {
+                    input_ask_amount += current_bid_amount;
+                    output_bid_amount += current_ask_amount;
+                }
+            });
+
+            assert!(output_bid_amount > input_bid_amount);
+            assert!(input_ask_amount > output_ask_amount);
+            (
+                (output_bid_amount - input_bid_amount),
+                (input_ask_amount - output_ask_amount),
+            )
+        };
+        // The partial order is paid exactly at its limit price, rounding up,
+        // anything left is surplus.
+        let ask_amount: u128 = order.1.ask_amount().unpack();
+        let bid_amount: u128 = order.1.bid_amount().unpack();
+        let required_ask_amount = ((U256::from(required_bid_amount) * U256::from(ask_amount)
+            + U256::from(bid_amount)
+            - 1)
+            / U256::from(bid_amount))
+        .as_u128();
+        assert!(required_ask_amount <= available_ask_amount);
+        fills.push(Fill {
+            order,
+            paid_amount: required_ask_amount,
+            partial: Some((required_bid_amount, key)),
+        });
+    }
+    Ok(fills)
+}
+
+// Batch auction matching: all crossing orders of the batch are settled at a
+// single uniform clearing price, so the fill of an order does not depend on
+// the ordering of otxs within the batch. Orders are still filled in
+// price-time priority, only the last filled order on one side might be
+// partially filled.
+fn match_batch_auction<B, S, E>(
+    limit_buys: &mut B,
+    limit_sells: &mut S,
+    limit_buy_key: [u8; 65],
+    limit_sell_key: [u8; 65],
+    emitter: &mut E,
+    budget: &mut PackingBudget,
+    locator: &mut OrderLocator,
+) -> Result<Vec<Fill>>
+where
+    B: Iterator<Item = ParsedData>,
+    S: Iterator<Item = ParsedData>,
+    E: ReduceEmitter<
+        <Dex1 as Assembler>::Key,
+        <Dex1 as Assembler>::Order,
+        <Dex1 as Assembler>::Value,
+        <Dex1 as Assembler>::PostValue,
+    >,
+{
+    // Orders are taken from both sides in turn, until the packing budget
+    // is used up.
+    let mut buys = Vec::new();
+    let mut sells = Vec::new();
+    let (mut buys_done, mut sells_done) = (false, false);
+    while !(buys_done && sells_done) {
+        if !buys_done {
+            match locator.locate(limit_buys, true, emitter)? {
+                Some(order) if budget.reserve(&order.0) => buys.push(order),
+                _ => buys_done = true,
+            }
+        }
+        if !sells_done {
+            match locator.locate(limit_sells, false, emitter)? {
+                Some(order) if budget.reserve(&order.0) => sells.push(order),
+                _ => sells_done = true,
+            }
+        }
+    }
+    let Some(price) = clearing_price(&buys, &sells)? else {
+        return Ok(Vec::new());
+    };
+    buys.retain(|(parsed_data, _)| parsed_data.price.recip() >= price);
+    sells.retain(|(parsed_data, _)| parsed_data.price <= price);
+    if buys.is_empty() || sells.is_empty() {
+        return Ok(Vec::new());
+    }
+    log::debug!(
+        "Batch clearing price: {}, {} buy orders, {} sell orders",
+        price,
+        buys.len(),
+        sells.len()
+    );
+
+    // Buy orders provide the second token, sell orders provide the first
+    // token. The side providing more than the other side can take at the
+    // clearing price is only partially filled. Rounding is always done in
+    // favor of the side providing tokens, so no more tokens are paid than
+    // provided.
+    let numer = U256::from(*price.numer());
+    let denom = U256::from(*price.denom());
+    let total_buy_amount = sum_bid_amounts(&buys)?;
+    let total_sell_amount = sum_bid_amounts(&sells)?;
+    let (buy_amount, sell_amount) =
+        if U256::from(total_buy_amount) * denom >= U256::from(total_sell_amount) * numer {
+            (
+                (U256::from(total_sell_amount) * numer / denom).as_u128(),
+                total_sell_amount,
+            )
+        } else {
+            (
+                total_buy_amount,
+                (U256::from(total_buy_amount) * denom / numer).as_u128(),
+            )
+        };
+    if buy_amount == 0 || sell_amount == 0 {
+        return Ok(Vec::new());
+    }
+    let mut fills = batch_fills(buys, buy_amount, sell_amount, denom, numer, limit_buy_key)?;
+    fills.extend(batch_fills(
+        sells,
+        sell_amount,
+        buy_amount,
+        numer,
+        denom,
+        limit_sell_key,
+    )?);
+    Ok(fills)
+}
+
+// Uniform clearing price of a batch, measured in second token per first
+// token. At the clearing price, the second token provided by buy orders
+// willing to pay at least this price, equals the value of the first token
+// provided by sell orders asking at most this price. Orders exactly at the
+// clearing price might be partially filled to balance both sides.
+fn clearing_price(
+    buys: &[(ParsedData, dex1::LimitOrder)],
+    sells: &[(ParsedData, dex1::LimitOrder)],
+) -> Result<Option<Ratio<u128>>> {
+    let buy_limits: Vec<(Ratio<u128>, u128)> = buys
+        .iter()
+        .map(|(parsed_data, limit_order)| {
+            (parsed_data.price.recip(), limit_order.bid_amount().unpack())
+        })
+        .collect();
+    let sell_limits: Vec<(Ratio<u128>, u128)> = sells
+        .iter()
+        .map(|(parsed_data, limit_order)| (parsed_data.price, limit_order.bid_amount().unpack()))
+        .collect();
+    let mut candidates: Vec<Ratio<u128>> = buy_limits
+        .iter()
+        .chain(sell_limits.iter())
+        .map(|(price, _)| *price)
+        .collect();
+    candidates.sort();
+    candidates.dedup();
+    for price in candidates {
+        let numer = U256::from(*price.numer());
+        let denom = U256::from(*price.denom());
+        let demand = sum_amounts(&buy_limits, |limit| *limit > price)?;
+        let demand_at_price = sum_amounts(&buy_limits, |limit| *limit == price)?;
+        let supply = sum_amounts(&sell_limits, |limit| *limit < price)?;
+        let supply_at_price = sum_amounts(&sell_limits, |limit| *limit == price)?;
+        let full_demand = demand
+            .checked_add(demand_at_price)
+            .ok_or_else(|| anyhow!("overflow!"))?;
+        let full_supply = supply
+            .checked_add(supply_at_price)
+            .ok_or_else(|| anyhow!("overflow!"))?;
+        // Demand still exceeds supply, the clearing price must be higher
+        if U256::from(demand) * denom > U256::from(full_supply) * numer {
+            continue;
+        }
+        // Both sides can be balanced by partially filling orders at this price
+        if U256::from(full_demand) * denom >= U256::from(supply) * numer {
+            return Ok(Some(price));
+        }
+        // Supply exceeds demand at this price, while demand exceeds supply at
+        // the previous candidate price. The same set of orders cross at any
+        // price in between, the one balancing both sides is picked.
+        if full_demand == 0 {
+            return Ok(None);
+        }
+        return Ok(Some(Ratio::new(full_demand, supply)));
+    }
+    Ok(None)
+}
+
+// Splits +filled_amount+ of bid token amongst orders in priority order, each
+// order is then paid at the rate of +rate_numer+ / +rate_denom+ from the
+// +paid_amount+ ask token provided by the other side. Only the last order
+// might be partially filled, which must be paid at least at its own limit
+// price, hence rounding up. Rounding dust is left as surplus.
+fn batch_fills(
+    orders: Vec<(ParsedData, dex1::LimitOrder)>,
+    filled_amount: u128,
+    paid_amount: u128,
+    rate_numer: U256,
+    rate_denom: U256,
+    key: [u8; 65],
+) -> Result<Vec<Fill>> {
+    let mut fills: Vec<Fill> = Vec::new();
+    let mut remaining_amount = filled_amount;
+    let mut remaining_paid_amount = paid_amount;
+    for order in orders {
+        if remaining_amount == 0 {
+            break;
+        }
+        let bid_amount: u128 = order.1.bid_amount().unpack();
+        let amount = std::cmp::min(bid_amount, remaining_amount);
+        remaining_amount -= amount;
+        let value = U256::from(amount) * rate_numer;
+        let (paid, partial) = if amount < bid_amount {
+            (
+                ((value + rate_denom - 1) / rate_denom).as_u128(),
+                Some((amount, key)),
+            )
+        } else {
+            ((value / rate_denom).as_u128(), None)
+        };
+        remaining_paid_amount = remaining_paid_amount
+            .checked_sub(paid)
+            .ok_or_else(|| anyhow!("Batch pays more tokens than provided!"))?;
+        fills.push(Fill {
+            order,
+            paid_amount: paid,
+            partial,
+        });
+    }
+    Ok(fills)
+}
+
+fn sum_bid_amounts(orders: &[(ParsedData, dex1::LimitOrder)]) -> Result<u128> {
+    orders.iter().try_fold(0u128, |acc, (_, limit_order)| {
+        acc.checked_add(limit_order.bid_amount().unpack())
+            .ok_or_else(|| anyhow!("overflow!"))
+    })
+}
+
+fn sum_amounts<F>(limits: &[(Ratio<u128>, u128)], f: F) -> Result<u128>
+where
+    F: Fn(&Ratio<u128>) -> bool,
+{
+    limits
+        .iter()
+        .filter(|(price, _)| f(price))
+        .try_fold(0u128, |acc, (_, amount)| {
+            acc.checked_add(*amount).ok_or_else(|| anyhow!("overflow!"))
+        })
+}
+
+// Depth first search for rings starting and ending at +start+ token. Each
+// ring is only visited from its smallest token, the first ring that can be
+// settled is returned.
+fn search_ring(
+    start: &[u8; 32],
+    current: &[u8; 32],
+    edges: &BTreeMap<[u8; 32], Vec<([u8; 32], usize)>>,
+    orders: &[((ParsedData, dex1::LimitOrder), [u8; 65])],
+    path: &mut Vec<usize>,
+    max_length: usize,
+) -> Option<Vec<Fill>> {
+    for (to, index) in edges.get(current).into_iter().flatten() {
+        if to == start {
+            if path.len() + 1 >= 3 {
+                let mut ring = path.clone();
+                ring.push(*index);
+                if let Some(fills) =
+                    ring_fills(ring.into_iter().map(|i| orders[i].clone()).collect())
+                {
+                    return Some(fills);
+                }
+            }
+            continue;
+        }
+        if to < start
+            || path.len() + 2 > max_length
+            || path
+                .iter()
+                .any(|i| token_hash(&orders[*i].0 .1.ask_token()) == *to)
+        {
+            continue;
+        }
+        path.push(*index);
+        if let Some(fills) = search_ring(start, to, edges, orders, path, max_length) {
+            return Some(fills);
+        }
+        path.pop();
+    }
+    None
+}
+
+// Order i in a ring provides the token asked by order i - 1, and asks for
+// the token provided by order i + 1. A ring can be settled when each order
+// provides enough tokens for the previous one, or when exactly one order
+// asks for more than the next order provides, in which case it is partially
+// filled, providing just enough tokens for the previous order.
+fn ring_fills(ring: Vec<((ParsedData, dex1::LimitOrder), [u8; 65])>) -> Option<Vec<Fill>> {
+    let n = ring.len();
+    let bid_amount = |i: usize| -> u128 { ring[i % n].0 .1.bid_amount().unpack() };
+    let ask_amount = |i: usize| -> u128 { ring[i % n].0 .1.ask_amount().unpack() };
+    let short: Vec<usize> = (0..n)
+        .filter(|i| bid_amount(i + 1) < ask_amount(*i))
+        .collect();
+    let partial = match short[..] {
+        [] => None,
+        [k] => {
+            let required_bid_amount = ask_amount(k + n - 1);
+            if required_bid_amount == 0 || required_bid_amount >= bid_amount(k) {
+                return None;
+            }
+            let required_ask_amount = ((U256::from(required_bid_amount)
+                * U256::from(ask_amount(k))
+                + U256::from(bid_amount(k))
+                - 1)
+                / U256::from(bid_amount(k)))
+            .as_u128();
+            if required_ask_amount > bid_amount(k + 1)
+                || required_ask_amount >= ask_amount(k)
+                || !partial_fill_allowed(&ring[k].0 .0.order, required_bid_amount)
+            {
+                return None;
+            }
+            Some((k, required_bid_amount, required_ask_amount))
+        }
+        _ => return None,
+    };
+    Some(
+        ring.into_iter()
+            .enumerate()
+            .map(|(i, (order, key))| match partial {
+                Some((k, required_bid_amount, required_ask_amount)) if k == i => Fill {
+                    order,
+                    paid_amount: required_ask_amount,
+                    partial: Some((required_bid_amount, key)),
+                },
+                _ => {
+                    let paid_amount = order.1.ask_amount().unpack();
+                    Fill {
+                        order,
+                        paid_amount,
+                        partial: None,
+                    }
+                }
+            })
+            .collect(),
+    )
+}
+
+// Check if recipient hash is used in current otx. Note this is a shortcut,
+// it's also possible to provide recipient script via OTX RPC.
+fn otx_recipient_script(tx: &RichOtx, recipient: &Byte32) -> Result<Script> {
+    tx.inputs
+        .iter()
+        .map(|(cell_output, _)| cell_output.lock())
+        .chain(
+            tx.tx
+                .raw()
+                .outputs()
+                .into_iter()
+                .map(|cell_output| cell_output.lock()),
+        )
+        .find(|s| s.calc_script_hash() == *recipient)
+        .ok_or_else(|| anyhow!("Recipient script is missing!"))
+}
+
+fn token_hash(token: &packed::Byte32) -> [u8; 32] {
+    let mut hash = [0u8; 32];
+    hash.copy_from_slice(&token.raw_data());
+    hash
+}
+
+// Capacity of all outputs except the fee change cell
+fn output_capacity(tx: &TransactionView, fee_change_output_cell_index: usize) -> Result<u64> {
+    let mut output_capacity: u64 = 0;
+    for output in tx.outputs().into_iter().take(fee_change_output_cell_index) {
+        output_capacity = output_capacity
+            .checked_add(output.capacity().unpack())
+            .ok_or_else(|| anyhow!("overflow!"))?;
+    }
+    Ok(output_capacity)
+}
+
+fn surplus_cell(
+    tokens: &TokenAdapters,
+    lock: Script,
+    token: &Script,
+    amount: u128,
+) -> Result<(CellOutput, packed::Bytes)> {
+    let capacity = tokens.occupied_capacity(lock.clone(), token);
+    tokens.cell(lock, token, amount, capacity)
+}
+
+// Checks an order trading +size+ first token for +notional+ second token
+// against trading rules of its pair.
+fn check_trading_rules(rules: &TradingRules, size: u128, notional: u128) -> Result<()> {
+    if let Some(min_size) = rules.min_size {
+        if size < min_size {
+            bail!("Order size {} is below minimum size {}!", size, min_size);
+        }
+    }
+    if let Some(min_notional) = rules.min_notional {
+        if notional < min_notional {
+            bail!(
+                "Order notional {} is below minimum notional {}!",
+                notional,
+                min_notional
+            );
+        }
+    }
+    let lot_size = rules.lot_size.unwrap_or(1);
+    if lot_size == 0 || !size.is_multiple_of(lot_size) {
+        bail!(
+            "Order size {} is not a multiple of lot size {}!",
+            size,
+            lot_size
+        );
+    }
+    if let Some(price_tick) = rules.price_tick {
+        // Price per lot = notional * lot_size / size
+        let lot_notional = U256::from(notional) * U256::from(lot_size);
+        if price_tick == 0
+            || lot_notional % U256::from(size) != 0
+            || (lot_notional / U256::from(size)) % U256::from(price_tick) != 0
+        {
+            bail!(
+                "Order price {}/{} is not a multiple of price tick {} per {} lot!",
+                notional,
+                size,
+                price_tick,
+                lot_size
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Serializes trading rules of all configured pairs, to be kept in dex1
+/// entity cell data so they are also enforced on chain.
+pub fn trading_rules_data(config: &RunnerConfig) -> Bytes {
+    let rules = config.dex1_trading_pair_hashes.iter().map(|pair| {
+        dex1::TradingRule::new_builder()
+            .first_token(pair.first.pack())
+            .second_token(pair.second.pack())
+            .min_size(pair.rules.min_size.unwrap_or(0).pack())
+            .min_notional(pair.rules.min_notional.unwrap_or(0).pack())
+            .price_tick(pair.rules.price_tick.unwrap_or(0).pack())
+            .lot_size(pair.rules.lot_size.unwrap_or(0).pack())
+            .build()
+    });
+    dex1::TradingRules::new_builder()
+        .extend(rules)
+        .build()
+        .as_bytes()
+}
+
 fn matchable_order(buy_order: &ParsedData, sell_order: &ParsedData) -> bool {
     buy_order.price.recip() >= sell_order.price
 }
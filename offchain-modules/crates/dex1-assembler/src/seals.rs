@@ -0,0 +1,89 @@
+use crate::schemas::basic;
+use anyhow::{anyhow, bail, Result};
+use ckb_hash::{blake2b_256, Blake2bBuilder};
+use ckb_sdk::{types::omni_lock::OmniLockWitnessLock, SECP256K1};
+use ckb_types::{
+    bytes::Bytes,
+    packed::{CellOutput, Script, Transaction},
+    prelude::*,
+};
+
+/// Signing message of an OTX, as defined by Cobuild: message, input cells
+/// (with their cell outputs and data), output cells, cell deps and header
+/// deps of the otx are all hashed.
+pub fn otx_signing_message(
+    otx: &basic::Otx,
+    tx: &Transaction,
+    inputs: &[(CellOutput, Bytes)],
+) -> [u8; 32] {
+    let raw = tx.raw();
+    let mut hasher = Blake2bBuilder::new(32)
+        .personal(b"ckb-tcob-otxhash")
+        .build();
+    hasher.update(otx.message().as_slice());
+    hasher.update(&(raw.inputs().len() as u32).to_le_bytes());
+    for (cell_input, (output, data)) in raw.inputs().into_iter().zip(inputs) {
+        hasher.update(cell_input.as_slice());
+        hasher.update(output.as_slice());
+        hasher.update(&(data.len() as u32).to_le_bytes());
+        hasher.update(data);
+    }
+    hasher.update(&(raw.outputs().len() as u32).to_le_bytes());
+    for (output, data) in raw.outputs().into_iter().zip(raw.outputs_data()) {
+        hasher.update(output.as_slice());
+        hasher.update(&(data.len() as u32).to_le_bytes());
+        hasher.update(&data.raw_data());
+    }
+    hasher.update(&(raw.cell_deps().len() as u32).to_le_bytes());
+    for cell_dep in raw.cell_deps() {
+        hasher.update(cell_dep.as_slice());
+    }
+    hasher.update(&(raw.header_deps().len() as u32).to_le_bytes());
+    for header_dep in raw.header_deps() {
+        hasher.update(header_dep.as_slice());
+    }
+    let mut hash = [0u8; 32];
+    hasher.finalize(&mut hash);
+    hash
+}
+
+/// Verifies an omnilock seal in an otx. Only omnilock using CKB's secp256k1
+/// signature(auth flag 0) is checked here, other modes are left to the script
+/// verification when the assembled tx is dry-run.
+pub fn verify_omnilock_seal(lock: &Script, seal: &[u8], message: &[u8; 32]) -> Result<()> {
+    let args = lock.args().raw_data();
+    if args.len() < 21 || args[0] != 0 {
+        return Ok(());
+    }
+    // The first byte denotes seal type, omnilock only has 0 for now
+    if seal.is_empty() || seal[0] != 0 {
+        bail!("Invalid omnilock seal type!");
+    }
+    let witness_lock = OmniLockWitnessLock::from_slice(&seal[1..])
+        .map_err(|e| anyhow!("Error parsing omnilock seal: {:?}", e))?;
+    let signature = witness_lock
+        .signature()
+        .to_opt()
+        .ok_or_else(|| anyhow!("Omnilock seal has no signature!"))?
+        .raw_data();
+    verify_secp256k1_signature(&signature, message, &args[1..21])
+}
+
+fn verify_secp256k1_signature(
+    signature: &[u8],
+    message: &[u8; 32],
+    pubkey_hash: &[u8],
+) -> Result<()> {
+    if signature.len() != 65 {
+        bail!("Invalid signature length: {}", signature.len());
+    }
+    let recovery_id = secp256k1::ecdsa::RecoveryId::from_i32(signature[64] as i32)?;
+    let signature =
+        secp256k1::ecdsa::RecoverableSignature::from_compact(&signature[0..64], recovery_id)?;
+    let message = secp256k1::Message::from_slice(message)?;
+    let pubkey = SECP256K1.recover_ecdsa(&message, &signature)?;
+    if &blake2b_256(&pubkey.serialize()[..])[0..20] != pubkey_hash {
+        bail!("Signature does not match lock script!");
+    }
+    Ok(())
+}
@@ -0,0 +1,509 @@
+use crate::config::{FeeLockConfig, RunnerConfig, SignerBackendConfig};
+use anyhow::{anyhow, bail, Result};
+use ckb_hash::blake2b_256;
+use ckb_sdk::{
+    constants::{MULTISIG_TYPE_HASH, SIGHASH_TYPE_HASH},
+    traits::{SecpCkbRawKeySigner, Signer, SignerError},
+    unlock::{
+        MultisigConfig, OmniLockConfig, OmniLockUnlocker, OmniUnlockMode, ScriptUnlocker,
+        SecpMultisigUnlocker, SecpSighashUnlocker,
+    },
+    ScriptId, SECP256K1,
+};
+use ckb_types::{
+    bytes::Bytes,
+    core::{ScriptHashType, TransactionView},
+    packed::{Script, WitnessArgs},
+    prelude::*,
+    H160, H256,
+};
+use serde_json::{json, Value as JsonValue};
+use std::{
+    collections::HashMap,
+    io::{Read, Write},
+    os::unix::net::UnixStream,
+};
+
+/// Fee cell signer bundles together the lock script used by processor's fee
+/// cells, and the unlocker that can sign for it.
+pub struct FeeCellSigner {
+    pub lock_script: Script,
+    pub placeholder_witness: WitnessArgs,
+    pub unlockers: HashMap<ScriptId, Box<dyn ScriptUnlocker>>,
+}
+
+impl FeeCellSigner {
+    /// Private keys are only used by the local signer backend.
+    pub fn new(config: &RunnerConfig, private_keys: &[H256]) -> Result<Self> {
+        let (signer, pubkey_hashes): (Box<dyn Signer>, Vec<H160>) = match &config.signer.backend {
+            SignerBackendConfig::Local => {
+                let mut secret_keys = Vec::with_capacity(private_keys.len());
+                let mut pubkey_hashes = Vec::with_capacity(private_keys.len());
+                for private_key in private_keys {
+                    let secret_key = secp256k1::SecretKey::from_slice(private_key.as_bytes())?;
+                    pubkey_hashes.push(pubkey_hash(&secret_key));
+                    secret_keys.push(secret_key);
+                }
+                (
+                    Box::new(SecpCkbRawKeySigner::new_with_secret_keys(secret_keys)),
+                    pubkey_hashes,
+                )
+            }
+            SignerBackendConfig::External { url, pubkey_hashes } => (
+                Box::new(ExternalSigner::new(url, pubkey_hashes.clone())),
+                pubkey_hashes.clone(),
+            ),
+        };
+
+        let (lock_script, placeholder_lock, script_id, unlocker): (
+            Script,
+            Bytes,
+            ScriptId,
+            Box<dyn ScriptUnlocker>,
+        ) = match &config.signer.lock {
+            FeeLockConfig::Sighash => {
+                let pubkey_hash = match pubkey_hashes.first() {
+                    Some(h) => h,
+                    None => bail!("Signer provides no keys!"),
+                };
+                (
+                    Script::new_builder()
+                        .code_hash(SIGHASH_TYPE_HASH.pack())
+                        .hash_type(ScriptHashType::Type.into())
+                        .args(Bytes::from(pubkey_hash.as_bytes().to_vec()).pack())
+                        .build(),
+                    Bytes::from(vec![0u8; 65]),
+                    ScriptId::new_type(SIGHASH_TYPE_HASH.clone()),
+                    Box::new(SecpSighashUnlocker::from(signer)),
+                )
+            }
+            FeeLockConfig::Multisig {
+                sighash_addresses,
+                require_first_n,
+                threshold,
+            } => {
+                let multisig_config = MultisigConfig::new_with(
+                    sighash_addresses.clone(),
+                    *require_first_n,
+                    *threshold,
+                )?;
+                let placeholder_lock = multisig_config
+                    .placeholder_witness()
+                    .lock()
+                    .to_opt()
+                    .unwrap()
+                    .raw_data();
+                (
+                    Script::new_builder()
+                        .code_hash(MULTISIG_TYPE_HASH.pack())
+                        .hash_type(ScriptHashType::Type.into())
+                        .args(Bytes::from(multisig_config.hash160().as_bytes().to_vec()).pack())
+                        .build(),
+                    placeholder_lock,
+                    ScriptId::new_type(MULTISIG_TYPE_HASH.clone()),
+                    Box::new(SecpMultisigUnlocker::from((signer, multisig_config))),
+                )
+            }
+            FeeLockConfig::Omnilock => {
+                let pubkey_hash = match pubkey_hashes.first() {
+                    Some(h) => h,
+                    None => bail!("Signer provides no keys!"),
+                };
+                let omnilock_config = OmniLockConfig::new_pubkey_hash(pubkey_hash.clone());
+                let omnilock_script: Script = config.omnilock.script.clone().into();
+                let script_id = ScriptId::from(&omnilock_script);
+                (
+                    omnilock_script
+                        .as_builder()
+                        .args(omnilock_config.build_args().pack())
+                        .build(),
+                    omnilock_config.placeholder_witness_lock(OmniUnlockMode::Normal)?,
+                    script_id,
+                    Box::new(OmniLockUnlocker::from((
+                        signer,
+                        omnilock_config,
+                        OmniUnlockMode::Normal,
+                    ))),
+                )
+            }
+        };
+
+        let mut unlockers = HashMap::default();
+        unlockers.insert(script_id, unlocker);
+        Ok(Self {
+            lock_script,
+            placeholder_witness: WitnessArgs::new_builder()
+                .lock(Some(placeholder_lock).pack())
+                .build(),
+            unlockers,
+        })
+    }
+}
+
+fn pubkey_hash(secret_key: &secp256k1::SecretKey) -> H160 {
+    let pubkey = secp256k1::PublicKey::from_secret_key(&SECP256K1, secret_key);
+    H160::from_slice(&blake2b_256(&pubkey.serialize()[..])[0..20]).expect("h160")
+}
+
+/// A signer forwarding signing requests to an external process, so no hot
+/// keys are kept by the processor. A JSON-RPC request is sent for each
+/// signature:
+///
+/// {"jsonrpc": "2.0", "id": 1, "method": "sign",
+///  "params": [<pubkey hash>, <message>, <recoverable>, <tx>]}
+///
+/// The full tx is included so the external signer can apply its own policies
+/// before signing. Result shall be the hex encoded signature.
+pub struct ExternalSigner {
+    url: String,
+    pubkey_hashes: Vec<H160>,
+}
+
+impl ExternalSigner {
+    pub fn new(url: &str, pubkey_hashes: Vec<H160>) -> Self {
+        Self {
+            url: url.to_string(),
+            pubkey_hashes,
+        }
+    }
+
+    fn request(&self, request: &JsonValue) -> Result<JsonValue> {
+        let response: JsonValue = if let Some(path) = self.url.strip_prefix("unix://") {
+            // One request per connection, the signer closes the connection
+            // after writing the response.
+            let mut stream = UnixStream::connect(path)?;
+            stream.write_all(&serde_json::to_vec(request)?)?;
+            stream.shutdown(std::net::Shutdown::Write)?;
+            let mut data = Vec::new();
+            stream.read_to_end(&mut data)?;
+            serde_json::from_slice(&data)?
+        } else {
+            reqwest::blocking::Client::new()
+                .post(&self.url)
+                .json(request)
+                .send()?
+                .json()?
+        };
+        if let Some(error) = response.get("error") {
+            bail!("External signer error: {}", error);
+        }
+        response
+            .get("result")
+            .cloned()
+            .ok_or_else(|| anyhow!("External signer returns no result!"))
+    }
+}
+
+impl Signer for ExternalSigner {
+    fn match_id(&self, id: &[u8]) -> bool {
+        self.pubkey_hashes
+            .iter()
+            .any(|pubkey_hash| pubkey_hash.as_bytes() == id)
+    }
+
+    fn sign(
+        &self,
+        id: &[u8],
+        message: &[u8],
+        recoverable: bool,
+        tx: &TransactionView,
+    ) -> Result<Bytes, SignerError> {
+        if !self.match_id(id) {
+            return Err(SignerError::IdNotFound);
+        }
+        let json_tx: ckb_jsonrpc_types::TransactionView = tx.clone().into();
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "sign",
+            "params": [
+                format!("0x{}", hex::encode(id)),
+                format!("0x{}", hex::encode(message)),
+                recoverable,
+                json_tx,
+            ],
+        });
+        let result = self.request(&request)?;
+        let signature = result
+            .as_str()
+            .and_then(|s| hex::decode(s.trim_start_matches("0x")).ok())
+            .ok_or_else(|| anyhow!("Invalid signature returned: {}", result))?;
+        Ok(Bytes::from(signature))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{config::SignerConfig, keystore::generate_private_key};
+    use ckb_sdk::{
+        traits::{TransactionDependencyError, TransactionDependencyProvider},
+        ScriptGroup,
+    };
+    use ckb_types::{
+        core::HeaderView,
+        packed::{Byte32, CellInput, CellOutput, OutPoint},
+    };
+    use std::{os::unix::net::UnixListener, path::PathBuf, thread};
+
+    // Signing fee cells never looks up tx dependencies
+    struct NoDependencies;
+
+    impl TransactionDependencyProvider for NoDependencies {
+        fn get_transaction(
+            &self,
+            _tx_hash: &Byte32,
+        ) -> Result<TransactionView, TransactionDependencyError> {
+            Err(TransactionDependencyError::NotFound(
+                "transaction".to_string(),
+            ))
+        }
+
+        fn get_cell(
+            &self,
+            _out_point: &OutPoint,
+        ) -> Result<CellOutput, TransactionDependencyError> {
+            Err(TransactionDependencyError::NotFound("cell".to_string()))
+        }
+
+        fn get_cell_data(
+            &self,
+            _out_point: &OutPoint,
+        ) -> Result<Bytes, TransactionDependencyError> {
+            Err(TransactionDependencyError::NotFound(
+                "cell data".to_string(),
+            ))
+        }
+
+        fn get_header(
+            &self,
+            _block_hash: &Byte32,
+        ) -> Result<HeaderView, TransactionDependencyError> {
+            Err(TransactionDependencyError::NotFound("header".to_string()))
+        }
+
+        fn get_block_extension(
+            &self,
+            _block_hash: &Byte32,
+        ) -> Result<Option<ckb_types::packed::Bytes>, TransactionDependencyError> {
+            Ok(None)
+        }
+    }
+
+    fn runner_config(lock: FeeLockConfig) -> RunnerConfig {
+        RunnerConfig {
+            signer: SignerConfig {
+                backend: SignerBackendConfig::Local,
+                lock,
+            },
+            ..Default::default()
+        }
+    }
+
+    fn placeholder_lock_size(signer: &FeeCellSigner) -> usize {
+        signer
+            .placeholder_witness
+            .lock()
+            .to_opt()
+            .expect("placeholder lock")
+            .raw_data()
+            .len()
+    }
+
+    fn pubkey_hash_of(private_key: &H256) -> H160 {
+        pubkey_hash(&secp256k1::SecretKey::from_slice(private_key.as_bytes()).unwrap())
+    }
+
+    // A tx spending a single fee cell, with the placeholder witness
+    fn fee_cell_tx(signer: &FeeCellSigner) -> TransactionView {
+        TransactionView::new_advanced_builder()
+            .input(
+                CellInput::new_builder()
+                    .previous_output(OutPoint::new([1u8; 32].pack(), 0))
+                    .build(),
+            )
+            .output(
+                CellOutput::new_builder()
+                    .lock(signer.lock_script.clone())
+                    .build(),
+            )
+            .output_data(Bytes::default().pack())
+            .witness(signer.placeholder_witness.as_bytes().pack())
+            .build()
+    }
+
+    #[test]
+    fn test_placeholder_witness_sizes() {
+        let private_keys = [generate_private_key()];
+
+        let signer = FeeCellSigner::new(&runner_config(FeeLockConfig::Sighash), &private_keys)
+            .expect("sighash signer");
+        assert_eq!(placeholder_lock_size(&signer), 65);
+
+        // Multisig script(4 bytes header + 20 bytes per address), followed by
+        // one signature per threshold
+        let multisig = FeeLockConfig::Multisig {
+            sighash_addresses: vec![
+                H160::default(),
+                H160::from([1u8; 20]),
+                H160::from([2u8; 20]),
+            ],
+            require_first_n: 0,
+            threshold: 2,
+        };
+        let signer =
+            FeeCellSigner::new(&runner_config(multisig), &private_keys).expect("multisig signer");
+        assert_eq!(placeholder_lock_size(&signer), 4 + 20 * 3 + 65 * 2);
+
+        // OmniLockWitnessLock table with only the signature present
+        let signer = FeeCellSigner::new(&runner_config(FeeLockConfig::Omnilock), &private_keys)
+            .expect("omnilock signer");
+        assert_eq!(placeholder_lock_size(&signer), 16 + 4 + 65);
+        let args = signer.lock_script.args().raw_data();
+        assert_eq!(args.len(), 22);
+        assert_eq!(&args[1..21], pubkey_hash_of(&private_keys[0]).as_bytes());
+    }
+
+    #[test]
+    fn test_signer_requires_keys() {
+        for lock in [FeeLockConfig::Sighash, FeeLockConfig::Omnilock] {
+            let err = FeeCellSigner::new(&runner_config(lock), &[])
+                .err()
+                .expect("no keys");
+            assert!(err.to_string().contains("no keys"), "{}", err);
+        }
+    }
+
+    #[test]
+    fn test_local_backend_signs_sighash_fee_cell() {
+        let private_key = generate_private_key();
+        let signer = FeeCellSigner::new(
+            &runner_config(FeeLockConfig::Sighash),
+            std::slice::from_ref(&private_key),
+        )
+        .expect("sighash signer");
+        assert_eq!(
+            signer.lock_script.args().raw_data(),
+            Bytes::from(pubkey_hash_of(&private_key).as_bytes().to_vec())
+        );
+
+        let tx = fee_cell_tx(&signer);
+        let mut script_group = ScriptGroup::from_lock_script(&signer.lock_script);
+        script_group.input_indices.push(0);
+        let unlocker = signer
+            .unlockers
+            .get(&ScriptId::new_type(SIGHASH_TYPE_HASH.clone()))
+            .expect("sighash unlocker");
+        let signed_tx = unlocker
+            .unlock(&tx, &script_group, &NoDependencies)
+            .expect("unlock");
+
+        // Sighash message covers tx hash and the witness with zeroed lock
+        let message = {
+            let witness = signer.placeholder_witness.as_bytes();
+            let mut hasher = ckb_hash::new_blake2b();
+            hasher.update(tx.hash().as_slice());
+            hasher.update(&(witness.len() as u64).to_le_bytes());
+            hasher.update(&witness);
+            let mut message = [0u8; 32];
+            hasher.finalize(&mut message);
+            message
+        };
+        let witness = WitnessArgs::from_slice(&signed_tx.witnesses().get(0).unwrap().raw_data())
+            .expect("witness");
+        let signature = witness.lock().to_opt().expect("signature").raw_data();
+        let recovery_id =
+            secp256k1::ecdsa::RecoveryId::from_i32(signature[64] as i32).expect("recovery id");
+        let signature =
+            secp256k1::ecdsa::RecoverableSignature::from_compact(&signature[0..64], recovery_id)
+                .expect("signature");
+        let pubkey = SECP256K1
+            .recover_ecdsa(
+                &secp256k1::Message::from_slice(&message).unwrap(),
+                &signature,
+            )
+            .expect("recover");
+        assert_eq!(
+            &blake2b_256(&pubkey.serialize()[..])[0..20],
+            pubkey_hash_of(&private_key).as_bytes()
+        );
+    }
+
+    // Serves a single JSON-RPC request on a unix socket with +response+,
+    // returning the request received.
+    fn serve_once(name: &str, response: JsonValue) -> (PathBuf, thread::JoinHandle<JsonValue>) {
+        let path =
+            std::env::temp_dir().join(format!("dex1-signer-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let listener = UnixListener::bind(&path).expect("bind");
+        let handle = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().expect("accept");
+            let mut data = Vec::new();
+            stream.read_to_end(&mut data).expect("read");
+            stream
+                .write_all(&serde_json::to_vec(&response).unwrap())
+                .expect("write");
+            serde_json::from_slice(&data).expect("request")
+        });
+        (path, handle)
+    }
+
+    #[test]
+    fn test_external_signer_forwards_requests() {
+        let pubkey_hash = H160::from([7u8; 20]);
+        let signature = vec![9u8; 65];
+        let (path, handle) = serve_once(
+            "ok",
+            json!({"jsonrpc": "2.0", "id": 1, "result": format!("0x{}", hex::encode(&signature))}),
+        );
+        let signer = ExternalSigner::new(
+            &format!("unix://{}", path.display()),
+            vec![pubkey_hash.clone()],
+        );
+        let tx = TransactionView::new_advanced_builder().build();
+
+        // Keys not held by the external signer are never requested
+        assert!(matches!(
+            signer.sign(&[8u8; 20], &[0u8; 32], true, &tx),
+            Err(SignerError::IdNotFound)
+        ));
+        let result = signer
+            .sign(pubkey_hash.as_bytes(), &[3u8; 32], true, &tx)
+            .expect("sign");
+        assert_eq!(result, Bytes::from(signature));
+
+        let request = handle.join().unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(request["method"], "sign");
+        assert_eq!(
+            request["params"][0],
+            format!("0x{}", hex::encode(pubkey_hash.as_bytes()))
+        );
+        assert_eq!(
+            request["params"][1],
+            format!("0x{}", hex::encode([3u8; 32]))
+        );
+        assert_eq!(request["params"][2], true);
+        assert_eq!(request["params"][3]["hash"], format!("{:#x}", tx.hash()));
+    }
+
+    #[test]
+    fn test_external_signer_error_is_returned() {
+        let pubkey_hash = H160::from([7u8; 20]);
+        let (path, handle) = serve_once(
+            "error",
+            json!({"jsonrpc": "2.0", "id": 1, "error": {"code": -1, "message": "policy violated"}}),
+        );
+        let signer = ExternalSigner::new(
+            &format!("unix://{}", path.display()),
+            vec![pubkey_hash.clone()],
+        );
+        let tx = TransactionView::new_advanced_builder().build();
+        let err = signer
+            .sign(pubkey_hash.as_bytes(), &[3u8; 32], true, &tx)
+            .unwrap_err();
+        handle.join().unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert!(err.to_string().contains("policy violated"), "{}", err);
+    }
+}
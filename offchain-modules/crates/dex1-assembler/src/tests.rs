@@ -0,0 +1,2426 @@
+use crate::{
+    config::{
+        Config, FullScript, MatchingConfig, PackingConfig, RingConfig, SelfTradeConfig,
+        SurplusConfig, SurplusDustConfig, TokenConfig, TokenStandard, TradingPair, TradingRules,
+    },
+    keystore::{generate_private_key, pubkey_hash},
+    schemas::{basic, dex1, top_level},
+    seals::otx_signing_message,
+    BaseTx, Dex1, OrderPriority, ParsedData, RichOtx, NFT_SELL,
+};
+use ckb_sdk::{traits::LiveCell, types::omni_lock::OmniLockWitnessLock, SECP256K1};
+use ckb_types::{
+    bytes::Bytes,
+    core::{ScriptHashType, TransactionView},
+    packed::{
+        self, Byte32, Byte32Vec, CellInput, CellOutput, OutPoint, Script, Transaction, WitnessArgs,
+    },
+    prelude::*,
+};
+use otx_traits::{Assembler, MapEmitter, ReduceEmitter, ReduceSource, Value};
+use rand::{rngs::StdRng, thread_rng, Rng, SeedableRng};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+// Otxs kept in memory, iterated in the assembler's order within each key
+#[derive(Default)]
+struct MemorySource {
+    data: HashMap<[u8; 65], BTreeMap<OrderPriority, Vec<ParsedData>>>,
+}
+
+impl MemorySource {
+    fn insert_otx(&mut self, key: [u8; 65], order: OrderPriority, value: ParsedData) {
+        self.data
+            .entry(key)
+            .or_default()
+            .entry(order)
+            .or_default()
+            .push(value);
+    }
+
+    fn purge_otxs(&mut self, out_points: &HashSet<OutPoint>) {
+        for otxs in self.data.values_mut() {
+            for values in otxs.values_mut() {
+                values.retain(|value| !value.spent(out_points));
+            }
+            otxs.retain(|_, values| !values.is_empty());
+        }
+    }
+
+    fn values(&self) -> impl Iterator<Item = &ParsedData> {
+        self.data.values().flat_map(|otxs| otxs.values().flatten())
+    }
+}
+
+impl ReduceSource<[u8; 65], ParsedData> for MemorySource {
+    fn otxs<'a>(&'a self, key: [u8; 65]) -> impl Iterator<Item = ParsedData>
+    where
+        ParsedData: 'a,
+    {
+        self.data
+            .get(&key)
+            .into_iter()
+            .flat_map(|otxs| otxs.values().flatten())
+            .cloned()
+    }
+}
+
+#[derive(Default)]
+struct MemoryEmitter {
+    otxs: Vec<([u8; 65], OrderPriority, ParsedData)>,
+    rejected_otxs: Vec<Transaction>,
+    txs: Vec<(Transaction, <Dex1 as Assembler>::PostValue)>,
+}
+
+impl MapEmitter<[u8; 65], OrderPriority, ParsedData> for MemoryEmitter {
+    fn emit(
+        &mut self,
+        key: [u8; 65],
+        order: OrderPriority,
+        value: ParsedData,
+    ) -> anyhow::Result<()> {
+        self.otxs.push((key, order, value));
+        Ok(())
+    }
+}
+
+impl ReduceEmitter<[u8; 65], OrderPriority, ParsedData, <Dex1 as Assembler>::PostValue>
+    for MemoryEmitter
+{
+    fn reject_otx(&mut self, otx: Transaction) -> anyhow::Result<()> {
+        self.rejected_otxs.push(otx);
+        Ok(())
+    }
+
+    fn emit_tx(
+        &mut self,
+        tx: Transaction,
+        post_value: <Dex1 as Assembler>::PostValue,
+    ) -> anyhow::Result<()> {
+        self.txs.push((tx, post_value));
+        Ok(())
+    }
+}
+
+fn random_script(rng: &mut StdRng) -> Script {
+    let mut code_hash = [0u8; 32];
+    rng.fill(&mut code_hash);
+    let mut args = [0u8; 32];
+    rng.fill(&mut args);
+    Script::new_builder()
+        .code_hash(code_hash.pack())
+        .hash_type(ScriptHashType::Type.into())
+        .args(Bytes::from(args.to_vec()).pack())
+        .build()
+}
+
+fn random_out_point(rng: &mut StdRng) -> OutPoint {
+    let mut data = [0u8; 32];
+    rng.fill(&mut data);
+    OutPoint::new(Byte32::new(data), rng.gen())
+}
+
+fn full_script(script: &Script) -> FullScript {
+    FullScript {
+        script: script.clone().into(),
+        cell_dep: Default::default(),
+    }
+}
+
+struct OrderBook {
+    config: Config,
+    dex1: Dex1,
+    dex1_script: Script,
+    first_token: Script,
+    second_token: Script,
+    tokens: Vec<Script>,
+    source: MemorySource,
+    // Keys sealing otxs that spend omnilock cells, by lock hash
+    sealing_keys: HashMap<Byte32, secp256k1::SecretKey>,
+}
+
+impl OrderBook {
+    fn new(rng: &mut StdRng, matching: MatchingConfig) -> Self {
+        Self::new_with_surplus(rng, matching, SurplusConfig::default())
+    }
+
+    fn new_with_surplus(
+        rng: &mut StdRng,
+        matching: MatchingConfig,
+        surplus: SurplusConfig,
+    ) -> Self {
+        Self::new_with_pairs(
+            rng,
+            2,
+            &[(0, 1)],
+            matching,
+            surplus,
+            PackingConfig::default(),
+        )
+    }
+
+    // Creates an order book of +token_count+ tokens, trading +pairs+ of
+    // token indices.
+    fn new_with_pairs(
+        rng: &mut StdRng,
+        token_count: usize,
+        pairs: &[(usize, usize)],
+        matching: MatchingConfig,
+        surplus: SurplusConfig,
+        packing: PackingConfig,
+    ) -> Self {
+        let dex1_script = random_script(rng);
+        let tokens: Vec<_> = (0..token_count).map(|_| random_script(rng)).collect();
+        let config = Config {
+            dex1_deployment: full_script(&dex1_script),
+            pairs: pairs
+                .iter()
+                .map(|(first, second)| TradingPair {
+                    first: full_script(&tokens[*first]),
+                    second: full_script(&tokens[*second]),
+                    rules: TradingRules::default(),
+                })
+                .collect(),
+            omnilock: full_script(&random_script(rng)),
+            matching,
+            surplus,
+            surplus_dust: None,
+            rings: RingConfig::default(),
+            packing,
+            self_trade: SelfTradeConfig::default(),
+            oracles: Vec::new(),
+            brokers: Vec::new(),
+            tokens: Vec::new(),
+        };
+        Self {
+            dex1: Dex1::new(&config),
+            config,
+            dex1_script,
+            first_token: tokens[0].clone(),
+            second_token: tokens[1].clone(),
+            tokens,
+            source: MemorySource::default(),
+            sealing_keys: HashMap::new(),
+        }
+    }
+
+    fn key(&self, suffix: u8) -> [u8; 65] {
+        let mut key = self.dex1.keys()[0];
+        key[64] = suffix;
+        key
+    }
+
+    // Submits an otx bidding +bid_amount+ of the first token(or the second
+    // one when +bid_first+ is false), asking for +ask_amount+ of the other.
+    fn submit(
+        &mut self,
+        rng: &mut StdRng,
+        bid_first: bool,
+        bid_amount: u128,
+        ask_amount: u128,
+    ) -> (OutPoint, Script) {
+        let (bid_token, ask_token) = if bid_first {
+            (self.first_token.clone(), self.second_token.clone())
+        } else {
+            (self.second_token.clone(), self.first_token.clone())
+        };
+        self.submit_tokens(rng, &bid_token, &ask_token, bid_amount, ask_amount)
+    }
+
+    fn submit_tokens(
+        &mut self,
+        rng: &mut StdRng,
+        bid_token: &Script,
+        ask_token: &Script,
+        bid_amount: u128,
+        ask_amount: u128,
+    ) -> (OutPoint, Script) {
+        let recipient = random_script(rng);
+        self.submit_as(
+            rng, &recipient, bid_token, ask_token, bid_amount, ask_amount,
+        )
+    }
+
+    // Submits an otx of +recipient+, whose input cell also uses +recipient+
+    // as the lock.
+    fn submit_as(
+        &mut self,
+        rng: &mut StdRng,
+        recipient: &Script,
+        bid_token: &Script,
+        ask_token: &Script,
+        bid_amount: u128,
+        ask_amount: u128,
+    ) -> (OutPoint, Script) {
+        self.try_submit_as(rng, recipient, bid_token, ask_token, bid_amount, ask_amount)
+            .expect("map")
+    }
+
+    fn try_submit_as(
+        &mut self,
+        rng: &mut StdRng,
+        recipient: &Script,
+        bid_token: &Script,
+        ask_token: &Script,
+        bid_amount: u128,
+        ask_amount: u128,
+    ) -> anyhow::Result<(OutPoint, Script)> {
+        self.try_submit_order(
+            rng,
+            recipient,
+            bid_token,
+            ask_token,
+            bid_amount,
+            ask_amount,
+            |limit_order| dex1::Order::new_builder().set(limit_order).build(),
+        )
+    }
+
+    // Submits an otx whose limit order is wrapped into an order variant by
+    // +wrap+.
+    #[allow(clippy::too_many_arguments)]
+    fn try_submit_order<F>(
+        &mut self,
+        rng: &mut StdRng,
+        recipient: &Script,
+        bid_token: &Script,
+        ask_token: &Script,
+        bid_amount: u128,
+        ask_amount: u128,
+        wrap: F,
+    ) -> anyhow::Result<(OutPoint, Script)>
+    where
+        F: FnOnce(dex1::LimitOrder) -> dex1::Order,
+    {
+        let recipient = recipient.clone();
+        // Bid CKB is provided on top of CKBytes
+        let (input, input_data) = if *bid_token == Script::default() {
+            (
+                CellOutput::new_builder()
+                    .lock(recipient.clone())
+                    .capacity((2000_0000_0000u64 + bid_amount as u64).pack())
+                    .build(),
+                Bytes::default(),
+            )
+        } else {
+            (
+                CellOutput::new_builder()
+                    .lock(recipient.clone())
+                    .type_(Some(bid_token.clone()).pack())
+                    .capacity(2000_0000_0000u64.pack())
+                    .build(),
+                Bytes::from(bid_amount.to_le_bytes().to_vec()),
+            )
+        };
+        let limit_order = dex1::LimitOrder::new_builder()
+            .bid_token(token_hash(bid_token))
+            .bid_amount(bid_amount.pack())
+            .ask_token(token_hash(ask_token))
+            .ask_amount(ask_amount.pack())
+            .recipient(recipient.calc_script_hash())
+            .claimed_ckbytes(1000_0000_0000u64.pack())
+            .build();
+        let out_point = self.try_submit_otx(rng, (input, input_data), wrap(limit_order))?;
+        Ok((out_point, recipient))
+    }
+
+    // Submits an otx spending +input+, carrying +order+.
+    fn try_submit_otx(
+        &mut self,
+        rng: &mut StdRng,
+        input: (CellOutput, Bytes),
+        order: dex1::Order,
+    ) -> anyhow::Result<OutPoint> {
+        self.try_submit_otx_with_outputs(rng, input, Vec::new(), order)
+    }
+
+    // Submits an otx spending +input+ and creating +outputs+, carrying
+    // +order+.
+    fn try_submit_otx_with_outputs(
+        &mut self,
+        rng: &mut StdRng,
+        input: (CellOutput, Bytes),
+        outputs: Vec<(CellOutput, Bytes)>,
+        order: dex1::Order,
+    ) -> anyhow::Result<OutPoint> {
+        let out_point = random_out_point(rng);
+        let dex1_action = dex1::Dex1Action::new_builder()
+            .orders(dex1::Orders::new_builder().push(order).build())
+            .build();
+        let action = basic::Action::new_builder()
+            .data(dex1_action.as_bytes().pack())
+            .script_hash(self.dex1_script.calc_script_hash())
+            .build();
+        let message = basic::Message::new_builder()
+            .actions(basic::ActionVec::new_builder().push(action).build())
+            .build();
+        let otx = basic::Otx::new_builder()
+            .message(message)
+            .input_cells(1u32.pack())
+            .output_cells((outputs.len() as u32).pack())
+            .build();
+        let tx = TransactionView::new_advanced_builder()
+            .input(
+                CellInput::new_builder()
+                    .previous_output(out_point.clone())
+                    .build(),
+            )
+            .outputs(outputs.iter().map(|(output, _)| output.clone()))
+            .outputs_data(outputs.iter().map(|(_, data)| data.pack()))
+            .build();
+        let lock_hash = input.0.lock().calc_script_hash();
+        let otx = match self.sealing_keys.get(&lock_hash) {
+            Some(secret_key) => {
+                let message = otx_signing_message(&otx, &tx.data(), std::slice::from_ref(&input));
+                let seal = basic::SealPair::new_builder()
+                    .script_hash(lock_hash)
+                    .seal(omnilock_seal(secret_key, &message).pack())
+                    .build();
+                otx.as_builder()
+                    .seals(basic::SealPairVec::new_builder().push(seal).build())
+                    .build()
+            }
+            None => otx,
+        };
+        let witness = top_level::WitnessLayout::new_builder()
+            .set(otx)
+            .build()
+            .as_bytes();
+        let tx = tx.as_advanced_builder().witness(witness.pack()).build();
+
+        let mut emitter = MemoryEmitter::default();
+        self.dex1.map(
+            RichOtx {
+                tx: tx.data(),
+                inputs: vec![input],
+            },
+            &mut emitter,
+        )?;
+        for (key, order, value) in emitter.otxs {
+            self.source.insert_otx(key, order, value);
+        }
+        Ok(out_point)
+    }
+
+    // Omnilock using secp256k1 signature of a new key, otxs spending cells
+    // of it are sealed by +sealing_key+, or the new key when it is not given.
+    fn omnilock(&mut self, sealing_key: Option<secp256k1::SecretKey>) -> Script {
+        let private_key = generate_private_key();
+        let mut args = vec![0u8];
+        args.extend_from_slice(pubkey_hash(&private_key).unwrap().as_bytes());
+        args.push(0);
+        let lock = Script::from(self.config.omnilock.script.clone())
+            .as_builder()
+            .args(Bytes::from(args).pack())
+            .build();
+        let sealing_key = sealing_key
+            .unwrap_or_else(|| secp256k1::SecretKey::from_slice(private_key.as_bytes()).unwrap());
+        self.sealing_keys
+            .insert(lock.calc_script_hash(), sealing_key);
+        lock
+    }
+
+    fn reduce(&self, rng: &mut StdRng) -> MemoryEmitter {
+        self.reduce_at(rng, 0, 0)
+    }
+
+    fn reduce_key(&self, rng: &mut StdRng, key: [u8; 65]) -> MemoryEmitter {
+        self.reduce_key_at(rng, key, 0, 0)
+    }
+
+    fn reduce_at(
+        &self,
+        rng: &mut StdRng,
+        block_number: u64,
+        expired_block_number: u64,
+    ) -> MemoryEmitter {
+        self.reduce_key_at(rng, self.dex1.keys()[0], block_number, expired_block_number)
+    }
+
+    // Reduces +key+ against a dex1 entity cell committed at +block_number+,
+    // treating orders with deadline before +expired_block_number+ as expired.
+    fn reduce_key_at(
+        &self,
+        rng: &mut StdRng,
+        key: [u8; 65],
+        block_number: u64,
+        expired_block_number: u64,
+    ) -> MemoryEmitter {
+        let dex1_cell = CellOutput::new_builder()
+            .lock(self.dex1_script.clone())
+            .build();
+        let base_tx = TransactionView::new_advanced_builder()
+            .input(
+                CellInput::new_builder()
+                    .previous_output(random_out_point(rng))
+                    .build(),
+            )
+            .output(dex1_cell)
+            .output_data(Bytes::default().pack())
+            .build();
+        let mut emitter = MemoryEmitter::default();
+        self.dex1
+            .reduce(
+                BaseTx {
+                    tx: RichOtx {
+                        tx: base_tx.data(),
+                        inputs: Vec::new(),
+                    },
+                    block_number,
+                    expired_block_number,
+                },
+                key,
+                &mut emitter,
+                &self.source,
+            )
+            .expect("reduce");
+        emitter
+    }
+}
+
+// Omnilock seal of a secp256k1 signature on +message+
+fn omnilock_seal(secret_key: &secp256k1::SecretKey, message: &[u8; 32]) -> Bytes {
+    let message = secp256k1::Message::from_slice(message).unwrap();
+    let (recovery_id, data) = SECP256K1
+        .sign_ecdsa_recoverable(&message, secret_key)
+        .serialize_compact();
+    let mut signature = data.to_vec();
+    signature.push(recovery_id.to_i32() as u8);
+    let witness_lock = OmniLockWitnessLock::new_builder()
+        .signature(Some(Bytes::from(signature)).pack())
+        .build();
+    let mut seal = vec![0u8];
+    seal.extend_from_slice(witness_lock.as_slice());
+    Bytes::from(seal)
+}
+
+// Token amounts paid to cells using +recipient+ lock
+fn paid_amounts(tx: &Transaction, recipient: &Script) -> Vec<u128> {
+    tx.raw()
+        .outputs()
+        .into_iter()
+        .zip(tx.raw().outputs_data())
+        .filter(|(output, _)| output.lock() == *recipient)
+        .map(|(_, data)| {
+            let mut amount = [0u8; 16];
+            amount.copy_from_slice(&data.raw_data()[0..16]);
+            u128::from_le_bytes(amount)
+        })
+        .collect()
+}
+
+// Native CKB is represented by a default script, using the all-zero hash
+fn token_hash(token: &Script) -> Byte32 {
+    if *token == Script::default() {
+        Byte32::default()
+    } else {
+        token.calc_script_hash()
+    }
+}
+
+fn input_out_points(tx: &Transaction) -> HashSet<OutPoint> {
+    tx.raw()
+        .inputs()
+        .into_iter()
+        .map(|cell_input| cell_input.previous_output())
+        .collect()
+}
+
+fn new_rng() -> StdRng {
+    let seed: u64 = {
+        let mut rng = thread_rng();
+        rng.gen()
+    };
+    println!("Seed: {}", seed);
+    StdRng::seed_from_u64(seed)
+}
+
+#[test]
+fn test_same_price_orders_fill_in_arrival_order() {
+    let mut rng = new_rng();
+    let mut book = OrderBook::new(&mut rng, MatchingConfig::Continuous);
+
+    let sells: Vec<_> = (0..3)
+        .map(|_| book.submit(&mut rng, true, 10, 20).0)
+        .collect();
+    let buy = book.submit(&mut rng, false, 20, 10).0;
+
+    let emitter = book.reduce(&mut rng);
+    assert!(emitter.rejected_otxs.is_empty());
+    assert_eq!(emitter.txs.len(), 1);
+    let (tx, post_value) = &emitter.txs[0];
+    assert!(post_value.is_empty());
+    let out_points = input_out_points(tx);
+    assert!(out_points.contains(&buy));
+    assert!(out_points.contains(&sells[0]));
+    assert!(!out_points.contains(&sells[1]));
+    assert!(!out_points.contains(&sells[2]));
+}
+
+#[test]
+fn test_partial_fill_remainder_keeps_time_priority() {
+    let mut rng = new_rng();
+    let mut book = OrderBook::new(&mut rng, MatchingConfig::Continuous);
+
+    let sells: Vec<_> = (0..3)
+        .map(|_| book.submit(&mut rng, true, 10, 20).0)
+        .collect();
+    book.submit(&mut rng, false, 30, 15);
+    let sell_key = book.key(b'S');
+    let sequences: Vec<_> = book
+        .source
+        .otxs(sell_key)
+        .map(|parsed_data| parsed_data.sequence)
+        .collect();
+
+    let emitter = book.reduce(&mut rng);
+    assert_eq!(emitter.txs.len(), 1);
+    let (tx, post_value) = emitter.txs.into_iter().next().unwrap();
+    let out_points = input_out_points(&tx);
+    assert!(out_points.contains(&sells[0]));
+    assert!(out_points.contains(&sells[1]));
+    assert!(!out_points.contains(&sells[2]));
+
+    // The partially filled second sell order re-enters the order book as
+    // a freestanding order, still ahead of the third sell order.
+    let mut emitter = MemoryEmitter::default();
+    book.dex1
+        .postprocess(tx.clone(), post_value, &mut emitter)
+        .expect("postprocess");
+    assert_eq!(emitter.otxs.len(), 1);
+    for (key, order, value) in emitter.otxs {
+        assert_eq!(key, sell_key);
+        assert_eq!(order.sequence, sequences[1]);
+        book.source.insert_otx(key, order, value);
+    }
+    book.source.purge_otxs(&out_points);
+
+    let remaining: Vec<_> = book
+        .source
+        .otxs(sell_key)
+        .map(|parsed_data| (parsed_data.freestanding_cell, parsed_data.sequence))
+        .collect();
+    assert_eq!(remaining, vec![(true, sequences[1]), (false, sequences[2])]);
+}
+
+#[test]
+fn test_batch_auction_settles_at_uniform_price() {
+    let mut rng = new_rng();
+    let mut book = OrderBook::new(&mut rng, MatchingConfig::BatchAuction { batch_blocks: 10 });
+
+    // Sell orders asking 1 and 1.5 second token per first token, a buy order
+    // paying up to 2 second token per first token.
+    let (sell1, seller1) = book.submit(&mut rng, true, 10, 10);
+    let (sell2, seller2) = book.submit(&mut rng, true, 10, 15);
+    let (buy, buyer) = book.submit(&mut rng, false, 30, 15);
+    // Does not cross the clearing price
+    let (sell3, _) = book.submit(&mut rng, true, 10, 25);
+
+    let emitter = book.reduce(&mut rng);
+    assert_eq!(emitter.txs.len(), 1);
+    let (tx, post_value) = &emitter.txs[0];
+    assert!(post_value.is_empty());
+    let out_points = input_out_points(tx);
+    assert!(out_points.contains(&sell1));
+    assert!(out_points.contains(&sell2));
+    assert!(out_points.contains(&buy));
+    assert!(!out_points.contains(&sell3));
+
+    // All orders are settled at 1.5 second token per first token
+    assert_eq!(paid_amounts(tx, &seller1), vec![15]);
+    assert_eq!(paid_amounts(tx, &seller2), vec![15]);
+    assert_eq!(paid_amounts(tx, &buyer), vec![20]);
+}
+
+#[test]
+fn test_batch_auction_partially_fills_order_at_clearing_price() {
+    let mut rng = new_rng();
+    let mut book = OrderBook::new(&mut rng, MatchingConfig::BatchAuction { batch_blocks: 10 });
+
+    let (_, seller1) = book.submit(&mut rng, true, 10, 10);
+    let (_, seller2) = book.submit(&mut rng, true, 10, 20);
+    let (_, buyer) = book.submit(&mut rng, false, 30, 10);
+
+    let emitter = book.reduce(&mut rng);
+    assert_eq!(emitter.txs.len(), 1);
+    let (tx, post_value) = emitter.txs.into_iter().next().unwrap();
+
+    // Clearing price is 2 second token per first token, at which the buy
+    // order only takes half of the second sell order.
+    assert_eq!(paid_amounts(&tx, &buyer), vec![15]);
+    assert_eq!(paid_amounts(&tx, &seller1), vec![20]);
+    let (key, remaining) = post_value.into_iter().next().expect("partial fill");
+    assert_eq!(key, book.key(b'S'));
+    // Freestanding cell keeps 5 first token, 10 second token is paid
+    assert_eq!(paid_amounts(&tx, &seller2), vec![10]);
+    let remaining_order = match remaining.order.to_enum() {
+        dex1::OrderUnion::LimitOrder(o) => o,
+        _ => unreachable!(),
+    };
+    let remaining_bid: u128 = remaining_order.bid_amount().unpack();
+    let remaining_ask: u128 = remaining_order.ask_amount().unpack();
+    assert_eq!((remaining_bid, remaining_ask), (5, 10));
+}
+
+#[test]
+fn test_sequence_keeps_increasing_across_restarts() {
+    let mut rng = new_rng();
+    let mut book = OrderBook::new(&mut rng, MatchingConfig::Continuous);
+    book.submit(&mut rng, true, 10, 10);
+    // A restarted processor starts with a fresh assembler, a restart takes
+    // well over a microsecond.
+    std::thread::sleep(std::time::Duration::from_millis(1));
+    book.dex1 = Dex1::new(&book.config);
+    book.submit(&mut rng, true, 10, 10);
+
+    let sequences: Vec<_> = book
+        .source
+        .otxs(book.key(b'S'))
+        .map(|parsed_data| parsed_data.sequence)
+        .collect();
+    assert_eq!(sequences.len(), 2);
+    assert!(sequences[0] < sequences[1]);
+}
+
+#[test]
+fn test_surplus_is_routed_by_policy() {
+    let mut rng = new_rng();
+    let fee_lock = random_script(&mut rng);
+    // Sell order asks 1 second token per first token, buy order pays up to
+    // 1.5, leaving 5 second token as surplus.
+    for (surplus, seller_paid, buyer_refund, fee) in [
+        (SurplusConfig::Maker, vec![15], vec![], vec![]),
+        (SurplusConfig::Taker, vec![10], vec![5], vec![]),
+        (SurplusConfig::Split, vec![13], vec![2], vec![]),
+        (
+            SurplusConfig::ProtocolFee {
+                lock: fee_lock.clone().into(),
+            },
+            vec![10],
+            vec![],
+            vec![5],
+        ),
+    ] {
+        let mut book = OrderBook::new_with_surplus(&mut rng, MatchingConfig::Continuous, surplus);
+        let (_, seller) = book.submit(&mut rng, true, 10, 10);
+        let (_, buyer) = book.submit(&mut rng, false, 15, 10);
+
+        let emitter = book.reduce(&mut rng);
+        assert_eq!(emitter.txs.len(), 1);
+        let (tx, _) = &emitter.txs[0];
+        assert_eq!(paid_amounts(tx, &seller), seller_paid);
+        // Buyer's payment cell comes first, surplus cells are appended last
+        let mut expected_buyer = vec![10];
+        expected_buyer.extend(buyer_refund);
+        assert_eq!(paid_amounts(tx, &buyer), expected_buyer);
+        assert_eq!(paid_amounts(tx, &fee_lock), fee);
+    }
+}
+
+#[test]
+fn test_surplus_dust_goes_to_dust_cell() {
+    let mut rng = new_rng();
+    let dust_lock = random_script(&mut rng);
+    // The taker receives first token, 5 second token of surplus cannot be
+    // added to its payment cell.
+    for (threshold, buyer_refund, dust) in [(6, vec![], vec![5]), (5, vec![5], vec![])] {
+        let mut book =
+            OrderBook::new_with_surplus(&mut rng, MatchingConfig::Continuous, SurplusConfig::Taker);
+        book.config.surplus_dust = Some(SurplusDustConfig {
+            threshold,
+            lock: dust_lock.clone().into(),
+        });
+        book.dex1 = Dex1::new(&book.config);
+        let (_, seller) = book.submit(&mut rng, true, 10, 10);
+        let (_, buyer) = book.submit(&mut rng, false, 15, 10);
+
+        let emitter = book.reduce(&mut rng);
+        assert_eq!(emitter.txs.len(), 1);
+        let (tx, _) = &emitter.txs[0];
+        assert_eq!(paid_amounts(tx, &seller), vec![10]);
+        let mut expected_buyer = vec![10];
+        expected_buyer.extend(buyer_refund);
+        assert_eq!(paid_amounts(tx, &buyer), expected_buyer);
+        assert_eq!(paid_amounts(tx, &dust_lock), dust);
+    }
+}
+
+#[test]
+fn test_surplus_is_added_to_partially_filled_maker() {
+    let mut rng = new_rng();
+    let mut book = OrderBook::new(&mut rng, MatchingConfig::Continuous);
+    // The seller is partially filled by 10 first token at 1 second token
+    // each, the buyer pays up to 1.5.
+    let (_, seller) = book.submit(&mut rng, true, 20, 20);
+    let (_, buyer) = book.submit(&mut rng, false, 15, 10);
+
+    let emitter = book.reduce(&mut rng);
+    assert_eq!(emitter.txs.len(), 1);
+    let (tx, post_value) = &emitter.txs[0];
+    assert_eq!(paid_amounts(tx, &seller), vec![15]);
+    assert_eq!(paid_amounts(tx, &buyer), vec![10]);
+    // No surplus cell is created, the remaining order asks for less
+    assert_eq!(post_value.len(), 1);
+    let remaining = crate::limit_order(&post_value[0].1.order).expect("limit order");
+    let bid_amount: u128 = remaining.bid_amount().unpack();
+    let ask_amount: u128 = remaining.ask_amount().unpack();
+    assert_eq!((bid_amount, ask_amount), (10, 5));
+}
+
+#[test]
+fn test_ring_of_three_pairs_is_settled() {
+    let mut rng = new_rng();
+    let mut book = OrderBook::new_with_pairs(
+        &mut rng,
+        3,
+        &[(0, 1), (1, 2), (0, 2)],
+        MatchingConfig::Continuous,
+        SurplusConfig::default(),
+        PackingConfig::default(),
+    );
+    let tokens = book.tokens.clone();
+
+    // A -> B, B -> C and C -> A, none of them crosses within its own pair
+    let (_, alice) = book.submit_tokens(&mut rng, &tokens[0], &tokens[1], 10, 10);
+    let (_, bob) = book.submit_tokens(&mut rng, &tokens[1], &tokens[2], 10, 10);
+    let (_, carol) = book.submit_tokens(&mut rng, &tokens[2], &tokens[0], 20, 20);
+
+    let keys = book.dex1.keys();
+    assert_eq!(keys.len(), 4);
+    for key in &keys[0..3] {
+        assert!(book.reduce_key(&mut rng, *key).txs.is_empty());
+    }
+
+    let emitter = book.reduce_key(&mut rng, keys[3]);
+    assert_eq!(emitter.txs.len(), 1);
+    let (tx, post_value) = emitter.txs.into_iter().next().unwrap();
+    assert_eq!(paid_amounts(&tx, &alice), vec![10]);
+    assert_eq!(paid_amounts(&tx, &bob), vec![10]);
+
+    // Carol only gets the 10 A provided by Alice, the rest of the order
+    // stays in the order book.
+    let (_, remaining) = post_value.into_iter().next().expect("partial fill");
+    assert_eq!(paid_amounts(&tx, &carol), vec![10]);
+    let remaining_order = match remaining.order.to_enum() {
+        dex1::OrderUnion::LimitOrder(o) => o,
+        _ => unreachable!(),
+    };
+    let remaining_bid: u128 = remaining_order.bid_amount().unpack();
+    let remaining_ask: u128 = remaining_order.ask_amount().unpack();
+    assert_eq!((remaining_bid, remaining_ask), (10, 10));
+}
+
+#[test]
+fn test_orders_are_packed_within_cycle_limit() {
+    let mut rng = new_rng();
+    // Room for 3 orders besides the fee cell & dex1 scripts
+    let mut book = OrderBook::new_with_pairs(
+        &mut rng,
+        2,
+        &[(0, 1)],
+        MatchingConfig::Continuous,
+        SurplusConfig::default(),
+        PackingConfig {
+            max_tx_size: 100_000,
+            max_cycles: 4500,
+            default_lock_cycles: 1000,
+        },
+    );
+
+    let sells: Vec<_> = (0..4).map(|_| book.submit(&mut rng, true, 1, 1)).collect();
+    let buy = book.submit(&mut rng, false, 4, 4).0;
+
+    let emitter = book.reduce(&mut rng);
+    // Remaining orders are left to a second tx
+    assert_eq!(emitter.txs.len(), 2);
+    let (tx, post_value) = &emitter.txs[0];
+    assert!(!post_value.is_empty());
+    let out_points = input_out_points(tx);
+    assert!(out_points.contains(&buy));
+    assert!(out_points.contains(&sells[0].0));
+    assert!(out_points.contains(&sells[1].0));
+    assert!(!out_points.contains(&sells[2].0));
+
+    // Once verified locks turn out to be cheap, more orders are packed.
+    let verified_inputs: Vec<_> = sells[2..]
+        .iter()
+        .map(|(_, lock)| CellOutput::new_builder().lock(lock.clone()).build())
+        .collect();
+    book.dex1.record_cycles(&verified_inputs, 200);
+    let emitter = book.reduce(&mut rng);
+    let (tx, post_value) = &emitter.txs[0];
+    assert!(post_value.is_empty());
+    let out_points = input_out_points(tx);
+    assert!(sells
+        .iter()
+        .all(|(out_point, _)| out_points.contains(out_point)));
+}
+
+#[test]
+fn test_crossing_book_is_settled_in_chained_txs() {
+    let mut rng = new_rng();
+    // Room for 3 orders per tx
+    let mut book = OrderBook::new_with_pairs(
+        &mut rng,
+        2,
+        &[(0, 1)],
+        MatchingConfig::Continuous,
+        SurplusConfig::default(),
+        PackingConfig {
+            max_tx_size: 100_000,
+            max_cycles: 4500,
+            default_lock_cycles: 1000,
+        },
+    );
+
+    let sells: Vec<_> = (0..4)
+        .map(|_| book.submit(&mut rng, true, 1, 1).0)
+        .collect();
+    let (buy, buyer) = book.submit(&mut rng, false, 4, 4);
+
+    let emitter = book.reduce(&mut rng);
+    assert!(emitter.rejected_otxs.is_empty());
+    assert_eq!(emitter.txs.len(), 2);
+
+    let (tx1, post_value1) = &emitter.txs[0];
+    let out_points1 = input_out_points(tx1);
+    assert!(out_points1.contains(&buy));
+    assert!(out_points1.contains(&sells[0]));
+    assert!(out_points1.contains(&sells[1]));
+    let (key, _) = post_value1.first().expect("partial fill");
+    assert_eq!(*key, book.key(b'B'));
+
+    // The remaining buy order is filled by the second tx, spending the
+    // freestanding cell created by the first tx.
+    let (tx2, post_value2) = &emitter.txs[1];
+    assert!(post_value2.is_empty());
+    let out_points2 = input_out_points(tx2);
+    assert!(out_points2.contains(&sells[2]));
+    assert!(out_points2.contains(&sells[3]));
+    assert!(out_points2
+        .iter()
+        .any(|out_point| out_point.tx_hash() == tx1.calc_tx_hash()));
+    assert_eq!(paid_amounts(tx1, &buyer), vec![2]);
+    assert_eq!(paid_amounts(tx2, &buyer), vec![2]);
+}
+
+#[test]
+fn test_self_trade_is_prevented_by_mode() {
+    let mut rng = new_rng();
+    for mode in [
+        SelfTradeConfig::CancelNewest,
+        SelfTradeConfig::CancelOldest,
+        SelfTradeConfig::Skip,
+    ] {
+        let mut book = OrderBook::new(&mut rng, MatchingConfig::Continuous);
+        book.dex1 = Dex1::new(&Config {
+            self_trade: mode.clone(),
+            ..book.config.clone()
+        });
+        let (first_token, second_token) = (book.first_token.clone(), book.second_token.clone());
+        let owner = random_script(&mut rng);
+        let (own_sell, _) = book.submit_as(&mut rng, &owner, &first_token, &second_token, 10, 10);
+        let (other_sell, _) = book.submit(&mut rng, true, 10, 10);
+        let (own_buy, _) = book.submit_as(&mut rng, &owner, &second_token, &first_token, 10, 10);
+
+        let emitter = book.reduce(&mut rng);
+        let rejected: Vec<_> = emitter.rejected_otxs.iter().map(input_out_points).collect();
+        match mode {
+            // The buy order is newer than the sell order of the same owner
+            SelfTradeConfig::CancelNewest => {
+                assert_eq!(rejected, vec![HashSet::from([own_buy.clone()])]);
+                assert!(emitter.txs.is_empty());
+            }
+            SelfTradeConfig::CancelOldest => {
+                assert_eq!(rejected, vec![HashSet::from([own_sell.clone()])]);
+                assert_eq!(emitter.txs.len(), 1);
+                let out_points = input_out_points(&emitter.txs[0].0);
+                assert!(out_points.contains(&own_buy));
+                assert!(out_points.contains(&other_sell));
+                assert!(!out_points.contains(&own_sell));
+            }
+            SelfTradeConfig::Skip => {
+                assert!(rejected.is_empty());
+                assert!(emitter.txs.is_empty());
+            }
+        }
+    }
+}
+
+#[test]
+fn test_orders_violating_trading_rules_are_rejected() {
+    let mut rng = new_rng();
+    let mut book = OrderBook::new(&mut rng, MatchingConfig::Continuous);
+    book.config.pairs[0].rules = TradingRules {
+        min_size: Some(10),
+        min_notional: Some(20),
+        price_tick: Some(2),
+        lot_size: Some(5),
+    };
+    book.dex1 = Dex1::new(&book.config);
+    let (first_token, second_token) = (book.first_token.clone(), book.second_token.clone());
+
+    // Sell orders trading first token for second token
+    for (size, notional, error) in [
+        (5, 100, Some("below minimum size")),
+        (10, 10, Some("below minimum notional")),
+        (12, 24, Some("not a multiple of lot size")),
+        // 15 second token per lot
+        (10, 30, Some("not a multiple of price tick")),
+        (10, 40, None),
+    ] {
+        let recipient = random_script(&mut rng);
+        let result = book.try_submit_as(
+            &mut rng,
+            &recipient,
+            &first_token,
+            &second_token,
+            size,
+            notional,
+        );
+        match error {
+            Some(error) => assert!(result.unwrap_err().to_string().contains(error)),
+            None => assert!(result.is_ok()),
+        }
+    }
+    // Buy orders are measured the same way
+    let recipient = random_script(&mut rng);
+    assert!(book
+        .try_submit_as(&mut rng, &recipient, &second_token, &first_token, 30, 10)
+        .is_err());
+    assert!(book
+        .try_submit_as(&mut rng, &recipient, &second_token, &first_token, 40, 10)
+        .is_ok());
+}
+
+#[test]
+fn test_fill_limits_are_honoured() {
+    let mut rng = new_rng();
+    let first_token = |book: &OrderBook| book.first_token.clone();
+    let second_token = |book: &OrderBook| book.second_token.clone();
+
+    // A fill-or-kill sell order that can only be partially filled is
+    // cancelled, leaving the buy order in the book.
+    let mut book = OrderBook::new(&mut rng, MatchingConfig::Continuous);
+    let recipient = random_script(&mut rng);
+    let (fill_or_kill, _) = book
+        .try_submit_order(
+            &mut rng,
+            &recipient,
+            &first_token(&book),
+            &second_token(&book),
+            10,
+            20,
+            |order| {
+                dex1::Order::new_builder()
+                    .set(dex1::FillOrKillOrder::new_builder().order(order).build())
+                    .build()
+            },
+        )
+        .expect("map");
+    book.submit(&mut rng, false, 10, 5);
+    let emitter = book.reduce(&mut rng);
+    assert!(emitter.txs.is_empty());
+    assert_eq!(emitter.rejected_otxs.len(), 1);
+    assert!(input_out_points(&emitter.rejected_otxs[0]).contains(&fill_or_kill));
+
+    // A partially filled immediate-or-cancel sell order gets remaining bid
+    // tokens back instead of a freestanding cell.
+    let mut book = OrderBook::new(&mut rng, MatchingConfig::Continuous);
+    let recipient = random_script(&mut rng);
+    book.try_submit_order(
+        &mut rng,
+        &recipient,
+        &first_token(&book),
+        &second_token(&book),
+        10,
+        20,
+        |order| {
+            dex1::Order::new_builder()
+                .set(
+                    dex1::ImmediateOrCancelOrder::new_builder()
+                        .order(order)
+                        .build(),
+                )
+                .build()
+        },
+    )
+    .expect("map");
+    book.submit(&mut rng, false, 10, 5);
+    let emitter = book.reduce(&mut rng);
+    assert!(emitter.rejected_otxs.is_empty());
+    assert_eq!(emitter.txs.len(), 1);
+    let (tx, post_value) = &emitter.txs[0];
+    assert!(post_value.is_empty());
+    assert_eq!(paid_amounts(tx, &recipient), vec![5, 10]);
+
+    // A minimum fill buy order is only partially filled by at least the
+    // minimum amount, the remaining order keeps the minimum.
+    let mut book = OrderBook::new(&mut rng, MatchingConfig::Continuous);
+    let recipient = random_script(&mut rng);
+    let min_fill = |order| {
+        dex1::Order::new_builder()
+            .set(
+                dex1::MinFillLimitOrder::new_builder()
+                    .order(order)
+                    .min_fill(12u128.pack())
+                    .build(),
+            )
+            .build()
+    };
+    book.try_submit_order(
+        &mut rng,
+        &recipient,
+        &second_token(&book),
+        &first_token(&book),
+        20,
+        10,
+        min_fill,
+    )
+    .expect("map");
+    book.submit(&mut rng, true, 5, 10);
+    let emitter = book.reduce(&mut rng);
+    assert!(emitter.txs.is_empty());
+    assert!(emitter.rejected_otxs.is_empty());
+
+    book.submit(&mut rng, true, 3, 6);
+    let emitter = book.reduce(&mut rng);
+    assert_eq!(emitter.txs.len(), 1);
+    let (_, post_value) = &emitter.txs[0];
+    let (_, remaining) = post_value.first().expect("partial fill");
+    let dex1::OrderUnion::MinFillLimitOrder(order) = remaining.order.to_enum() else {
+        panic!("Remaining order is not a minimum fill order!");
+    };
+    let min_fill: u128 = order.min_fill().unpack();
+    let bid_amount: u128 = order.order().bid_amount().unpack();
+    assert_eq!(min_fill, 12);
+    assert_eq!(bid_amount, 4);
+}
+
+// Oracle cell of +oracle+ type script, valuing +base_amount+ of +base_token+
+// at +quote_amount+ of +quote_token+.
+fn oracle_cell(
+    rng: &mut StdRng,
+    oracle: &Script,
+    base_token: &Script,
+    quote_token: &Script,
+    base_amount: u128,
+    quote_amount: u128,
+) -> LiveCell {
+    let price = dex1::OraclePrice::new_builder()
+        .base_token(base_token.calc_script_hash())
+        .quote_token(quote_token.calc_script_hash())
+        .base_amount(base_amount.pack())
+        .quote_amount(quote_amount.pack())
+        .build();
+    LiveCell {
+        output: CellOutput::new_builder()
+            .lock(random_script(rng))
+            .type_(Some(oracle.clone()).pack())
+            .build(),
+        output_data: price.as_bytes(),
+        out_point: random_out_point(rng),
+        block_number: 0,
+        tx_index: 0,
+    }
+}
+
+#[test]
+fn test_stop_limit_order_waits_for_oracle_trigger() {
+    let mut rng = new_rng();
+    let mut book = OrderBook::new(&mut rng, MatchingConfig::Continuous);
+    let oracle = random_script(&mut rng);
+    book.dex1 = Dex1::new(&Config {
+        oracles: vec![oracle.clone().into()],
+        ..book.config.clone()
+    });
+    let (first_token, second_token) = (book.first_token.clone(), book.second_token.clone());
+
+    // Stop-loss sell order, activated once the first token is worth at most
+    // 2 second tokens.
+    let recipient = random_script(&mut rng);
+    let stop_order = |order| {
+        dex1::Order::new_builder()
+            .set(
+                dex1::StopLimitOrder::new_builder()
+                    .order(order)
+                    .oracle(oracle.calc_script_hash())
+                    .trigger_bid_amount(1u128.pack())
+                    .trigger_ask_amount(2u128.pack())
+                    .build(),
+            )
+            .build()
+    };
+    let (stop_sell, _) = book
+        .try_submit_order(
+            &mut rng,
+            &recipient,
+            &first_token,
+            &second_token,
+            10,
+            10,
+            stop_order,
+        )
+        .expect("map");
+    let (buy, _) = book.submit(&mut rng, false, 10, 10);
+
+    // No oracle price is known yet
+    let emitter = book.reduce(&mut rng);
+    assert!(emitter.txs.is_empty());
+    assert!(emitter.rejected_otxs.is_empty());
+
+    // First token is worth 3 second tokens
+    let cell = oracle_cell(&mut rng, &oracle, &first_token, &second_token, 1, 3);
+    book.dex1.update_oracles(&[cell]);
+    let emitter = book.reduce(&mut rng);
+    assert!(emitter.txs.is_empty());
+    assert!(emitter.rejected_otxs.is_empty());
+
+    // First token is worth 2 second tokens, priced the other way round
+    let cell = oracle_cell(&mut rng, &oracle, &second_token, &first_token, 2, 1);
+    let oracle_out_point = cell.out_point.clone();
+    book.dex1.update_oracles(&[cell]);
+    let emitter = book.reduce(&mut rng);
+    assert_eq!(emitter.txs.len(), 1);
+    let (tx, _) = &emitter.txs[0];
+    let out_points = input_out_points(tx);
+    assert!(out_points.contains(&stop_sell));
+    assert!(out_points.contains(&buy));
+    let cell_deps: Vec<_> = tx
+        .raw()
+        .cell_deps()
+        .into_iter()
+        .map(|cell_dep| cell_dep.out_point())
+        .collect();
+    assert_eq!(cell_deps, vec![oracle_out_point]);
+}
+
+#[test]
+fn test_iceberg_order_fills_displayed_part_per_tx() {
+    let mut rng = new_rng();
+    let mut book = OrderBook::new(&mut rng, MatchingConfig::Continuous);
+    let (first_token, second_token) = (book.first_token.clone(), book.second_token.clone());
+
+    // Sell 30 first tokens at 1, showing 10 at a time
+    let recipient = random_script(&mut rng);
+    let iceberg = |order| {
+        dex1::Order::new_builder()
+            .set(
+                dex1::IcebergOrder::new_builder()
+                    .order(order)
+                    .display_amount(10u128.pack())
+                    .build(),
+            )
+            .build()
+    };
+    book.try_submit_order(
+        &mut rng,
+        &recipient,
+        &first_token,
+        &second_token,
+        30,
+        30,
+        iceberg,
+    )
+    .expect("map");
+    book.submit(&mut rng, false, 25, 25);
+    let buy_sequence = book
+        .source
+        .otxs(book.key(b'B'))
+        .next()
+        .expect("buy order")
+        .sequence;
+
+    let emitter = book.reduce(&mut rng);
+    assert!(emitter.rejected_otxs.is_empty());
+    let paid: Vec<_> = emitter
+        .txs
+        .iter()
+        .map(|(tx, _)| paid_amounts(tx, &recipient))
+        .collect();
+    assert_eq!(paid, vec![vec![10], vec![10], vec![5]]);
+
+    // The hidden part re-enters the book behind orders already there
+    let (_, post_value) = &emitter.txs[0];
+    assert_eq!(post_value.len(), 2);
+    let (_, remaining) = post_value
+        .iter()
+        .find(|(key, _)| *key == book.key(b'S'))
+        .expect("iceberg remainder");
+    let dex1::OrderUnion::IcebergOrder(order) = remaining.order.to_enum() else {
+        panic!("Remaining order is not an iceberg order!");
+    };
+    let bid_amount: u128 = order.order().bid_amount().unpack();
+    let display_amount: u128 = order.display_amount().unpack();
+    assert_eq!(bid_amount, 20);
+    assert_eq!(display_amount, 10);
+    assert!(remaining.sequence > buy_sequence);
+}
+
+#[test]
+fn test_sliced_order_fills_one_slice_per_interval() {
+    let mut rng = new_rng();
+    let mut book = OrderBook::new(&mut rng, MatchingConfig::Continuous);
+    let (first_token, second_token) = (book.first_token.clone(), book.second_token.clone());
+
+    // Sell 30 first tokens at 1, in slices of 10 every 10 blocks
+    let recipient = random_script(&mut rng);
+    let sliced = |order| {
+        dex1::Order::new_builder()
+            .set(
+                dex1::SlicedOrder::new_builder()
+                    .order(order)
+                    .slice_amount(10u128.pack())
+                    .interval(10u64.pack())
+                    .not_before(0u64.pack())
+                    .build(),
+            )
+            .build()
+    };
+    book.try_submit_order(
+        &mut rng,
+        &recipient,
+        &first_token,
+        &second_token,
+        30,
+        30,
+        sliced,
+    )
+    .expect("map");
+    book.submit(&mut rng, false, 25, 25);
+
+    let emitter = book.reduce(&mut rng);
+    assert!(emitter.rejected_otxs.is_empty());
+    assert_eq!(emitter.txs.len(), 1);
+    let (tx, post_value) = emitter.txs.into_iter().next().unwrap();
+    assert_eq!(paid_amounts(&tx, &recipient), vec![10]);
+
+    let (_, remaining) = post_value
+        .iter()
+        .find(|(key, _)| *key == book.key(b'S'))
+        .expect("sliced remainder");
+    let dex1::OrderUnion::SlicedOrder(order) = remaining.order.to_enum() else {
+        panic!("Remaining order is not a sliced order!");
+    };
+    let bid_amount: u128 = order.order().bid_amount().unpack();
+    let not_before: u64 = order.not_before().unpack();
+    assert_eq!(bid_amount, 20);
+    assert_eq!(not_before, 10);
+
+    let mut emitter = MemoryEmitter::default();
+    book.dex1
+        .postprocess(tx.clone(), post_value, &mut emitter)
+        .expect("postprocess");
+    for (key, order, value) in emitter.otxs {
+        book.source.insert_otx(key, order, value);
+    }
+    book.source.purge_otxs(&input_out_points(&tx));
+
+    // The next slice is not matched before it is due, nor rejected
+    let emitter = book.reduce_at(&mut rng, 9, 0);
+    assert!(emitter.txs.is_empty());
+    assert!(emitter.rejected_otxs.is_empty());
+
+    let emitter = book.reduce_at(&mut rng, 10, 0);
+    assert_eq!(emitter.txs.len(), 1);
+    assert_eq!(paid_amounts(&emitter.txs[0].0, &recipient), vec![10]);
+}
+
+#[test]
+fn test_late_slice_schedules_next_slice_from_fill_block() {
+    let mut rng = new_rng();
+    let mut book = OrderBook::new(&mut rng, MatchingConfig::Continuous);
+    let (first_token, second_token) = (book.first_token.clone(), book.second_token.clone());
+
+    // Sell 30 first tokens at 1, in slices of 10 every 10 blocks, but the
+    // first slice is only filled at block 25.
+    let recipient = random_script(&mut rng);
+    let sliced = |order| {
+        dex1::Order::new_builder()
+            .set(
+                dex1::SlicedOrder::new_builder()
+                    .order(order)
+                    .slice_amount(10u128.pack())
+                    .interval(10u64.pack())
+                    .not_before(0u64.pack())
+                    .build(),
+            )
+            .build()
+    };
+    book.try_submit_order(
+        &mut rng,
+        &recipient,
+        &first_token,
+        &second_token,
+        30,
+        30,
+        sliced,
+    )
+    .expect("map");
+    book.submit(&mut rng, false, 25, 25);
+
+    let emitter = book.reduce_at(&mut rng, 25, 0);
+    // The remainder is not due right away, so no chained tx fills it
+    assert_eq!(emitter.txs.len(), 1);
+    let (tx, post_value) = emitter.txs.into_iter().next().unwrap();
+    let not_before = |post_value: &<Dex1 as Assembler>::PostValue| {
+        let (_, remaining) = post_value
+            .iter()
+            .find(|(key, _)| *key == book.key(b'S'))
+            .expect("sliced remainder");
+        let dex1::OrderUnion::SlicedOrder(order) = remaining.order.to_enum() else {
+            panic!("Remaining order is not a sliced order!");
+        };
+        Unpack::<u64>::unpack(&order.not_before())
+    };
+    assert_eq!(not_before(&post_value), 35);
+
+    // Sealed with a dex1 cell committed later, the next slice moves along
+    let (tx, post_value) = book.dex1.reschedule_slices(&tx, post_value, 30);
+    assert_eq!(not_before(&post_value), 40);
+
+    let mut emitter = MemoryEmitter::default();
+    book.dex1
+        .postprocess(tx.clone(), post_value, &mut emitter)
+        .expect("postprocess");
+    for (key, order, value) in emitter.otxs {
+        book.source.insert_otx(key, order, value);
+    }
+    book.source.purge_otxs(&input_out_points(&tx));
+
+    let emitter = book.reduce_at(&mut rng, 39, 0);
+    assert!(emitter.txs.is_empty());
+
+    let emitter = book.reduce_at(&mut rng, 40, 0);
+    assert_eq!(emitter.txs.len(), 1);
+    assert_eq!(paid_amounts(&emitter.txs[0].0, &recipient), vec![10]);
+}
+
+#[test]
+fn test_rescheduling_moves_slice_spent_by_chained_tx() {
+    let mut rng = new_rng();
+    let mut book = OrderBook::new(&mut rng, MatchingConfig::Continuous);
+    let (first_token, second_token) = (book.first_token.clone(), book.second_token.clone());
+
+    let recipient = random_script(&mut rng);
+    let sliced = |order| {
+        dex1::Order::new_builder()
+            .set(
+                dex1::SlicedOrder::new_builder()
+                    .order(order)
+                    .slice_amount(10u128.pack())
+                    .interval(10u64.pack())
+                    .not_before(0u64.pack())
+                    .build(),
+            )
+            .build()
+    };
+    book.try_submit_order(
+        &mut rng,
+        &recipient,
+        &first_token,
+        &second_token,
+        30,
+        30,
+        sliced,
+    )
+    .expect("map");
+    book.submit(&mut rng, false, 10, 10);
+    let emitter = book.reduce_at(&mut rng, 25, 0);
+    assert_eq!(emitter.txs.len(), 1);
+    let (tx, post_value) = emitter.txs.into_iter().next().unwrap();
+
+    // While the first tx is pending, the remainder becomes due and is
+    // filled by a chained tx.
+    let mut emitter = MemoryEmitter::default();
+    book.dex1
+        .postprocess(tx.clone(), post_value.clone(), &mut emitter)
+        .expect("postprocess");
+    book.source.purge_otxs(&input_out_points(&tx));
+    for (key, order, value) in emitter.otxs {
+        book.source.insert_otx(key, order, value);
+    }
+    book.submit(&mut rng, false, 10, 10);
+    let emitter = book.reduce_at(&mut rng, 35, 0);
+    assert_eq!(emitter.txs.len(), 1);
+    let chained_inputs = input_out_points(&emitter.txs[0].0);
+    let spent: Vec<_> = (0..tx.raw().outputs().len())
+        .filter(|i| chained_inputs.contains(&OutPoint::new(tx.calc_tx_hash(), *i as u32)))
+        .collect();
+    assert_eq!(spent.len(), 1);
+
+    // Sealed later, the first tx moves the remainder to a new lock, the
+    // chained tx no longer spends the cell it was built against.
+    let (rescheduled_tx, _) = book.dex1.reschedule_slices(&tx, post_value, 40);
+    let spent_lock = |tx: &Transaction| tx.raw().outputs().get(spent[0]).unwrap().lock();
+    assert_ne!(spent_lock(&rescheduled_tx), spent_lock(&tx));
+}
+
+#[test]
+fn test_native_ckb_is_traded_against_udt() {
+    let mut rng = new_rng();
+    let mut book = OrderBook::new(&mut rng, MatchingConfig::Continuous);
+    // CKB is the first token of the pair
+    book.first_token = Script::default();
+    book.tokens[0] = Script::default();
+    book.config.pairs[0].first = FullScript::default();
+    book.dex1 = Dex1::new(&book.config);
+    let (ckb, udt) = (book.first_token.clone(), book.second_token.clone());
+    let claimed_ckbytes = 1000_0000_0000u64;
+
+    // Sell 100 CKB at 0.5, partially filled by a buy order of 20 UDT
+    let seller = random_script(&mut rng);
+    book.submit_as(&mut rng, &seller, &ckb, &udt, 100_0000_0000, 50_0000_0000);
+    let buyer = random_script(&mut rng);
+    book.submit_as(&mut rng, &buyer, &udt, &ckb, 20_0000_0000, 40_0000_0000);
+
+    let emitter = book.reduce(&mut rng);
+    assert!(emitter.rejected_otxs.is_empty());
+    assert_eq!(emitter.txs.len(), 1);
+    let (tx, post_value) = emitter.txs.into_iter().next().unwrap();
+    assert_eq!(paid_amounts(&tx, &seller), vec![20_0000_0000]);
+
+    // Traded CKB is paid on top of claimed CKBytes, in a cell without type
+    let outputs: Vec<_> = tx
+        .raw()
+        .outputs()
+        .into_iter()
+        .zip(tx.raw().outputs_data())
+        .collect();
+    let (buyer_cell, buyer_data) = outputs
+        .iter()
+        .find(|(output, _)| output.lock() == buyer)
+        .expect("buyer payment");
+    assert!(buyer_cell.type_().is_none());
+    assert!(buyer_data.raw_data().is_empty());
+    let capacity: u64 = buyer_cell.capacity().unpack();
+    assert_eq!(capacity, claimed_ckbytes + 40_0000_0000);
+
+    // Remaining CKB is kept in the freestanding cell data, besides capacity
+    let (_, remaining) = post_value.first().expect("ckb remainder");
+    let order = remaining.order.to_enum();
+    let dex1::OrderUnion::LimitOrder(order) = order else {
+        panic!("Remaining order is not a limit order!");
+    };
+    let bid_amount: u128 = order.bid_amount().unpack();
+    let remaining_ckbytes: u64 = order.claimed_ckbytes().unpack();
+    assert_eq!(bid_amount, 60_0000_0000);
+    let (freestanding_cell, freestanding_data) = outputs
+        .iter()
+        .find(|(output, _)| {
+            output.lock().code_hash() == book.dex1_script.code_hash()
+                && output.lock().args().len() == 96
+        })
+        .expect("freestanding cell");
+    let capacity: u64 = freestanding_cell.capacity().unpack();
+    assert_eq!(capacity, remaining_ckbytes + 60_0000_0000);
+    assert_eq!(
+        freestanding_data.raw_data(),
+        Bytes::from(60_0000_0000u128.to_le_bytes().to_vec())
+    );
+
+    let mut emitter = MemoryEmitter::default();
+    book.dex1
+        .postprocess(tx, post_value, &mut emitter)
+        .expect("postprocess");
+    assert_eq!(emitter.otxs.len(), 1);
+}
+
+#[test]
+fn test_xudt_cells_keep_extension_data() {
+    let mut rng = new_rng();
+    let mut book = OrderBook::new(&mut rng, MatchingConfig::Continuous);
+    let (sudt, xudt) = (book.first_token.clone(), book.second_token.clone());
+    let extension_data = vec![0u8, 1, 2, 3];
+    book.config.tokens = vec![TokenConfig {
+        code_hash: xudt.code_hash().unpack(),
+        hash_type: ckb_jsonrpc_types::ScriptHashType::Type,
+        standard: TokenStandard::Xudt {
+            extension_data: ckb_jsonrpc_types::JsonBytes::from_vec(extension_data.clone()),
+        },
+    }];
+    book.dex1 = Dex1::new(&book.config);
+
+    // Buy 20 sUDT with up to 40 xUDT, partially filled by a sell order of 10
+    let buyer = random_script(&mut rng);
+    book.submit_as(&mut rng, &buyer, &xudt, &sudt, 40, 20);
+    let seller = random_script(&mut rng);
+    book.submit_as(&mut rng, &seller, &sudt, &xudt, 10, 20);
+
+    let emitter = book.reduce(&mut rng);
+    assert!(emitter.rejected_otxs.is_empty());
+    assert_eq!(emitter.txs.len(), 1);
+    let (tx, _) = &emitter.txs[0];
+    let xudt_data: Vec<_> = tx
+        .raw()
+        .outputs()
+        .into_iter()
+        .zip(tx.raw().outputs_data())
+        .filter(|(output, _)| output.type_().to_opt() == Some(xudt.clone()))
+        .map(|(_, data)| data.raw_data())
+        .collect();
+    // Freestanding cell of the buyer, and payment cell of the seller
+    let mut expected = Vec::new();
+    for amount in [20u128, 20] {
+        let mut data = amount.to_le_bytes().to_vec();
+        data.extend_from_slice(&extension_data);
+        expected.push(Bytes::from(data));
+    }
+    assert_eq!(xudt_data, expected);
+}
+
+impl OrderBook {
+    // Turns the first sell order, an otx order of 10 first token, into one
+    // kept in a freestanding cell.
+    fn keep_in_freestanding_cell(&mut self, rng: &mut StdRng) -> (OutPoint, ParsedData) {
+        let sell_key = self.key(b'S');
+        let mut parsed_data = self.source.otxs(sell_key).next().unwrap();
+        let freestanding_out_point = random_out_point(rng);
+        let freestanding_cell = CellOutput::new_builder()
+            .lock(
+                self.dex1
+                    .freestanding_lock(&parsed_data.recipient_script, &parsed_data.order),
+            )
+            .type_(Some(self.first_token.clone()).pack())
+            .capacity(1000_0000_0000u64.pack())
+            .build();
+        let freestanding_data = Bytes::from(10u128.to_le_bytes().to_vec());
+        parsed_data.tx = RichOtx {
+            tx: TransactionView::new_advanced_builder()
+                .input(
+                    CellInput::new_builder()
+                        .previous_output(freestanding_out_point.clone())
+                        .build(),
+                )
+                .build()
+                .data(),
+            inputs: vec![(freestanding_cell, freestanding_data)],
+        };
+        parsed_data.freestanding_cell = true;
+        self.source
+            .insert_otx(sell_key, parsed_data.priority(), parsed_data.clone());
+        (freestanding_out_point, parsed_data)
+    }
+
+    // Lists an NFT cell of +nft+ for +ask_amount+ of +token+
+    fn submit_nft_sell(
+        &mut self,
+        rng: &mut StdRng,
+        nft: &Script,
+        nft_data: &Bytes,
+        token: &Script,
+        ask_amount: u128,
+    ) -> anyhow::Result<Script> {
+        let seller = random_script(rng);
+        let sell_order = dex1::NftSellOrder::new_builder()
+            .nft(nft.calc_script_hash())
+            .ask_token(token_hash(token))
+            .ask_amount(ask_amount.pack())
+            .recipient(seller.calc_script_hash())
+            .claimed_ckbytes(1000_0000_0000u64.pack())
+            .build();
+        let nft_cell = CellOutput::new_builder()
+            .lock(seller.clone())
+            .type_(Some(nft.clone()).pack())
+            .capacity(2000_0000_0000u64.pack())
+            .build();
+        self.try_submit_otx(
+            rng,
+            (nft_cell, nft_data.clone()),
+            dex1::Order::new_builder().set(sell_order).build(),
+        )?;
+        Ok(seller)
+    }
+
+    // Bids +bid_amount+ of +token+ for +nft+
+    fn submit_nft_buy(
+        &mut self,
+        rng: &mut StdRng,
+        nft: &Script,
+        token: &Script,
+        bid_amount: u128,
+        claimed_ckbytes: u64,
+    ) -> anyhow::Result<Script> {
+        let buyer = random_script(rng);
+        let buy_order = dex1::NftBuyOrder::new_builder()
+            .nft(nft.calc_script_hash())
+            .bid_token(token_hash(token))
+            .bid_amount(bid_amount.pack())
+            .recipient(buyer.calc_script_hash())
+            .claimed_ckbytes(claimed_ckbytes.pack())
+            .build();
+        let input = CellOutput::new_builder()
+            .lock(buyer.clone())
+            .type_(Some(token.clone()).pack())
+            .capacity(2000_0000_0000u64.pack())
+            .build();
+        self.try_submit_otx(
+            rng,
+            (input, Bytes::from(bid_amount.to_le_bytes().to_vec())),
+            dex1::Order::new_builder().set(buy_order).build(),
+        )?;
+        Ok(buyer)
+    }
+
+    fn nft_key(&self, nft: &Script) -> [u8; 65] {
+        *self
+            .dex1
+            .keys()
+            .iter()
+            .find(|key| key[0..32] == nft.calc_script_hash().raw_data()[..])
+            .expect("nft key")
+    }
+}
+
+// Outputs of +tx+ using +lock+, along with their indices
+fn outputs_of(tx: &Transaction, lock: &Script) -> Vec<(usize, (CellOutput, packed::Bytes))> {
+    tx.raw()
+        .outputs()
+        .into_iter()
+        .zip(tx.raw().outputs_data())
+        .enumerate()
+        .filter(|(_, (output, _))| output.lock() == *lock)
+        .collect()
+}
+
+#[test]
+fn test_nft_is_sold_to_highest_bid_at_ask_price() {
+    let mut rng = new_rng();
+    let mut book = OrderBook::new(&mut rng, MatchingConfig::Continuous);
+    let udt = book.first_token.clone();
+    let nft = random_script(&mut rng);
+    let nft_data = Bytes::from(vec![7u8; 64]);
+    let claimed_ckbytes = 1000_0000_0000u64;
+
+    // List the NFT for 100 UDT
+    let seller = book
+        .submit_nft_sell(&mut rng, &nft, &nft_data, &udt, 100)
+        .expect("map");
+    // Bids of 90 and 120 UDT, only the higher one can buy the NFT
+    let buyers: Vec<_> = [90u128, 120]
+        .into_iter()
+        .map(|bid_amount| {
+            book.submit_nft_buy(&mut rng, &nft, &udt, bid_amount, claimed_ckbytes)
+                .expect("map")
+        })
+        .collect();
+
+    let emitter = book.reduce_key(&mut rng, book.nft_key(&nft));
+    assert!(emitter.rejected_otxs.is_empty());
+    assert_eq!(emitter.txs.len(), 1);
+    let (tx, _) = &emitter.txs[0];
+    assert_eq!(input_out_points(tx).len(), 3);
+    assert_eq!(paid_amounts(tx, &seller), vec![100]);
+    assert!(paid_amounts(tx, &buyers[0]).is_empty());
+
+    // The NFT cell keeps its data, the rest of the bid is refunded in the
+    // cell right after it.
+    let outputs = outputs_of(tx, &buyers[1]);
+    assert_eq!(outputs.len(), 2);
+    assert_eq!(outputs[0].0 + 1, outputs[1].0);
+    let outputs: Vec<_> = outputs.into_iter().map(|(_, output)| output).collect();
+    let (bought_cell, bought_data) = &outputs[0];
+    assert_eq!(bought_cell.type_().to_opt(), Some(nft.clone()));
+    assert_eq!(bought_data.raw_data(), nft_data);
+    assert_eq!(outputs[1].0.type_().to_opt(), Some(udt.clone()));
+    assert_eq!(
+        outputs[1].1.raw_data(),
+        Bytes::from(20u128.to_le_bytes().to_vec())
+    );
+    // Both cells are funded by claimed ckbytes of the buyer
+    let refund_capacity: u64 = outputs[1].0.capacity().unpack();
+    assert_eq!(
+        refund_capacity,
+        book.dex1.tokens.occupied_capacity(buyers[1].clone(), &udt)
+    );
+    let capacity: u64 = bought_cell.capacity().unpack();
+    assert_eq!(capacity + refund_capacity, claimed_ckbytes);
+}
+
+#[test]
+fn test_nft_refund_cell_is_funded_by_buyer() {
+    let mut rng = new_rng();
+    let mut book = OrderBook::new(&mut rng, MatchingConfig::Continuous);
+    let udt = book.first_token.clone();
+    let nft = random_script(&mut rng);
+    let nft_data = Bytes::from(vec![7u8; 64]);
+
+    // Claimed ckbytes cannot even hold the refund cell
+    let refund_capacity = book
+        .dex1
+        .tokens
+        .occupied_capacity(random_script(&mut rng), &udt);
+    let err = book
+        .submit_nft_buy(&mut rng, &nft, &udt, 100, refund_capacity - 1)
+        .unwrap_err();
+    assert!(err.to_string().contains("refund cell"), "{}", err);
+
+    // A bid matching the ask exactly gets no refund cell, the NFT cell keeps
+    // all claimed ckbytes.
+    book.submit_nft_sell(&mut rng, &nft, &nft_data, &udt, 100)
+        .expect("map");
+    let buyer = book
+        .submit_nft_buy(&mut rng, &nft, &udt, 100, 1000_0000_0000)
+        .expect("map");
+    let emitter = book.reduce_key(&mut rng, book.nft_key(&nft));
+    assert!(emitter.rejected_otxs.is_empty());
+    assert_eq!(emitter.txs.len(), 1);
+    let outputs = outputs_of(&emitter.txs[0].0, &buyer);
+    assert_eq!(outputs.len(), 1);
+    let (bought_cell, _) = &outputs[0].1;
+    assert_eq!(bought_cell.type_().to_opt(), Some(nft.clone()));
+    assert_eq!(
+        Unpack::<u64>::unpack(&bought_cell.capacity()),
+        1000_0000_0000
+    );
+}
+
+#[test]
+fn test_nft_sell_orders_without_nft_cell_are_rejected() {
+    let mut rng = new_rng();
+    let mut book = OrderBook::new(&mut rng, MatchingConfig::Continuous);
+    let udt = book.first_token.clone();
+    let nft = random_script(&mut rng);
+    let nft_data = Bytes::from(vec![7u8; 64]);
+
+    book.submit_nft_sell(&mut rng, &nft, &nft_data, &udt, 100)
+        .expect("map");
+    let seller = book
+        .submit_nft_sell(&mut rng, &nft, &nft_data, &udt, 100)
+        .expect("map");
+    let buyer = book
+        .submit_nft_buy(&mut rng, &nft, &udt, 100, 1000_0000_0000)
+        .expect("map");
+    // The first sell order loses its NFT cell
+    let mut sell_key = book.nft_key(&nft);
+    sell_key[64] = NFT_SELL;
+    let (_, sells) = book
+        .source
+        .data
+        .get_mut(&sell_key)
+        .and_then(|otxs| otxs.iter_mut().next())
+        .expect("sell otxs");
+    let invalid = sells[0].tx.tx.clone();
+    let (nft_cell, _) = &mut sells[0].tx.inputs[0];
+    *nft_cell = nft_cell
+        .clone()
+        .as_builder()
+        .type_(packed::ScriptOpt::default())
+        .build();
+
+    let emitter = book.reduce_key(&mut rng, book.nft_key(&nft));
+    assert_eq!(emitter.rejected_otxs.len(), 1);
+    assert_eq!(emitter.rejected_otxs[0].as_slice(), invalid.as_slice());
+    assert_eq!(emitter.txs.len(), 1);
+    let (tx, _) = &emitter.txs[0];
+    assert_eq!(paid_amounts(tx, &seller), vec![100]);
+    assert_eq!(outputs_of(tx, &buyer).len(), 1);
+}
+
+#[test]
+fn test_route_order_is_filled_across_pairs() {
+    let mut rng = new_rng();
+    let mut book = OrderBook::new_with_pairs(
+        &mut rng,
+        3,
+        &[(0, 1), (1, 2)],
+        MatchingConfig::Continuous,
+        SurplusConfig::default(),
+        PackingConfig::default(),
+    );
+    let tokens = book.tokens.clone();
+
+    // Liquidity of the first hop takes 100 of token 0 for 110 of token 1,
+    // the second order is partially filled.
+    let first_maker = random_script(&mut rng);
+    book.submit_as(&mut rng, &first_maker, &tokens[1], &tokens[0], 60, 50);
+    let second_maker = random_script(&mut rng);
+    book.submit_as(&mut rng, &second_maker, &tokens[1], &tokens[0], 100, 100);
+    // Liquidity of the second hop takes 110 of token 1 for 55 of token 2
+    let third_maker = random_script(&mut rng);
+    book.submit_as(&mut rng, &third_maker, &tokens[2], &tokens[1], 55, 110);
+
+    let trader = random_script(&mut rng);
+    let route_order = dex1::RouteOrder::new_builder()
+        .path(
+            Byte32Vec::new_builder()
+                .extend(tokens.iter().map(token_hash))
+                .build(),
+        )
+        .bid_amount(100u128.pack())
+        .minimum_ask(50u128.pack())
+        .recipient(trader.calc_script_hash())
+        .claimed_ckbytes(1000_0000_0000u64.pack())
+        .build();
+    let input = CellOutput::new_builder()
+        .lock(trader.clone())
+        .type_(Some(tokens[0].clone()).pack())
+        .capacity(2000_0000_0000u64.pack())
+        .build();
+    book.try_submit_otx(
+        &mut rng,
+        (input, Bytes::from(100u128.to_le_bytes().to_vec())),
+        dex1::Order::new_builder().set(route_order).build(),
+    )
+    .expect("map");
+
+    // Route orders are reduced after all pairs
+    let route_key = *book.dex1.keys().last().unwrap();
+    let emitter = book.reduce_key(&mut rng, route_key);
+    assert!(emitter.rejected_otxs.is_empty());
+    assert_eq!(emitter.txs.len(), 1);
+    let (tx, post_value) = &emitter.txs[0];
+    assert_eq!(input_out_points(tx).len(), 5);
+    assert_eq!(paid_amounts(tx, &trader), vec![55]);
+    assert_eq!(paid_amounts(tx, &first_maker), vec![50]);
+    assert_eq!(paid_amounts(tx, &second_maker), vec![50]);
+    assert_eq!(paid_amounts(tx, &third_maker), vec![110]);
+    // The remaining order of the second maker is kept in a freestanding cell
+    assert_eq!(post_value.len(), 1);
+    let dex1::OrderUnion::LimitOrder(remaining) = post_value[0].1.order.to_enum() else {
+        panic!("Remaining order is not a limit order!");
+    };
+    let bid_amount: u128 = remaining.bid_amount().unpack();
+    assert_eq!(bid_amount, 50);
+
+    // A route not reaching its minimum ask is left in the book
+    let mut book = OrderBook::new_with_pairs(
+        &mut rng,
+        2,
+        &[(0, 1)],
+        MatchingConfig::Continuous,
+        SurplusConfig::default(),
+        PackingConfig::default(),
+    );
+    let tokens = book.tokens.clone();
+    book.submit(&mut rng, false, 40, 100);
+    let route_order = dex1::RouteOrder::new_builder()
+        .path(
+            Byte32Vec::new_builder()
+                .extend(tokens.iter().map(token_hash))
+                .build(),
+        )
+        .bid_amount(100u128.pack())
+        .minimum_ask(50u128.pack())
+        .recipient(trader.calc_script_hash())
+        .claimed_ckbytes(1000_0000_0000u64.pack())
+        .build();
+    let input = CellOutput::new_builder()
+        .lock(trader.clone())
+        .type_(Some(tokens[0].clone()).pack())
+        .capacity(2000_0000_0000u64.pack())
+        .build();
+    book.try_submit_otx(
+        &mut rng,
+        (input, Bytes::from(100u128.to_le_bytes().to_vec())),
+        dex1::Order::new_builder().set(route_order).build(),
+    )
+    .expect("map");
+    let route_key = *book.dex1.keys().last().unwrap();
+    let emitter = book.reduce_key(&mut rng, route_key);
+    assert!(emitter.rejected_otxs.is_empty());
+    assert!(emitter.txs.is_empty());
+}
+
+#[test]
+fn test_brokered_order_pays_fee_to_broker() {
+    let mut rng = new_rng();
+    let mut book = OrderBook::new(&mut rng, MatchingConfig::Continuous);
+    let broker = random_script(&mut rng);
+    book.config.brokers = vec![broker.clone().into()];
+    book.dex1 = Dex1::new(&book.config);
+    let (first_token, second_token) = (book.first_token.clone(), book.second_token.clone());
+    let brokered = |broker: &Script| {
+        let broker = broker.calc_script_hash();
+        move |limit_order: dex1::LimitOrder| {
+            dex1::Order::new_builder()
+                .set(
+                    dex1::BrokeredLimitOrder::new_builder()
+                        .order(limit_order)
+                        .broker(broker)
+                        .fee_bps(250u32.pack())
+                        .build(),
+                )
+                .build()
+        }
+    };
+
+    // Only configured brokers can be paid
+    let recipient = random_script(&mut rng);
+    let stranger = random_script(&mut rng);
+    assert!(book
+        .try_submit_order(
+            &mut rng,
+            &recipient,
+            &second_token,
+            &first_token,
+            200,
+            100,
+            brokered(&stranger),
+        )
+        .is_err());
+    // Claimed ckbytes must fund the broker cell on top of settlement cells
+    let err = book
+        .try_submit_order(
+            &mut rng,
+            &recipient,
+            &second_token,
+            &first_token,
+            200,
+            100,
+            |limit_order| {
+                brokered(&broker)(
+                    limit_order
+                        .as_builder()
+                        .claimed_ckbytes(400_0000_0000u64.pack())
+                        .build(),
+                )
+            },
+        )
+        .unwrap_err();
+    assert!(err.to_string().contains("broker cell"), "{}", err);
+
+    // Buy 100 first token with 200 second token, partially filled by 40
+    book.try_submit_order(
+        &mut rng,
+        &recipient,
+        &second_token,
+        &first_token,
+        200,
+        100,
+        brokered(&broker),
+    )
+    .expect("map");
+    let seller = random_script(&mut rng);
+    book.submit_as(&mut rng, &seller, &first_token, &second_token, 40, 80);
+
+    let emitter = book.reduce(&mut rng);
+    assert!(emitter.rejected_otxs.is_empty());
+    assert_eq!(emitter.txs.len(), 1);
+    let (tx, post_value) = &emitter.txs[0];
+    // 2.5% of 40 is paid to the broker, right after the payment cell
+    assert_eq!(paid_amounts(tx, &recipient), vec![39]);
+    assert_eq!(paid_amounts(tx, &broker), vec![1]);
+    assert_eq!(paid_amounts(tx, &seller), vec![80]);
+    let locks: Vec<_> = tx.raw().outputs().into_iter().map(|o| o.lock()).collect();
+    let paid_index = locks.iter().position(|lock| *lock == recipient).unwrap();
+    assert_eq!(locks[paid_index + 1], broker);
+
+    // The remaining order still pays the broker, the broker cell is funded by
+    // claimed ckbytes of the order.
+    assert_eq!(post_value.len(), 1);
+    let dex1::OrderUnion::BrokeredLimitOrder(remaining) = post_value[0].1.order.to_enum() else {
+        panic!("Remaining order is not brokered!");
+    };
+    let bid_amount: u128 = remaining.order().bid_amount().unpack();
+    let ask_amount: u128 = remaining.order().ask_amount().unpack();
+    assert_eq!((bid_amount, ask_amount), (120, 60));
+    let capacity =
+        |index: usize| -> u64 { tx.raw().outputs().get(index).unwrap().capacity().unpack() };
+    let remaining_ckbytes: u64 = remaining.order().claimed_ckbytes().unpack();
+    assert_eq!(
+        remaining_ckbytes + capacity(paid_index) + capacity(paid_index + 1),
+        1000_0000_0000
+    );
+}
+
+#[test]
+fn test_brokered_order_skips_broker_cell_without_fee() {
+    let mut rng = new_rng();
+    let mut book = OrderBook::new(&mut rng, MatchingConfig::Continuous);
+    let broker = random_script(&mut rng);
+    book.config.brokers = vec![broker.clone().into()];
+    book.dex1 = Dex1::new(&book.config);
+    let (first_token, second_token) = (book.first_token.clone(), book.second_token.clone());
+    let broker_hash = broker.calc_script_hash();
+    let recipient = random_script(&mut rng);
+    book.try_submit_order(
+        &mut rng,
+        &recipient,
+        &second_token,
+        &first_token,
+        20,
+        10,
+        |limit_order| {
+            dex1::Order::new_builder()
+                .set(
+                    dex1::BrokeredLimitOrder::new_builder()
+                        .order(limit_order)
+                        .broker(broker_hash)
+                        .fee_bps(250u32.pack())
+                        .build(),
+                )
+                .build()
+        },
+    )
+    .expect("map");
+    let seller = random_script(&mut rng);
+    book.submit_as(&mut rng, &seller, &first_token, &second_token, 10, 20);
+
+    // 2.5% of 10 rounds down to 0, the recipient keeps all claimed ckbytes
+    let emitter = book.reduce(&mut rng);
+    assert!(emitter.rejected_otxs.is_empty());
+    assert_eq!(emitter.txs.len(), 1);
+    let (tx, _) = &emitter.txs[0];
+    assert_eq!(paid_amounts(tx, &recipient), vec![10]);
+    assert!(outputs_of(tx, &broker).is_empty());
+    let outputs = outputs_of(tx, &recipient);
+    assert_eq!(outputs.len(), 1);
+    let capacity: u64 = outputs[0].1 .0.capacity().unpack();
+    assert_eq!(capacity, 1000_0000_0000);
+}
+
+#[test]
+fn test_owned_order_keeps_freestanding_cell_for_owner() {
+    let mut rng = new_rng();
+    let mut book = OrderBook::new(&mut rng, MatchingConfig::Continuous);
+    let (first_token, second_token) = (book.first_token.clone(), book.second_token.clone());
+    let owner = random_script(&mut rng).calc_script_hash();
+    let owned = |limit_order| {
+        dex1::Order::new_builder()
+            .set(
+                dex1::OwnedLimitOrder::new_builder()
+                    .order(limit_order)
+                    .owner(owner.clone())
+                    .build(),
+            )
+            .build()
+    };
+
+    // Buy 100 first token with 200 second token, partially filled by 40
+    let recipient = random_script(&mut rng);
+    book.try_submit_order(
+        &mut rng,
+        &recipient,
+        &second_token,
+        &first_token,
+        200,
+        100,
+        owned,
+    )
+    .expect("map");
+    book.submit(&mut rng, true, 40, 80);
+
+    let emitter = book.reduce(&mut rng);
+    assert_eq!(emitter.txs.len(), 1);
+    let (tx, post_value) = emitter.txs.into_iter().next().unwrap();
+    // Filled tokens are paid to recipient, the freestanding cell is owned by
+    // the owner
+    assert_eq!(paid_amounts(&tx, &recipient), vec![40]);
+    let freestanding_cell = tx
+        .raw()
+        .outputs()
+        .into_iter()
+        .find(|output| {
+            output.lock().code_hash() == book.dex1_script.code_hash()
+                && output.lock().args().len() == 96
+        })
+        .expect("freestanding cell");
+    assert_eq!(
+        freestanding_cell.lock().args().raw_data()[32..64],
+        owner.raw_data()[..]
+    );
+    assert_eq!(post_value.len(), 1);
+    assert!(matches!(
+        post_value[0].1.order.to_enum(),
+        dex1::OrderUnion::OwnedLimitOrder(_)
+    ));
+
+    let mut emitter = MemoryEmitter::default();
+    book.dex1
+        .postprocess(tx, post_value, &mut emitter)
+        .expect("postprocess");
+    assert_eq!(emitter.otxs.len(), 1);
+}
+
+#[test]
+fn test_orders_near_deadline_are_skipped() {
+    let mut rng = new_rng();
+    let mut book = OrderBook::new(&mut rng, MatchingConfig::Continuous);
+    let (first_token, second_token) = (book.first_token.clone(), book.second_token.clone());
+    let with_deadline = |limit_order| {
+        dex1::Order::new_builder()
+            .set(
+                dex1::LimitOrderWithDeadline::new_builder()
+                    .order(limit_order)
+                    .deadline(200u64.pack())
+                    .build(),
+            )
+            .build()
+    };
+    let recipient = random_script(&mut rng);
+    book.try_submit_order(
+        &mut rng,
+        &recipient,
+        &first_token,
+        &second_token,
+        10,
+        10,
+        with_deadline,
+    )
+    .expect("map");
+    book.submit(&mut rng, false, 10, 10);
+
+    // Still valid when the tx lands as late as the expiry buffer allows
+    let emitter = book.reduce_at(&mut rng, 50, 150);
+    assert!(emitter.rejected_otxs.is_empty());
+    assert_eq!(emitter.txs.len(), 1);
+    assert_eq!(paid_amounts(&emitter.txs[0].0, &recipient), vec![10]);
+
+    // The tx might only land after the deadline, the order sits out this
+    // round but stays in the order book.
+    let emitter = book.reduce_at(&mut rng, 100, 200);
+    assert!(emitter.txs.is_empty());
+    assert!(emitter.rejected_otxs.is_empty());
+
+    // Past the deadline, the otx is rejected
+    let emitter = book.reduce_at(&mut rng, 200, 300);
+    assert!(emitter.txs.is_empty());
+    assert_eq!(emitter.rejected_otxs.len(), 1);
+}
+
+#[test]
+fn test_expired_freestanding_cells_are_swept_to_recipient() {
+    let mut rng = new_rng();
+    let mut book = OrderBook::new(&mut rng, MatchingConfig::Continuous);
+    let (first_token, second_token) = (book.first_token.clone(), book.second_token.clone());
+    let with_deadline = |limit_order| {
+        dex1::Order::new_builder()
+            .set(
+                dex1::LimitOrderWithDeadline::new_builder()
+                    .order(limit_order)
+                    .deadline(100u64.pack())
+                    .build(),
+            )
+            .build()
+    };
+    let recipient = random_script(&mut rng);
+    book.try_submit_order(
+        &mut rng,
+        &recipient,
+        &first_token,
+        &second_token,
+        10,
+        20,
+        with_deadline,
+    )
+    .expect("map");
+
+    let (freestanding_out_point, parsed_data) = book.keep_in_freestanding_cell(&mut rng);
+    let (freestanding_cell, freestanding_data) = parsed_data.tx.inputs[0].clone();
+
+    // Otx orders are never swept, neither are orders before their deadline
+    assert!(book
+        .dex1
+        .sweep_expired_orders(book.source.values(), 99, |_| None)
+        .is_none());
+    let tx = book
+        .dex1
+        .sweep_expired_orders(book.source.values(), 100, |_| None)
+        .expect("sweep");
+    assert_eq!(
+        input_out_points(&tx),
+        HashSet::from([freestanding_out_point])
+    );
+    let cell_input = tx.raw().inputs().get(0).unwrap();
+    assert_eq!(Unpack::<u64>::unpack(&cell_input.since()), 100);
+
+    // The whole cell is returned to the recipient, the order is revealed in
+    // the witness for the freestanding lock to check.
+    let output = tx.raw().outputs().get(0).unwrap();
+    assert_eq!(output.lock(), recipient);
+    assert_eq!(output.type_(), freestanding_cell.type_());
+    assert_eq!(output.capacity(), freestanding_cell.capacity());
+    assert_eq!(
+        tx.raw().outputs_data().get(0).unwrap().raw_data(),
+        freestanding_data
+    );
+    let witness_args =
+        WitnessArgs::from_slice(&tx.witnesses().get(0).unwrap().raw_data()).expect("witness");
+    assert_eq!(
+        witness_args.lock().to_opt().unwrap().raw_data(),
+        parsed_data.order.as_bytes()
+    );
+    assert_eq!(tx.witnesses().len(), 2);
+}
+
+#[test]
+fn test_orders_without_deadline_are_swept_by_age() {
+    let mut rng = new_rng();
+    let mut book = OrderBook::new(&mut rng, MatchingConfig::Continuous);
+    let (first_token, second_token) = (book.first_token.clone(), book.second_token.clone());
+    let iceberg = |limit_order| {
+        dex1::Order::new_builder()
+            .set(
+                dex1::IcebergOrder::new_builder()
+                    .order(limit_order)
+                    .display_amount(5u128.pack())
+                    .build(),
+            )
+            .build()
+    };
+    let recipient = random_script(&mut rng);
+    book.try_submit_order(
+        &mut rng,
+        &recipient,
+        &first_token,
+        &second_token,
+        10,
+        20,
+        iceberg,
+    )
+    .expect("map");
+    let (freestanding_out_point, _) = book.keep_in_freestanding_cell(&mut rng);
+
+    // The cell expires MAX_ORDER_AGE blocks after it is committed, which is
+    // unknown until the creating tx is committed.
+    let created_block_number =
+        |tx_hash: &Byte32| (*tx_hash == freestanding_out_point.tx_hash()).then_some(1000);
+    let tip_block_number = 1000 + crate::MAX_ORDER_AGE;
+    assert!(book
+        .dex1
+        .sweep_expired_orders(book.source.values(), tip_block_number, |_| None)
+        .is_none());
+    assert!(book
+        .dex1
+        .sweep_expired_orders(
+            book.source.values(),
+            tip_block_number - 1,
+            created_block_number
+        )
+        .is_none());
+    let tx = book
+        .dex1
+        .sweep_expired_orders(book.source.values(), tip_block_number, created_block_number)
+        .expect("sweep");
+    assert_eq!(
+        input_out_points(&tx),
+        HashSet::from([freestanding_out_point])
+    );
+    // Relative since in block numbers, counted from the creating block
+    let cell_input = tx.raw().inputs().get(0).unwrap();
+    assert_eq!(
+        Unpack::<u64>::unpack(&cell_input.since()),
+        (0x80 << 56) | crate::MAX_ORDER_AGE
+    );
+    assert_eq!(tx.raw().outputs().get(0).unwrap().lock(), recipient);
+}
+
+#[test]
+fn test_expired_owned_orders_are_swept_to_owner() {
+    let mut rng = new_rng();
+    let mut book = OrderBook::new(&mut rng, MatchingConfig::Continuous);
+    let (first_token, second_token) = (book.first_token.clone(), book.second_token.clone());
+    let owner = random_script(&mut rng);
+    let owned = |limit_order| {
+        dex1::Order::new_builder()
+            .set(
+                dex1::OwnedLimitOrder::new_builder()
+                    .order(limit_order)
+                    .owner(owner.calc_script_hash())
+                    .build(),
+            )
+            .build()
+    };
+    let created_block_number = |_: &Byte32| Some(1000);
+    let tip_block_number = 1000 + crate::MAX_ORDER_AGE;
+
+    // The owner lock is unknown to an otx only carrying the recipient, the
+    // cell is left alone rather than returned to the wrong party.
+    let recipient = random_script(&mut rng);
+    book.try_submit_order(
+        &mut rng,
+        &recipient,
+        &first_token,
+        &second_token,
+        10,
+        20,
+        owned,
+    )
+    .expect("map");
+    book.keep_in_freestanding_cell(&mut rng);
+    assert!(book
+        .dex1
+        .sweep_expired_orders(book.source.values(), tip_block_number, created_block_number)
+        .is_none());
+
+    // Sell 10 first token from a cell of the owner, the recipient lock comes
+    // from an output of the otx.
+    let mut book = OrderBook::new(&mut rng, MatchingConfig::Continuous);
+    let (first_token, second_token) = (book.first_token.clone(), book.second_token.clone());
+    let input = CellOutput::new_builder()
+        .lock(owner.clone())
+        .type_(Some(first_token.clone()).pack())
+        .capacity(2000_0000_0000u64.pack())
+        .build();
+    let recipient_output = CellOutput::new_builder()
+        .lock(recipient.clone())
+        .capacity(100_0000_0000u64.pack())
+        .build();
+    let limit_order = dex1::LimitOrder::new_builder()
+        .bid_token(token_hash(&first_token))
+        .bid_amount(10u128.pack())
+        .ask_token(token_hash(&second_token))
+        .ask_amount(20u128.pack())
+        .recipient(recipient.calc_script_hash())
+        .claimed_ckbytes(1000_0000_0000u64.pack())
+        .build();
+    book.try_submit_otx_with_outputs(
+        &mut rng,
+        (input, Bytes::from(10u128.to_le_bytes().to_vec())),
+        vec![(recipient_output, Bytes::default())],
+        owned(limit_order),
+    )
+    .expect("map");
+    let (freestanding_out_point, _) = book.keep_in_freestanding_cell(&mut rng);
+    let tx = book
+        .dex1
+        .sweep_expired_orders(book.source.values(), tip_block_number, created_block_number)
+        .expect("sweep");
+    assert_eq!(
+        input_out_points(&tx),
+        HashSet::from([freestanding_out_point])
+    );
+    assert_eq!(tx.raw().outputs().get(0).unwrap().lock(), owner);
+}
+
+#[test]
+fn test_otx_with_valid_omnilock_seal_is_mapped() {
+    let mut rng = new_rng();
+    let mut book = OrderBook::new(&mut rng, MatchingConfig::Continuous);
+    let (first_token, second_token) = (book.first_token.clone(), book.second_token.clone());
+
+    let recipient = book.omnilock(None);
+    book.try_submit_as(&mut rng, &recipient, &first_token, &second_token, 10, 10)
+        .expect("map");
+    book.submit(&mut rng, false, 10, 10);
+
+    let emitter = book.reduce(&mut rng);
+    assert!(emitter.rejected_otxs.is_empty());
+    assert_eq!(emitter.txs.len(), 1);
+    assert_eq!(paid_amounts(&emitter.txs[0].0, &recipient), vec![10]);
+}
+
+#[test]
+fn test_otx_with_invalid_or_missing_omnilock_seal_is_rejected() {
+    let mut rng = new_rng();
+    let mut book = OrderBook::new(&mut rng, MatchingConfig::Continuous);
+    let (first_token, second_token) = (book.first_token.clone(), book.second_token.clone());
+
+    // Sealed by a key other than the one of the lock
+    let other_key = secp256k1::SecretKey::from_slice(generate_private_key().as_bytes()).unwrap();
+    let recipient = book.omnilock(Some(other_key));
+    let err = book
+        .try_submit_as(&mut rng, &recipient, &first_token, &second_token, 10, 10)
+        .unwrap_err();
+    assert!(err.to_string().contains("does not match"), "{}", err);
+
+    // Not sealed at all
+    let recipient = book.omnilock(None);
+    book.sealing_keys.remove(&recipient.calc_script_hash());
+    let err = book
+        .try_submit_as(&mut rng, &recipient, &first_token, &second_token, 10, 10)
+        .unwrap_err();
+    assert!(err.to_string().contains("Missing seal"), "{}", err);
+
+    assert_eq!(book.source.values().count(), 0);
+}
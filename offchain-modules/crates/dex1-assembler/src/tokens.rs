@@ -0,0 +1,163 @@
+use crate::config::{TokenConfig, TokenStandard};
+use anyhow::{anyhow, Result};
+use ckb_types::{
+    bytes::Bytes,
+    core::{Capacity, ScriptHashType},
+    packed::{self, Byte32, CellOutput, Script, ScriptOpt},
+    prelude::*,
+};
+use std::collections::HashMap;
+
+/// A token adapter knows how a token standard keeps amounts in cell data.
+pub trait TokenAdapter: Send + Sync {
+    /// Amount kept in cell data, None when the data is malformed.
+    fn amount(&self, data: &[u8]) -> Option<u128>;
+
+    /// Data of a cell keeping +amount+ of the token.
+    fn cell_data(&self, amount: u128) -> Bytes;
+}
+
+/// sUDT keeps the amount in the first 16 bytes of cell data.
+pub struct Sudt;
+
+impl TokenAdapter for Sudt {
+    fn amount(&self, data: &[u8]) -> Option<u128> {
+        let amount: [u8; 16] = data.get(0..16)?.try_into().ok()?;
+        Some(u128::from_le_bytes(amount))
+    }
+
+    fn cell_data(&self, amount: u128) -> Bytes {
+        Bytes::from(amount.to_le_bytes().to_vec())
+    }
+}
+
+/// xUDT keeps the amount in the first 16 bytes of cell data, followed by
+/// optional extension data. Extension args and owner mode flags live in type
+/// script args, so they are part of the token type hash. Cells created by
+/// dex1 carry +extension_data+ after the amount.
+pub struct Xudt {
+    pub extension_data: Bytes,
+}
+
+impl TokenAdapter for Xudt {
+    fn amount(&self, data: &[u8]) -> Option<u128> {
+        Sudt.amount(data)
+    }
+
+    fn cell_data(&self, amount: u128) -> Bytes {
+        let mut data = amount.to_le_bytes().to_vec();
+        data.extend_from_slice(&self.extension_data);
+        Bytes::from(data)
+    }
+}
+
+/// Token adapters of configured token standards, keyed by code hash and hash
+/// type of token type scripts. Tokens of other type scripts are treated as
+/// sUDT. Native CKB has no type script, its amounts are kept in cell
+/// capacity instead.
+pub struct TokenAdapters {
+    adapters: HashMap<(Byte32, u8), Box<dyn TokenAdapter>>,
+}
+
+impl TokenAdapters {
+    pub fn new(tokens: &[TokenConfig]) -> Self {
+        Self {
+            adapters: tokens
+                .iter()
+                .map(|token| {
+                    let adapter: Box<dyn TokenAdapter> = match &token.standard {
+                        TokenStandard::Sudt => Box::new(Sudt),
+                        TokenStandard::Xudt { extension_data } => Box::new(Xudt {
+                            extension_data: extension_data.clone().into_bytes(),
+                        }),
+                    };
+                    let hash_type: ScriptHashType = token.hash_type.clone().into();
+                    ((token.code_hash.pack(), hash_type.into()), adapter)
+                })
+                .collect(),
+        }
+    }
+
+    /// Adapter of a UDT token.
+    pub fn get(&self, token: &Script) -> &dyn TokenAdapter {
+        self.adapters
+            .get(&(token.code_hash(), token.hash_type().into()))
+            .map(|adapter| adapter.as_ref())
+            .unwrap_or(&Sudt)
+    }
+
+    /// Data size of cells keeping +token+. Freestanding cells of CKB also keep
+    /// remaining CKB in 16 bytes of data.
+    pub fn data_size(&self, token: &Script) -> usize {
+        if is_ckb(token) {
+            16
+        } else {
+            self.get(token).cell_data(0).len()
+        }
+    }
+
+    /// Minimal capacity of a cell keeping +token+ using +lock+.
+    pub fn occupied_capacity(&self, lock: Script, token: &Script) -> u64 {
+        let data_size = self.data_size(token);
+        CellOutput::new_builder()
+            .lock(lock)
+            .type_(token_type(token))
+            .build()
+            .occupied_capacity(Capacity::bytes(data_size).expect("overflow"))
+            .expect("overflow")
+            .as_u64()
+    }
+
+    /// Cell holding +amount+ of +token+ on top of +ckbytes+ for +lock+. UDT
+    /// amounts are kept in cell data, while CKB is added to cell capacity.
+    pub fn cell(
+        &self,
+        lock: Script,
+        token: &Script,
+        amount: u128,
+        ckbytes: u64,
+    ) -> Result<(CellOutput, packed::Bytes)> {
+        let (capacity, data) = if is_ckb(token) {
+            let capacity = u64::try_from(amount)
+                .ok()
+                .and_then(|amount| ckbytes.checked_add(amount))
+                .ok_or_else(|| anyhow!("overflow!"))?;
+            (capacity, Bytes::default())
+        } else {
+            (ckbytes, self.get(token).cell_data(amount))
+        };
+        Ok((
+            CellOutput::new_builder()
+                .lock(lock)
+                .type_(token_type(token))
+                .capacity(capacity.pack())
+                .build(),
+            data.pack(),
+        ))
+    }
+}
+
+/// Native CKB is traded as a token without type script. It is configured by
+/// a default script, and referred to by the all-zero token hash in orders.
+pub fn is_ckb(token: &Script) -> bool {
+    token.as_slice() == Script::default().as_slice()
+}
+
+/// Token hash used in orders.
+pub fn token_script_hash(token: &Script) -> Byte32 {
+    if is_ckb(token) {
+        Byte32::default()
+    } else {
+        token.calc_script_hash()
+    }
+}
+
+/// Type script of cells keeping +token+.
+pub fn token_type(token: &Script) -> ScriptOpt {
+    if is_ckb(token) {
+        None
+    } else {
+        Some(token.clone())
+    }
+    .pack()
+}
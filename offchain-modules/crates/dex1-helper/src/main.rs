@@ -1,10 +1,12 @@
 mod utils;
+mod wallet;
 
 use crate::utils::{
     build_config, build_genesis_sighash_lock, build_lock_from_cli, build_omnilock_lock,
     build_private_key, build_recipient_script, print_script, save_tx, to_tx, wait_for_tx,
 };
-use ckb_hash::{new_blake2b, Blake2bBuilder};
+use crate::wallet::{wallet, wallet_command};
+use ckb_hash::new_blake2b;
 use ckb_jsonrpc_types::{self as ckbrpc, OutputsValidator, Status};
 use ckb_sdk::{
     constants::{SIGHASH_TYPE_HASH, TYPE_ID_CODE_HASH},
@@ -40,6 +42,8 @@ use clap::{arg, command, value_parser, ArgAction, ArgMatches, Command};
 use dex1_assembler::{
     config::FullScript,
     schemas::{basic, dex1, top_level},
+    seals::otx_signing_message,
+    trading_rules_data,
 };
 use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
@@ -83,7 +87,14 @@ fn main() {
                     .value_parser(value_parser!(PathBuf)),
                 ),
         )
-        .subcommand(Command::new("deploy-dex1").about("Deploy a new dex1 cell"))
+        .subcommand(
+            Command::new("deploy-dex1")
+                .about("Deploy a new dex1 cell")
+                .arg(
+                    arg!(--trading_rules "Enforce trading rules of configured pairs on chain")
+                        .action(ArgAction::SetTrue),
+                ),
+        )
         .subcommand(
             Command::new("create-otx")
                 .about("create and (optionally) send new otx")
@@ -225,12 +236,13 @@ fn main() {
                         .value_parser(value_parser!(H256)),
                 ),
         )
+        .subcommand(wallet_command())
         .get_matches();
 
     if let Some(command_matches) = matches.subcommand_matches("deploy") {
         deploy(&command_matches, &matches);
-    } else if let Some(_command_matches) = matches.subcommand_matches("deploy-dex1") {
-        deploy_dex1(&matches);
+    } else if let Some(command_matches) = matches.subcommand_matches("deploy-dex1") {
+        deploy_dex1(command_matches, &matches);
     } else if let Some(command_matches) = matches.subcommand_matches("create-otx") {
         create_otx(&command_matches, &matches);
     } else if let Some(_command_matches) = matches.subcommand_matches("cancel-otx") {
@@ -251,6 +263,8 @@ fn main() {
         issue_udt(&command_matches, &matches);
     } else if let Some(command_matches) = matches.subcommand_matches("transfer-udt") {
         transfer_udt(&command_matches, &matches);
+    } else if let Some(command_matches) = matches.subcommand_matches("wallet") {
+        wallet(command_matches);
     }
 }
 
@@ -399,7 +413,7 @@ fn deploy(command_matches: &ArgMatches, top_matches: &ArgMatches) {
     wait_for_tx(tx.hash().unpack(), &ckb_rpc);
 }
 
-fn deploy_dex1(top_matches: &ArgMatches) {
+fn deploy_dex1(command_matches: &ArgMatches, top_matches: &ArgMatches) {
     let config = build_config(top_matches);
     let private_key = build_private_key(top_matches);
     let ckb_client = CkbRpcClient::new(&config.ckb_rpc);
@@ -426,6 +440,11 @@ fn deploy_dex1(top_matches: &ArgMatches) {
     let tx_dep_provider = DefaultTransactionDependencyProvider::new(&config.ckb_rpc, 10);
 
     // Actually building the transaction
+    let entity_data = if command_matches.get_flag("trading_rules") {
+        trading_rules_data(&config)
+    } else {
+        Bytes::new()
+    };
     let output = {
         let t: ScriptHashType = config.dex1.script.hash_type.clone().into();
         let dummy = CellOutput::new_builder()
@@ -444,7 +463,7 @@ fn deploy_dex1(top_matches: &ArgMatches) {
             .build();
 
         let required_capacity = dummy
-            .occupied_capacity(Capacity::zero())
+            .occupied_capacity(Capacity::bytes(entity_data.len()).expect("capacity overflow"))
             .expect("capacity overflow");
 
         dummy
@@ -452,7 +471,7 @@ fn deploy_dex1(top_matches: &ArgMatches) {
             .capacity(required_capacity.pack())
             .build()
     };
-    let builder = CapacityTransferBuilder::new(vec![(output, Bytes::new())]);
+    let builder = CapacityTransferBuilder::new(vec![(output, entity_data)]);
     let dummy_tx = builder
         .build_balanced(
             &mut cell_collector,
@@ -794,34 +813,11 @@ fn create_otx(command_matches: &ArgMatches, top_matches: &ArgMatches) {
             panic!("Unexpected witness layout type!");
         };
 
-        let mut hasher = Blake2bBuilder::new(32)
-            .personal(b"ckb-tcob-otxhash")
-            .build();
-        hasher.update(otx.message().as_slice());
-        hasher.update(&(tx.inputs().len() as u32).to_le_bytes());
-        for (i, cell_input) in tx.inputs().into_iter().enumerate() {
-            hasher.update(cell_input.as_slice());
-            hasher.update(input_live_cells[i].output.as_slice());
-            hasher.update(&(input_live_cells[i].output_data.len() as u32).to_le_bytes());
-            hasher.update(&input_live_cells[i].output_data);
-        }
-        hasher.update(&(tx.outputs().len() as u32).to_le_bytes());
-        for (output, data) in tx.outputs_with_data_iter() {
-            hasher.update(output.as_slice());
-            hasher.update(&(data.len() as u32).to_le_bytes());
-            hasher.update(&data);
-        }
-        hasher.update(&(tx.cell_deps().len() as u32).to_le_bytes());
-        for cell_dep in tx.cell_deps() {
-            hasher.update(cell_dep.as_slice());
-        }
-        hasher.update(&(tx.header_deps().len() as u32).to_le_bytes());
-        for header_dep in tx.header_deps() {
-            hasher.update(header_dep.as_slice());
-        }
-        let mut hash = [0u8; 32];
-        hasher.finalize(&mut hash);
-        hash.into()
+        let inputs: Vec<_> = input_live_cells
+            .iter()
+            .map(|live_cell| (live_cell.output.clone(), live_cell.output_data.clone()))
+            .collect();
+        otx_signing_message(&otx, &tx.data(), &inputs).into()
     };
     let signer = SecpCkbRawKeySigner::new_with_secret_keys(vec![sender_key]);
     let signature = signer
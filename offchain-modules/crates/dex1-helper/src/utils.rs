@@ -9,9 +9,11 @@ use ckb_types::{
     H256,
 };
 use clap::ArgMatches;
-use dex1_assembler::config::{FullScript, RunnerConfig};
-use std::path::PathBuf;
-use std::str::FromStr;
+use dex1_assembler::{
+    config::{FullScript, RunnerConfig},
+    keystore,
+};
+use std::path::{Path, PathBuf};
 
 pub fn build_config(top_matches: &ArgMatches) -> RunnerConfig {
     let config_path = top_matches
@@ -25,13 +27,9 @@ pub fn build_private_key(top_matches: &ArgMatches) -> H256 {
     read_private_key(wallet_path)
 }
 
-pub fn read_private_key(path: &PathBuf) -> H256 {
-    let wallet_content = std::fs::read_to_string(path)
-        .expect("read wallet content")
-        .trim()
-        .to_string();
-    // TODO: other formats will be added later, such as keystore files
-    H256::from_str(&wallet_content).expect("parse raw private key")
+// Both raw hex private keys and encrypted keystores are supported
+pub fn read_private_key(path: &Path) -> H256 {
+    keystore::read_wallet(path).expect("read wallet")
 }
 
 pub fn build_recipient_script(command_matches: &ArgMatches, config: &RunnerConfig) -> Script {
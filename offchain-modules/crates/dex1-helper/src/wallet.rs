@@ -0,0 +1,143 @@
+use ckb_types::H256;
+use clap::{arg, value_parser, ArgMatches, Command};
+use dex1_assembler::keystore::{
+    generate_private_key, pubkey_hash, read_new_password, read_password, read_wallet, Keystore,
+};
+use std::path::{Path, PathBuf};
+
+pub fn wallet_command() -> Command {
+    Command::new("wallet")
+        .about("manage encrypted keystore wallets")
+        .subcommand_required(true)
+        .subcommand(
+            Command::new("create").about("create a new keystore").arg(
+                arg!(--keystore_dir <KEYSTORE_DIR> "Folder to save keystore files")
+                    .required(true)
+                    .value_parser(value_parser!(PathBuf)),
+            ),
+        )
+        .subcommand(
+            Command::new("import")
+                .about("import a raw hex private key into a new keystore")
+                .arg(
+                    arg!(--private_key <PRIVATE_KEY> "File containing raw hex private key")
+                        .required(true)
+                        .value_parser(value_parser!(PathBuf)),
+                )
+                .arg(
+                    arg!(--keystore_dir <KEYSTORE_DIR> "Folder to save keystore files")
+                        .required(true)
+                        .value_parser(value_parser!(PathBuf)),
+                ),
+        )
+        .subcommand(
+            Command::new("export")
+                .about("export raw hex private key from a keystore")
+                .arg(
+                    arg!(--keystore <KEYSTORE> "Keystore file")
+                        .required(true)
+                        .value_parser(value_parser!(PathBuf)),
+                )
+                .arg(
+                    arg!(--output <OUTPUT> "File to write private key, stdout is used if missing")
+                        .value_parser(value_parser!(PathBuf)),
+                ),
+        )
+        .subcommand(
+            Command::new("list")
+                .about("list keystores in a folder")
+                .arg(
+                    arg!(--keystore_dir <KEYSTORE_DIR> "Folder containing keystore files")
+                        .required(true)
+                        .value_parser(value_parser!(PathBuf)),
+                ),
+        )
+}
+
+pub fn wallet(command_matches: &ArgMatches) {
+    match command_matches.subcommand() {
+        Some(("create", matches)) => create(matches),
+        Some(("import", matches)) => import(matches),
+        Some(("export", matches)) => export(matches),
+        Some(("list", matches)) => list(matches),
+        _ => unreachable!(),
+    }
+}
+
+fn create(command_matches: &ArgMatches) {
+    let keystore_dir = command_matches.get_one::<PathBuf>("keystore_dir").unwrap();
+    save_keystore(keystore_dir, &generate_private_key());
+}
+
+fn import(command_matches: &ArgMatches) {
+    let private_key_path = command_matches.get_one::<PathBuf>("private_key").unwrap();
+    let keystore_dir = command_matches.get_one::<PathBuf>("keystore_dir").unwrap();
+    let private_key = read_wallet(private_key_path).expect("read wallet");
+    save_keystore(keystore_dir, &private_key);
+}
+
+fn export(command_matches: &ArgMatches) {
+    let keystore_path = command_matches.get_one::<PathBuf>("keystore").unwrap();
+    let private_key = read_keystore(keystore_path)
+        .decrypt(
+            read_password("Password: ")
+                .expect("read password")
+                .as_bytes(),
+        )
+        .expect("decrypt keystore");
+    // Same raw hex format accepted by --from wallet files
+    let content = format!("{:x}", private_key);
+    match command_matches.get_one::<PathBuf>("output") {
+        Some(output) => {
+            std::fs::write(output, content).expect("write private key");
+            println!("Private key written to {}", output.display());
+        }
+        None => println!("{}", content),
+    }
+}
+
+fn list(command_matches: &ArgMatches) {
+    let keystore_dir = command_matches.get_one::<PathBuf>("keystore_dir").unwrap();
+    let mut paths: Vec<_> = std::fs::read_dir(keystore_dir)
+        .expect("read keystore dir")
+        .map(|entry| entry.expect("read dir entry").path())
+        .filter(|path| path.extension().map(|e| e == "json").unwrap_or(false))
+        .collect();
+    paths.sort();
+    for path in paths {
+        match std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str::<Keystore>(&content).ok())
+        {
+            Some(keystore) => println!("0x{} {}", keystore.address, path.display()),
+            None => log::warn!("{} is not a valid keystore!", path.display()),
+        }
+    }
+}
+
+fn save_keystore(keystore_dir: &Path, private_key: &H256) {
+    let password = read_new_password().expect("read password");
+    let keystore = Keystore::encrypt(private_key, password.as_bytes()).expect("encrypt keystore");
+    std::fs::create_dir_all(keystore_dir).expect("create keystore dir");
+    let path = keystore_dir.join(format!("{}.json", keystore.address));
+    assert!(
+        !path.exists(),
+        "Keystore {} already exists!",
+        path.display()
+    );
+    std::fs::write(
+        &path,
+        serde_json::to_string_pretty(&keystore).expect("json"),
+    )
+    .expect("write keystore");
+    println!(
+        "Keystore for {:#x} saved to {}",
+        pubkey_hash(private_key).expect("pubkey hash"),
+        path.display()
+    );
+}
+
+fn read_keystore(path: &Path) -> Keystore {
+    serde_json::from_str(&std::fs::read_to_string(path).expect("read keystore"))
+        .expect("parse keystore")
+}
@@ -5,17 +5,23 @@ use chrono::prelude::*;
 use ckb_jsonrpc_types::{OutPoint, OutputsValidator, Status, Transaction};
 use ckb_sdk::{CkbRpcClient, RpcError};
 use ckb_types::{core::TransactionView, packed, prelude::*, H256};
-use clap::{arg, command, value_parser, ArgMatches};
+use clap::{arg, command, value_parser, ArgAction, ArgMatches};
 use core::hash::Hash;
-use dex1_assembler::{config::RunnerConfig, schemas::top_level, Dex1, Dex1Env};
+use dex1_assembler::{
+    config::{FeeConfig, MatchingConfig, RunnerConfig},
+    fee_cells::FeeCellError,
+    keystore,
+    schemas::top_level,
+    signer::FeeCellSigner,
+    Dex1, Dex1Env,
+};
 use jsonrpc_core::Result as JsonrpcResult;
 use jsonrpc_derive::rpc;
 use jsonrpc_http_server::ServerBuilder;
 use otx_traits::{Assembler, MapEmitter, ReduceEmitter, ReduceSource, Value};
 use regex::Regex;
 use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
-use std::path::PathBuf;
-use std::str::FromStr;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 use std::thread;
 use std::time::Duration;
@@ -42,6 +48,15 @@ impl OtxRpc for OtxRpcImpl {
     }
 }
 
+/// An assembled CKB transaction that has been sent to CKB, but not yet committed.
+pub struct InflightTx<A: Assembler> {
+    pub tx: TransactionView,
+    // Post value is kept so postprocessing can be redone if the tx is replaced.
+    pub post_value: A::PostValue,
+    pub fee_rate: u64,
+    pub submitted_block: u64,
+}
+
 /// This is a minimal data source which:
 /// 1. Works with only one assembler
 /// 2. Keeps all open transactions in memory
@@ -49,11 +64,18 @@ impl OtxRpc for OtxRpcImpl {
 /// A more mature source would be able to support more assemblers at once,
 /// and also store open transactions in a persistent layer such as rocksdb.
 /// But this simple one here already shows what a data source can do.
+///
+/// Otxs of the same key are iterated in the assembler's order, so it is up
+/// to the assembler to decide the priority amongst otxs, e.g., Dex1 uses
+/// price-time priority.
 pub struct SingleInMemorySource<A: Assembler> {
     data: HashMap<A::Key, BTreeMap<A::Order, Vec<A::Value>>>,
 
-    inflight_tx: Option<TransactionView>,
+    inflight_tx: Option<InflightTx<A>>,
     pending_txs: VecDeque<(packed::Transaction, A::PostValue)>,
+    // Commit blocks of txs creating freestanding cells, which tell when
+    // orders without a deadline expire.
+    created_blocks: HashMap<packed::Byte32, u64>,
 }
 
 impl<A: Assembler> SingleInMemorySource<A> {
@@ -61,7 +83,7 @@ impl<A: Assembler> SingleInMemorySource<A> {
         self.pending_txs
             .iter()
             .map(|(tx, _)| tx.raw().inputs())
-            .chain(self.inflight_tx.iter().map(|tx_view| tx_view.inputs()))
+            .chain(self.inflight_tx.iter().map(|inflight| inflight.tx.inputs()))
             .map(|inputs| {
                 inputs
                     .into_iter()
@@ -80,6 +102,11 @@ impl<A: Assembler> SingleInMemorySource<A> {
             .fold(0, |acc, l| acc + l)
     }
 
+    // Iterate over all otxs, regardless of keys
+    pub fn values(&self) -> impl Iterator<Item = &A::Value> {
+        self.data.values().flat_map(|otxs| otxs.values().flatten())
+    }
+
     // Given a set of already-spent outpoints, this method purges all otxs
     // that also consume such outpoints
     pub fn purge_otxs(&mut self, outpoints: &HashSet<packed::OutPoint>) {
@@ -96,6 +123,64 @@ impl<A: Assembler> SingleInMemorySource<A> {
             log::info!("Purged {} otxs due to spent outpoints", before - after);
         }
     }
+
+    // Pending txs are assembled one after another, so a later pending tx might
+    // depend on an earlier one. Once a pending tx consumes any of the spent
+    // outpoints, all the following pending txs are dropped as well.
+    pub fn purge_pending_txs(&mut self, outpoints: &HashSet<packed::OutPoint>) {
+        if let Some(i) = self.pending_txs.iter().position(|(pending_tx, _)| {
+            pending_tx
+                .raw()
+                .inputs()
+                .into_iter()
+                .any(|cell_input| outpoints.contains(&cell_input.previous_output()))
+        }) {
+            self.pending_txs.drain(i..);
+        }
+    }
+
+    // A pending tx might spend freestanding cells created by an earlier
+    // pending tx assembled in the same round, referring to the earlier tx by
+    // its unsealed hash. Once the earlier tx is sealed, such inputs are
+    // updated to refer to the sealed tx instead.
+    pub fn rebase_pending_txs(
+        &mut self,
+        unsealed_hash: &packed::Byte32,
+        sealed_hash: &packed::Byte32,
+    ) {
+        for (pending_tx, _) in self.pending_txs.iter_mut() {
+            let raw = pending_tx.raw();
+            if !raw
+                .inputs()
+                .into_iter()
+                .any(|cell_input| cell_input.previous_output().tx_hash() == *unsealed_hash)
+            {
+                continue;
+            }
+            let inputs = raw
+                .inputs()
+                .into_iter()
+                .map(|cell_input| {
+                    let out_point = cell_input.previous_output();
+                    if out_point.tx_hash() == *unsealed_hash {
+                        cell_input
+                            .as_builder()
+                            .previous_output(
+                                out_point.as_builder().tx_hash(sealed_hash.clone()).build(),
+                            )
+                            .build()
+                    } else {
+                        cell_input
+                    }
+                })
+                .collect::<Vec<_>>();
+            *pending_tx = pending_tx
+                .clone()
+                .as_builder()
+                .raw(raw.as_builder().inputs(inputs.pack()).build())
+                .build();
+        }
+    }
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -172,6 +257,7 @@ impl<A: Assembler> Default for SingleInMemorySource<A> {
             data: HashMap::new(),
             inflight_tx: None,
             pending_txs: VecDeque::new(),
+            created_blocks: HashMap::new(),
         }
     }
 }
@@ -220,8 +306,9 @@ fn main() {
     let matches = command!()
         .arg(
             arg!(
-                -f --from <WALLET> "Wallet file for from address"
+                -f --from <WALLET> "Wallet file for from address, can be repeated for multisig fee cells"
             )
+            .action(ArgAction::Append)
             .value_parser(value_parser!(PathBuf)),
         )
         .arg(
@@ -270,10 +357,12 @@ fn main() {
     // Periodically, update open transaction list, then submit assembled
     // CKB transaction to L1 network
     thread::spawn(move || {
-        let private_key = build_private_key(&matches);
-        let mut dex1_env = Dex1Env::new(&config, private_key);
+        let private_keys = build_private_keys(&matches);
+        let signer = FeeCellSigner::new(&config, &private_keys).expect("build signer");
+        let mut dex1_env = Dex1Env::new(&config, signer);
         let dex1 = Dex1::new(&config.config());
         let mut source: SingleInMemorySource<Dex1> = SingleInMemorySource::default();
+        let mut last_batch = None;
 
         loop {
             // If any in-flight tx exists, we will wait till it is committed.
@@ -297,31 +386,30 @@ fn main() {
             // an actual otx does not need to care which dex1 cell will be used, and the
             // OTX processor can multiplex amongst different dex1 cells, achieving higher
             // throughput.
-            let inflight_committed = source.inflight_tx.clone().map(|tx| {
-                match client.get_transaction_status(tx.hash().unpack()) {
-                    Ok(status) => match status.tx_status.status {
-                        Status::Committed => true,
-                        Status::Unknown => {
+            let inflight_status = source.inflight_tx.as_ref().map(|inflight| {
+                match client.get_transaction_status(inflight.tx.hash().unpack()) {
+                    Ok(status) => {
+                        if status.tx_status.status == Status::Unknown {
                             client
                                 .send_transaction(
-                                    tx.data().into(),
+                                    inflight.tx.data().into(),
                                     Some(OutputsValidator::Passthrough),
                                 )
                                 .expect("send inflight tx error!");
-                            false
                         }
-                        _ => false,
-                    },
+                        Some(status.tx_status.status)
+                    }
                     Err(e) => {
                         log::error!("CKB RPC error: {:?}", e);
-                        false
+                        None
                     }
                 }
             });
-            match inflight_committed {
-                Some(true) => {
-                    let tx = source.inflight_tx.take().unwrap();
+            match inflight_status {
+                Some(Some(Status::Committed)) => {
+                    let tx = source.inflight_tx.take().unwrap().tx;
                     log::info!("TX {:x} committed to chain!", tx.hash());
+                    dex1_env.release_fee_cell(&tx.hash());
                     let out_points = tx
                         .inputs()
                         .into_iter()
@@ -330,19 +418,22 @@ fn main() {
                     // Purge otxs that have already spent outpoints
                     source.purge_otxs(&out_points);
                     // Purge pending txs that have already spent outpoints
-                    if let Some(i) =
-                        source.pending_txs.iter().position(|(pending_tx, _)| {
-                            pending_tx.raw().inputs().into_iter().any(|cell_input| {
-                                out_points.contains(&cell_input.previous_output())
-                            })
-                        })
-                    {
-                        source.pending_txs.drain(i..);
-                    }
+                    source.purge_pending_txs(&out_points);
                 }
-                Some(false) => {
+                Some(status) => {
                     // In case an inflight transaction is present but not yet
-                    // committed, we will wait for it, and start over.
+                    // committed, we will wait for it, and start over. A tx that
+                    // stays unproposed for too long gets its fee bumped.
+                    if status == Some(Status::Pending) {
+                        bump_inflight_tx(
+                            &client,
+                            &mut dex1_env,
+                            &dex1,
+                            &mut source,
+                            &config.fee,
+                            matches.get_one::<PathBuf>("txs"),
+                        );
+                    }
                     thread::sleep(Duration::from_secs(5));
                     continue;
                 }
@@ -360,47 +451,66 @@ fn main() {
             // constructed, but the processor is just a dummy machine, it won't
             // really hurt doing retries.
             if source.inflight_tx.is_none() {
+                refill_fee_cells(&client, &mut dex1_env);
+                sweep_expired_orders(&client, &dex1, &mut source);
                 if let Some((pending_tx, post_value)) = source.pending_txs.pop_front() {
-                    let sealed_tx = dex1_env
-                        .seal_tx(&pending_tx)
-                        .expect("sealing tx")
-                        .into_view();
-
-                    log::info!("Sealed tx for submission: {:x}", sealed_tx.hash());
-                    if let Some(txs) = matches.get_one::<PathBuf>("txs") {
-                        let now: DateTime<Utc> = Utc::now();
-                        let timestamp = now.format("%Y%m%d%H%M%S%.f").to_string();
-                        let json_tx: Transaction = sealed_tx.data().into();
-
-                        std::fs::write(
-                            txs.join(format!("{}_{:x}.json", timestamp, sealed_tx.hash())),
-                            serde_json::to_string_pretty(&json_tx).expect("json"),
-                        )
-                        .expect("write tx");
-                    }
+                    let fee_rate = dex1_env.fee_rate();
+                    // Sliced remainders are scheduled against the dex1 cell
+                    // the tx is sealed with.
+                    let block_number = dex1_env.dex1_block_number().expect("dex1 cell");
+                    let (rescheduled_tx, rescheduled_post_value) =
+                        dex1.reschedule_slices(&pending_tx, post_value.clone(), block_number);
+                    let sealed_tx = match dex1_env.seal_tx(&rescheduled_tx, fee_rate) {
+                        Ok(tx) => tx.into_view(),
+                        Err(e) => {
+                            requeue_unsealed_tx(
+                                &mut source,
+                                pending_tx,
+                                post_value,
+                                &e,
+                                config.fee.fee_cell_capacity,
+                            );
+                            thread::sleep(Duration::from_secs(5));
+                            continue;
+                        }
+                    };
+                    let post_value = rescheduled_post_value;
+                    purge_rescheduled_dependents(&mut source, &pending_tx, &rescheduled_tx);
+                    source.rebase_pending_txs(&pending_tx.calc_tx_hash(), &sealed_tx.hash());
+
+                    log::info!(
+                        "Sealed tx for submission: {:x}, fee rate: {}",
+                        sealed_tx.hash(),
+                        fee_rate
+                    );
+                    save_tx(matches.get_one::<PathBuf>("txs"), &sealed_tx);
 
-                    match client.send_transaction(
-                        sealed_tx.data().into(),
-                        Some(OutputsValidator::Passthrough),
-                    ) {
+                    // Dry run the tx first, so invalid otxs are evicted before
+                    // the tx is ever broadcasted.
+                    match dex1_env.verify_tx(&sealed_tx.data()).and_then(|cycles| {
+                        log::debug!("Tx {:x} verified, cycles: {}", sealed_tx.hash(), cycles);
+                        match dex1_env.input_cells(&sealed_tx) {
+                            Ok(inputs) => dex1.record_cycles(&inputs, cycles),
+                            Err(e) => log::error!("Error resolving input cells: {:?}", e),
+                        }
+                        client.send_transaction(
+                            sealed_tx.data().into(),
+                            Some(OutputsValidator::Passthrough),
+                        )
+                    }) {
                         Ok(_) => {
-                            source.inflight_tx = Some(sealed_tx.clone());
-
-                            let mut emitter: MemoryEmitter<Dex1> = MemoryEmitter::default();
-                            if let Err(e) =
-                                dex1.postprocess(sealed_tx.data(), post_value, &mut emitter)
-                            {
-                                log::warn!(
-                                    "Error in postprocessor: {:?} for tx: {:x}",
-                                    e,
-                                    sealed_tx.hash()
-                                );
-                            }
-                            for (key, order, value) in emitter.otxs {
-                                source.insert_otx(key, order, value);
-                            }
+                            postprocess_tx(&dex1, &mut source, &sealed_tx, post_value.clone());
+                            let submitted_block =
+                                client.get_tip_block_number().expect("rpc").value();
+                            source.inflight_tx = Some(InflightTx {
+                                tx: sealed_tx,
+                                post_value,
+                                fee_rate,
+                                submitted_block,
+                            });
                         }
                         Err(e) => {
+                            dex1_env.release_fee_cell(&sealed_tx.hash());
                             let mut defected_out_points = HashSet::new();
                             // Parse the error:
                             // * For script validation errors, we would remove the affected otxs
@@ -432,7 +542,6 @@ fn main() {
                                             .unwrap()
                                             .previous_output(),
                                     );
-                                    break;
                                 }
                                 ParsedRpcError::OutputCellScriptError(i) => {
                                     log::info!(
@@ -452,7 +561,18 @@ fn main() {
                                             .map(|cell_input| cell_input.previous_output()),
                                     );
                                 }
-                                ParsedRpcError::Other(e) => panic!("CKB RPC error: {:?}", e),
+                                ParsedRpcError::Other(e) => {
+                                    // The failure cannot be attributed to any otx, such
+                                    // as a CKB RPC error. Otxs are kept, so they will be
+                                    // assembled into a new tx and retried.
+                                    log::error!(
+                                        "Error submitting tx {:x}: {:?}, will retry!",
+                                        sealed_tx.hash(),
+                                        e
+                                    );
+                                    source.pending_txs.clear();
+                                    dex1_env.refresh_deps();
+                                }
                             }
                             if !defected_out_points.is_empty() {
                                 source.purge_otxs(&defected_out_points);
@@ -501,7 +621,18 @@ fn main() {
 
             // Assembling new CKB transactions from otxs
             let mut assembled = false;
-            for key in dex1.keys() {
+            let keys = if batch_window_closed(&client, &config.matching, &mut last_batch) {
+                dex1.keys()
+            } else {
+                Vec::new()
+            };
+            if !keys.is_empty() {
+                match dex1_env.oracle_cells() {
+                    Ok(cells) => dex1.update_oracles(&cells),
+                    Err(e) => log::warn!("Error fetching oracle cells: {:?}", e),
+                }
+            }
+            for key in keys {
                 let base_tx = dex1_env.base_tx().expect("creating base tx");
                 let txs = {
                     let mut emitter: MemoryEmitter<Dex1> = MemoryEmitter::default();
@@ -545,6 +676,269 @@ fn main() {
     server.wait();
 }
 
+// In batch auction mode, otxs are only reduced once a batch window closes, so
+// all otxs arriving within the same window are settled together.
+fn batch_window_closed(
+    client: &CkbRpcClient,
+    matching: &MatchingConfig,
+    last_batch: &mut Option<u64>,
+) -> bool {
+    let MatchingConfig::BatchAuction { batch_blocks } = matching else {
+        return true;
+    };
+    let tip_block_number = match client.get_tip_block_number() {
+        Ok(number) => number.value(),
+        Err(e) => {
+            log::error!("CKB RPC error: {:?}", e);
+            return false;
+        }
+    };
+    let batch = tip_block_number / std::cmp::max(*batch_blocks, 1);
+    let closed = last_batch.map(|last| batch > last).unwrap_or(false);
+    if closed || last_batch.is_none() {
+        *last_batch = Some(batch);
+    }
+    if closed {
+        log::info!("Batch window closed at block {}", tip_block_number);
+    }
+    closed
+}
+
+fn postprocess_tx(
+    dex1: &Dex1,
+    source: &mut SingleInMemorySource<Dex1>,
+    tx: &TransactionView,
+    post_value: <Dex1 as Assembler>::PostValue,
+) {
+    let mut emitter: MemoryEmitter<Dex1> = MemoryEmitter::default();
+    if let Err(e) = dex1.postprocess(tx.data(), post_value, &mut emitter) {
+        log::warn!("Error in postprocessor: {:?} for tx: {:x}", e, tx.hash());
+    }
+    for (key, order, value) in emitter.otxs {
+        source.insert_otx(key, order, value);
+    }
+}
+
+// Replace an inflight tx that has not been proposed for a while, with the same
+// tx sealed using a higher fee rate.
+fn bump_inflight_tx(
+    client: &CkbRpcClient,
+    dex1_env: &mut Dex1Env,
+    dex1: &Dex1,
+    source: &mut SingleInMemorySource<Dex1>,
+    fee_config: &FeeConfig,
+    txs: Option<&PathBuf>,
+) {
+    let tip_block_number = match client.get_tip_block_number() {
+        Ok(number) => number.value(),
+        Err(e) => {
+            log::error!("CKB RPC error: {:?}", e);
+            return;
+        }
+    };
+    let inflight = source.inflight_tx.as_ref().unwrap();
+    if tip_block_number < inflight.submitted_block + fee_config.bump_after_blocks {
+        return;
+    }
+    let Some(fee_rate) = fee_config.bumped_fee_rate(
+        dex1_env.fee_rate(),
+        inflight.fee_rate,
+        dex1_env.min_rbf_rate(),
+    ) else {
+        log::warn!(
+            "Tx {:x} is not proposed, but fee rate {} cannot be bumped further!",
+            inflight.tx.hash(),
+            inflight.fee_rate
+        );
+        return;
+    };
+    let bumped_tx = match dex1_env.bump_fee(&inflight.tx.data(), fee_rate) {
+        Ok(tx) => tx.into_view(),
+        Err(e) => {
+            log::error!("Error bumping fee for tx {:x}: {:?}", inflight.tx.hash(), e);
+            return;
+        }
+    };
+    save_tx(txs, &bumped_tx);
+    if let Err(e) =
+        client.send_transaction(bumped_tx.data().into(), Some(OutputsValidator::Passthrough))
+    {
+        log::warn!(
+            "Error replacing tx {:x} with {:x}: {:?}",
+            inflight.tx.hash(),
+            bumped_tx.hash(),
+            e
+        );
+        return;
+    }
+    dex1_env.commit_replacement(&inflight.tx.hash(), &bumped_tx.data());
+    log::info!(
+        "Replaced tx {:x} with {:x}, fee rate: {} -> {}",
+        inflight.tx.hash(),
+        bumped_tx.hash(),
+        inflight.fee_rate,
+        fee_rate
+    );
+
+    let inflight = source.inflight_tx.take().unwrap();
+    // Cells created by the replaced tx are gone, so are freestanding orders
+    // and pending txs built upon them.
+    let stale_out_points = (0..inflight.tx.outputs().len())
+        .map(|i| packed::OutPoint::new(inflight.tx.hash(), i as u32))
+        .collect();
+    source.purge_otxs(&stale_out_points);
+    source.purge_pending_txs(&stale_out_points);
+    postprocess_tx(dex1, source, &bumped_tx, inflight.post_value.clone());
+    source.inflight_tx = Some(InflightTx {
+        tx: bumped_tx,
+        post_value: inflight.post_value,
+        fee_rate,
+        submitted_block: tip_block_number,
+    });
+}
+
+// A pending tx that cannot be sealed is put back at the front of pending txs,
+// it is retried once fee cells are refilled at the start of the next round.
+// When even a fresh fee cell cannot fund its outputs, it is dropped along
+// with pending txs built upon it instead.
+fn requeue_unsealed_tx(
+    source: &mut SingleInMemorySource<Dex1>,
+    pending_tx: packed::Transaction,
+    post_value: <Dex1 as Assembler>::PostValue,
+    error: &anyhow::Error,
+    fee_cell_capacity: u64,
+) {
+    let tx_hash = pending_tx.calc_tx_hash();
+    match error.downcast_ref::<FeeCellError>() {
+        Some(FeeCellError::InsufficientCapacity { required }) if *required > fee_cell_capacity => {
+            log::error!("Error sealing tx {:x}: {}, dropping it!", tx_hash, error);
+            let stale_out_points = (0..pending_tx.raw().outputs().len())
+                .map(|i| packed::OutPoint::new(tx_hash.clone(), i as u32))
+                .collect();
+            source.purge_pending_txs(&stale_out_points);
+        }
+        _ => {
+            log::warn!("Error sealing tx {:x}: {}, will retry!", tx_hash, error);
+            source.pending_txs.push_front((pending_tx, post_value));
+        }
+    }
+}
+
+// Rescheduling a sliced remainder moves its freestanding cell to a new lock,
+// pending txs spending the cell were built against the old order, they are
+// dropped along with the ones following them. The remainder is matched again
+// once the rescheduled tx is postprocessed.
+fn purge_rescheduled_dependents(
+    source: &mut SingleInMemorySource<Dex1>,
+    pending_tx: &packed::Transaction,
+    rescheduled_tx: &packed::Transaction,
+) {
+    let tx_hash = pending_tx.calc_tx_hash();
+    let rescheduled_out_points: HashSet<_> = pending_tx
+        .raw()
+        .outputs()
+        .into_iter()
+        .zip(rescheduled_tx.raw().outputs())
+        .enumerate()
+        .filter(|(_, (output, rescheduled))| output.as_slice() != rescheduled.as_slice())
+        .map(|(i, _)| packed::OutPoint::new(tx_hash.clone(), i as u32))
+        .collect();
+    if !rescheduled_out_points.is_empty() {
+        source.purge_pending_txs(&rescheduled_out_points);
+    }
+}
+
+// Fee cell refilling tx does not touch any dex1 cell or otx, hence it is
+// submitted on its own without being tracked as inflight tx.
+fn refill_fee_cells(client: &CkbRpcClient, dex1_env: &mut Dex1Env) {
+    match dex1_env.refill_fee_cells() {
+        Ok(Some(tx)) => {
+            let tx = tx.into_view();
+            if let Err(e) =
+                client.send_transaction(tx.data().into(), Some(OutputsValidator::Passthrough))
+            {
+                log::error!(
+                    "Error sending fee cell refilling tx {:x}: {:?}",
+                    tx.hash(),
+                    e
+                );
+                dex1_env.refresh_deps();
+            }
+        }
+        Ok(None) => (),
+        Err(e) => log::error!("Error refilling fee cells: {:?}", e),
+    }
+}
+
+// Freestanding cells of expired orders are returned to their recipients. The
+// sweeping tx is queued as a pending tx, so it is sealed, verified and tracked
+// like assembled txs, and swept orders are purged once it is committed.
+fn sweep_expired_orders(
+    client: &CkbRpcClient,
+    dex1: &Dex1,
+    source: &mut SingleInMemorySource<Dex1>,
+) {
+    // Pending txs might still fill the same freestanding cells
+    if !source.pending_txs.is_empty() {
+        return;
+    }
+    let tip_block_number = match client.get_tip_block_number() {
+        Ok(number) => number.value(),
+        Err(e) => {
+            log::error!("CKB RPC error: {:?}", e);
+            return;
+        }
+    };
+    // Commit blocks are only looked up for freestanding cells still around
+    let created_tx_hashes: HashSet<_> = source
+        .values()
+        .filter(|parsed_data| parsed_data.freestanding_cell)
+        .filter_map(|parsed_data| parsed_data.tx.tx.raw().inputs().get(0))
+        .map(|cell_input| cell_input.previous_output().tx_hash())
+        .collect();
+    source
+        .created_blocks
+        .retain(|tx_hash, _| created_tx_hashes.contains(tx_hash));
+    for tx_hash in created_tx_hashes {
+        if source.created_blocks.contains_key(&tx_hash) {
+            continue;
+        }
+        match client.get_transaction_status(tx_hash.unpack()) {
+            Ok(status) => {
+                if let Some(block_number) = status.tx_status.block_number {
+                    source.created_blocks.insert(tx_hash, block_number.value());
+                }
+            }
+            Err(e) => log::error!("CKB RPC error: {:?}", e),
+        }
+    }
+    let created_blocks = &source.created_blocks;
+    if let Some(tx) = dex1.sweep_expired_orders(source.values(), tip_block_number, |tx_hash| {
+        created_blocks.get(tx_hash).copied()
+    }) {
+        log::info!(
+            "Sweeping {} expired freestanding cells at block {}",
+            tx.raw().inputs().len(),
+            tip_block_number
+        );
+        source.pending_txs.push_back((tx, Vec::new()));
+    }
+}
+
+fn save_tx(txs: Option<&PathBuf>, tx: &TransactionView) {
+    if let Some(txs) = txs {
+        let now: DateTime<Utc> = Utc::now();
+        let timestamp = now.format("%Y%m%d%H%M%S%.f").to_string();
+        let json_tx: Transaction = tx.data().into();
+
+        std::fs::write(
+            txs.join(format!("{}_{:x}.json", timestamp, tx.hash())),
+            serde_json::to_string_pretty(&json_tx).expect("json"),
+        )
+        .expect("write tx");
+    }
+}
+
 fn locate_otx_in_tx(tx: &packed::Transaction, output_index: usize) -> packed::Transaction {
     let mut first_otx = None;
     let mut input_cell = 0u32;
@@ -643,16 +1037,15 @@ fn locate_otx_in_tx(tx: &packed::Transaction, output_index: usize) -> packed::Tr
     panic!("Output {} does not belong to any OTX!", output_index);
 }
 
-pub fn build_private_key(top_matches: &ArgMatches) -> H256 {
-    let wallet_path = top_matches.get_one::<PathBuf>("from").unwrap();
-    read_private_key(wallet_path)
+// Wallet files are only required by local signer backend
+pub fn build_private_keys(top_matches: &ArgMatches) -> Vec<H256> {
+    top_matches
+        .get_many::<PathBuf>("from")
+        .map(|paths| paths.map(|path| read_private_key(path)).collect())
+        .unwrap_or_default()
 }
 
-pub fn read_private_key(path: &PathBuf) -> H256 {
-    let wallet_content = std::fs::read_to_string(path)
-        .expect("read wallet content")
-        .trim()
-        .to_string();
-    // TODO: other formats might be added later, such as keystore files
-    H256::from_str(&wallet_content).expect("parse raw private key")
+// Both raw hex private keys and encrypted keystores are supported
+pub fn read_private_key(path: &Path) -> H256 {
+    keystore::read_wallet(path).expect("read wallet")
 }
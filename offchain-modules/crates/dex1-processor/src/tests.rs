@@ -1,10 +1,14 @@
-use crate::ParsedRpcError;
+use crate::{
+    purge_rescheduled_dependents, requeue_unsealed_tx, ParsedRpcError, SingleInMemorySource,
+};
 use ckb_script::ScriptError;
 use ckb_sdk::RpcError;
 use ckb_types::{
-    core::error::OutPointError,
-    packed::{Byte32, OutPoint},
+    core::{error::OutPointError, TransactionView},
+    packed::{Byte32, CellInput, CellOutput, OutPoint, Script, Transaction},
+    prelude::*,
 };
+use dex1_assembler::{fee_cells::FeeCellError, Dex1};
 use jsonrpc_core::types::error::{Error as JsonrpcError, ErrorCode};
 use rand::{rngs::StdRng, thread_rng, Rng, SeedableRng};
 
@@ -69,3 +73,97 @@ fn test_parse_rpc_error() {
     });
     assert_eq!(ParsedRpcError::InvalidOutPoint(out_point2), error5.into());
 }
+
+fn pending_tx(previous_output: OutPoint) -> Transaction {
+    TransactionView::new_advanced_builder()
+        .input(
+            CellInput::new_builder()
+                .previous_output(previous_output)
+                .build(),
+        )
+        .output(CellOutput::default())
+        .output_data(Default::default())
+        .build()
+        .data()
+}
+
+#[test]
+fn test_unsealed_tx_is_requeued_until_fee_cells_are_refilled() {
+    const FEE_CELL_CAPACITY: u64 = 1000_0000_0000;
+    let tx = pending_tx(OutPoint::new([1u8; 32].pack(), 0));
+    let other_tx = pending_tx(OutPoint::new([2u8; 32].pack(), 0));
+    let mut source: SingleInMemorySource<Dex1> = SingleInMemorySource::default();
+    source.pending_txs.push_back((other_tx.clone(), Vec::new()));
+
+    // Neither error panics, the tx is retried first in the next round, once
+    // the pool is refilled with fee cells of configured capacity.
+    for error in [
+        FeeCellError::NoFreeFeeCell,
+        FeeCellError::InsufficientCapacity {
+            required: FEE_CELL_CAPACITY,
+        },
+    ] {
+        requeue_unsealed_tx(
+            &mut source,
+            tx.clone(),
+            Vec::new(),
+            &error.into(),
+            FEE_CELL_CAPACITY,
+        );
+        assert_eq!(source.pending_txs.len(), 2);
+        let (pending_tx, _) = source.pending_txs.pop_front().unwrap();
+        assert_eq!(pending_tx.as_slice(), tx.as_slice());
+    }
+    assert_eq!(source.pending_txs[0].0.as_slice(), other_tx.as_slice());
+}
+
+#[test]
+fn test_unfundable_tx_is_dropped_with_chained_txs() {
+    const FEE_CELL_CAPACITY: u64 = 1000_0000_0000;
+    let tx = pending_tx(OutPoint::new([1u8; 32].pack(), 0));
+    let other_tx = pending_tx(OutPoint::new([2u8; 32].pack(), 0));
+    let chained_tx = pending_tx(OutPoint::new(tx.calc_tx_hash(), 0));
+    let mut source: SingleInMemorySource<Dex1> = SingleInMemorySource::default();
+    source.pending_txs.push_back((other_tx.clone(), Vec::new()));
+    source.pending_txs.push_back((chained_tx, Vec::new()));
+
+    requeue_unsealed_tx(
+        &mut source,
+        tx,
+        Vec::new(),
+        &FeeCellError::InsufficientCapacity {
+            required: FEE_CELL_CAPACITY + 1,
+        }
+        .into(),
+        FEE_CELL_CAPACITY,
+    );
+    assert_eq!(source.pending_txs.len(), 1);
+    assert_eq!(source.pending_txs[0].0.as_slice(), other_tx.as_slice());
+}
+
+#[test]
+fn test_txs_chained_on_rescheduled_slice_are_dropped() {
+    let tx = pending_tx(OutPoint::new([1u8; 32].pack(), 0));
+    let other_tx = pending_tx(OutPoint::new([2u8; 32].pack(), 0));
+    // The freestanding cell of a sliced remainder is spent by a chained tx
+    let chained_tx = pending_tx(OutPoint::new(tx.calc_tx_hash(), 0));
+    let mut source: SingleInMemorySource<Dex1> = SingleInMemorySource::default();
+    source.pending_txs.push_back((other_tx.clone(), Vec::new()));
+    source.pending_txs.push_back((chained_tx, Vec::new()));
+
+    // Nothing is rescheduled, chained txs are kept
+    purge_rescheduled_dependents(&mut source, &tx, &tx);
+    assert_eq!(source.pending_txs.len(), 2);
+
+    let rescheduled_tx = tx
+        .clone()
+        .as_advanced_builder()
+        .set_outputs(vec![CellOutput::new_builder()
+            .lock(Script::new_builder().args([3u8; 96].pack()).build())
+            .build()])
+        .build()
+        .data();
+    purge_rescheduled_dependents(&mut source, &tx, &rescheduled_tx);
+    assert_eq!(source.pending_txs.len(), 1);
+    assert_eq!(source.pending_txs[0].0.as_slice(), other_tx.as_slice());
+}